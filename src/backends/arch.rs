@@ -0,0 +1,88 @@
+/// CPU architecture of Python interpreters, e.g., to catch x86_64 interpreters
+/// running under Rosetta on Apple Silicon.
+use anyhow::Result;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Arch {
+    #[value(name = "arm64", alias = "aarch64")]
+    Arm64,
+    #[value(name = "x86_64", alias = "amd64")]
+    X86_64,
+}
+
+impl Arch {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arch::Arm64 => "arm64",
+            Arch::X86_64 => "x86_64",
+        }
+    }
+
+    /// Parses the output of `uname -m` or `platform.machine()`.
+    pub fn from_machine(machine: &str) -> Option<Self> {
+        match machine.trim().to_ascii_lowercase().as_str() {
+            "arm64" | "aarch64" => Some(Arch::Arm64),
+            "x86_64" | "amd64" => Some(Arch::X86_64),
+            _ => None,
+        }
+    }
+
+    /// Builds a uv Python request selecting a managed interpreter of this architecture,
+    /// e.g., `cpython-3.12-macos-aarch64-none` for `3.12`.
+    pub fn uv_python_request(&self, python: &str) -> Result<String> {
+        if python.is_empty() || !python.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            anyhow::bail!(
+                "--arch requires a Python version (e.g., 3.12) rather than '{python}', as the interpreter is selected by uv"
+            );
+        }
+        let arch = match self {
+            Arch::Arm64 => "aarch64",
+            Arch::X86_64 => "x86_64",
+        };
+        let (os, libc) = match std::env::consts::OS {
+            "macos" => ("macos", "none"),
+            "windows" => ("windows", "none"),
+            _ => ("linux", "gnu"),
+        };
+        Ok(format!("cpython-{python}-{os}-{arch}-{libc}"))
+    }
+}
+
+/// Returns the architecture of the current shell on macOS, which is x86_64 under Rosetta
+/// even on Apple Silicon. Other platforms cannot mix architectures, so `None` is returned.
+pub fn current_shell_arch() -> Option<Arch> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let output = Command::new("uname").arg("-m").output().ok()?;
+    Arch::from_machine(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_machine() {
+        let cases = [
+            ("arm64\n", Some(Arch::Arm64)),
+            ("aarch64", Some(Arch::Arm64)),
+            ("x86_64", Some(Arch::X86_64)),
+            ("AMD64", Some(Arch::X86_64)),
+            ("riscv64", None),
+        ];
+        for (machine, expected) in cases {
+            assert_eq!(Arch::from_machine(machine), expected);
+        }
+    }
+
+    #[test]
+    fn uv_python_request_requires_version() -> Result<()> {
+        let request = Arch::Arm64.uv_python_request("3.12")?;
+        assert!(request.starts_with("cpython-3.12-"));
+        assert!(request.contains("-aarch64-"));
+        assert!(Arch::X86_64.uv_python_request("/usr/bin/python3").is_err());
+        Ok(())
+    }
+}
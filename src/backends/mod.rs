@@ -0,0 +1,223 @@
+mod arch;
+mod process;
+mod pyenv;
+mod python;
+mod std_venv;
+mod uv;
+
+use crate::config::Config;
+use crate::envs::EnvVars;
+use crate::error::MeowdaError;
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+
+pub use self::arch::{Arch, current_shell_arch};
+pub use self::process::{
+    OutputMode, ProcessOptions, display_command, format_utc_timestamp, run_command, set_log_dir,
+};
+pub use self::pyenv::{PYENV_PREFIX, resolve_pyenv_python};
+pub use self::python::{requested_implementation, split_implementation, validate_python_request};
+pub use self::std_venv::StdVenvBackend;
+pub use self::uv::{MIN_UV_VERSION, UvBackend, UvVersionReq, query_uv_version};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Keeps every backend off the network, called once at startup.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Options for creating a bare virtual environment.
+pub struct VenvOptions<'a> {
+    /// Python version or executable used as the base interpreter.
+    pub python: &'a str,
+    pub seed: bool,
+    pub include_system_site_packages: bool,
+    /// Architecture of the interpreter, the default interpreter is used if unset.
+    pub arch: Option<Arch>,
+    /// Prompt prefix recorded in `pyvenv.cfg`, the directory name is used if unset.
+    pub prompt: Option<&'a str>,
+}
+
+/// A tool able to create virtual environments and manage their packages.
+///
+/// Backends only build the commands, executing them is left to the caller.
+pub trait Backend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Builds the command creating a virtual environment at `venv_path`.
+    fn venv_command(&self, venv_path: &Path, options: &VenvOptions<'_>) -> Result<Command>;
+
+    /// Builds a pip command (e.g., `install`) targeting the virtual environment at `venv_path`.
+    fn pip_command(&self, venv_path: &Path, subcommand: &str) -> Result<Command>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    Uv,
+    StdVenv,
+    /// uv if available, the `venv` backend otherwise.
+    Auto,
+}
+
+impl FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "uv" => Ok(BackendKind::Uv),
+            "venv" | "std" | "std-venv" => Ok(BackendKind::StdVenv),
+            "auto" => Ok(BackendKind::Auto),
+            other => anyhow::bail!("Unknown backend '{other}', expected one of: uv, venv, auto"),
+        }
+    }
+}
+
+impl BackendKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendKind::Uv => "uv",
+            BackendKind::StdVenv => "venv",
+            BackendKind::Auto => "auto",
+        }
+    }
+
+    /// Resolves the backend kind, prefer, in order:
+    ///
+    /// 1. The backend specified by the user, i.e., `MEOWDA_BACKEND`
+    /// 2. The `backend` key in the config file
+    /// 3. uv
+    ///
+    /// `auto` falls back to the `venv` backend when uv is unavailable, see [`select_backend`].
+    pub fn resolve(config: &Config) -> Result<Self> {
+        if let Some(backend) = std::env::var(EnvVars::MEOWDA_BACKEND)
+            .ok()
+            .filter(|s| !s.is_empty())
+        {
+            return backend.parse();
+        }
+        match config.backend.as_deref() {
+            Some(backend) => backend.parse(),
+            None => Ok(BackendKind::Uv),
+        }
+    }
+}
+
+pub fn select_backend(config: &Config) -> Result<Box<dyn Backend>> {
+    match BackendKind::resolve(config)? {
+        BackendKind::Uv => Ok(Box::new(UvBackend::new(config)?)),
+        BackendKind::StdVenv => Ok(Box::new(StdVenvBackend::new()?)),
+        BackendKind::Auto => match UvBackend::new(config) {
+            Ok(backend) => Ok(Box::new(backend)),
+            Err(e) => {
+                // Only the message, the hint and code of the error are about using uv
+                let reason = e
+                    .downcast_ref::<MeowdaError>()
+                    .map_or_else(|| e.to_string(), |e| e.message.clone());
+                warn!(
+                    "{}, falling back to the `venv` backend (`python -m venv` + `pip`), uv-only features such as `--arch` are unavailable",
+                    reason.trim_end_matches('.')
+                );
+                Ok(Box::new(StdVenvBackend::new()?))
+            }
+        },
+    }
+}
+
+/// Splits a command line into arguments on whitespace, honoring single and double quotes.
+pub fn split_args(input: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+    for c in input.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if let Some(q) = quote {
+        anyhow::bail!("Unterminated {q} quote in arguments: {input}");
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+pub fn check_command_available(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_kind_parses_known_names() -> Result<()> {
+        let cases = [
+            ("uv", BackendKind::Uv),
+            ("UV", BackendKind::Uv),
+            ("venv", BackendKind::StdVenv),
+            ("std", BackendKind::StdVenv),
+            (" std-venv ", BackendKind::StdVenv),
+            ("auto", BackendKind::Auto),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<BackendKind>()?, expected, "input: {input}");
+        }
+        assert!("conda".parse::<BackendKind>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn split_args_honors_quotes() -> Result<()> {
+        let cases: [(&str, &[&str]); 5] = [
+            ("", &[]),
+            ("--offline", &["--offline"]),
+            (
+                "  --offline   --native-tls ",
+                &["--offline", "--native-tls"],
+            ),
+            (
+                "--index-url 'https://example.com/simple'",
+                &["--index-url", "https://example.com/simple"],
+            ),
+            (
+                r#"--config-file "my config.toml" """#,
+                &["--config-file", "my config.toml", ""],
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(split_args(input)?, expected, "input: {input}");
+        }
+        assert!(split_args("--index-url 'oops").is_err());
+        Ok(())
+    }
+}
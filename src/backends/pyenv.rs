@@ -0,0 +1,123 @@
+//! Interpreters installed by pyenv, used for `--python` requests instead of downloading
+//! another copy of the same version, e.g., `3.11` or `pyenv:3.11.8`.
+
+use crate::envs::EnvVars;
+use crate::error::ErrorCode;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Prefix of `--python` requests which must be served by pyenv, e.g., `pyenv:3.11.8`.
+pub const PYENV_PREFIX: &str = "pyenv:";
+
+/// The root directory of pyenv, `PYENV_ROOT` or `~/.pyenv`, if it has installed versions.
+fn pyenv_root() -> Option<PathBuf> {
+    std::env::var_os(EnvVars::PYENV_ROOT)
+        .filter(|root| !root.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| etcetera::home_dir().ok().map(|home| home.join(".pyenv")))
+        .filter(|root| root.join("versions").is_dir())
+}
+
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect()
+}
+
+/// The interpreter of the installed version `version_dir`, pyenv-win installs it at the root.
+fn python_in_version(version_dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        version_dir.join("python.exe")
+    } else {
+        version_dir.join("bin").join("python")
+    }
+}
+
+/// Finds the interpreter of the installed version named `requested`, or else the newest
+/// one of the versions starting with its components, e.g., `3.11.8` for `3.11`.
+fn find_version(versions_dir: &Path, requested: &str) -> Option<PathBuf> {
+    let exact = python_in_version(&versions_dir.join(requested));
+    if exact.is_file() {
+        return Some(exact);
+    }
+    let requested = parse_version(requested)?;
+    versions_dir
+        .read_dir()
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let version = parse_version(entry.file_name().to_str()?)?;
+            let python = python_in_version(&entry.path());
+            (version.starts_with(&requested) && python.is_file()).then_some((version, python))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, python)| python)
+}
+
+/// Resolves a `--python` request to a pyenv interpreter: `pyenv:<version>` must be
+/// installed by pyenv, plain versions use it if available and are left to the backend
+/// otherwise, like paths and executables.
+pub fn resolve_pyenv_python(python: &str) -> Result<Option<PathBuf>> {
+    let Some(version) = python.strip_prefix(PYENV_PREFIX) else {
+        return Ok(parse_version(python)
+            .and(pyenv_root())
+            .and_then(|root| find_version(&root.join("versions"), python)));
+    };
+    let Some(root) = pyenv_root() else {
+        return Err(ErrorCode::BackendUnavailable.err_with_hint(
+            format!("'{python}' requests a pyenv interpreter, but no pyenv installation was found"),
+            "install pyenv, or set `PYENV_ROOT` to its root directory",
+        ));
+    };
+    find_version(&root.join("versions"), version)
+        .map(Some)
+        .ok_or_else(|| {
+            ErrorCode::BackendUnavailable.err_with_hint(
+                format!(
+                    "Python {version} is not installed by pyenv in {}",
+                    root.display()
+                ),
+                format!("install it with `pyenv install {version}`"),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_version_prefers_the_exact_then_the_newest_match() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        for version in ["3.11.2", "3.11.10", "3.12.4", "miniconda3-latest"] {
+            let python = python_in_version(&temp.path().join(version));
+            std::fs::create_dir_all(python.parent().unwrap_or(temp.path()))?;
+            std::fs::write(&python, "")?;
+        }
+        let found = |requested| {
+            find_version(temp.path(), requested).map(|python| {
+                python
+                    .strip_prefix(temp.path())
+                    .map(|relative| relative.iter().next().unwrap_or_default().to_owned())
+                    .unwrap_or_default()
+            })
+        };
+        let cases = [
+            ("3.11.2", Some("3.11.2")),
+            ("3.11", Some("3.11.10")),
+            ("3", Some("3.12.4")),
+            ("miniconda3-latest", Some("miniconda3-latest")),
+            ("3.1", None),
+            ("3.13", None),
+        ];
+        for (requested, expected) in cases {
+            assert_eq!(
+                found(requested),
+                expected.map(Into::into),
+                "requested: {requested}"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Python requests of `--python`, e.g., `3.12`, `/usr/bin/python3` or `pypy@3.10` selecting
+//! an implementation with uv's syntax.
+
+/// Implementations which can be requested, with the name `platform.python_implementation()`
+/// reports for them.
+const IMPLEMENTATIONS: [(&str, &str); 3] = [
+    ("cpython", "CPython"),
+    ("pypy", "PyPy"),
+    ("graalpy", "GraalVM"),
+];
+
+fn is_version(version: &str) -> bool {
+    !version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// Splits a request selecting an implementation, `pypy@3.10` or `pypy3.10`, into the
+/// implementation and the version, which may be empty for `pypy`.
+pub fn split_implementation(python: &str) -> Option<(&'static str, &str)> {
+    let lower = python.to_ascii_lowercase();
+    IMPLEMENTATIONS.iter().find_map(|&(implementation, _)| {
+        let rest = lower.strip_prefix(implementation)?;
+        let rest = rest.strip_prefix('@').unwrap_or(rest);
+        (rest.is_empty() || is_version(rest))
+            .then(|| (implementation, &python[python.len() - rest.len()..]))
+    })
+}
+
+/// The name the interpreter of a request reports as its implementation, e.g., `PyPy` for
+/// `pypy@3.10`, `None` if the request does not select one.
+pub fn requested_implementation(python: &str) -> Option<&'static str> {
+    let (implementation, _) = split_implementation(python)?;
+    IMPLEMENTATIONS
+        .iter()
+        .find(|(name, _)| *name == implementation)
+        .map(|(_, reported)| *reported)
+}
+
+/// Validates a `--python` request, only `<implementation>@<version>` has a syntax to check,
+/// versions, paths and executables are left to the backend.
+pub fn validate_python_request(python: &str) -> Result<(), String> {
+    let is_path = python.contains(['/', '\\']);
+    let Some((implementation, version)) = python.split_once('@').filter(|_| !is_path) else {
+        return Ok(());
+    };
+    if !IMPLEMENTATIONS
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case(implementation))
+    {
+        let names = IMPLEMENTATIONS.map(|(name, _)| name).join(", ");
+        return Err(format!(
+            "unknown Python implementation '{implementation}', expected one of: {names}"
+        ));
+    }
+    if !is_version(version) {
+        return Err(format!(
+            "invalid version '{version}' of {implementation}, expected e.g. {implementation}@3.10"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn implementation_requests_are_parsed() {
+        assert_eq!(split_implementation("pypy@3.10"), Some(("pypy", "3.10")));
+        assert_eq!(split_implementation("PyPy3.10"), Some(("pypy", "3.10")));
+        assert_eq!(split_implementation("cpython"), Some(("cpython", "")));
+        assert_eq!(split_implementation("3.12"), None);
+        assert_eq!(split_implementation("python3.12"), None);
+        assert_eq!(requested_implementation("graalpy@24"), Some("GraalVM"));
+
+        assert!(validate_python_request("pypy@3.10").is_ok());
+        assert!(validate_python_request("/opt/py@3/bin/python").is_ok());
+        assert!(validate_python_request("jython@2.7").is_err());
+        assert!(validate_python_request("pypy@latest").is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use super::{Backend, VenvOptions, check_command_available, is_offline, split_implementation};
+use crate::error::ErrorCode;
+use crate::venv::{minor_version, python_path_in_venv};
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+use tracing::warn;
+
+/// Fallback backend based on the standard library `venv` module and `pip`,
+/// for machines where uv cannot be installed.
+pub struct StdVenvBackend;
+
+impl StdVenvBackend {
+    pub fn new() -> Result<Self> {
+        if !["python3", "python"]
+            .iter()
+            .any(|python| check_command_available(python, &["-m", "venv", "--help"]))
+        {
+            return Err(ErrorCode::BackendUnavailable.err_with_hint(
+                "The `venv` backend requires a Python interpreter with the `venv` module available on PATH",
+                "install Python with the `venv` module, e.g., the `python3-venv` package on Debian and Ubuntu, or install uv and unset `MEOWDA_BACKEND`",
+            ));
+        }
+        Ok(StdVenvBackend)
+    }
+
+    /// Maps a uv-style Python request to an executable, e.g., `3.12` to `python3.12`.
+    ///
+    /// Executables are only named after the minor version, so `3.12.4` is `python3.12` too.
+    fn resolve_python(python: &str) -> String {
+        if let Some((implementation, version)) = split_implementation(python) {
+            // CPython installs `python3.12`, other implementations `pypy3.10`
+            let executable = match implementation {
+                "cpython" => "python",
+                implementation => implementation,
+            };
+            format!("{executable}{}", minor_version(version))
+        } else if !python.is_empty() && python.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            format!("python{}", minor_version(python))
+        } else {
+            python.to_string()
+        }
+    }
+}
+
+impl Backend for StdVenvBackend {
+    fn name(&self) -> &'static str {
+        "venv"
+    }
+
+    fn venv_command(&self, venv_path: &Path, options: &VenvOptions<'_>) -> Result<Command> {
+        if let Some(arch) = options.arch {
+            anyhow::bail!(
+                "Selecting the {} architecture requires the uv backend",
+                arch.name()
+            );
+        }
+        if !options.seed {
+            warn!("The `venv` backend always seeds pip, as it is required to manage packages");
+        }
+        let mut command = Command::new(Self::resolve_python(options.python));
+        command.args(["-m", "venv"]).arg(venv_path);
+        if options.include_system_site_packages {
+            command.arg("--system-site-packages");
+        }
+        if let Some(prompt) = options.prompt {
+            command.args(["--prompt", prompt]);
+        }
+        Ok(command)
+    }
+
+    fn pip_command(&self, venv_path: &Path, subcommand: &str) -> Result<Command> {
+        let mut command = Command::new(python_path_in_venv(venv_path));
+        command.args(["-m", "pip", subcommand]);
+        // pip has no offline mode, not looking up an index is the closest
+        if is_offline() && subcommand == "install" {
+            command.arg("--no-index");
+        }
+        // The output is not shown while running, an unanswered prompt would hang
+        if subcommand == "uninstall" {
+            command.arg("-y");
+        }
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_python_maps_versions_to_executables() {
+        let cases = [
+            ("3.12", "python3.12"),
+            ("3.12.4", "python3.12"),
+            ("3", "python3"),
+            ("/usr/bin/python3", "/usr/bin/python3"),
+            ("pypy3", "pypy3"),
+            ("pypy@3.10", "pypy3.10"),
+            ("cpython@3.12.4", "python3.12"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(StdVenvBackend::resolve_python(input), expected);
+        }
+    }
+
+    #[test]
+    fn pip_uninstall_does_not_prompt() -> Result<()> {
+        let venv_path = Path::new("/venvs/app");
+        let args = |subcommand| -> Result<Vec<String>> {
+            let command = StdVenvBackend.pip_command(venv_path, subcommand)?;
+            Ok(command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect())
+        };
+        assert_eq!(args("uninstall")?, ["-m", "pip", "uninstall", "-y"]);
+        assert_eq!(args("list")?, ["-m", "pip", "list"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+use super::{
+    Backend, VenvOptions, check_command_available, is_offline, split_args, split_implementation,
+};
+use crate::config::Config;
+use crate::envs::EnvVars;
+use crate::error::ErrorCode;
+use crate::store::venv_store::user_state_dir;
+use anyhow::Result;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The oldest uv version supporting every flag meowda passes to uv.
+pub const MIN_UV_VERSION: (u64, u64, u64) = (0, 4, 0);
+
+/// Features of uv newer than [`MIN_UV_VERSION`], only used if the detected uv supports them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UvFeature {
+    /// `<implementation>@<version>` Python requests, e.g., `pypy@3.10`.
+    ImplementationRequest,
+}
+
+impl UvFeature {
+    pub fn min_version(&self) -> (u64, u64, u64) {
+        match self {
+            UvFeature::ImplementationRequest => (0, 5, 0),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            UvFeature::ImplementationRequest => "`<implementation>@<version>` Python requests",
+        }
+    }
+}
+
+fn format_version((major, minor, patch): (u64, u64, u64)) -> String {
+    format!("{major}.{minor}.{patch}")
+}
+
+/// Parses the output of `uv --version`, e.g., `uv 0.8.3 (7e78f54e7 2025-07-24)`.
+pub fn parse_uv_version(output: &str) -> Option<(u64, u64, u64)> {
+    let version = output.split_whitespace().nth(1)?;
+    let mut parts = version
+        .split(['.', '-', '+'])
+        .map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Runs `uv --version` with the uv executable at `uv_path`.
+pub fn query_uv_version(uv_path: &str) -> Option<(u64, u64, u64)> {
+    Command::new(uv_path)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| parse_uv_version(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// A pinned uv version from the `uv_version` config key, e.g., `0.5.x`, `0.5` or `0.5.3`.
+///
+/// Omitted and `x` components match any value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UvVersionReq {
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+}
+
+impl UvVersionReq {
+    pub fn parse(req: &str) -> Result<Self> {
+        let invalid = || {
+            anyhow::anyhow!("Invalid uv version '{req}', expected e.g. '0.5.x', '0.5' or '0.5.3'")
+        };
+        let parse_part = |part: Option<&str>| match part {
+            None | Some("x" | "X" | "*") => Ok(None),
+            Some(part) => part.parse::<u64>().map(Some).map_err(|_| invalid()),
+        };
+        let mut parts = req.trim().split('.');
+        let major = parts
+            .next()
+            .and_then(|part| part.parse::<u64>().ok())
+            .ok_or_else(invalid)?;
+        let minor = parse_part(parts.next())?;
+        let patch = parse_part(parts.next())?;
+        if parts.next().is_some() || (minor.is_none() && patch.is_some()) {
+            return Err(invalid());
+        }
+        Ok(UvVersionReq {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    pub fn matches(&self, version: (u64, u64, u64)) -> bool {
+        self.major == version.0
+            && self.minor.is_none_or(|minor| minor == version.1)
+            && self.patch.is_none_or(|patch| patch == version.2)
+    }
+
+    /// The exact version to install, only known if every component is given.
+    pub fn exact(&self) -> Option<(u64, u64, u64)> {
+        Some((self.major, self.minor?, self.patch?))
+    }
+}
+
+impl Display for UvVersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let part = |part: Option<u64>| part.map_or("x".to_string(), |part| part.to_string());
+        write!(f, "{}.{}", self.major, part(self.minor))?;
+        if self.minor.is_some() {
+            write!(f, ".{}", part(self.patch))?;
+        }
+        Ok(())
+    }
+}
+
+/// Directory holding bootstrapped copies of uv, one `<version>/uv` per version.
+pub fn bootstrapped_uv_dir() -> Option<PathBuf> {
+    user_state_dir().map(|dir| dir.join("uv"))
+}
+
+/// Finds the newest bootstrapped copy of uv matching `req`.
+fn find_bootstrapped_uv(req: &UvVersionReq) -> Option<PathBuf> {
+    let exe = if cfg!(windows) { "uv.exe" } else { "uv" };
+    bootstrapped_uv_dir()?
+        .read_dir()
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let version = parse_uv_version(&format!("uv {}", name.to_str()?))?;
+            let path = entry.path().join(exe);
+            (req.matches(version) && path.is_file()).then_some((version, path))
+        })
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, path)| path)
+}
+
+/// Where the uv executable path was taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UvPathSource {
+    EnvVar,
+    Config,
+    Default,
+    /// A copy of the version pinned by `uv_version`, installed by the user into the meowda data directory.
+    Bootstrapped,
+}
+
+impl Display for UvPathSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UvPathSource::EnvVar => write!(f, "from `{}`", EnvVars::MEOWDA_UV_PATH),
+            UvPathSource::Config => write!(f, "from `uv_path` in config"),
+            UvPathSource::Default => write!(f, "from PATH"),
+            UvPathSource::Bootstrapped => write!(f, "bootstrapped copy"),
+        }
+    }
+}
+
+/// The official installer of uv for the current platform.
+const UV_INSTALL_COMMAND: &str = if cfg!(windows) {
+    r#"powershell -ExecutionPolicy ByPass -c "irm https://astral.sh/uv/install.ps1 | iex""#
+} else {
+    "curl -LsSf https://astral.sh/uv/install.sh | sh"
+};
+
+pub struct UvBackend {
+    uv_path: String,
+    extra_args: Vec<String>,
+    /// Version of the uv executable, `None` if `uv --version` cannot be parsed.
+    version: Option<(u64, u64, u64)>,
+}
+
+impl UvBackend {
+    pub fn new(config: &Config) -> Result<Self> {
+        let (uv_path, source) = Self::resolve_pinned_path(config)?;
+        if !check_command_available(&uv_path, &["--version"]) {
+            return Err(match source {
+                UvPathSource::Default => ErrorCode::BackendUnavailable.err_with_hint(
+                    "uv is not available.",
+                    format!(
+                        "install it with `{UV_INSTALL_COMMAND}` (see https://docs.astral.sh/uv/getting-started/installation/ for other ways), or use the fallback backend with `MEOWDA_BACKEND=venv`, or `MEOWDA_BACKEND=auto` to use it only where uv is missing"
+                    ),
+                ),
+                _ => ErrorCode::BackendUnavailable.err_with_hint(
+                    format!("uv at '{uv_path}' ({source}) is not a working uv executable"),
+                    "fix the path, or unset it to use uv from PATH",
+                ),
+            });
+        }
+
+        let version = query_uv_version(&uv_path);
+        if let Some(version) = version.filter(|&version| version < MIN_UV_VERSION) {
+            return Err(ErrorCode::BackendUnavailable.err_with_hint(
+                format!(
+                    "uv {} at '{uv_path}' ({source}) is older than {}, the oldest version meowda supports",
+                    format_version(version),
+                    format_version(MIN_UV_VERSION)
+                ),
+                "upgrade it with `uv self update`, or pin a newer version with `uv_version` in config",
+            ));
+        }
+
+        Ok(UvBackend {
+            uv_path,
+            extra_args: Self::resolve_extra_args(config)?,
+            version,
+        })
+    }
+
+    /// Whether the detected uv supports `feature`, assumed if its version is unknown.
+    pub fn supports(&self, feature: UvFeature) -> bool {
+        self.version
+            .is_none_or(|version| version >= feature.min_version())
+    }
+
+    /// Fails early, before running anything, if the detected uv lacks `feature`.
+    fn require(&self, feature: UvFeature) -> Result<()> {
+        if self.supports(feature) {
+            return Ok(());
+        }
+        Err(ErrorCode::BackendUnavailable.err_with_hint(
+            format!(
+                "{} require uv {}, but uv at '{}' is {}",
+                feature.description(),
+                format_version(feature.min_version()),
+                self.uv_path,
+                self.version.map(format_version).unwrap_or_default()
+            ),
+            "upgrade it with `uv self update`",
+        ))
+    }
+
+    /// Resolves the extra arguments passed to every uv invocation, prefer, in order:
+    ///
+    /// 1. The arguments specified by the user, i.e., `MEOWDA_UV_ARGS`
+    /// 2. The `uv_args` key in the config file
+    pub fn resolve_extra_args(config: &Config) -> Result<Vec<String>> {
+        if let Some(uv_args) = std::env::var(EnvVars::MEOWDA_UV_ARGS)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+        {
+            return split_args(&uv_args);
+        }
+        Ok(config.uv_args.clone().unwrap_or_default())
+    }
+
+    /// Resolves the uv executable like [`UvBackend::resolve_path`], but enforces the
+    /// `uv_version` pin: a non-matching uv falls back to a bootstrapped copy of the
+    /// pinned version, and is an error if there is none.
+    pub fn resolve_pinned_path(config: &Config) -> Result<(String, UvPathSource)> {
+        let (uv_path, source) = Self::resolve_path(config);
+        let Some(req) = config.uv_version.as_deref() else {
+            return Ok((uv_path, source));
+        };
+        let req = UvVersionReq::parse(req)?;
+        let version = query_uv_version(&uv_path);
+        if version.is_some_and(|version| req.matches(version)) {
+            return Ok((uv_path, source));
+        }
+        if let Some(path) = find_bootstrapped_uv(&req) {
+            return Ok((path.display().to_string(), UvPathSource::Bootstrapped));
+        }
+
+        let found = match version {
+            Some((major, minor, patch)) => format!("uv {major}.{minor}.{patch} ({source})"),
+            None => format!("uv at '{uv_path}' ({source}) is not available and"),
+        };
+        Err(ErrorCode::BackendUnavailable.err_with_hint(
+            format!("{found} does not match the version {req} pinned by `uv_version` in config."),
+            format!(
+                "install the pinned version with `{}`",
+                Self::bootstrap_command(&req)
+            ),
+        ))
+    }
+
+    /// The command installing a copy of the pinned uv for [`UvBackend::resolve_pinned_path`].
+    pub fn bootstrap_command(req: &UvVersionReq) -> String {
+        let version = req
+            .exact()
+            .map_or("<version>".to_string(), |(major, minor, patch)| {
+                format!("{major}.{minor}.{patch}")
+            });
+        let dir = bootstrapped_uv_dir()
+            .map(|dir| dir.join(&version).display().to_string())
+            .unwrap_or_else(|| format!("<data_dir>/meowda/uv/{version}"));
+        format!(
+            "curl -LsSf https://astral.sh/uv/{version}/install.sh | env UV_UNMANAGED_INSTALL=\"{dir}\" sh"
+        )
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(&self.uv_path);
+        command.args(args).args(&self.extra_args);
+        if is_offline() && !self.extra_args.iter().any(|arg| arg == "--offline") {
+            command.arg("--offline");
+        }
+        command
+    }
+
+    /// Resolves the uv executable, prefer, in order:
+    ///
+    /// 1. The path specified by the user, i.e., `MEOWDA_UV_PATH`
+    /// 2. The `uv_path` key in the config file
+    /// 3. `uv` found on PATH
+    pub fn resolve_path(config: &Config) -> (String, UvPathSource) {
+        if let Some(uv_path) = std::env::var(EnvVars::MEOWDA_UV_PATH)
+            .ok()
+            .filter(|s| !s.is_empty())
+        {
+            return (uv_path, UvPathSource::EnvVar);
+        }
+        if let Some(uv_path) = config.uv_path.as_ref().filter(|s| !s.is_empty()) {
+            return (uv_path.clone(), UvPathSource::Config);
+        }
+        ("uv".to_string(), UvPathSource::Default)
+    }
+}
+
+impl Backend for UvBackend {
+    fn name(&self) -> &'static str {
+        "uv"
+    }
+
+    fn venv_command(&self, venv_path: &Path, options: &VenvOptions<'_>) -> Result<Command> {
+        let venv_path_str = venv_path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid path for virtual environment"))?;
+
+        if split_implementation(options.python).is_some() {
+            self.require(UvFeature::ImplementationRequest)?;
+        }
+        let python = match options.arch {
+            Some(arch) => arch.uv_python_request(options.python)?,
+            None => options.python.to_string(),
+        };
+        let mut command = self.command(&["venv", venv_path_str, "--python", &python]);
+        if options.seed {
+            command.arg("--seed");
+        }
+        if options.include_system_site_packages {
+            command.arg("--system-site-packages");
+        }
+        if let Some(prompt) = options.prompt {
+            command.args(["--prompt", prompt]);
+        }
+        Ok(command)
+    }
+
+    fn pip_command(&self, venv_path: &Path, subcommand: &str) -> Result<Command> {
+        // uv pip detects the target environment from `VIRTUAL_ENV`
+        let mut command = self.command(&["pip", subcommand]);
+        command.env("VIRTUAL_ENV", venv_path);
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_uv_version_reads_semver() {
+        let cases = [
+            ("uv 0.8.3 (7e78f54e7 2025-07-24)", Some((0, 8, 3))),
+            ("uv 0.5.0", Some((0, 5, 0))),
+            ("uv 1.0.0-rc.1", Some((1, 0, 0))),
+            ("uv", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_uv_version(input), expected, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn supports_gates_features_on_the_detected_version() {
+        let backend = |version| UvBackend {
+            uv_path: "uv".to_string(),
+            extra_args: Vec::new(),
+            version,
+        };
+        let feature = UvFeature::ImplementationRequest;
+        let cases = [
+            (Some((0, 4, 30)), false),
+            (Some(feature.min_version()), true),
+            (Some((1, 0, 0)), true),
+            (None, true),
+        ];
+        for (version, expected) in cases {
+            assert_eq!(
+                backend(version).supports(feature),
+                expected,
+                "version: {version:?}"
+            );
+        }
+        let options = VenvOptions {
+            python: "pypy@3.10",
+            seed: false,
+            include_system_site_packages: false,
+            arch: None,
+            prompt: None,
+        };
+        let venv_path = Path::new("/tmp/app");
+        assert!(
+            backend(Some((0, 4, 30)))
+                .venv_command(venv_path, &options)
+                .is_err()
+        );
+        assert!(
+            backend(Some((0, 5, 0)))
+                .venv_command(venv_path, &options)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn uv_version_req_matches_wildcards() -> Result<()> {
+        let cases = [
+            ("0.5.x", (0, 5, 11), true),
+            ("0.5.x", (0, 6, 0), false),
+            ("0.5", (0, 5, 0), true),
+            ("0.5.3", (0, 5, 3), true),
+            ("0.5.3", (0, 5, 4), false),
+            ("1", (1, 2, 3), true),
+            ("1.x.x", (0, 2, 3), false),
+        ];
+        for (req, version, expected) in cases {
+            assert_eq!(
+                UvVersionReq::parse(req)?.matches(version),
+                expected,
+                "req: {req}, version: {version:?}"
+            );
+        }
+        assert_eq!(UvVersionReq::parse("0.5.x")?.to_string(), "0.5.x");
+        assert_eq!(UvVersionReq::parse("0.5.3")?.exact(), Some((0, 5, 3)));
+        assert_eq!(UvVersionReq::parse("0.5")?.exact(), None);
+        for invalid in ["", "x", "0.x.3", "0.5.3.1", "latest"] {
+            assert!(UvVersionReq::parse(invalid).is_err(), "req: {invalid}");
+        }
+        Ok(())
+    }
+}
@@ -1,20 +1,590 @@
-use crate::cli::args::ActivateArgs;
-use crate::store::venv_store::VenvStore;
-use anyhow::Result;
+use crate::cli::args::{ActivateArgs, ScopeArgs, Shell, ShellArgs};
+use crate::cli::init::{default_shell_profile, is_init_script_installed};
+use crate::config::Config;
+use crate::envs::EnvVars;
+use crate::error::ErrorCode;
+use crate::interrupt::Interrupts;
+use crate::store::activate_index::{self, IndexEntry};
+use crate::store::backend::Discovery;
+use crate::store::conda;
+use crate::store::venv_store::{
+    VenvScope, VenvStore, get_candidate_scopes, is_no_recurse, is_read_only_root, scope_label,
+};
+use crate::ui;
+use crate::venv::{
+    EnvConfig, EnvMetadata, VenvService, bin_dir_in_venv, touch_last_used, warn_on_arch_mismatch,
+    warn_on_missing_interpreter,
+};
+use anyhow::{Context, Result};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{debug, warn};
 
-pub async fn activate(_args: ActivateArgs) -> Result<()> {
-    anyhow::bail!("Please run `meowda init <shell_profile>` to set up the activation script.");
+/// Falls back to `default_env` in config when no environment name is given.
+pub(crate) fn resolve_env_name(name: Option<String>, config: &Config) -> Result<String> {
+    name.or_else(|| config.default_env.clone()).context(
+        "No virtual environment specified, pass a name or set `default_env` in meowda.toml",
+    )
+}
+
+/// Returns the unqualified name and the path of the environment `env_ref`, e.g., `global:myenv`.
+///
+/// Looks the path up in the activate index first, the full store discovery only runs on a miss.
+pub(crate) fn detect_venv_path(
+    scope: &ScopeArgs,
+    env_ref: &str,
+    config: &Config,
+) -> Result<(String, PathBuf)> {
+    detect_venv_path_in(&Discovery::current(), scope, env_ref, config)
+}
+
+/// Like [`detect_venv_path`], discovering the stores as given by `discovery`.
+pub(crate) fn detect_venv_path_in(
+    discovery: &Discovery,
+    scope: &ScopeArgs,
+    env_ref: &str,
+    config: &Config,
+) -> Result<(String, PathBuf)> {
+    let start = Instant::now();
+    let scope_type = scope.try_into_scope_type()?;
+    if let Some(name) = conda::strip_conda_ref(env_ref) {
+        if scope_type.is_restricted() {
+            return Err(ErrorCode::InvalidScope.err(format!(
+                "'{env_ref}' selects a conda environment, which contradicts the scope flag"
+            )));
+        }
+        let env = conda::find_conda_env(name).ok_or_else(|| {
+            ErrorCode::EnvNotFound.err_with_hint(
+                format!("Conda environment '{name}' not found."),
+                "list the conda environments with `meowda list --include-conda`",
+            )
+        })?;
+        return Ok((env.name, env.path));
+    }
+    let cwd = discovery.cwd()?;
+    // The index remembers resolutions across all stores, so it is bypassed by `--no-recurse`
+    if let Some(entry) = (!is_no_recurse())
+        .then(|| activate_index::lookup(config, discovery, scope_type, env_ref))
+        .flatten()
+    {
+        debug!(
+            "Resolved '{env_ref}' from the activate index in {:?}",
+            start.elapsed()
+        );
+        return Ok((entry.name, entry.path));
+    }
+
+    let (detected_venv_scope, name) =
+        crate::cli::utils::search_venv_in(discovery, scope_type, env_ref)?;
+    let venv_store = VenvStore::from_scope_in(discovery, detected_venv_scope.clone())?;
+    let venv_path = venv_store.env_path(name);
+    debug!(
+        "Resolved '{env_ref}' by store discovery in {:?}",
+        start.elapsed()
+    );
+    let shadowed_envs =
+        crate::cli::utils::shadowed_envs(discovery, scope_type, &detected_venv_scope, name)?;
+    for shadowed in &shadowed_envs {
+        warn!(
+            "Using '{}', it shadows '{}' of an outer local store (pass `--no-recurse` to only search the nearest one)",
+            venv_path.display(),
+            shadowed.display()
+        );
+    }
+    // Shadowing resolutions are not indexed so that every activation warns about them
+    if !is_no_recurse() && shadowed_envs.is_empty() {
+        let local_level = match detected_venv_scope {
+            VenvScope::Local(level) => Some(level),
+            VenvScope::Global | VenvScope::Named(_) => None,
+        };
+        let entry = IndexEntry {
+            name: name.to_string(),
+            path: venv_path.clone(),
+            local_level,
+        };
+        activate_index::record(config, &cwd, scope_type, env_ref, entry);
+    }
+    Ok((name.to_string(), venv_path))
+}
+
+/// Records the activation of the environment at `venv_path`, except for conda environments
+/// and those of read-only stores, which are only read.
+fn record_activation(venv_path: &Path) {
+    if !conda::is_conda_env(venv_path) && !venv_path.parent().is_some_and(is_read_only_root) {
+        touch_last_used(venv_path);
+    }
+}
+
+/// Lets the user pick an environment of the candidate stores with a fuzzy search.
+///
+/// The picker draws on stderr, so it also works when stdout is captured by the shell hook.
+fn pick_env(scope: &ScopeArgs) -> Result<(String, PathBuf)> {
+    let mut envs = Vec::new();
+    for scope in get_candidate_scopes(scope.try_into_scope_type()?)? {
+        let store = VenvStore::from_specified_scope(scope.clone())?;
+        if store.is_ready() {
+            let mut store_envs = VenvService::list_venvs_in_store(&store, None)?;
+            store_envs.sort_by(|a, b| a.name.cmp(&b.name));
+            envs.extend(store_envs.into_iter().map(|env| (scope.clone(), env)));
+        }
+    }
+    if envs.is_empty() {
+        return Err(ErrorCode::EnvNotFound
+            .err("No virtual environments found, create one with `meowda create <name>`"));
+    }
+    let items = envs
+        .iter()
+        .map(|(scope, env)| format!("{} ({scope})", env.name))
+        .collect::<Vec<_>>();
+    let selection = dialoguer::FuzzySelect::new()
+        .with_prompt("Select a virtual environment")
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .context("Failed to read the selection")?;
+    let Some(index) = selection else {
+        anyhow::bail!("No virtual environment selected");
+    };
+    let (_, env) = envs.swap_remove(index);
+    Ok((env.name, env.path))
+}
+
+/// Resolves the environment to activate: the given name, `default_env` in config, or,
+/// on a terminal, the one picked interactively.
+fn resolve_venv(
+    name: Option<String>,
+    scope: &ScopeArgs,
+    config: &Config,
+) -> Result<(String, PathBuf)> {
+    if name.is_none()
+        && config.default_env.is_none()
+        && !ui::is_plain()
+        && std::io::stdin().is_terminal()
+        && std::io::stderr().is_terminal()
+    {
+        return pick_env(scope);
+    }
+    let env_ref = resolve_env_name(name, config)?;
+    detect_venv_path(scope, &env_ref, config)
+}
+
+fn detect_shell() -> Shell {
+    let shell = std::env::var("SHELL").unwrap_or_default();
+    match Path::new(&shell).file_name().and_then(|name| name.to_str()) {
+        Some("zsh") => Shell::Zsh,
+        Some("fish") => Shell::Fish,
+        Some("sh") => Shell::Sh,
+        _ => Shell::Bash,
+    }
+}
+
+/// The command activating `env_ref` in `shell`. The `meowda` function of the shell hook
+/// only exists for bash and zsh, other shells and shells without the hook evaluate the
+/// printed activation script.
+fn activation_command(shell: Shell, env_ref: &str, hook_installed: bool) -> String {
+    match shell {
+        Shell::Bash | Shell::Zsh if hook_installed => format!("meowda activate {env_ref}"),
+        Shell::Fish => format!("meowda activate {env_ref} --print | source"),
+        _ => format!("eval \"$(meowda activate {env_ref} --print)\""),
+    }
+}
+
+/// Instructions to activate `env_ref` in the shell of the user, printed after creating an
+/// environment.
+pub(crate) fn activation_hint(env_ref: &str) -> String {
+    let shell = detect_shell();
+    let profile = default_shell_profile();
+    let hook_installed = profile.as_deref().is_some_and(is_init_script_installed);
+    let command = activation_command(shell, env_ref, hook_installed);
+    match profile {
+        Some(profile) if !hook_installed => format!(
+            "To activate it, run `{command}`, or run `meowda init {}` once to set up `meowda activate {env_ref}`.",
+            profile.display()
+        ),
+        _ => format!("To activate it, run `{command}`."),
+    }
+}
+
+pub const DEFAULT_PROMPT_FORMAT: &str = "({name}) ";
+
+/// Replaces the `{name}` and `{scope}` placeholders of a `prompt_format`.
+fn render_prompt(format: &str, name: &str, scope: &str) -> String {
+    format.replace("{name}", name).replace("{scope}", scope)
+}
+
+/// The prompt prefix of the activated environment `name`, `None` if disabled in config.
+///
+/// `{name}` is the prompt given at creation (`meowda create --prompt`) if any.
+fn prompt_prefix(config: &Config, name: &str, venv_path: &Path) -> Option<String> {
+    if config.prompt == Some(false) {
+        return None;
+    }
+    let created_prompt = EnvConfig::parse(venv_path.join("pyvenv.cfg"))
+        .ok()
+        .and_then(|config| config.prompt);
+    let name = created_prompt.as_deref().unwrap_or(name);
+    let format = config
+        .prompt_format
+        .as_deref()
+        .unwrap_or(DEFAULT_PROMPT_FORMAT);
+    // Only look the stores up when the scope is shown
+    let scope = if format.contains("{scope}") {
+        scope_label(venv_path)
+    } else {
+        String::new()
+    };
+    Some(render_prompt(format, name, &scope))
+}
+
+/// Quotes `value` as a single-quoted shell word.
+fn quote(shell: Shell, value: &str) -> String {
+    match shell {
+        Shell::Bash | Shell::Zsh | Shell::Sh => format!("'{}'", value.replace('\'', r"'\''")),
+        Shell::Fish => format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'")),
+    }
+}
+
+/// Renders the commands activating the environment at `venv_path` in `shell`, prefixing
+/// the prompt with `prompt` unless it is `None`. Deactivating restores the prompt.
+///
+/// Conda environments have no `activate` script of their own, they get the `CONDA_PREFIX`
+/// and `CONDA_DEFAULT_ENV` tools look them up by as well.
+fn activation_script(
+    shell: Shell,
+    name: &str,
+    venv_path: &Path,
+    prompt: Option<&str>,
+    frozen: bool,
+) -> String {
+    let venv = quote(shell, &venv_path.display().to_string());
+    let bin = quote(shell, &venv_path.join("bin").display().to_string());
+    let prompt_prefix = prompt.map(|prompt| quote(shell, prompt));
+    let prompt = quote(shell, name);
+    let frozen_var = EnvVars::MEOWDA_FROZEN;
+    let conda = conda::is_conda_env(venv_path);
+    let conda_vars = if conda {
+        " CONDA_PREFIX CONDA_DEFAULT_ENV"
+    } else {
+        ""
+    };
+    match shell {
+        Shell::Bash | Shell::Zsh | Shell::Sh => {
+            let frozen = if frozen {
+                format!("export {frozen_var}=1")
+            } else {
+                format!("unset {frozen_var}")
+            };
+            let ps1 = prompt_prefix
+                .map(|prefix| {
+                    format!(
+                        "if [ -z \"${{VIRTUAL_ENV_DISABLE_PROMPT-}}\" ]; then PS1={prefix}\"${{PS1-}}\"; fi\n"
+                    )
+                })
+                .unwrap_or_default();
+            let conda_exports = if conda {
+                format!("export CONDA_PREFIX={venv}\nexport CONDA_DEFAULT_ENV={prompt}\n")
+            } else {
+                String::new()
+            };
+            format!(
+                r#"deactivate nondestructive 2>/dev/null || true
+_OLD_VIRTUAL_PATH="$PATH"
+_OLD_VIRTUAL_PS1="${{PS1-}}"
+deactivate () {{
+    PATH="$_OLD_VIRTUAL_PATH"
+    export PATH
+    PS1="$_OLD_VIRTUAL_PS1"
+    unset VIRTUAL_ENV VIRTUAL_ENV_PROMPT{conda_vars} {frozen_var} _OLD_VIRTUAL_PATH _OLD_VIRTUAL_PS1
+    unset -f deactivate
+    hash -r 2>/dev/null || true
+}}
+export VIRTUAL_ENV={venv}
+export VIRTUAL_ENV_PROMPT={prompt}
+{conda_exports}export PATH={bin}:"$PATH"
+{ps1}unset PYTHONHOME
+{frozen}
+hash -r 2>/dev/null || true
+"#
+            )
+        }
+        Shell::Fish => {
+            let frozen = if frozen {
+                format!("set -gx {frozen_var} 1")
+            } else {
+                format!("set -e {frozen_var}")
+            };
+            // Wraps `fish_prompt`, keeping the original one to restore on deactivation
+            let fish_prompt = prompt_prefix
+                .map(|prefix| {
+                    format!(
+                        r#"functions -c fish_prompt _old_fish_prompt
+function fish_prompt
+    set -l old_status $status
+    printf '%s' {prefix}
+    echo "exit $old_status" | .
+    _old_fish_prompt
+end
+"#
+                    )
+                })
+                .unwrap_or_default();
+            let conda_exports = if conda {
+                format!("set -gx CONDA_PREFIX {venv}\nset -gx CONDA_DEFAULT_ENV {prompt}\n")
+            } else {
+                String::new()
+            };
+            format!(
+                r#"functions -q deactivate; and deactivate
+set -g _OLD_VIRTUAL_PATH $PATH
+function deactivate
+    set -gx PATH $_OLD_VIRTUAL_PATH
+    set -e VIRTUAL_ENV VIRTUAL_ENV_PROMPT{conda_vars} {frozen_var} _OLD_VIRTUAL_PATH
+    if functions -q _old_fish_prompt
+        functions -e fish_prompt
+        functions -c _old_fish_prompt fish_prompt
+        functions -e _old_fish_prompt
+    end
+    functions -e deactivate
+end
+set -gx VIRTUAL_ENV {venv}
+set -gx VIRTUAL_ENV_PROMPT {prompt}
+{conda_exports}set -gx PATH {bin} $PATH
+set -e PYTHONHOME
+{fish_prompt}{frozen}
+"#
+            )
+        }
+    }
+}
+
+pub async fn activate(args: ActivateArgs, config: &Config) -> Result<()> {
+    if !args.print {
+        let name = resolve_env_name(args.name, config)?;
+        anyhow::bail!(
+            "Please run `meowda init <shell_profile>` to set up the activation script, or use `eval \"$(meowda activate {name} --print)\"`."
+        );
+    }
+    let (name, venv_path) = resolve_venv(args.name, &args.scope, config)?;
+    warn_on_arch_mismatch(&venv_path);
+    warn_on_missing_interpreter(&venv_path);
+    record_activation(&venv_path);
+    print_activation_script(args.shell, &name, &venv_path, config)
+}
+
+fn print_activation_script(
+    shell: Option<Shell>,
+    name: &str,
+    venv_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let frozen = EnvMetadata::load(venv_path)?.frozen;
+    let shell = shell.unwrap_or_else(detect_shell);
+    let prompt = prompt_prefix(config, name, venv_path);
+    print!(
+        "{}",
+        activation_script(shell, name, venv_path, prompt.as_deref(), frozen)
+    );
+    Ok(())
 }
 
 pub async fn deactivate() -> Result<()> {
     anyhow::bail!("Please run `meowda init <shell_profile>` to set up the activation script.");
 }
 
-pub async fn detect_activate_venv_path(args: ActivateArgs) -> Result<()> {
-    let scope_type = args.scope.try_into_scope_type()?;
-    let detected_venv_scope = crate::cli::utils::search_venv(scope_type, &args.name)?;
-    let venv_store = VenvStore::from_specified_scope(detected_venv_scope)?;
-    let venv_path = venv_store.path().join(&args.name);
+/// The activation script starts by deactivating the current environment, so `--print`
+/// renders the same commands as `meowda activate --print`.
+pub async fn switch(args: ActivateArgs, config: &Config) -> Result<()> {
+    if !args.print {
+        let name = resolve_env_name(args.name, config)?;
+        anyhow::bail!(
+            "Please run `meowda init <shell_profile>` to set up the activation script, or use `eval \"$(meowda switch {name} --print)\"`."
+        );
+    }
+    activate(args, config).await
+}
+
+/// Spawns `$SHELL` with the environment active, the parent environment is left
+/// untouched, so exiting the subshell restores everything.
+pub async fn shell(args: ShellArgs, config: &Config) -> Result<()> {
+    if let Ok(active) = std::env::var(EnvVars::MEOWDA_SHELL) {
+        anyhow::bail!(
+            "Already in a meowda shell of '{active}', exit it before starting another one"
+        );
+    }
+    let (name, venv_path) = resolve_venv(args.name, &args.scope, config)?;
+    warn_on_arch_mismatch(&venv_path);
+    warn_on_missing_interpreter(&venv_path);
+    record_activation(&venv_path);
+    let frozen = EnvMetadata::load(&venv_path)?.frozen;
+    let program = std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+
+    let bin_dir = bin_dir_in_venv(&venv_path);
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let path = std::env::join_paths(std::iter::once(bin_dir).chain(std::env::split_paths(&path)))
+        .context("Failed to build PATH for the subshell")?;
+
+    let mut command = tokio::process::Command::new(&program);
+    command
+        .env("VIRTUAL_ENV", &venv_path)
+        .env("VIRTUAL_ENV_PROMPT", &name)
+        .env("PATH", path)
+        .env(EnvVars::MEOWDA_SHELL, &name)
+        .env_remove("PYTHONHOME");
+    if frozen {
+        command.env(EnvVars::MEOWDA_FROZEN, "1");
+    } else {
+        command.env_remove(EnvVars::MEOWDA_FROZEN);
+    }
+
+    println!(
+        "Entering a shell with '{}' active, run `exit` to leave it.",
+        name
+    );
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to execute '{program}'"))?;
+    // Ctrl-C belongs to the interactive subshell, meowda keeps waiting for it
+    let mut interrupts = Interrupts::subscribe();
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status.with_context(|| format!("Failed to wait for '{program}'"))?,
+            _ = interrupts.next() => continue,
+        }
+    };
+    println!("Left the shell of '{name}'.");
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Prints the path of the environment to activate, followed by a line with the prompt
+/// prefix if enabled, e.g., for `.envrc` files. The shell hook passes `--print` for the
+/// activation commands instead.
+pub async fn detect_activate_venv_path(args: ActivateArgs, config: &Config) -> Result<()> {
+    let (name, venv_path) = resolve_venv(args.name, &args.scope, config)?;
+    warn_on_arch_mismatch(&venv_path);
+    warn_on_missing_interpreter(&venv_path);
+    record_activation(&venv_path);
+    if args.print {
+        return print_activation_script(args.shell, &name, &venv_path, config);
+    }
     println!("{}", venv_path.display());
+    if let Some(prompt) = prompt_prefix(config, &name, &venv_path) {
+        println!("{prompt}");
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        let cases = [
+            (Shell::Bash, "/tmp/env", "'/tmp/env'"),
+            (Shell::Zsh, "it's", r"'it'\''s'"),
+            (Shell::Fish, "it's", r"'it\'s'"),
+            (Shell::Fish, r"a\b", r"'a\\b'"),
+        ];
+        for (shell, value, expected) in cases {
+            assert_eq!(quote(shell, value), expected);
+        }
+    }
+
+    #[test]
+    fn activation_command_follows_the_shell_hook() {
+        let cases = [
+            (Shell::Zsh, true, "meowda activate app --local"),
+            (
+                Shell::Bash,
+                false,
+                r#"eval "$(meowda activate app --local --print)""#,
+            ),
+            (
+                Shell::Sh,
+                true,
+                r#"eval "$(meowda activate app --local --print)""#,
+            ),
+            (
+                Shell::Fish,
+                true,
+                "meowda activate app --local --print | source",
+            ),
+        ];
+        for (shell, hook_installed, expected) in cases {
+            assert_eq!(
+                activation_command(shell, "app --local", hook_installed),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn activation_script_exports_env() {
+        let venv_path = Path::new("/tmp/venvs/app");
+        let script = activation_script(Shell::Bash, "app", venv_path, Some("(app) "), true);
+        assert!(script.contains("export VIRTUAL_ENV='/tmp/venvs/app'"));
+        assert!(script.contains("export PATH='/tmp/venvs/app/bin':\"$PATH\""));
+        assert!(script.contains(r#"PS1='(app) '"${PS1-}""#));
+        assert!(script.contains(&format!("export {}=1", EnvVars::MEOWDA_FROZEN)));
+
+        let script = activation_script(Shell::Fish, "app", venv_path, Some("(app) "), false);
+        assert!(script.contains("set -gx VIRTUAL_ENV '/tmp/venvs/app'"));
+        assert!(script.contains("set -gx PATH '/tmp/venvs/app/bin' $PATH"));
+        assert!(script.contains("printf '%s' '(app) '"));
+    }
+
+    #[test]
+    fn activation_script_exports_conda_prefix_for_conda_envs() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let env_path = temp.path().join("envs").join("ml");
+        std::fs::create_dir_all(env_path.join("conda-meta"))?;
+        std::fs::create_dir_all(env_path.join("bin"))?;
+        let prefix = env_path.display().to_string();
+
+        let script = activation_script(Shell::Bash, "ml", &env_path, None, false);
+        assert!(script.contains(&format!("export CONDA_PREFIX='{prefix}'")));
+        assert!(script.contains("export CONDA_DEFAULT_ENV='ml'"));
+        assert!(script.contains(&format!("export PATH='{prefix}/bin':\"$PATH\"")));
+        assert!(
+            script.contains("unset VIRTUAL_ENV VIRTUAL_ENV_PROMPT CONDA_PREFIX CONDA_DEFAULT_ENV")
+        );
+        let script = activation_script(Shell::Fish, "ml", &env_path, None, false);
+        assert!(script.contains(&format!("set -gx CONDA_PREFIX '{prefix}'")));
+        assert!(
+            script.contains("set -e VIRTUAL_ENV VIRTUAL_ENV_PROMPT CONDA_PREFIX CONDA_DEFAULT_ENV")
+        );
+
+        let script = activation_script(Shell::Bash, "app", Path::new("/tmp/app"), None, false);
+        assert!(!script.contains("CONDA_PREFIX"));
+        Ok(())
+    }
+
+    #[test]
+    fn prompt_can_be_customized_or_disabled() {
+        assert_eq!(
+            render_prompt(DEFAULT_PROMPT_FORMAT, "app", "global"),
+            "(app) "
+        );
+        assert_eq!(
+            render_prompt("[{scope}:{name}] ", "app", "local"),
+            "[local:app] "
+        );
+        let config = Config {
+            prompt: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(prompt_prefix(&config, "app", Path::new("/tmp/app")), None);
+
+        let script = activation_script(Shell::Bash, "app", Path::new("/tmp/app"), None, false);
+        assert!(!script.contains("PS1='"));
+        let script = activation_script(Shell::Fish, "app", Path::new("/tmp/app"), None, false);
+        assert!(!script.contains("function fish_prompt"));
+    }
+}
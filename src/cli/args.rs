@@ -1,7 +1,16 @@
-use crate::store::venv_store::ScopeType;
+use crate::backends::{Arch, validate_python_request};
+use crate::cli::output::OutputFormat;
+use crate::error::ErrorCode;
+use crate::store::history::HISTORY_FILE_NAME;
+use crate::store::migrate::STORE_FILE_NAME;
+use crate::store::venv_store::{DefaultScope, ScopeType};
+use crate::ui::ColorMode;
+use anyhow::Context;
 use clap::builder::Styles;
 use clap::builder::styling::{AnsiColor, Effects};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::str::FromStr;
 
 // Configures Clap v3-style help menu colors
 const STYLES: Styles = Styles::styled()
@@ -16,6 +25,69 @@ const STYLES: Styles = Styles::styled()
 pub struct Args {
     #[command(subcommand)]
     pub command: Commands,
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Hide the progress of long operations, output is still shown on failure"
+    )]
+    pub quiet: bool,
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = clap::ArgAction::Count,
+        conflicts_with = "quiet",
+        help = "Show the full output of uv/pip instead of a spinner and more details, repeat for more logs (-v: info, -vv: debug, -vvv: trace)"
+    )]
+    pub verbose: u8,
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Write the full output of each uv/pip invocation to a timestamped file in DIR, e.g., for CI post-mortems"
+    )]
+    pub log_dir: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        help = "Screen-reader-friendly output: no colors, spinners or symbols, explicit text markers instead (also MEOWDA_ACCESSIBLE=1)"
+    )]
+    pub accessible: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Non-interactive mode for CI: no prompts or colors, plain output, bounded lock waits and one-line errors (also MEOWDA_CI=1)"
+    )]
+    pub ci: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Never access the network: `--offline` is passed to every uv invocation, packages and Python versions must already be cached (also MEOWDA_OFFLINE=1)"
+    )]
+    pub offline: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Only search the nearest local store instead of every `.meowda/venvs` up to the root (also MEOWDA_NO_RECURSE=1)"
+    )]
+    pub no_recurse: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "table",
+        help = "Output format of list, info, diff, doctor and the other reporting commands: aligned columns, one JSON document or tab-separated rows for scripts"
+    )]
+    pub format: OutputFormat,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "auto",
+        help = "When to color the output: auto colors terminals and honors NO_COLOR and CLICOLOR_FORCE"
+    )]
+    pub color: ColorMode,
 }
 
 #[derive(Debug, Subcommand, PartialEq)]
@@ -24,51 +96,520 @@ pub enum Commands {
     Create(CreateArgs),
     #[clap(about = "Fork a virtual environment from an existing environment or Python executable")]
     Fork(ForkArgs),
-    #[clap(about = "Remove a virtual environment")]
+    #[clap(about = "Move a virtual environment to another scope, keeping its Python and packages")]
+    Move(MoveArgs),
+    #[clap(
+        about = "Recreate a virtual environment in place with the same Python and its recorded spec or installed packages"
+    )]
+    Rebuild(RebuildArgs),
+    #[clap(about = "Adopt an existing virtual environment, e.g., a hand-made .venv, into a store")]
+    Adopt(AdoptArgs),
+    #[clap(
+        about = "Register an external virtual environment, e.g., on another disk, without moving it"
+    )]
+    Register(RegisterArgs),
+    #[clap(
+        about = "Create a virtual environment from the lock file of another tool, e.g., Pipfile.lock"
+    )]
+    Import(ImportArgs),
+    #[clap(about = "Export the installed packages of an environment, e.g., as pylock.toml")]
+    Export(ExportArgs),
+    #[clap(about = "Print Dockerfile instructions recreating an environment in a container build")]
+    Dockerize(DockerizeArgs),
+    #[clap(about = "Pack a virtual environment into a portable archive of its spec")]
+    Pack(PackArgs),
+    #[clap(about = "Recreate a virtual environment from an archive of `meowda pack`")]
+    Unpack(UnpackArgs),
+    #[clap(
+        about = "Create or update a virtual environment to match a lock file, e.g., pylock.toml"
+    )]
+    Sync(SyncArgs),
+    #[clap(about = "Create or update all virtual environments declared in the `[envs]` config")]
+    SyncAll(SyncAllArgs),
+    #[clap(
+        about = "Print a stable hash of the Python version and requirements of an environment, to key CI caches on"
+    )]
+    CacheKey(CacheKeyArgs),
+    #[clap(about = "Create one virtual environment per Python version concurrently")]
+    CreateMatrix(CreateMatrixArgs),
+    #[command(subcommand)]
+    #[clap(about = "Manage environments across Python versions, e.g., to test a project on each")]
+    Matrix(MatrixCommandsArgs),
+    #[command(subcommand)]
+    #[clap(about = "Label environments with tags, e.g., gpu, to filter `meowda list --tag`")]
+    Tag(TagCommandsArgs),
+    #[clap(about = "Remove environments that have not been used for a while")]
+    Prune(PruneArgs),
+    #[clap(about = "Remove one or more virtual environments")]
     Remove(RemoveArgs),
+    #[clap(about = "Restore a removed virtual environment from the trash of its store")]
+    Restore(RestoreArgs),
+    #[clap(about = "List the virtual environments of all scopes")]
+    List(ListArgs),
+    #[clap(about = "Show who created, removed or changed the environments of the stores, and when")]
+    History(HistoryArgs),
     #[command(subcommand)]
     #[clap(about = "Manage virtual environments")]
     Env(EnvCommandsArgs),
     #[clap(about = "Initialize the shell for Meowda, to get meowda activate/deactivate commands")]
     Init(InitArgs),
+    #[clap(about = "Print the lazy-loading shell hook, or benchmark its shell startup overhead")]
+    Hook(HookArgs),
     #[clap(about = "Activate a virtual environment")]
     Activate(ActivateArgs),
     #[clap(about = "Deactivate the current virtual environment")]
     Deactivate,
+    #[clap(
+        about = "Deactivate the current virtual environment and activate another one in one step"
+    )]
+    Switch(ActivateArgs),
+    #[clap(about = "Spawn a subshell with a virtual environment active, exit it to deactivate")]
+    Shell(ShellArgs),
+    #[clap(
+        about = "Run a script in a cached environment with the dependencies of its inline `# /// script` block (PEP 723)"
+    )]
+    RunScript(RunScriptArgs),
+    #[clap(
+        about = "Run a command of a package in a cached environment with the package, e.g., `meowda x cowsay hello`"
+    )]
+    X(XArgs),
     #[clap(
         about = "Install packages in the current virtual environment (alias for `uv pip install`)"
     )]
     Install(InstallArgs),
+    #[clap(about = "List the tools of the global tools environment, see `install --tools`")]
+    Tools(ToolsArgs),
+    #[command(subcommand)]
+    #[clap(about = "Manage command-line tools installed into isolated environments, like pipx")]
+    Tool(ToolCommandsArgs),
+    #[command(subcommand)]
+    #[clap(about = "Manage launchers running executables of environments without activation")]
+    Shim(ShimCommandsArgs),
     #[clap(
         about = "Uninstall packages from the current virtual environment (alias for `uv pip uninstall`)"
     )]
     Uninstall(UninstallArgs),
+    #[clap(
+        about = "Install packages in the current virtual environment and record them in its spec"
+    )]
+    Add(AddArgs),
+    #[clap(
+        about = "Uninstall packages from the current virtual environment and drop them from its spec"
+    )]
+    RemoveDep(RemoveDepArgs),
     #[clap(about = "Link a project to the virtual environment")]
     Link(LinkArgs),
     #[clap(about = "Unlink a project from the virtual environment")]
     Unlink(UnlinkArgs),
+    #[clap(about = "Print the directory, Python executable and bin directory of an environment")]
+    Which(WhichArgs),
+    #[clap(
+        about = "Show the details of an environment: Python, size, dates, packages and metadata"
+    )]
+    Info(InfoArgs),
+    #[clap(about = "Compare the Python versions and installed packages of two environments")]
+    Diff(DiffArgs),
+    #[clap(
+        about = "Check that the installed packages of an environment satisfy a requirements file, e.g., for CI"
+    )]
+    Check(CheckArgs),
+    #[clap(about = "Print the active environment, fast and lock-free for prompt integrations")]
+    Status(StatusArgs),
+    #[clap(about = "Show the stores environments are resolved from and what moved them")]
+    Stores(StoresArgs),
+    #[clap(about = "Write an .envrc activating an environment for direnv")]
+    Direnv(DirenvArgs),
+    #[clap(about = "Point VS Code at an environment in .vscode/settings.json")]
+    Vscode(VscodeArgs),
+    #[clap(about = "Print the Python executable of an environment, e.g., for build scripts")]
+    PythonPath(PythonPathArgs),
+    #[clap(about = "Search every store for environments matching a name or glob pattern")]
+    Locate(LocateArgs),
+    #[clap(about = "Print everything an editor plugin needs as one JSON document")]
+    CompletionData(CompletionDataArgs),
+    #[clap(about = "Follow environment change events, e.g., for desktop integrations")]
+    WatchEvents(WatchEventsArgs),
+    #[clap(
+        about = "Answer list, status and resolve-env queries over JSON-RPC, e.g., for prompts and editors"
+    )]
+    Daemon(DaemonArgs),
+    #[clap(about = "Check that the current project is ready to use, with fix suggestions")]
+    VerifySetup(VerifySetupArgs),
+    #[clap(about = "Diagnose the meowda installation and configuration")]
+    Doctor(DoctorArgs),
+    #[clap(about = "Show a detailed explanation of a meowda error code")]
+    Explain(ExplainArgs),
+    #[command(subcommand)]
+    #[clap(about = "Create fake environments for hermetic tests, without uv or the network")]
+    TestFixture(TestFixtureCommandsArgs),
     #[clap(name = "generate-init-script", hide = true)]
     _GenerateInitScript,
     #[clap(name = "detect-activate-venv-path", hide = true)]
     _DetectActivateVenvPath(ActivateArgs),
 }
 
+impl Commands {
+    /// Whether the command runs on every prompt or directory change, so it must stay fast
+    /// and must not wait on locks, e.g., for migrating stores.
+    pub fn is_hot_path(&self) -> bool {
+        matches!(
+            self,
+            Commands::Status(_) | Commands::Hook(_) | Commands::_DetectActivateVenvPath(_)
+        )
+    }
+}
+
 #[derive(Debug, Parser, PartialEq)]
 pub struct CreateArgs {
+    #[arg(
+        long,
+        value_enum,
+        help = "Architecture of the interpreter, e.g., arm64 or x86_64 on Apple Silicon (uv backend only)"
+    )]
+    pub arch: Option<Arch>,
+    #[arg(value_parser = parse_env_name, help = "Name of the virtual environment")]
+    pub name: String,
+    #[arg(short, long, value_parser = parse_python, help = "Python version/path to use (default: 3.14), e.g., 3.12 or pypy@3.10")]
+    pub python: Option<String>,
+    #[arg(short, long, help = "Clear existing virtual environment")]
+    pub clear: bool,
+    #[arg(
+        long,
+        overrides_with = "no_seed",
+        help = "Install seed packages (e.g., pip) into the environment (default)"
+    )]
+    pub seed: bool,
+    #[arg(long, overrides_with = "seed", help = "Do not install seed packages")]
+    pub no_seed: bool,
+    #[arg(
+        long,
+        help = "Give the environment access to the packages of the base interpreter"
+    )]
+    pub system_site_packages: bool,
+    #[arg(
+        long,
+        value_name = "TEXT",
+        help = "Prompt prefix of the activated environment (default: its name)"
+    )]
+    pub prompt: Option<String>,
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Package index recorded in the environment and used by all its installs, e.g., a private registry"
+    )]
+    pub index_url: Option<String>,
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Extra package index recorded in the environment and used by all its installs, can be repeated"
+    )]
+    pub extra_index_url: Vec<String>,
+    #[arg(
+        long,
+        help = "Ignore the `[create.local]`/`[create.global]` defaults from config"
+    )]
+    pub no_defaults: bool,
+    #[arg(
+        long,
+        help = "Print the commands and filesystem operations without performing them"
+    )]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Pinned pip requirements, `name==version` per line.
+    Requirements,
+    /// PEP 751 lock file.
+    Pylock,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ExportArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[arg(
+        long = "as",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "requirements",
+        help = "Format of the written file, `--format` selects the output format of reports instead"
+    )]
+    pub export_format: ExportFormat,
+    #[arg(
+        short,
+        long,
+        help = "File to write, e.g., pylock.toml (default: stdout)"
+    )]
+    pub output: Option<String>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ImportArgs {
+    #[arg(
+        long = "from",
+        value_name = "LOCK_FILE",
+        help = "Lock file to import, e.g., Pipfile.lock or pylock.toml"
+    )]
+    pub source: String,
+    #[arg(long, value_parser = parse_env_name, help = "Name of the virtual environment")]
+    pub name: String,
+    #[arg(
+        long,
+        help = "Also import development packages, e.g., the `develop` section of Pipfile.lock or the extras and dependency groups of pylock.toml"
+    )]
+    pub dev: bool,
+    #[arg(
+        short,
+        long,
+        value_parser = parse_python,
+        help = "Python version/path to use (default: the version required by the lock file)"
+    )]
+    pub python: Option<String>,
+    #[arg(short, long, help = "Clear existing virtual environment")]
+    pub clear: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct PackArgs {
     #[arg(help = "Name of the virtual environment")]
     pub name: String,
-    #[arg(short, long, help = "Python version/path to use (default: 3.14)")]
+    #[arg(
+        short,
+        long,
+        help = "Archive to write, compressed by extension, e.g., .tar.zst or .tar.gz (default: <name>.tar.zst)"
+    )]
+    pub output: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Bundle the wheels of all packages, to unpack on machines without network access"
+    )]
+    pub with_wheels: bool,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct UnpackArgs {
+    #[arg(help = "Archive written by `meowda pack`")]
+    pub archive: PathBuf,
+    #[arg(
+        long,
+        value_parser = parse_env_name,
+        help = "Name of the virtual environment (default: the name of the packed one)"
+    )]
+    pub name: Option<String>,
+    #[arg(
+        short,
+        long,
+        value_parser = parse_python,
+        help = "Python version/path to use (default: the version of the packed environment)"
+    )]
     pub python: Option<String>,
     #[arg(short, long, help = "Clear existing virtual environment")]
     pub clear: bool,
     #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
     pub scope: ScopeArgs,
 }
 
 #[derive(Debug, Parser, PartialEq)]
-pub struct ForkArgs {
+pub struct CreateMatrixArgs {
+    #[arg(
+        short,
+        long,
+        required = true,
+        value_delimiter = ',',
+        value_parser = parse_python,
+        help = "Comma-separated Python versions/paths, e.g., 3.10,3.11,3.12"
+    )]
+    pub python: Vec<String>,
+    #[arg(
+        long,
+        help = "Prefix of the environment names, the Python version is appended, e.g., test- creates test-3.12"
+    )]
+    pub name_prefix: String,
+    #[arg(short, long, help = "Clear existing virtual environments")]
+    pub clear: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct RestoreArgs {
+    #[arg(
+        required_unless_present = "list",
+        conflicts_with = "list",
+        help = "Name of the removed virtual environment, its latest removal is restored"
+    )]
+    pub name: Option<String>,
+    #[arg(long, help = "List the removed virtual environments in the trash")]
+    pub list: bool,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct PruneArgs {
+    #[arg(
+        long,
+        value_parser = parse_age,
+        help = "Remove environments not activated or changed for this long, e.g., 90d, 12w or 36h"
+    )]
+    pub unused_for: u64,
+    #[arg(long, help = "Only list the environments that would be removed")]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum TagCommandsArgs {
+    #[clap(about = "Add tags to a virtual environment")]
+    Add(TagArgs),
+    #[clap(about = "Remove tags from a virtual environment")]
+    Remove(TagArgs),
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct TagArgs {
     #[arg(help = "Name of the virtual environment")]
     pub name: String,
+    #[arg(required = true, value_parser = parse_tag, help = "Tags, e.g., gpu ml")]
+    pub tags: Vec<String>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+fn parse_python(python: &str) -> Result<String, String> {
+    validate_python_request(python)?;
+    Ok(python.to_string())
+}
+
+fn parse_tag(tag: &str) -> Result<String, String> {
+    if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err("tags must be non-empty and contain no whitespace or commas".to_string());
+    }
+    Ok(tag.to_string())
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum MatrixCommandsArgs {
+    #[clap(
+        about = "Create `<name>-py<version>` for each Python version concurrently, e.g., app-py312"
+    )]
+    Create(MatrixCreateArgs),
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct MatrixCreateArgs {
+    #[arg(help = "Base name of the environments, the Python version is appended")]
+    pub name: String,
+    #[arg(
+        short,
+        long,
+        required = true,
+        value_delimiter = ',',
+        value_parser = parse_python,
+        help = "Comma-separated Python versions, e.g., 3.10,3.11,3.12"
+    )]
+    pub pythons: Vec<String>,
+    #[arg(
+        short,
+        long,
+        help = "Requirements file installed into each environment"
+    )]
+    pub requirements: Option<String>,
+    #[arg(short, long, help = "Clear existing virtual environments")]
+    pub clear: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct CacheKeyArgs {
+    #[arg(
+        long,
+        required_unless_present = "requirements",
+        conflicts_with_all = ["requirements", "python"],
+        help = "Name of an environment declared in the `[envs]` config"
+    )]
+    pub name: Option<String>,
+    #[arg(
+        short,
+        long,
+        help = "Requirements files, instead of a declared environment"
+    )]
+    pub requirements: Vec<PathBuf>,
+    #[arg(short, long, help = "Python version of the requirements")]
+    pub python: Option<String>,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct SyncArgs {
+    #[arg(value_parser = parse_env_name, help = "Name of the virtual environment")]
+    pub name: String,
+    #[arg(
+        long = "from",
+        value_name = "LOCK_FILE",
+        help = "Lock file to sync to, e.g., pylock.toml or Pipfile.lock"
+    )]
+    pub source: String,
+    #[arg(
+        long,
+        help = "Also install development packages, e.g., the `develop` section of Pipfile.lock or the extras and dependency groups of pylock.toml"
+    )]
+    pub dev: bool,
+    #[arg(
+        short,
+        long,
+        value_parser = parse_python,
+        help = "Python version/path to use (default: the version required by the lock file)"
+    )]
+    pub python: Option<String>,
+    #[arg(
+        long,
+        help = "Print the commands and filesystem operations without performing them"
+    )]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct SyncAllArgs {
+    #[arg(
+        long,
+        help = "Print the commands and filesystem operations without performing them"
+    )]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ForkArgs {
+    #[arg(value_parser = parse_env_name, help = "Name of the virtual environment")]
+    pub name: String,
     #[arg(
         long = "from",
         value_name = "SOURCE",
@@ -78,13 +619,70 @@ pub struct ForkArgs {
     #[arg(short, long, help = "Clear existing virtual environment")]
     pub clear: bool,
     #[clap(flatten)]
+    pub confirm: YesArgs,
+    #[clap(flatten)]
     pub scope: ScopeArgs,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MoveTarget {
+    Local,
+    Global,
+}
+
 #[derive(Debug, Parser, PartialEq)]
-pub struct RemoveArgs {
-    #[arg(help = "Name of the virtual environment to remove")]
+pub struct MoveArgs {
+    #[arg(help = "Name of the virtual environment to move, searched outside the target scope")]
+    pub name: String,
+    #[arg(long, value_enum, help = "Scope to move the virtual environment to")]
+    pub to: MoveTarget,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct AdoptArgs {
+    #[arg(help = "Path to the virtual environment to adopt, e.g., ./.venv")]
+    pub path: PathBuf,
+    #[arg(long, value_parser = parse_env_name, help = "Name of the virtual environment in the store")]
     pub name: String,
+    #[arg(
+        long = "move",
+        help = "Move the environment into the store instead of linking it, only for relocatable environments"
+    )]
+    pub move_env: bool,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct RegisterArgs {
+    #[arg(help = "Path to the virtual environment to register")]
+    pub path: PathBuf,
+    #[arg(long, value_parser = parse_env_name, help = "Name of the virtual environment in the store")]
+    pub name: String,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct RemoveArgs {
+    #[arg(
+        required_unless_present = "all",
+        conflicts_with = "all",
+        help = "Names of the virtual environments to remove"
+    )]
+    pub names: Vec<String>,
+    #[arg(
+        long,
+        help = "Remove all virtual environments of the store (global unless --local is passed)"
+    )]
+    pub all: bool,
+    #[arg(
+        long,
+        help = "Print the commands and filesystem operations without performing them"
+    )]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub confirm: YesArgs,
     #[clap(flatten)]
     pub scope: ScopeArgs,
 }
@@ -93,6 +691,54 @@ pub struct RemoveArgs {
 pub struct InitArgs {
     #[arg(help = "Path to the shell profile to inject the initialization script")]
     pub shell_profile: String,
+    #[arg(
+        long,
+        help = "Remove the initialization script from the shell profile instead"
+    )]
+    pub uninstall: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct HookArgs {
+    #[arg(long, help = "Measure the shell startup latency added by the hook")]
+    pub benchmark: bool,
+    #[arg(
+        long,
+        help = "Shell used for the benchmark (default: detected from $SHELL, then bash)"
+    )]
+    pub shell: Option<String>,
+    #[arg(
+        long,
+        default_value_t = 20,
+        help = "Number of shell startups measured for the benchmark"
+    )]
+    pub iterations: u32,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum TestFixtureCommandsArgs {
+    #[clap(about = "Create fake-but-valid environments (pyvenv.cfg, bin/python stub)")]
+    Create(TestFixtureCreateArgs),
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct TestFixtureCreateArgs {
+    #[arg(required = true, value_parser = parse_env_name, help = "Names of the fixture environments")]
+    pub names: Vec<String>,
+    #[arg(
+        short,
+        long,
+        default_value = "3.12.0",
+        help = "Python version reported by the fixture environments"
+    )]
+    pub python: String,
+    #[arg(
+        long,
+        help = "Store directory to create the environments in, instead of the local/global store"
+    )]
+    pub dir: Option<String>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
 }
 
 #[derive(Debug, Subcommand, PartialEq)]
@@ -101,16 +747,73 @@ pub enum EnvCommandsArgs {
     Create(CreateArgs),
     #[clap(about = "Fork a virtual environment from an existing environment or Python executable")]
     Fork(ForkArgs),
-    #[clap(about = "Remove a virtual environment")]
+    #[clap(about = "Remove one or more virtual environments")]
     Remove(RemoveArgs),
     #[clap(about = "List all virtual environments")]
     List(ListArgs),
     #[clap(about = "Show directory of the virtual environment store")]
     Dir(DirArgs),
+    #[clap(about = "Freeze a virtual environment, refusing further package changes")]
+    Freeze(FreezeArgs),
+    #[clap(about = "Thaw a frozen virtual environment, allowing package changes again")]
+    Thaw(FreezeArgs),
 }
 
 #[derive(Debug, Parser, PartialEq)]
 pub struct ListArgs {
+    #[arg(
+        long,
+        value_enum,
+        help = "Sort all environments by name, or by size, creation or last activation, largest/newest first"
+    )]
+    pub sort: Option<ListSort>,
+    #[arg(
+        long,
+        help = "Only list environments whose name matches the glob, e.g., 'ml-*'"
+    )]
+    pub filter: Option<String>,
+    #[arg(
+        long,
+        help = "Only list environments of this Python version, e.g., 3.12"
+    )]
+    pub python: Option<String>,
+    #[arg(
+        long,
+        help = "Only list environments with this tag, repeat to require several"
+    )]
+    pub tag: Vec<String>,
+    #[arg(
+        long,
+        help = "Also list conda environments (read-only), activated with `meowda activate conda:<name>`"
+    )]
+    pub include_conda: bool,
+    #[arg(
+        short,
+        long,
+        action = clap::ArgAction::Count,
+        help = "Also show the Python implementation of each environment, e.g., CPython or PyPy"
+    )]
+    pub verbose: u8,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListSort {
+    Name,
+    Size,
+    Created,
+    LastUsed,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct HistoryArgs {
+    #[arg(long, help = "Only show the operations on this environment")]
+    pub name: Option<String>,
+    #[arg(long, help = "Only show the most recent N operations")]
+    pub limit: Option<usize>,
+    #[arg(long, help = "Print one JSON object per operation")]
+    pub json: bool,
     #[clap(flatten)]
     pub scope: ScopeArgs,
 }
@@ -122,15 +825,107 @@ pub struct DirArgs {
 }
 
 #[derive(Debug, Parser, PartialEq)]
-pub struct ActivateArgs {
-    #[arg(help = "Name of the virtual environment to activate")]
+pub struct FreezeArgs {
+    #[arg(help = "Name of the virtual environment")]
+    pub name: String,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct RebuildArgs {
+    #[arg(help = "Name of the virtual environment")]
     pub name: String,
     #[clap(flatten)]
     pub scope: ScopeArgs,
 }
 
+#[derive(Debug, Parser, PartialEq)]
+pub struct ActivateArgs {
+    #[arg(help = "Name of the virtual environment to activate (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[arg(
+        long,
+        help = "Print the activation commands instead, for `eval \"$(meowda activate <name> --print)\"` without `meowda init`"
+    )]
+    pub print: bool,
+    #[arg(
+        long,
+        value_enum,
+        requires = "print",
+        help = "Shell syntax of the printed commands (default: detected from $SHELL)"
+    )]
+    pub shell: Option<Shell>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ShellArgs {
+    #[arg(
+        help = "Name of the virtual environment to activate in the subshell (default: `default_env` in config)"
+    )]
+    pub name: Option<String>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Sh,
+    Fish,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct RunScriptArgs {
+    #[arg(
+        long,
+        help = "Recreate the environment of the script, e.g., to pick up new releases"
+    )]
+    pub refresh: bool,
+    #[arg(help = "Path to the Python script")]
+    pub script: String,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    #[clap(help = "Arguments passed to the script")]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct XArgs {
+    #[arg(
+        long,
+        help = "Package providing the command, e.g., httpie for `http` (default: the command)"
+    )]
+    pub from: Option<String>,
+    #[arg(short, long, value_parser = parse_python, help = "Python version to use, e.g., 3.12 or pypy@3.10")]
+    pub python: Option<String>,
+    #[arg(
+        long,
+        help = "Recreate the environment of the package, e.g., to pick up new releases"
+    )]
+    pub refresh: bool,
+    #[arg(
+        help = "Command to run, or a requirement of the package named like it, e.g., ruff==0.5.0"
+    )]
+    pub command: String,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    #[clap(help = "Arguments passed to the command")]
+    pub args: Vec<String>,
+}
+
 #[derive(Debug, Parser, PartialEq)]
 pub struct InstallArgs {
+    #[arg(
+        long,
+        help = "Install into the global tools environment (created on demand), whose entry points the shell hook puts on PATH"
+    )]
+    pub tools: bool,
+    #[arg(long, help = "Freeze the environment after a successful install")]
+    pub freeze_env: bool,
+    #[arg(long, help = "Allow changing a frozen environment, and thaw it")]
+    pub thaw: bool,
     #[arg(trailing_var_arg = true)]
     #[arg(allow_hyphen_values = true)]
     #[clap(
@@ -139,8 +934,92 @@ pub struct InstallArgs {
     pub extra_args: Vec<String>,
 }
 
+#[derive(Debug, Parser, PartialEq)]
+pub struct ToolsArgs {
+    #[arg(long, help = "Only print the bin directory of the tools environment")]
+    pub bin_dir: bool,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum ToolCommandsArgs {
+    #[clap(about = "Install a tool into an environment of its own and expose its commands")]
+    Install(ToolInstallArgs),
+    #[clap(about = "List the installed tools and their commands")]
+    List(ToolListArgs),
+    #[clap(about = "Upgrade a tool within the requirement it was installed with")]
+    Upgrade(ToolNameArgs),
+    #[clap(about = "Uninstall a tool and remove its commands")]
+    Uninstall(ToolNameArgs),
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ToolInstallArgs {
+    #[arg(help = "Requirement of the tool, e.g., ruff or ruff==0.5.0")]
+    pub requirement: String,
+    #[arg(short, long, value_parser = parse_python, help = "Python version/path to use (default: 3.14), e.g., 3.12 or pypy@3.10")]
+    pub python: Option<String>,
+    #[arg(long, help = "Reinstall the tool if it is already installed")]
+    pub force: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ToolListArgs {
+    #[arg(
+        long,
+        help = "Only print the directory of the commands, which the shell hook adds to PATH"
+    )]
+    pub shims_dir: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ToolNameArgs {
+    #[arg(help = "Name of the tool")]
+    pub name: String,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum ShimCommandsArgs {
+    #[clap(about = "Add launchers of executables of an environment, e.g., black")]
+    Add(ShimAddArgs),
+    #[clap(about = "List the launchers and the environments they run")]
+    List(ShimListArgs),
+    #[clap(about = "Remove launchers")]
+    Remove(ShimRemoveArgs),
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ShimAddArgs {
+    #[arg(help = "Name of the virtual environment")]
+    pub env: String,
+    #[arg(required = true, help = "Executables of the environment, e.g., black")]
+    pub commands: Vec<String>,
+    #[arg(long, help = "Replace launchers of the same names")]
+    pub force: bool,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ShimListArgs {
+    #[arg(
+        long,
+        help = "Only print the directory of the launchers, which the shell hook adds to PATH"
+    )]
+    pub bin_dir: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ShimRemoveArgs {
+    #[arg(required = true, help = "Names of the launchers")]
+    pub names: Vec<String>,
+}
+
 #[derive(Debug, Parser, PartialEq)]
 pub struct UninstallArgs {
+    #[arg(long, help = "Freeze the environment after a successful uninstall")]
+    pub freeze_env: bool,
+    #[arg(long, help = "Allow changing a frozen environment, and thaw it")]
+    pub thaw: bool,
     #[arg(trailing_var_arg = true)]
     #[arg(allow_hyphen_values = true)]
     #[clap(
@@ -149,6 +1028,29 @@ pub struct UninstallArgs {
     pub extra_args: Vec<String>,
 }
 
+#[derive(Debug, Parser, PartialEq)]
+pub struct AddArgs {
+    #[arg(long, help = "Freeze the environment after a successful install")]
+    pub freeze_env: bool,
+    #[arg(long, help = "Allow changing a frozen environment, and thaw it")]
+    pub thaw: bool,
+    #[arg(
+        required = true,
+        help = "Requirements to install and record, e.g., `requests` or \"django>=4.0\""
+    )]
+    pub requirements: Vec<String>,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct RemoveDepArgs {
+    #[arg(long, help = "Freeze the environment after a successful uninstall")]
+    pub freeze_env: bool,
+    #[arg(long, help = "Allow changing a frozen environment, and thaw it")]
+    pub thaw: bool,
+    #[arg(required = true, help = "Names of the packages to uninstall and drop")]
+    pub names: Vec<String>,
+}
+
 #[derive(Debug, Parser, PartialEq)]
 pub struct LinkArgs {
     #[arg(help = "Name of project to link")]
@@ -163,28 +1065,379 @@ pub struct UnlinkArgs {
     pub name: String,
 }
 
+#[derive(Debug, Parser, PartialEq)]
+pub struct WhichArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[arg(long, help = "Print the paths as JSON")]
+    pub json: bool,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct VscodeArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct DockerizeArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[arg(
+        long,
+        help = "Print a complete Dockerfile based on the python image instead of a fragment"
+    )]
+    pub full: bool,
+    #[arg(
+        short,
+        long,
+        help = "File to write, e.g., Dockerfile (default: stdout)"
+    )]
+    pub output: Option<String>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct DirenvArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[arg(long, help = "Print the .envrc block instead of writing it")]
+    pub print: bool,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct StatusArgs {
+    #[arg(
+        long,
+        conflicts_with = "json",
+        help = "Print `name<TAB>scope<TAB>python`, or nothing if no environment is active"
+    )]
+    pub porcelain: bool,
+    #[arg(long, help = "Print the active environment as JSON, `null` if none")]
+    pub json: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct DaemonArgs {
+    #[arg(
+        long,
+        help = "Unix socket (named pipe on Windows) to listen on [default: daemon.sock in the meowda state directory]"
+    )]
+    pub socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct StoresArgs {
+    #[arg(long, help = "Print the overrides and stores as JSON")]
+    pub json: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct InfoArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[arg(long, help = "Print the details as JSON")]
+    pub json: bool,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct PythonPathArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct CheckArgs {
+    #[arg(help = "Name of the virtual environment (default: `default_env` in config)")]
+    pub name: Option<String>,
+    #[arg(
+        long,
+        help = "Requirements file whose specifiers the installed versions must satisfy"
+    )]
+    pub against: String,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct DiffArgs {
+    #[arg(help = "Name of the environment to compare against")]
+    pub left: String,
+    #[arg(help = "Name of the environment to compare")]
+    pub right: String,
+    #[clap(flatten)]
+    pub scope: ScopeArgs,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct LocateArgs {
+    #[arg(help = "Name or glob pattern (supports `*` and `?`) of the environments to locate")]
+    pub pattern: String,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct DoctorArgs {
+    #[arg(
+        long,
+        help = "Remove stale locks left behind by crashed meowda processes"
+    )]
+    pub fix_locks: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct CompletionDataArgs {
+    #[arg(long, help = "Ignore the cached document and rebuild it")]
+    pub no_cache: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct WatchEventsArgs {
+    #[arg(
+        long,
+        help = "Print the events already recorded before following new ones"
+    )]
+    pub from_start: bool,
+    #[arg(long, help = "Print the raw JSON lines")]
+    pub json: bool,
+    #[arg(long, help = "Print the current index snapshot and exit")]
+    pub snapshot: bool,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct VerifySetupArgs {
+    #[arg(
+        long,
+        help = "Shell profile to check for the shell integration (default: detected from $SHELL)"
+    )]
+    pub shell_profile: Option<String>,
+}
+
+#[derive(Debug, Parser, PartialEq)]
+pub struct ExplainArgs {
+    #[arg(help = "Error code to explain, e.g., M001 (lists all error codes if omitted)")]
+    pub code: Option<String>,
+}
+
+/// The `--yes` flag of the commands asking for a confirmation before destroying anything.
+#[derive(Debug, Parser, PartialEq)]
+pub struct YesArgs {
+    #[arg(
+        short,
+        long,
+        help = "Skip the confirmation prompt, e.g., for automation"
+    )]
+    pub yes: bool,
+}
+
 #[derive(Debug, Parser, PartialEq)]
 pub struct ScopeArgs {
-    #[arg(long, help = "Select local virtual environment")]
+    #[arg(
+        short,
+        long,
+        conflicts_with = "global",
+        help = "Select local virtual environment"
+    )]
     pub local: bool,
-    #[arg(long, help = "Select global virtual environment")]
+    #[arg(short, long, help = "Select global virtual environment")]
     pub global: bool,
+    #[arg(
+        long,
+        value_parser = DefaultScope::from_str,
+        conflicts_with_all = ["local", "global"],
+        help = "Select the scope: local, global or auto, the nearest local store up the tree if any, the global store otherwise (default: `default_scope` in config)"
+    )]
+    pub scope: Option<DefaultScope>,
 }
 
 impl ScopeArgs {
     pub fn try_into_scope_type(&self) -> anyhow::Result<ScopeType> {
         if self.local && self.global {
-            return Err(anyhow::anyhow!(
-                "Cannot specify both local and global scopes"
-            ));
+            return Err(ErrorCode::InvalidScope.err("Cannot specify both local and global scopes"));
+        }
+        match (self.local, self.global, self.scope) {
+            (true, _, _) | (_, _, Some(DefaultScope::Local)) => Ok(ScopeType::Local),
+            (_, true, _) | (_, _, Some(DefaultScope::Global)) => Ok(ScopeType::Global),
+            (_, _, Some(DefaultScope::Auto)) => Ok(ScopeType::Auto),
+            _ => Ok(ScopeType::Unspecified),
         }
-        if !self.local && !self.global {
-            // Unspecified scope
-            return Ok(ScopeType::Unspecified);
+    }
+}
+
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Files meowda keeps next to the environments in a store.
+const STORE_FILE_NAMES: [&str; 2] = [HISTORY_FILE_NAME, STORE_FILE_NAME];
+
+/// Checks that `name` is a single portable path component, usable as an environment directory.
+pub fn validate_env_name(name: &str) -> anyhow::Result<()> {
+    let reason = if name.is_empty() {
+        Some("it is empty")
+    } else if name.contains(['/', '\\']) || name == ".." {
+        Some("it contains a path separator or `..`")
+    } else if name.starts_with('.') {
+        Some("it starts with `.`")
+    } else if name
+        .chars()
+        .any(|c| c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*'))
+    {
+        Some("it contains characters invalid on Windows")
+    } else if name.ends_with(['.', ' ']) {
+        Some("it ends with `.` or a space")
+    } else if WINDOWS_RESERVED_NAMES.iter().any(|reserved| {
+        name.split('.')
+            .next()
+            .unwrap_or(name)
+            .eq_ignore_ascii_case(reserved)
+    }) {
+        Some("it is reserved on Windows")
+    } else if STORE_FILE_NAMES.contains(&name) {
+        Some("it names a file of the store")
+    } else {
+        None
+    };
+    match reason {
+        Some(reason) => Err(ErrorCode::InvalidEnvName.err(format!(
+            "Invalid virtual environment name '{name}': {reason}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn parse_env_name(name: &str) -> anyhow::Result<String> {
+    validate_env_name(name)?;
+    Ok(name.to_string())
+}
+
+/// Parses an age like `90d`, `12w`, `36h` or `30m` into seconds.
+pub fn parse_age(age: &str) -> anyhow::Result<u64> {
+    let unit_start = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (amount, unit) = age.split_at(unit_start);
+    let amount: u64 = amount
+        .parse()
+        .with_context(|| format!("Invalid age '{age}', expected e.g. 90d"))?;
+    let unit_secs = match unit {
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => anyhow::bail!("Invalid age '{age}', the unit must be one of m, h, d and w"),
+    };
+    amount
+        .checked_mul(unit_secs)
+        .with_context(|| format!("Invalid age '{age}', it is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_env_name() {
+        let cases = [
+            ("my-project", true),
+            ("test-3.12", true),
+            ("py_env.1", true),
+            ("", false),
+            ("../evil", false),
+            ("..", false),
+            ("a/b", false),
+            ("a\\b", false),
+            (".hidden", false),
+            ("what?", false),
+            ("tab\tname", false),
+            ("trailing.", false),
+            ("con", false),
+            ("NUL.txt", false),
+            ("console", true),
+            ("history.jsonl", false),
+            ("store.toml", false),
+        ];
+        for (name, valid) in cases {
+            assert_eq!(validate_env_name(name).is_ok(), valid, "{name:?}");
         }
-        if self.local {
-            return Ok(ScopeType::Local);
+    }
+
+    #[test]
+    fn parse_age_supports_units() {
+        assert_eq!(parse_age("90d").unwrap(), 90 * 24 * 60 * 60);
+        assert_eq!(parse_age("2w").unwrap(), 14 * 24 * 60 * 60);
+        assert_eq!(parse_age("36h").unwrap(), 36 * 60 * 60);
+        assert!(parse_age("90").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+        assert!(parse_age(&format!("{}w", u64::MAX / 2)).is_err());
+    }
+
+    #[test]
+    fn create_args_reject_invalid_names() {
+        assert!(Args::try_parse_from(["meowda", "create", "../evil"]).is_err());
+        assert!(Args::try_parse_from(["meowda", "create", "good-name"]).is_ok());
+    }
+
+    #[test]
+    fn scope_flags_have_exclusive_short_forms() -> anyhow::Result<()> {
+        use clap::CommandFactory;
+
+        Args::command().debug_assert();
+        let scope = |args: &[&str]| -> anyhow::Result<ScopeType> {
+            let args = Args::try_parse_from(["meowda", "list"].iter().chain(args))?;
+            match args.command {
+                Commands::List(list_args) => list_args.scope.try_into_scope_type(),
+                _ => unreachable!("parsed `meowda list`"),
+            }
+        };
+        assert_eq!(scope(&[])?, ScopeType::Unspecified);
+        assert_eq!(scope(&["-l"])?, ScopeType::Local);
+        assert_eq!(scope(&["--global"])?, ScopeType::Global);
+        assert_eq!(scope(&["--scope", "auto"])?, ScopeType::Auto);
+        assert_eq!(scope(&["--scope", "global"])?, ScopeType::Global);
+        assert!(scope(&["--scope", "auto", "-l"]).is_err());
+        assert!(scope(&["--scope", "nearest"]).is_err());
+        assert!(scope(&["-l", "-g"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn accessible_flag_is_global() {
+        let args = Args::try_parse_from(["meowda", "env", "list", "--accessible"]).unwrap();
+        assert!(args.accessible);
+    }
+
+    #[test]
+    fn format_flag_is_global() {
+        let cases = [
+            (&["meowda", "list"][..], OutputFormat::Table),
+            (
+                &["meowda", "list", "--format", "json"][..],
+                OutputFormat::Json,
+            ),
+            (
+                &["meowda", "--format", "plain", "info"][..],
+                OutputFormat::Plain,
+            ),
+            // `diff --format text` predates the global flag
+            (
+                &["meowda", "diff", "a", "b", "--format", "text"][..],
+                OutputFormat::Table,
+            ),
+        ];
+        for (argv, expected) in cases {
+            let args = Args::try_parse_from(argv).unwrap();
+            assert_eq!(args.format, expected, "{argv:?}");
         }
-        Ok(ScopeType::Global)
     }
 }
@@ -0,0 +1,132 @@
+use crate::cli::args::CacheKeyArgs;
+use crate::cli::sync::declarations_base_dir;
+use crate::config::Config;
+use crate::error::ErrorCode;
+use crate::hash::sha256_hex;
+use crate::venv::normalize_name;
+use anstream::println;
+use anyhow::{Context, Result};
+
+/// Version of the hashed content, bumped when its format changes so old keys miss.
+const CACHE_KEY_VERSION: &str = "meowda-cache-key-v1";
+
+/// The lines of a requirements file which matter for the environment: comments, blank lines,
+/// whitespace and the spelling of project names are dropped, continuations joined.
+fn canonical_lines(content: &str) -> Vec<String> {
+    let content = content.replace("\r\n", "\n").replace("\\\n", " ");
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = if line.trim_start().starts_with('#') {
+                ""
+            } else {
+                line.split(" #").next().unwrap_or_default()
+            };
+            let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+            if line.is_empty() {
+                return None;
+            }
+            let name_end = line
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+                .unwrap_or(line.len());
+            // Options like `--index-url` and paths like `./pkg` keep their spelling
+            if line.starts_with('-') || line[name_end..].starts_with(['/', ':']) {
+                return Some(line);
+            }
+            Some(format!(
+                "{}{}",
+                normalize_name(&line[..name_end]),
+                line[name_end..].replace(' ', "")
+            ))
+        })
+        .collect()
+}
+
+/// A hash of `python` and the content of requirements files, the same for equivalent
+/// requirements in any order.
+fn cache_key_of(python: Option<&str>, requirements: &[String]) -> String {
+    let mut lines = requirements
+        .iter()
+        .flat_map(|content| canonical_lines(content))
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines.dedup();
+    let content = format!(
+        "{CACHE_KEY_VERSION}\npython={}\n{}\n",
+        python.unwrap_or_default().trim(),
+        lines.join("\n")
+    );
+    sha256_hex(content.as_bytes())
+}
+
+/// Prints the cache key of a declared environment or of requirements files. Requirements
+/// files included by `-r` are not followed, pass them too.
+pub fn cache_key(args: CacheKeyArgs, config: &Config) -> Result<()> {
+    let (python, files) = match &args.name {
+        Some(name) => {
+            let declaration = config
+                .envs
+                .as_ref()
+                .and_then(|envs| envs.get(name))
+                .ok_or_else(|| {
+                    ErrorCode::EnvNotFound.err_with_hint(
+                        format!("'{name}' is not declared in the `[envs]` config"),
+                        "declare it in meowda.toml, or pass its --requirements and --python",
+                    )
+                })?;
+            let base_dir = declarations_base_dir()?;
+            let files = declaration
+                .requirements
+                .iter()
+                .map(|path| base_dir.join(path))
+                .collect::<Vec<_>>();
+            (declaration.python.clone(), files)
+        }
+        None => (args.python, args.requirements),
+    };
+    let requirements = files
+        .iter()
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read requirements file '{}'", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    println!("{}", cache_key_of(python.as_deref(), &requirements));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_keys_ignore_formatting_but_not_pins() {
+        let key = cache_key_of(
+            Some("3.12"),
+            &["numpy>=1.20\nrequests==2.32.3\n".to_string()],
+        );
+        let reformatted = cache_key_of(
+            Some("3.12"),
+            &["# deps\r\nRequests == 2.32.3  # http\r\n\r\nNumPy>=1.20\r\n".to_string()],
+        );
+        assert_eq!(key, reformatted);
+        assert_ne!(
+            key,
+            cache_key_of(
+                Some("3.13"),
+                &["numpy>=1.20\nrequests==2.32.3\n".to_string()]
+            )
+        );
+        assert_ne!(
+            key,
+            cache_key_of(
+                Some("3.12"),
+                &["numpy>=1.20\nrequests==2.32.4\n".to_string()]
+            )
+        );
+        assert_eq!(
+            canonical_lines("--index-url https://example.com/simple\n./vendor/Pkg\n"),
+            ["--index-url https://example.com/simple", "./vendor/Pkg"]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use crate::cli::activate::resolve_env_name;
+use crate::cli::args::CheckArgs;
+use crate::cli::utils::{report_check, search_venv};
+use crate::config::Config;
+use crate::store::venv_store::VenvStore;
+use crate::venv::{Requirement, installed_packages, parse_requirement};
+use anstream::println;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::collections::BTreeMap;
+
+/// How an installed environment compares to one requirement.
+#[derive(Debug, PartialEq)]
+enum Compliance {
+    Satisfied(String),
+    Missing,
+    Mismatched(String),
+}
+
+fn check_requirement(
+    requirement: &Requirement,
+    installed: &BTreeMap<String, String>,
+) -> Compliance {
+    match installed.get(&requirement.name) {
+        None => Compliance::Missing,
+        Some(version)
+            if requirement
+                .specifiers
+                .iter()
+                .all(|specifier| specifier.contains(version)) =>
+        {
+            Compliance::Satisfied(version.clone())
+        }
+        Some(version) => Compliance::Mismatched(version.clone()),
+    }
+}
+
+/// Checks that the installed packages of an environment satisfy a requirements file,
+/// failing if any is missing or installed in a version not satisfying its specifiers.
+pub async fn check(args: CheckArgs, config: &Config) -> Result<()> {
+    let env_ref = resolve_env_name(args.name, config)?;
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = search_venv(scope_type, &env_ref)?;
+    let venv_path = VenvStore::from_specified_scope(detected_venv_scope)?.env_path(name);
+    let requirements = std::fs::read_to_string(&args.against)
+        .with_context(|| format!("Failed to read requirements file '{}'", args.against))?;
+    let installed = installed_packages(&venv_path);
+
+    let mut checked = 0;
+    let mut failed = 0;
+    for line in requirements.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(requirement) = parse_requirement(line) else {
+            println!(
+                "{}",
+                format!("- skipped {line}: not a named requirement").dimmed()
+            );
+            continue;
+        };
+        if let Some(marker) = &requirement.marker {
+            println!(
+                "{}",
+                format!("- skipped {line}: the marker `{marker}` is not evaluated").dimmed()
+            );
+            continue;
+        }
+        let specifiers = requirement
+            .specifiers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        checked += 1;
+        match check_requirement(&requirement, &installed) {
+            Compliance::Satisfied(version) if specifiers.is_empty() => {
+                report_check(true, &requirement.name, format!("{version} installed"));
+            }
+            Compliance::Satisfied(version) => report_check(
+                true,
+                &requirement.name,
+                format!("{version} satisfies {specifiers}"),
+            ),
+            Compliance::Missing => {
+                failed += 1;
+                report_check(false, &requirement.name, "not installed");
+            }
+            Compliance::Mismatched(version) => {
+                failed += 1;
+                report_check(
+                    false,
+                    &requirement.name,
+                    format!("{version} does not satisfy {specifiers}"),
+                );
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {checked} requirement(s) not satisfied by '{name}', see {}",
+            args.against
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        format!("'{name}' satisfies all {checked} requirement(s).").green()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirements_are_checked_against_installed_versions() {
+        let installed = BTreeMap::from([
+            ("numpy".to_string(), "1.26.4".to_string()),
+            ("typing-extensions".to_string(), "4.12.2".to_string()),
+        ]);
+        let check = |line: &str| {
+            let requirement = parse_requirement(line).expect("valid requirement");
+            check_requirement(&requirement, &installed)
+        };
+
+        assert_eq!(
+            check("numpy>=1.20,<2"),
+            Compliance::Satisfied("1.26.4".to_string())
+        );
+        assert_eq!(
+            check("numpy>=2"),
+            Compliance::Mismatched("1.26.4".to_string())
+        );
+        assert_eq!(
+            check("Typing_Extensions"),
+            Compliance::Satisfied("4.12.2".to_string())
+        );
+        assert_eq!(check("torch"), Compliance::Missing);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::cli::args::CompletionDataArgs;
+use crate::config::Config;
+use crate::store::backend::path_slug;
+use crate::store::venv_store::{ScopeType, VenvStore, get_candidate_scopes};
+use crate::venv::{ActiveEnvMatcher, VenvService, python_path_in_venv};
+use anyhow::{Context, Result};
+use etcetera::BaseStrategy;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Bumped on incompatible changes of the document format.
+const COMPLETION_DATA_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct CompletionData {
+    version: u32,
+    cwd: PathBuf,
+    /// The activated environment, i.e., `VIRTUAL_ENV`.
+    active_env: Option<PathBuf>,
+    /// `default_env` of the config resolved for the current directory.
+    default_env: Option<String>,
+    stores: Vec<StoreData>,
+    envs: Vec<EnvData>,
+}
+
+#[derive(Debug, Serialize)]
+struct StoreData {
+    scope: String,
+    path: PathBuf,
+    ready: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct EnvData {
+    name: String,
+    scope: String,
+    path: PathBuf,
+    python: PathBuf,
+    python_version: Option<String>,
+    active: bool,
+}
+
+fn cache_path(cwd: &Path) -> Option<PathBuf> {
+    etcetera::base_strategy::choose_base_strategy()
+        .ok()
+        .map(|dirs| {
+            dirs.cache_dir()
+                .join("meowda")
+                .join("completion-data")
+                .join(format!("{}.json", path_slug(cwd)))
+        })
+}
+
+/// Summarizes everything the document depends on, creating or removing an
+/// environment updates the modification time of its store.
+fn fingerprint(stores: &[VenvStore], active_env: Option<&PathBuf>, config: &Config) -> String {
+    let mut parts = vec![format!("v{COMPLETION_DATA_VERSION}")];
+    for store in stores {
+        let mtime = std::fs::metadata(store.path())
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        parts.push(format!("{}={mtime}", store.path().display()));
+    }
+    parts.push(format!("active={active_env:?}"));
+    parts.push(format!("default={:?}", config.default_env));
+    parts.join(";")
+}
+
+/// Builds the document of `stores`, e.g., also for `meowda daemon` queries.
+pub(crate) fn build(
+    stores: &[VenvStore],
+    cwd: PathBuf,
+    active_env: Option<PathBuf>,
+    config: &Config,
+) -> Result<CompletionData> {
+    let mut data = CompletionData {
+        version: COMPLETION_DATA_VERSION,
+        cwd,
+        active_env: active_env.clone(),
+        default_env: config.default_env.clone(),
+        stores: Vec::new(),
+        envs: Vec::new(),
+    };
+    let mut active = ActiveEnvMatcher::new(active_env.as_ref());
+    for store in stores {
+        let scope = store.scope().map(ToString::to_string).unwrap_or_default();
+        data.stores.push(StoreData {
+            scope: scope.clone(),
+            path: store.path().clone(),
+            ready: store.is_ready(),
+        });
+        if !store.is_ready() {
+            continue;
+        }
+        let mut envs = VenvService::list_venvs_matching(store, &mut active)?;
+        envs.sort_by(|a, b| a.name.cmp(&b.name));
+        data.envs.extend(envs.into_iter().map(|env| EnvData {
+            python: python_path_in_venv(&env.path),
+            python_version: env.config.and_then(|config| config.version),
+            name: env.name,
+            scope: scope.clone(),
+            path: env.path,
+            active: env.is_active,
+        }));
+    }
+    Ok(data)
+}
+
+/// Writes the cache atomically, so concurrent readers never need a lock.
+fn write_cache(path: &Path, fingerprint: &str, document: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+    }
+    let tmp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp_path, format!("{fingerprint}\n{document}"))
+        .with_context(|| format!("Failed to write '{}'", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to write '{}'", path.display()))
+}
+
+pub async fn completion_data(args: CompletionDataArgs, config: &Config) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current working directory")?;
+    let stores = get_candidate_scopes(ScopeType::Unspecified)?
+        .into_iter()
+        .map(VenvStore::from_specified_scope)
+        .collect::<Result<Vec<_>>>()?;
+    let active_env = VenvService::detect_current_venv();
+    let fingerprint = fingerprint(&stores, active_env.as_ref(), config);
+    let cache_path = cache_path(&cwd);
+
+    if !args.no_cache
+        && let Some(cached) = cache_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+        && let Some((cached_fingerprint, document)) = cached.split_once('\n')
+        && cached_fingerprint == fingerprint
+    {
+        println!("{document}");
+        return Ok(());
+    }
+
+    let data = build(&stores, cwd, active_env, config)?;
+    let document = serde_json::to_string(&data).context("Failed to serialize completion data")?;
+    if let Some(cache_path) = &cache_path
+        && let Err(e) = write_cache(cache_path, &fingerprint, &document)
+    {
+        tracing::debug!("Failed to cache completion data: {e:#}");
+    }
+    println!("{document}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+
+    #[test]
+    fn fingerprint_changes_with_the_store() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let stores = [VenvStore::from_path(temp.path().join("venvs"))];
+        let config = Config::default();
+        let before = fingerprint(&stores, None, &config);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        create_fixture_store(&stores[0], &["app".to_string()], "3.12.0")?;
+        assert_ne!(fingerprint(&stores, None, &config), before);
+
+        let data = build(&stores, temp.path().to_path_buf(), None, &config)?;
+        assert_eq!(data.envs.len(), 1);
+        assert_eq!(data.envs[0].python_version.as_deref(), Some("3.12.0"));
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+use crate::cli::activate::{detect_venv_path_in, resolve_env_name};
+use crate::cli::args::{DaemonArgs, ScopeArgs};
+use crate::cli::completion_data;
+use crate::cli::status::active_env_at;
+use crate::config::Config;
+use crate::error::{ErrorCode, MeowdaError};
+use crate::interrupt;
+use crate::store::backend::{Discovery, StoreSettings};
+use crate::store::venv_store::{VenvStore, get_candidate_scopes_in};
+use crate::venv::{EnvMetadata, python_path_in_venv};
+use anstream::eprintln;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::{debug, warn};
+
+/// JSON-RPC error codes of the specification, and the one of meowda errors, whose
+/// `data.code` is the meowda error code, e.g., `M001`.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const MEOWDA_ERROR: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Absent for notifications, which get no response.
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Params,
+}
+
+/// Parameters of all methods, the client passes the state of its shell as the daemon has
+/// its own working directory and environment.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Params {
+    /// Working directory local stores are discovered from, the one of the daemon if unset.
+    cwd: Option<PathBuf>,
+    /// `VIRTUAL_ENV` of the client.
+    virtual_env: Option<PathBuf>,
+    /// Environment reference of `resolve-env`, `default_env` if unset.
+    name: Option<String>,
+    /// `local` or `global`, both if unset.
+    scope: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn from_error(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<MeowdaError>() {
+            Some(meowda_error) => RpcError {
+                code: MEOWDA_ERROR,
+                message: meowda_error.message.clone(),
+                data: Some(json!({
+                    "code": meowda_error.code.code(),
+                    "hint": meowda_error.hint,
+                })),
+            },
+            None => RpcError {
+                code: MEOWDA_ERROR,
+                message: format!("{error:#}"),
+                data: ErrorCode::of(error).map(|code| json!({ "code": code.code() })),
+            },
+        }
+    }
+}
+
+struct DaemonState {
+    /// The working directory of the daemon, that of requests without `cwd`.
+    home: PathBuf,
+}
+
+fn parse_scope(scope: Option<&str>) -> Result<ScopeArgs, RpcError> {
+    match scope {
+        None => Ok(ScopeArgs {
+            local: false,
+            global: false,
+            scope: None,
+        }),
+        Some(scope) => match scope.parse() {
+            Ok(scope) => Ok(ScopeArgs {
+                local: false,
+                global: false,
+                scope: Some(scope),
+            }),
+            Err(e) => Err(RpcError::new(INVALID_PARAMS, format!("{e:#}"))),
+        },
+    }
+}
+
+fn status(params: Params) -> Result<Value> {
+    let env = params.virtual_env.and_then(|path| {
+        let frozen = EnvMetadata::load(&path).is_ok_and(|metadata| metadata.frozen);
+        active_env_at(path, frozen)
+    });
+    serde_json::to_value(env).context("Failed to serialize status")
+}
+
+fn list(
+    params: Params,
+    scope: &ScopeArgs,
+    discovery: &Discovery,
+    config: &Config,
+) -> Result<Value> {
+    let stores = get_candidate_scopes_in(discovery, scope.try_into_scope_type()?)?
+        .into_iter()
+        .map(|scope| VenvStore::from_scope_in(discovery, scope))
+        .collect::<Result<Vec<_>>>()?;
+    let data = completion_data::build(&stores, discovery.cwd()?, params.virtual_env, config)?;
+    serde_json::to_value(data).context("Failed to serialize environments")
+}
+
+fn resolve_env(
+    params: Params,
+    scope: &ScopeArgs,
+    discovery: &Discovery,
+    config: &Config,
+) -> Result<Value> {
+    let env_ref = resolve_env_name(params.name, config)?;
+    let (name, path) = detect_venv_path_in(discovery, scope, &env_ref, config)?;
+    Ok(json!({
+        "name": name,
+        "python": python_path_in_venv(&path),
+        "path": path,
+    }))
+}
+
+/// Answers a single request line, `None` for notifications.
+fn handle_line(line: &str, state: &DaemonState) -> Option<Response> {
+    let request = match serde_json::from_str::<Request>(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(Response {
+                jsonrpc: "2.0",
+                id: Value::Null,
+                result: None,
+                error: Some(RpcError::new(PARSE_ERROR, format!("Invalid request: {e}"))),
+            });
+        }
+    };
+    debug!("Daemon request `{}`", request.method);
+    let result = handle_request(request.method.as_str(), request.params, state);
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => Response {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        },
+        Err(error) => Response {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(error),
+        },
+    })
+}
+
+fn handle_request(method: &str, params: Params, state: &DaemonState) -> Result<Value, RpcError> {
+    if method == "status" {
+        // Only reads the given environment, no need to switch the working directory
+        return status(params).map_err(|e| RpcError::from_error(&e));
+    }
+    if !matches!(method, "list" | "resolve-env") {
+        return Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{method}', expected one of: list, status, resolve-env"),
+        ));
+    }
+    let scope = parse_scope(params.scope.as_deref())?;
+    // Requests are served concurrently, so the working directory and the config of the
+    // daemon stay untouched: stores are discovered from `cwd` with the config applying there
+    let cwd = match &params.cwd {
+        Some(cwd) => state.home.join(cwd),
+        None => state.home.clone(),
+    };
+    if !cwd.is_dir() {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("Invalid cwd '{}': not a directory", cwd.display()),
+        ));
+    }
+    let config = Config::load_in(&cwd).map_err(|e| RpcError::from_error(&e))?;
+    let settings = StoreSettings::from_config(&config).map_err(|e| RpcError::from_error(&e))?;
+    let discovery = Discovery::new(cwd, &settings);
+    match method {
+        "list" => list(params, &scope, &discovery, &config),
+        _ => resolve_env(params, &scope, &discovery, &config),
+    }
+    .map_err(|e| RpcError::from_error(&e))
+}
+
+/// Serves newline-delimited JSON-RPC requests of one client until it disconnects.
+async fn serve_connection(stream: impl AsyncRead + AsyncWrite + Unpin, state: Arc<DaemonState>) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => return,
+            Err(e) => {
+                debug!("Daemon client disconnected: {e}");
+                return;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Handlers read stores and config files, off the threads serving connections
+        let handler_state = state.clone();
+        let response =
+            match tokio::task::spawn_blocking(move || handle_line(&line, &handler_state)).await {
+                Ok(Some(response)) => response,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Daemon request handler failed: {e}");
+                    return;
+                }
+            };
+        let mut content = match serde_json::to_string(&response) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Failed to serialize daemon response: {e}");
+                return;
+            }
+        };
+        content.push('\n');
+        if let Err(e) = writer.write_all(content.as_bytes()).await {
+            debug!("Daemon client disconnected: {e}");
+            return;
+        }
+    }
+}
+
+/// The socket of the daemon, `daemon.sock` in the user state directory.
+#[cfg(unix)]
+fn default_socket_path() -> Result<PathBuf> {
+    crate::store::venv_store::user_state_dir()
+        .map(|dir| dir.join("daemon.sock"))
+        .context("Failed to determine user state directory")
+}
+
+/// The named pipe of the daemon, per user as pipes share one global namespace.
+#[cfg(windows)]
+fn default_socket_path() -> Result<PathBuf> {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    Ok(PathBuf::from(format!(r"\\.\pipe\meowda-daemon-{user}")))
+}
+
+#[cfg(unix)]
+async fn serve(socket: &Path, state: Arc<DaemonState>) -> Result<()> {
+    use tokio::net::{UnixListener, UnixStream};
+
+    if socket.exists() {
+        if UnixStream::connect(socket).await.is_ok() {
+            anyhow::bail!(
+                "A meowda daemon is already listening on {}",
+                socket.display()
+            );
+        }
+        // Left behind by a daemon which did not shut down cleanly
+        std::fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket '{}'", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+    }
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to listen on '{}'", socket.display()))?;
+    eprintln!("Listening on {}, press Ctrl-C to stop.", socket.display());
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve_connection(stream, state.clone()));
+                }
+                Err(e) => break Err(e).context("Failed to accept a daemon client"),
+            },
+            _ = interrupt::ctrl_c() => break Ok(()),
+        }
+    };
+    if let Err(e) = std::fs::remove_file(socket) {
+        debug!("Failed to remove '{}': {e}", socket.display());
+    }
+    result
+}
+
+#[cfg(windows)]
+async fn serve(socket: &Path, state: Arc<DaemonState>) -> Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let pipe_name = socket.as_os_str();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(pipe_name)
+        .with_context(|| format!("Failed to listen on '{}'", socket.display()))?;
+    eprintln!("Listening on {}, press Ctrl-C to stop.", socket.display());
+    loop {
+        tokio::select! {
+            connected = server.connect() => {
+                connected.context("Failed to accept a daemon client")?;
+                // A new instance serves the next client while this one is handled
+                let next = ServerOptions::new()
+                    .create(pipe_name)
+                    .with_context(|| format!("Failed to listen on '{}'", socket.display()))?;
+                let client = std::mem::replace(&mut server, next);
+                tokio::spawn(serve_connection(client, state.clone()));
+            }
+            _ = interrupt::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+/// Serves `list`, `status` and `resolve-env` queries over a unix socket (a named pipe on
+/// Windows), so that prompts and editor plugins get answers without starting a process.
+pub async fn daemon(args: DaemonArgs) -> Result<()> {
+    let socket = match args.socket {
+        Some(socket) => std::path::absolute(&socket)
+            .with_context(|| format!("Invalid socket path '{}'", socket.display()))?,
+        None => default_socket_path()?,
+    };
+    let state = Arc::new(DaemonState {
+        home: std::env::current_dir().context("Failed to get current working directory")?,
+    });
+    serve(&socket, state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::{create_fixture_env, create_fixture_store};
+
+    #[test]
+    fn requests_are_answered_as_json_rpc() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let env_path = temp.path().join("app");
+        create_fixture_env(&env_path, "3.12.0")?;
+        let state = DaemonState {
+            home: temp.path().to_path_buf(),
+        };
+        let respond = |line: &str| {
+            handle_line(line, &state)
+                .map(|response| serde_json::to_value(response).unwrap_or_default())
+        };
+
+        let status = serde_json::to_string(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "status",
+            "params": { "virtual_env": env_path },
+        }))?;
+        let response = respond(&status).unwrap_or_default();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["name"], "app");
+        assert_eq!(response["result"]["python"], "3.12.0");
+
+        let cases = [
+            (r#"{"id": 2, "method": "status"}"#, Value::Null),
+            (
+                r#"{"id": 3, "method": "shutdown"}"#,
+                json!(METHOD_NOT_FOUND),
+            ),
+            (
+                r#"{"id": 4, "method": "list", "params": {"scope": "team"}}"#,
+                json!(INVALID_PARAMS),
+            ),
+            ("not json", json!(PARSE_ERROR)),
+        ];
+        for (line, expected) in cases {
+            let response = respond(line).unwrap_or_default();
+            let actual = match response.get("error") {
+                Some(error) => error["code"].clone(),
+                None => response["result"].clone(),
+            };
+            assert_eq!(actual, expected, "request: {line}");
+        }
+        assert!(respond(r#"{"method": "status"}"#).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn requests_use_the_config_of_their_cwd() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let project = temp.path().join("project");
+        let team = temp.path().join("team");
+        create_fixture_store(
+            &VenvStore::from_path(project.join(".meowda").join("venvs")),
+            &["app".to_string()],
+            "3.12.0",
+        )?;
+        create_fixture_env(&team.join("tool"), "3.13.1")?;
+        std::fs::write(
+            project.join("meowda.toml"),
+            format!(
+                "default_env = \"app\"\n[stores]\nteam = {:?}\n",
+                team.display().to_string()
+            ),
+        )?;
+        let state = DaemonState {
+            home: temp.path().to_path_buf(),
+        };
+        let cases = [
+            (json!({ "cwd": project }), project.join(".meowda/venvs/app")),
+            (
+                json!({ "cwd": project, "name": "team:tool" }),
+                team.join("tool"),
+            ),
+        ];
+        for (params, expected) in cases {
+            let request = json!({ "id": 1, "method": "resolve-env", "params": params });
+            let response = handle_line(&request.to_string(), &state)
+                .map(|response| serde_json::to_value(response).unwrap_or_default())
+                .unwrap_or_default();
+            assert_eq!(response["result"]["path"], json!(expected), "{params}");
+        }
+        // Without `cwd`, the daemon's own directory has no such config
+        let request = json!({ "id": 2, "method": "resolve-env" });
+        let response = handle_line(&request.to_string(), &state);
+        assert!(
+            response.is_some_and(|response| response.error.is_some()),
+            "resolved without default_env"
+        );
+        Ok(())
+    }
+}
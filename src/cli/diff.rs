@@ -0,0 +1,211 @@
+use crate::cli::args::DiffArgs;
+use crate::cli::output::{self, OutputFormat};
+use crate::cli::utils::search_venv;
+use crate::store::venv_store::{ScopeType, VenvStore};
+use crate::venv::{EnvConfig, installed_packages};
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Package {
+    name: String,
+    version: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ChangedPackage {
+    name: String,
+    left: String,
+    right: String,
+}
+
+/// How the packages of the right environment differ from those of the left one.
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct PackageDiff {
+    /// Only installed in the right environment.
+    added: Vec<Package>,
+    /// Only installed in the left environment.
+    removed: Vec<Package>,
+    changed: Vec<ChangedPackage>,
+}
+
+impl PackageDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EnvDiff {
+    left: String,
+    right: String,
+    left_python: Option<String>,
+    right_python: Option<String>,
+    #[serde(flatten)]
+    packages: PackageDiff,
+}
+
+fn diff_packages(left: &BTreeMap<String, String>, right: &BTreeMap<String, String>) -> PackageDiff {
+    let mut diff = PackageDiff::default();
+    for (name, version) in left {
+        match right.get(name) {
+            None => diff.removed.push(Package {
+                name: name.clone(),
+                version: version.clone(),
+            }),
+            Some(other) if other != version => diff.changed.push(ChangedPackage {
+                name: name.clone(),
+                left: version.clone(),
+                right: other.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (name, version) in right {
+        if !left.contains_key(name) {
+            diff.added.push(Package {
+                name: name.clone(),
+                version: version.clone(),
+            });
+        }
+    }
+    diff
+}
+
+/// The Python version and installed packages of the environment `env_ref`.
+fn load_env(
+    scope_type: ScopeType,
+    env_ref: &str,
+) -> Result<(Option<String>, BTreeMap<String, String>)> {
+    let (scope, name) = search_venv(scope_type, env_ref)?;
+    let venv_path = VenvStore::from_specified_scope(scope)?.env_path(name);
+    let python = EnvConfig::parse(venv_path.join("pyvenv.cfg"))
+        .ok()
+        .and_then(|config| config.version);
+    Ok((python, installed_packages(&venv_path)))
+}
+
+/// Compares the Python versions and installed packages of two environments.
+pub async fn diff(args: DiffArgs) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (left_python, left) = load_env(scope_type, &args.left)?;
+    let (right_python, right) = load_env(scope_type, &args.right)?;
+    let diff = EnvDiff {
+        left: args.left,
+        right: args.right,
+        packages: diff_packages(&left, &right),
+        left_python,
+        right_python,
+    };
+
+    let or_unknown = |python: &Option<String>| python.clone().unwrap_or_else(|| "?".to_string());
+    match output::format() {
+        OutputFormat::Json => return output::print_json(&diff),
+        OutputFormat::Plain => {
+            // `<kind> <name> <left> <right>`, `-` for the side without the package
+            output::print_plain_row(&[
+                "python",
+                "-",
+                &or_unknown(&diff.left_python),
+                &or_unknown(&diff.right_python),
+            ]);
+            for package in &diff.packages.removed {
+                output::print_plain_row(&["removed", &package.name, &package.version, "-"]);
+            }
+            for package in &diff.packages.added {
+                output::print_plain_row(&["added", &package.name, "-", &package.version]);
+            }
+            for package in &diff.packages.changed {
+                output::print_plain_row(&["changed", &package.name, &package.left, &package.right]);
+            }
+            return Ok(());
+        }
+        OutputFormat::Table => {}
+    }
+
+    if diff.left_python == diff.right_python {
+        println!("Python {} in both", or_unknown(&diff.left_python));
+    } else {
+        println!(
+            "Python {} in '{}', {} in '{}'",
+            or_unknown(&diff.left_python).red(),
+            diff.left,
+            or_unknown(&diff.right_python).green(),
+            diff.right
+        );
+    }
+    if diff.packages.is_empty() {
+        println!("The same packages are installed in both.");
+        return Ok(());
+    }
+    for package in &diff.packages.removed {
+        let line = format!("- {}=={}", package.name, package.version);
+        println!("{}", line.red());
+    }
+    for package in &diff.packages.added {
+        let line = format!("+ {}=={}", package.name, package.version);
+        println!("{}", line.green());
+    }
+    for package in &diff.packages.changed {
+        println!(
+            "{} {} {} -> {}",
+            "~".yellow(),
+            package.name,
+            package.left.red(),
+            package.right.green()
+        );
+    }
+    println!(
+        "{}",
+        format!(
+            "{} only in '{}', {} only in '{}', {} with another version",
+            diff.packages.removed.len(),
+            diff.left,
+            diff.packages.added.len(),
+            diff.right,
+            diff.packages.changed.len()
+        )
+        .dimmed()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_packages_reports_added_removed_and_changed() {
+        let packages = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(name, version)| (name.to_string(), version.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let left = packages(&[("numpy", "1.26.4"), ("pip", "24.0"), ("six", "1.16.0")]);
+        let right = packages(&[("numpy", "2.0.0"), ("pip", "24.0"), ("torch", "2.3.0")]);
+
+        assert_eq!(
+            diff_packages(&left, &right),
+            PackageDiff {
+                added: vec![Package {
+                    name: "torch".to_string(),
+                    version: "2.3.0".to_string(),
+                }],
+                removed: vec![Package {
+                    name: "six".to_string(),
+                    version: "1.16.0".to_string(),
+                }],
+                changed: vec![ChangedPackage {
+                    name: "numpy".to_string(),
+                    left: "1.26.4".to_string(),
+                    right: "2.0.0".to_string(),
+                }],
+            }
+        );
+        assert!(diff_packages(&left, &left).is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::cli::activate::{detect_venv_path, resolve_env_name};
+use crate::cli::args::DirenvArgs;
+use crate::config::Config;
+use crate::venv::METADATA_FILE_NAME;
+use anstream::println;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::path::Path;
+
+pub const ENVRC_FILE_NAME: &str = ".envrc";
+
+const BEGIN_MARKER: &str = "# BEGIN meowda direnv";
+const END_MARKER: &str = "# END meowda direnv";
+
+/// The `.envrc` block activating `env_ref`. The environment is resolved when direnv loads
+/// the file, like the shell hook does, so the block stays valid if the environment is
+/// recreated or the project moves.
+fn envrc_block(env_ref: &str) -> String {
+    format!(
+        r#"{BEGIN_MARKER} ({env_ref})
+venv_path="$(meowda detect-activate-venv-path {env_ref})" || exit 1
+# The first line is the path, the prompt prefix of the shell hook is not used by direnv
+venv_path="${{venv_path%%$'\n'*}}"
+export VIRTUAL_ENV="$venv_path"
+PATH_add "$venv_path/bin"
+unset PYTHONHOME
+if grep -qs '^frozen = true' "$venv_path/{METADATA_FILE_NAME}"; then
+    export MEOWDA_FROZEN=1
+fi
+watch_file "$venv_path/pyvenv.cfg"
+{END_MARKER}
+"#
+    )
+}
+
+/// Inserts `block` into the `.envrc` `content`, replacing the block of a previous
+/// `meowda direnv` and keeping everything else.
+fn upsert_envrc_block(content: &str, block: &str) -> String {
+    let begin = content.find(BEGIN_MARKER);
+    let end = begin.and_then(|begin| {
+        content[begin..]
+            .find(END_MARKER)
+            .map(|end| begin + end + END_MARKER.len())
+    });
+    match (begin, end) {
+        (Some(begin), Some(end)) => {
+            let end = if content[end..].starts_with('\n') {
+                end + 1
+            } else {
+                end
+            };
+            format!("{}{block}{}", &content[..begin], &content[end..])
+        }
+        _ => {
+            let mut updated = content.to_string();
+            if !updated.is_empty() && !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            updated.push_str(block);
+            updated
+        }
+    }
+}
+
+/// Writes an `.envrc` activating an environment into the current directory, or prints
+/// it, so that projects using direnv get auto-activation without the shell hook.
+pub async fn direnv(args: DirenvArgs, config: &Config) -> Result<()> {
+    let env_ref = resolve_env_name(args.name, config)?;
+    // Fail early for unknown environments rather than when direnv loads the file
+    detect_venv_path(&args.scope, &env_ref, config)?;
+    let scope_flag = if args.scope.local {
+        " --local"
+    } else if args.scope.global {
+        " --global"
+    } else {
+        ""
+    };
+    let block = envrc_block(&format!("{env_ref}{scope_flag}"));
+    if args.print {
+        print!("{block}");
+        return Ok(());
+    }
+
+    let path = Path::new(ENVRC_FILE_NAME);
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).context("Failed to read .envrc"),
+    };
+    let updated = upsert_envrc_block(&content, &block);
+    if updated == content {
+        println!(
+            "{}",
+            format!("{ENVRC_FILE_NAME} already activates '{env_ref}'.").yellow()
+        );
+        return Ok(());
+    }
+    std::fs::write(path, updated).context("Failed to write .envrc")?;
+    println!(
+        "{}",
+        format!("{ENVRC_FILE_NAME} now activates '{env_ref}', run `direnv allow` to enable it.")
+            .green()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envrc_block_is_replaced_in_place() {
+        let block = envrc_block("app");
+        let written = upsert_envrc_block("export FOO=1", &block);
+        assert_eq!(written, format!("export FOO=1\n{block}"));
+        assert_eq!(upsert_envrc_block(&written, &block), written);
+
+        let other = envrc_block("other --global");
+        let content = format!("export FOO=1\n{block}dotenv\n");
+        assert_eq!(
+            upsert_envrc_block(&content, &other),
+            format!("export FOO=1\n{other}dotenv\n")
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::backends::{BackendKind, UvBackend, check_command_available};
+use crate::cli::args::DoctorArgs;
+use crate::cli::output::{self, OutputFormat};
+use crate::cli::utils::report_check;
+use crate::config::{Config, find_project_config, user_config_path};
+use crate::store::backend::select_store_backend;
+use crate::store::file_lock::{FileLock, LockState};
+use crate::store::venv_store::{ScopeType, VenvStore, get_candidate_scopes};
+use anyhow::Result;
+use serde::Serialize;
+use std::process::Command;
+
+#[derive(Debug, Serialize)]
+struct Check {
+    ok: bool,
+    label: String,
+    detail: String,
+}
+
+/// The results of all checks, printed at once in the selected output format.
+#[derive(Debug, Default, Serialize)]
+struct Report {
+    healthy: bool,
+    checks: Vec<Check>,
+}
+
+impl Report {
+    fn add(&mut self, ok: bool, label: &str, detail: impl Into<String>) {
+        self.checks.push(Check {
+            ok,
+            label: label.to_string(),
+            detail: detail.into(),
+        });
+    }
+
+    fn print(&self) -> Result<()> {
+        match output::format() {
+            OutputFormat::Json => return output::print_json(self),
+            OutputFormat::Plain => {
+                for check in &self.checks {
+                    let marker = if check.ok { "OK" } else { "ERROR" };
+                    output::print_plain_row(&[marker, &check.label, &check.detail]);
+                }
+            }
+            OutputFormat::Table => {
+                for check in &self.checks {
+                    report_check(check.ok, &check.label, &check.detail);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn command_version(program: &str) -> Option<String> {
+    Command::new(program)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Reports the held locks of the reachable stores, stale ones fail the check unless
+/// `fix_locks` removes them. Returns whether all locks are fine.
+fn check_locks(report: &mut Report, fix_locks: bool) -> Result<bool> {
+    let mut stores = get_candidate_scopes(ScopeType::Unspecified)?
+        .into_iter()
+        .filter_map(|scope| VenvStore::from_specified_scope(scope).ok())
+        .collect::<Vec<_>>();
+    stores.extend(VenvStore::named_stores());
+
+    let mut healthy = true;
+    let mut held = 0;
+    for path in stores.iter().flat_map(|store| store.lock_paths()) {
+        let holder = match FileLock::inspect(&path) {
+            Ok(LockState::Free) => continue,
+            Ok(LockState::Held(holder)) => holder,
+            Err(e) => {
+                report.add(false, "lock", format!("{}: {e}", path.display()));
+                healthy = false;
+                continue;
+            }
+        };
+        held += 1;
+        match holder {
+            Some(holder) if holder.is_stale() && fix_locks => match FileLock::remove_if_free(&path)
+            {
+                Ok(true) => report.add(
+                    true,
+                    "lock",
+                    format!("{}: removed stale lock of {holder}", path.display()),
+                ),
+                Ok(false) => {
+                    healthy = false;
+                    report.add(
+                        false,
+                        "lock",
+                        format!(
+                            "{}: stale lock of {holder} is still held, e.g., by the lock manager of a network filesystem, not removing it",
+                            path.display()
+                        ),
+                    );
+                }
+                Err(e) => {
+                    healthy = false;
+                    report.add(
+                        false,
+                        "lock",
+                        format!("{}: failed to remove stale lock: {e}", path.display()),
+                    );
+                }
+            },
+            Some(holder) if holder.is_stale() => {
+                healthy = false;
+                report.add(
+                    false,
+                    "lock",
+                    format!(
+                        "{}: stale lock of {holder}, run `meowda doctor --fix-locks` to remove it",
+                        path.display()
+                    ),
+                );
+            }
+            Some(holder) => report.add(
+                true,
+                "lock",
+                format!("{}: held by {holder}", path.display()),
+            ),
+            None => report.add(
+                true,
+                "lock",
+                format!("{}: held by an unknown process", path.display()),
+            ),
+        }
+    }
+    if held == 0 {
+        report.add(true, "locks", "none held");
+    }
+    Ok(healthy)
+}
+
+pub async fn doctor(args: DoctorArgs, config: &Config) -> Result<()> {
+    let mut report = Report::default();
+    let mut healthy = true;
+
+    match BackendKind::resolve(config) {
+        Ok(kind) => report.add(true, "backend", kind.name()),
+        Err(e) => {
+            healthy = false;
+            report.add(false, "backend", e.to_string());
+        }
+    }
+
+    match select_store_backend(config) {
+        Ok(backend) => report.add(true, "store backend", backend.name()),
+        Err(e) => {
+            healthy = false;
+            report.add(false, "store backend", format!("{e:#}"));
+        }
+    }
+
+    let (uv_path, source) =
+        UvBackend::resolve_pinned_path(config).unwrap_or_else(|_| UvBackend::resolve_path(config));
+    match command_version(&uv_path) {
+        Some(version) => report.add(true, "uv", format!("{uv_path} ({source}), {version}")),
+        None => {
+            // The `auto` backend falls back to `venv` without uv
+            healthy &= !matches!(BackendKind::resolve(config), Ok(BackendKind::Uv));
+            report.add(
+                false,
+                "uv",
+                format!("{uv_path} ({source}) is not available"),
+            );
+        }
+    }
+
+    match UvBackend::resolve_extra_args(config) {
+        Ok(args) if args.is_empty() => report.add(true, "uv args", "none"),
+        Ok(args) => report.add(true, "uv args", args.join(" ")),
+        Err(e) => {
+            healthy = false;
+            report.add(false, "uv args", e.to_string());
+        }
+    }
+
+    let python_ok = ["python3", "python"]
+        .iter()
+        .any(|python| check_command_available(python, &["--version"]));
+    report.add(
+        python_ok,
+        "python",
+        if python_ok {
+            "found on PATH"
+        } else {
+            "no python found on PATH"
+        },
+    );
+
+    for (label, path) in [
+        ("user config", user_config_path()),
+        ("project config", find_project_config()?),
+    ] {
+        match path {
+            Some(path) if path.is_file() => report.add(true, label, path.display().to_string()),
+            Some(path) => report.add(true, label, format!("{} (not present)", path.display())),
+            None => report.add(true, label, "not found"),
+        }
+    }
+
+    match VenvStore::from_scope_type(ScopeType::Global) {
+        Ok(store) => report.add(
+            true,
+            "global store",
+            if store.is_ready() {
+                store.path().display().to_string()
+            } else {
+                format!("{} (not initialized yet)", store.path().display())
+            },
+        ),
+        Err(e) => {
+            healthy = false;
+            report.add(false, "global store", e.to_string());
+        }
+    }
+
+    healthy &= check_locks(&mut report, args.fix_locks)?;
+
+    report.healthy = healthy;
+    report.print()?;
+    if !healthy {
+        anyhow::bail!("Some checks failed, see the report above");
+    }
+    Ok(())
+}
@@ -1,98 +1,713 @@
-use crate::cli::args::{CreateArgs, DirArgs, ListArgs, RemoveArgs};
-use crate::store::venv_store::{ScopeType, VenvScope, VenvStore};
-use crate::venv::{CreateOptions, EnvInfo, VenvService};
+use crate::backends::format_utc_timestamp;
+use crate::cli::activate::activation_hint;
+use crate::cli::args::{
+    AdoptArgs, CreateArgs, CreateMatrixArgs, DirArgs, FreezeArgs, ListArgs, ListSort,
+    MatrixCreateArgs, MoveArgs, MoveTarget, RebuildArgs, RegisterArgs, RemoveArgs, RestoreArgs,
+    TagArgs, validate_env_name,
+};
+use crate::cli::output::{self, OutputFormat};
+use crate::cli::utils::{confirm, glob_match, print_plan, report_check};
+use crate::config::{Config, CreateDefaults};
+use crate::error::ErrorCode;
+use crate::store::conda;
+use crate::store::trash;
+use crate::store::venv_store::{
+    ScopeType, VenvScope, VenvStore, get_candidate_scopes, nearest_local_scope,
+};
+use crate::ui;
+use crate::venv::{
+    CreateOptions, EnvInfo, EnvMetadata, RebuildSource, VenvService, check_not_reserved,
+    created_at, dir_size, last_used,
+};
 use anstream::println;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::path::Path;
 
-pub async fn create(args: CreateArgs, venv_service: &VenvService) -> Result<()> {
+/// Resolves the Python and seed settings of `meowda create`, explicit arguments win over
+/// the defaults of the scope from config, seeding is on unless disabled.
+fn resolve_create_settings(args: &CreateArgs, defaults: CreateDefaults) -> (Option<String>, bool) {
+    let seed = match (args.seed, args.no_seed) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    };
+    (
+        args.python.clone().or(defaults.python),
+        seed.or(defaults.seed).unwrap_or(true),
+    )
+}
+
+pub async fn create(args: CreateArgs, config: &Config, venv_service: &VenvService) -> Result<()> {
     let scope_type = args.scope.try_into_scope_type()?;
+    // Without a scope flag, the default scope from config picks the store
     let store = VenvStore::from_scope_type(scope_type)?;
+    check_not_reserved(&store, &args.name)?;
+    let defaults = if args.no_defaults {
+        CreateDefaults::default()
+    } else {
+        config.create_defaults(matches!(store.scope(), Some(VenvScope::Local(_))))
+    };
+    let (python, seed) = resolve_create_settings(&args, defaults);
+    let options = CreateOptions {
+        python: python.as_deref(),
+        clear: args.clear,
+        arch: args.arch,
+        seed,
+        index_url: args.index_url.as_deref(),
+        extra_index_urls: &args.extra_index_url,
+        system_site_packages: args.system_site_packages,
+        prompt: args.prompt.as_deref(),
+    };
+    if args.dry_run {
+        print_plan(&venv_service.plan_create(&store, &args.name, options, None)?);
+        return Ok(());
+    }
+    store.init_if_needed()?;
+    if args.clear && store.exists(&args.name) {
+        confirm(
+            &format!(
+                "Recreate virtual environment '{}', removing all its packages?",
+                args.name
+            ),
+            args.confirm.yes,
+        )?;
+    }
+    venv_service.create(&store, &args.name, options).await?;
+    println!("Virtual environment '{}' created successfully.", args.name);
+    let env_ref = match scope_type {
+        ScopeType::Local => format!("{} --local", args.name),
+        ScopeType::Global => format!("{} --global", args.name),
+        ScopeType::Auto | ScopeType::Unspecified => args.name.clone(),
+    };
+    println!("{}", activation_hint(&env_ref).dimmed());
+    Ok(())
+}
+
+/// Creates the environments `envs` of (name, Python) pairs concurrently, installing
+/// `requirements` into each, and reports which succeeded.
+async fn create_envs_concurrently(
+    store: &VenvStore,
+    envs: &[(String, String)],
+    requirements: Option<&str>,
+    clear: bool,
+    yes: bool,
+    venv_service: &VenvService,
+) -> Result<()> {
     store.init_if_needed()?;
+    for (name, _) in envs {
+        validate_env_name(name)?;
+    }
+    let existing = envs
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| store.exists(name))
+        .collect::<Vec<_>>();
+    if clear && !existing.is_empty() {
+        confirm(
+            &format!(
+                "Recreate virtual environments {}, removing all their packages?",
+                existing.join(", ")
+            ),
+            yes,
+        )?;
+    }
+    let options = envs
+        .iter()
+        .map(|(name, python)| {
+            (
+                name.clone(),
+                CreateOptions {
+                    python: Some(python.as_str()),
+                    clear,
+                    arch: None,
+                    seed: true,
+                    index_url: None,
+                    extra_index_urls: &[],
+                    system_site_packages: false,
+                    prompt: None,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    let results = venv_service
+        .create_many(store, &options, requirements)
+        .await;
+
+    let mut failed = 0;
+    for ((name, python), result) in envs.iter().zip(results) {
+        match result {
+            Ok(()) => report_check(true, name, format!("created with Python {python}")),
+            Err(e) => {
+                failed += 1;
+                report_check(false, name, format!("{e:#}"));
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} virtual environments failed to be created",
+            envs.len()
+        );
+    }
+    println!(
+        "All {} virtual environments created successfully.",
+        envs.len()
+    );
+    Ok(())
+}
+
+pub async fn create_matrix(args: CreateMatrixArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let store = VenvStore::from_scope_type(scope_type)?;
+    // The names embed the Python versions, which may be paths
+    let envs = args
+        .python
+        .iter()
+        .map(|python| (format!("{}{}", args.name_prefix, python), python.clone()))
+        .collect::<Vec<_>>();
+    create_envs_concurrently(
+        &store,
+        &envs,
+        None,
+        args.clear,
+        args.confirm.yes,
+        venv_service,
+    )
+    .await
+}
+
+/// The name of the environment of `python` in the matrix `name`, e.g., `app-py312` for
+/// `3.12` or `app-pypy310` for `pypy3.10`.
+fn matrix_env_name(name: &str, python: &str) -> String {
+    let version = python.replace('.', "");
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{name}-py{version}")
+    } else {
+        format!("{name}-{version}")
+    }
+}
+
+pub async fn matrix_create(args: MatrixCreateArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let store = VenvStore::from_scope_type(scope_type)?;
+    let requirements = args
+        .requirements
+        .as_ref()
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read requirements file '{path}'"))
+        })
+        .transpose()?;
+    let envs = args
+        .pythons
+        .iter()
+        .map(|python| (matrix_env_name(&args.name, python), python.clone()))
+        .collect::<Vec<_>>();
+    create_envs_concurrently(
+        &store,
+        &envs,
+        requirements.as_deref(),
+        args.clear,
+        args.confirm.yes,
+        venv_service,
+    )
+    .await
+}
+
+pub async fn move_env(args: MoveArgs, venv_service: &VenvService) -> Result<()> {
+    let (target_scope_type, other_scope_type) = match args.to {
+        MoveTarget::Local => (ScopeType::Local, ScopeType::Global),
+        MoveTarget::Global => (ScopeType::Global, ScopeType::Local),
+    };
+    // Unqualified names are searched outside the target scope, where the environment comes from
+    let search_scope_type = if args.name.contains(':') {
+        ScopeType::Unspecified
+    } else {
+        other_scope_type
+    };
+    let (source_scope, name) = crate::cli::utils::search_venv(search_scope_type, &args.name)?;
+    let source_store = VenvStore::from_specified_scope(source_scope)?;
+    let target_store = VenvStore::from_scope_type(target_scope_type)?;
+    target_store.init_if_needed()?;
     venv_service
-        .create(
-            &store,
-            &args.name,
-            CreateOptions {
-                python: args.python.as_deref(),
-                clear: args.clear,
-            },
-        )
+        .move_env(&source_store, &target_store, name)
         .await?;
-    println!("Virtual environment '{}' created successfully.", args.name);
+    println!(
+        "Virtual environment '{name}' moved to {}.",
+        target_store.path().join(name).display()
+    );
+    Ok(())
+}
+
+pub async fn adopt(args: AdoptArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let store = VenvStore::from_scope_type(scope_type)?;
+    store.init_if_needed()?;
+    venv_service
+        .adopt(&store, &args.name, &args.path, args.move_env)
+        .await?;
+    println!(
+        "Virtual environment '{}' adopted, activate it with `meowda activate {}`.",
+        args.name, args.name
+    );
+    Ok(())
+}
+
+pub async fn register(args: RegisterArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let store = VenvStore::from_scope_type(scope_type)?;
+    store.init_if_needed()?;
+    venv_service
+        .register(&store, &args.name, &args.path)
+        .await?;
+    println!(
+        "Virtual environment '{}' registered, activate it with `meowda activate {}`.",
+        args.name, args.name
+    );
     Ok(())
 }
 
 pub async fn remove(args: RemoveArgs, venv_service: &VenvService) -> Result<()> {
     let scope_type = args.scope.try_into_scope_type()?;
-    let detected_venv_scope = crate::cli::utils::search_venv(scope_type, &args.name)?;
-    let store = VenvStore::from_specified_scope(detected_venv_scope)?;
-    if !store.exists(&args.name) {
+    if let [name] = args.names.as_slice() {
+        return remove_one(
+            name,
+            scope_type,
+            args.confirm.yes,
+            args.dry_run,
+            venv_service,
+        )
+        .await;
+    }
+
+    // Group the environments by store, each one is removed under its own lock
+    let mut stores: Vec<(VenvStore, Vec<String>)> = Vec::new();
+    let mut failures = Vec::new();
+    if args.all {
+        let store = VenvStore::from_scope_type(scope_type)?;
+        store.ensure_writable()?;
+        let names = if store.is_ready() {
+            VenvService::list_venvs_in_store(&store, None)?
+                .into_iter()
+                .map(|env| env.name)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if names.is_empty() {
+            println!("No virtual environments to remove in the {store}.");
+            return Ok(());
+        }
+        stores.push((store, names));
+    } else {
+        for name in &args.names {
+            match crate::cli::utils::search_venv(scope_type, name).and_then(|(scope, name)| {
+                let store = VenvStore::from_specified_scope(scope)?;
+                store.ensure_writable()?;
+                Ok((store, name.to_string()))
+            }) {
+                Ok((store, name)) => {
+                    match stores.iter_mut().find(|(s, _)| s.path() == store.path()) {
+                        Some((_, names)) => names.push(name),
+                        None => stores.push((store, vec![name])),
+                    }
+                }
+                Err(e) => failures.push((name.clone(), e)),
+            }
+        }
+    }
+
+    if args.dry_run {
+        for (name, e) in &failures {
+            report_check(false, name, format!("{e:#}"));
+        }
+        for (store, names) in &stores {
+            for name in names {
+                print_plan(&VenvService::plan_remove(
+                    store,
+                    name,
+                    venv_service.uses_trash(),
+                )?);
+            }
+        }
+        if !failures.is_empty() {
+            anyhow::bail!(
+                "{} of {} virtual environments cannot be removed",
+                failures.len(),
+                args.names.len()
+            );
+        }
+        return Ok(());
+    }
+
+    let planned = stores
+        .iter()
+        .flat_map(|(store, names)| {
+            names
+                .iter()
+                .map(move |name| format!("  {}", store.env_path(name).display()))
+        })
+        .collect::<Vec<_>>();
+    if !planned.is_empty() {
+        confirm(
+            &format!(
+                "Remove {} virtual environments?\n{}\n",
+                planned.len(),
+                planned.join("\n")
+            ),
+            args.confirm.yes,
+        )?;
+    }
+
+    let mut total = failures.len();
+    for (name, e) in &failures {
+        report_check(false, name, format!("{e:#}"));
+    }
+    for (store, names) in &stores {
+        total += names.len();
+        let results = venv_service.remove_many(store, names).await?;
+        for (name, result) in names.iter().zip(results) {
+            match result {
+                Ok(()) => report_check(true, name, "removed"),
+                Err(e) => {
+                    report_check(false, name, format!("{e:#}"));
+                    failures.push((name.clone(), e));
+                }
+            }
+        }
+    }
+    if !failures.is_empty() {
         anyhow::bail!(
-            "Virtual environment '{}' does not exist in the specified scope.",
-            args.name
+            "{} of {total} virtual environments failed to be removed",
+            failures.len()
         );
     }
-    venv_service.remove(&store, &args.name).await?;
-    println!("Virtual environment '{}' removed successfully.", args.name);
+    println!("All {total} virtual environments removed successfully.");
     Ok(())
 }
 
-fn show_envs(envs: &[EnvInfo], shadowed_names: &[String]) -> Result<()> {
-    if envs.is_empty() {
+async fn remove_one(
+    env_ref: &str,
+    scope_type: ScopeType,
+    yes: bool,
+    dry_run: bool,
+    venv_service: &VenvService,
+) -> Result<()> {
+    let (detected_venv_scope, name) = crate::cli::utils::search_venv(scope_type, env_ref)?;
+    let store = VenvStore::from_specified_scope(detected_venv_scope)?;
+    if !store.exists(name) {
+        return Err(ErrorCode::EnvNotFound.err(format!(
+            "Virtual environment '{name}' does not exist in the specified scope."
+        )));
+    }
+    store.ensure_writable()?;
+    if dry_run {
+        print_plan(&VenvService::plan_remove(
+            &store,
+            name,
+            venv_service.uses_trash(),
+        )?);
         return Ok(());
     }
-    for env in envs {
-        let indicator = if env.is_active { "* " } else { "  " };
-        let mut name_display = format!("{}{}", indicator, env.name);
-        let mut info_display = env.path.display().blue().to_string();
-        if shadowed_names.contains(&env.name) && !env.is_active {
-            name_display = name_display.dimmed().to_string();
-        }
-        if env.is_active {
-            name_display = name_display.green().bold().to_string();
-        }
-        if let Some(config) = &env.config
-            && let Some(version) = &config.version
-        {
-            info_display = format!(
-                "{} {}",
-                info_display,
-                format!("python {version}").cyan().bold()
-            );
-        }
-        println!("{} ({})", name_display, info_display);
+    let prompt = if store.is_registered(name) {
+        format!(
+            "Unregister virtual environment '{name}' at {}? It is left in place.",
+            store.env_path(name).display()
+        )
+    } else {
+        format!(
+            "Remove virtual environment '{name}' at {}?",
+            store.env_path(name).display()
+        )
+    };
+    confirm(&prompt, yes)?;
+    let trashed = venv_service.uses_trash() && !store.is_registered(name);
+    venv_service.remove(&store, name).await?;
+    if trashed {
+        println!(
+            "Virtual environment '{name}' removed successfully, restore it with `meowda restore {name}`."
+        );
+    } else {
+        println!("Virtual environment '{name}' removed successfully.");
     }
     Ok(())
 }
 
-pub async fn list(args: ListArgs, venv_service: &VenvService) -> Result<()> {
-    let all_envs = venv_service.list().await?;
+/// Restores the latest removal of an environment from the trash of the nearest store
+/// which has one, or lists the trash of every store.
+pub async fn restore(args: RestoreArgs, venv_service: &VenvService) -> Result<()> {
     let scope_type = args.scope.try_into_scope_type()?;
-    let show_local = matches!(scope_type, ScopeType::Local | ScopeType::Unspecified);
-    let show_global = matches!(scope_type, ScopeType::Global | ScopeType::Unspecified);
-    let mut shadowed_names = vec![];
-    let mut local_title_shown = false;
-    for (scope, envs) in all_envs {
-        if matches!(scope, VenvScope::Local(_)) && !show_local {
-            continue;
+    let mut stores = Vec::new();
+    for scope in get_candidate_scopes(scope_type)? {
+        let store = VenvStore::from_specified_scope(scope)?;
+        if store.is_ready() {
+            stores.push(store);
+        }
+    }
+    let Some(name) = args.name else {
+        let mut empty = true;
+        for store in &stores {
+            for entry in trash::list_trash(store) {
+                empty = false;
+                println!(
+                    "{} {}",
+                    entry.name,
+                    format!(
+                        "(removed {} from the {store})",
+                        format_utc_timestamp(entry.removed_at)
+                    )
+                    .dimmed()
+                );
+            }
+        }
+        if empty {
+            println!("No removed virtual environments in the trash.");
+        }
+        return Ok(());
+    };
+    let store = stores
+        .into_iter()
+        .find(|store| {
+            trash::list_trash(store)
+                .iter()
+                .any(|entry| entry.name == name)
+        })
+        .map_or_else(|| VenvStore::from_scope_type(scope_type), Ok)?;
+    venv_service.restore(&store, &name).await?;
+    println!("Virtual environment '{name}' restored successfully.");
+    Ok(())
+}
+
+/// One environment in the unified listing of all scopes.
+#[derive(Debug, PartialEq, Serialize)]
+struct ListRow {
+    active: bool,
+    name: String,
+    scope: String,
+    python: String,
+    /// Python implementation, e.g., `CPython`, `-` if unknown.
+    implementation: String,
+    path: String,
+    /// Another environment of the same name in a nearer store wins on activation.
+    shadowed: bool,
+    installing: bool,
+    created: Option<u64>,
+    last_used: Option<u64>,
+    tags: Vec<String>,
+}
+
+/// Flattens the environments of all stores, nearest store first, into listing rows.
+fn list_rows(all_envs: Vec<(VenvScope, Vec<EnvInfo>)>) -> Vec<(VenvScope, ListRow)> {
+    let mut seen_names = std::collections::HashSet::new();
+    let mut rows = Vec::new();
+    for (scope, mut envs) in all_envs {
+        envs.sort_by(|a, b| a.name.cmp(&b.name));
+        for env in envs {
+            let shadowed = !seen_names.insert(env.name.clone());
+            let metadata = EnvMetadata::load(&env.path).unwrap_or_default();
+            rows.push((
+                scope.clone(),
+                ListRow {
+                    active: env.is_active,
+                    python: env
+                        .config
+                        .as_ref()
+                        .and_then(|config| config.version.clone())
+                        .unwrap_or_else(|| "-".to_string()),
+                    // Recorded at creation since, uv also records it in `pyvenv.cfg`
+                    implementation: metadata
+                        .interpreter
+                        .as_ref()
+                        .map(|interpreter| interpreter.implementation.clone())
+                        .or_else(|| {
+                            env.config
+                                .as_ref()
+                                .and_then(|config| config.implementation.clone())
+                        })
+                        .unwrap_or_else(|| "-".to_string()),
+                    path: env.path.display().to_string(),
+                    installing: metadata.installing,
+                    tags: metadata.tags,
+                    created: created_at(&env.path),
+                    last_used: last_used(&env.path),
+                    name: env.name,
+                    scope: scope.to_string(),
+                    shadowed,
+                },
+            ));
+        }
+    }
+    rows
+}
+
+/// Listing rows of the conda environments, shown read-only in the `conda` scope.
+fn conda_rows(current_venv: Option<&Path>) -> Vec<ListRow> {
+    let current_venv = current_venv.and_then(|path| path.canonicalize().ok());
+    conda::list_conda_envs()
+        .into_iter()
+        .map(|env| ListRow {
+            active: current_venv.is_some() && env.path.canonicalize().ok() == current_venv,
+            python: env.python_version().unwrap_or_else(|| "-".to_string()),
+            implementation: "-".to_string(),
+            path: env.path.display().to_string(),
+            shadowed: false,
+            installing: false,
+            created: None,
+            last_used: None,
+            tags: Vec::new(),
+            name: env.name,
+            scope: "conda".to_string(),
+        })
+        .collect()
+}
+
+/// Whether the Python `version` of an environment, e.g., `3.12.4`, is the `requested` one,
+/// e.g., `3.12` or `3.12.4` but not `3.1`.
+fn python_matches(requested: &str, version: &str) -> bool {
+    version == requested
+        || version
+            .strip_prefix(requested)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Sorts the rows across scopes, sizes are only measured when sorting by them. Without
+/// `sort` the rows stay grouped by scope.
+fn sort_rows(mut rows: Vec<ListRow>, sort: Option<ListSort>) -> Vec<ListRow> {
+    match sort {
+        None => {}
+        Some(ListSort::Name) => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+        Some(ListSort::Size) => {
+            let mut sized = rows
+                .into_iter()
+                .map(|row| (dir_size(Path::new(&row.path)), row))
+                .collect::<Vec<_>>();
+            sized.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+            rows = sized.into_iter().map(|(_, row)| row).collect();
         }
-        if matches!(scope, VenvScope::Global) && !show_global {
-            continue;
+        Some(ListSort::Created) => rows.sort_by_key(|row| std::cmp::Reverse(row.created)),
+        Some(ListSort::LastUsed) => rows.sort_by_key(|row| std::cmp::Reverse(row.last_used)),
+    }
+    rows
+}
+
+fn show_rows(rows: &[ListRow], verbose: bool) {
+    let mut header = vec!["", "NAME", "SCOPE", "PYTHON"];
+    if verbose {
+        header.push("IMPLEMENTATION");
+    }
+    header.push("PATH");
+    let mut table = output::table(&header);
+    for row in rows {
+        let indicator = match (row.active, ui::is_plain()) {
+            (true, true) => "ACTIVE",
+            (true, false) => "*",
+            (false, _) => "",
+        };
+        let name = if row.active {
+            row.name.green().bold().to_string()
+        } else if row.shadowed {
+            row.name.dimmed().to_string()
+        } else {
+            row.name.clone()
+        };
+        let mut path = row.path.blue().to_string();
+        if row.shadowed {
+            path = format!("{path} {}", "(shadowed)".dimmed());
+        }
+        if !row.tags.is_empty() {
+            path = format!("{path} {}", format!("[{}]", row.tags.join(", ")).magenta());
         }
-        if envs.is_empty() {
-            continue;
+        if row.installing {
+            path = format!("{path} {}", "installing".yellow());
         }
-        if !local_title_shown && matches!(scope, VenvScope::Local(_)) {
-            println!("Available local virtual environments:");
-            local_title_shown = true;
+        let mut cells = vec![
+            indicator.to_string(),
+            name,
+            row.scope.clone(),
+            row.python.cyan().to_string(),
+        ];
+        if verbose {
+            cells.push(row.implementation.clone());
+        }
+        cells.push(path);
+        table.add_row(cells);
+    }
+    output::print_table(&table);
+}
+
+/// Lists the environments of all scopes in one view, `--local`/`--global` narrow it down
+/// and `--verbose` adds the Python implementation of each.
+pub async fn list(args: ListArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = match args.scope.try_into_scope_type()?.or_default_scope() {
+        ScopeType::Auto => match nearest_local_scope() {
+            Some(_) => ScopeType::Local,
+            None => ScopeType::Global,
+        },
+        scope_type => scope_type,
+    };
+    // Shadowing is decided across all scopes, even when only one is shown
+    let rows = list_rows(venv_service.list().await?)
+        .into_iter()
+        .filter(|(scope, _)| match scope {
+            VenvScope::Local(_) => matches!(scope_type, ScopeType::Local | ScopeType::Unspecified),
+            VenvScope::Global => matches!(scope_type, ScopeType::Global | ScopeType::Unspecified),
+            VenvScope::Named(_) => scope_type == ScopeType::Unspecified,
+        })
+        .map(|(_, row)| row)
+        .chain(
+            if args.include_conda && scope_type == ScopeType::Unspecified {
+                conda_rows(VenvService::detect_current_venv().as_deref())
+            } else {
+                Vec::new()
+            },
+        )
+        .filter(|row| {
+            args.filter
+                .as_ref()
+                .is_none_or(|pattern| glob_match(pattern, &row.name))
+        })
+        .filter(|row| {
+            args.python
+                .as_ref()
+                .is_none_or(|python| python_matches(python, &row.python))
+        })
+        .filter(|row| args.tag.iter().all(|tag| row.tags.contains(tag)))
+        .collect::<Vec<_>>();
+    let rows = sort_rows(rows, args.sort);
+    match output::row_format() {
+        OutputFormat::Json => return output::print_json(&rows),
+        OutputFormat::Plain => {
+            // Absent fields are `-`, so every row has the same number of fields
+            for row in &rows {
+                let tags = match row.tags.as_slice() {
+                    [] => "-".to_string(),
+                    tags => tags.join(","),
+                };
+                output::print_plain_row(&[
+                    row.name.as_str(),
+                    &row.scope,
+                    &row.python,
+                    &row.implementation,
+                    &row.path,
+                    if row.active { "active" } else { "-" },
+                    &tags,
+                ]);
+            }
+            return Ok(());
         }
-        if matches!(scope, VenvScope::Global) {
-            println!("Available global virtual environments:");
+        OutputFormat::Table => {}
+    }
+    if rows.is_empty() {
+        if args.filter.is_some() || args.python.is_some() || !args.tag.is_empty() {
+            println!("No virtual environments match the filters.");
+        } else {
+            println!("No virtual environments found, create one with `meowda create <name>`.");
         }
-        show_envs(&envs, &shadowed_names)?;
-        shadowed_names.extend(envs.iter().map(|env| env.name.clone()));
+        return Ok(());
     }
+    show_rows(&rows, args.verbose > 0);
     Ok(())
 }
 
@@ -103,3 +718,138 @@ pub async fn dir(args: DirArgs, venv_service: &VenvService) -> Result<()> {
     println!("{}", path.display());
     Ok(())
 }
+
+pub async fn set_tags(args: TagArgs, add: bool, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = crate::cli::utils::search_venv(scope_type, &args.name)?;
+    let store = VenvStore::from_specified_scope(detected_venv_scope)?;
+    let tags = venv_service.set_tags(&store, name, &args.tags, add).await?;
+    if tags.is_empty() {
+        println!("Virtual environment '{name}' has no tags.");
+    } else {
+        println!(
+            "Virtual environment '{name}' is tagged {}.",
+            tags.join(", ")
+        );
+    }
+    Ok(())
+}
+
+pub async fn set_frozen(args: FreezeArgs, frozen: bool, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = crate::cli::utils::search_venv(scope_type, &args.name)?;
+    let store = VenvStore::from_specified_scope(detected_venv_scope)?;
+    venv_service.set_frozen(&store, name, frozen).await?;
+    if frozen {
+        println!("Virtual environment '{name}' frozen.");
+    } else {
+        println!("Virtual environment '{name}' thawed.");
+    }
+    Ok(())
+}
+
+pub async fn rebuild(args: RebuildArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = crate::cli::utils::search_venv(scope_type, &args.name)?;
+    let store = VenvStore::from_specified_scope(detected_venv_scope)?;
+    let source = venv_service.rebuild(&store, name).await?;
+    let from = match source {
+        RebuildSource::Spec => "from its recorded spec",
+        RebuildSource::Installed => "with its previously installed packages",
+        RebuildSource::Nothing => "without packages",
+    };
+    println!("Virtual environment '{}' rebuilt {from}.", name.green());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+
+    #[test]
+    fn resolve_create_settings_prefers_explicit_args() -> Result<()> {
+        use clap::Parser;
+
+        let defaults = || CreateDefaults {
+            python: Some("3.12".to_string()),
+            seed: Some(false),
+        };
+        let cases = [
+            ("", CreateDefaults::default(), (None, true)),
+            ("", defaults(), (Some("3.12"), false)),
+            ("-p 3.13", defaults(), (Some("3.13"), false)),
+            ("--seed", defaults(), (Some("3.12"), true)),
+            ("--seed --no-seed", CreateDefaults::default(), (None, false)),
+        ];
+        for (extra_args, defaults, expected) in cases {
+            let args = CreateArgs::try_parse_from(
+                ["create", "app"]
+                    .into_iter()
+                    .chain(extra_args.split_whitespace()),
+            )?;
+            let (python, seed) = resolve_create_settings(&args, defaults);
+            assert_eq!((python.as_deref(), seed), expected, "args: {extra_args:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn matrix_env_name_compacts_the_version() {
+        assert_eq!(matrix_env_name("app", "3.12"), "app-py312");
+        assert_eq!(matrix_env_name("app", "3.9"), "app-py39");
+        assert_eq!(matrix_env_name("app", "pypy3.10"), "app-pypy310");
+    }
+
+    #[test]
+    fn python_matches_whole_version_components() {
+        assert!(python_matches("3.12", "3.12.4"));
+        assert!(python_matches("3.12.4", "3.12.4"));
+        assert!(!python_matches("3.1", "3.12.4"));
+        assert!(!python_matches("3.12", "-"));
+    }
+
+    #[test]
+    fn list_rows_annotates_shadowed_envs() -> Result<()> {
+        let local = tempfile::tempdir()?;
+        let global = tempfile::tempdir()?;
+        let local_store = VenvStore::from_path(local.path().to_path_buf());
+        let global_store = VenvStore::from_path(global.path().to_path_buf());
+        create_fixture_store(&local_store, &["app".to_string()], "3.13.1")?;
+        create_fixture_store(
+            &global_store,
+            &["tools".to_string(), "app".to_string()],
+            "3.12.0",
+        )?;
+
+        let all_envs = vec![
+            (
+                VenvScope::Local(0),
+                VenvService::list_venvs_in_store(&local_store, None)?,
+            ),
+            (
+                VenvScope::Global,
+                VenvService::list_venvs_in_store(&global_store, None)?,
+            ),
+        ];
+        let summary = list_rows(all_envs)
+            .into_iter()
+            .map(|(_, row)| (row.name, row.scope, row.python, row.shadowed))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("app", "local", "3.13.1", false),
+            ("app", "global", "3.12.0", true),
+            ("tools", "global", "3.12.0", false),
+        ]
+        .map(|(name, scope, python, shadowed)| {
+            (
+                name.to_string(),
+                scope.to_string(),
+                python.to_string(),
+                shadowed,
+            )
+        });
+        assert_eq!(summary, expected);
+        Ok(())
+    }
+}
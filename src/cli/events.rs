@@ -0,0 +1,81 @@
+use crate::cli::args::WatchEventsArgs;
+use crate::interrupt;
+use crate::store::events::{Event, events_path, read_snapshot, snapshot_path};
+use anstream::println;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn print_event(line: &str, json: bool) {
+    if json {
+        println!("{line}");
+        return;
+    }
+    match serde_json::from_str::<Event>(line) {
+        Ok(event) => println!(
+            "{} {} {} ({})",
+            event.timestamp.to_string().dimmed(),
+            format!("{:?}", event.kind).to_lowercase().cyan().bold(),
+            event.env.green(),
+            event.store.display().blue()
+        ),
+        Err(_) => println!("{line}"),
+    }
+}
+
+pub async fn watch_events(args: WatchEventsArgs) -> Result<()> {
+    if args.snapshot {
+        let path = snapshot_path()
+            .ok_or_else(|| anyhow::anyhow!("Failed to determine user state directory"))?;
+        let snapshot = read_snapshot(&path)?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&snapshot).context("Failed to serialize snapshot")?
+        );
+        return Ok(());
+    }
+
+    let path =
+        events_path().ok_or_else(|| anyhow::anyhow!("Failed to determine user state directory"))?;
+    let mut position = if args.from_start {
+        0
+    } else {
+        std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0)
+    };
+    let mut pending = String::new();
+    loop {
+        if let Ok(file) = std::fs::File::open(&path) {
+            let len = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            if len < position {
+                // The file has been rotated, start over from the beginning
+                position = 0;
+            }
+            let mut reader = BufReader::new(file);
+            reader
+                .seek(SeekFrom::Start(position))
+                .with_context(|| format!("Failed to read '{}'", path.display()))?;
+            loop {
+                let read = reader
+                    .read_line(&mut pending)
+                    .with_context(|| format!("Failed to read '{}'", path.display()))?;
+                if read == 0 {
+                    break;
+                }
+                position += read as u64;
+                // Keep partially written lines until they are complete
+                if pending.ends_with('\n') {
+                    print_event(pending.trim_end(), args.json);
+                    pending.clear();
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = interrupt::ctrl_c() => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::cli::args::ExplainArgs;
+use crate::error::ErrorCode;
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+
+pub async fn explain(args: ExplainArgs) -> Result<()> {
+    let Some(code) = args.code else {
+        for error in ErrorCode::ALL {
+            println!(
+                "{}  {:>3}  {}",
+                error.code().bold(),
+                error.exit_code(),
+                error.summary()
+            );
+        }
+        return Ok(());
+    };
+    let error = ErrorCode::from_code(&code).ok_or_else(|| {
+        anyhow::anyhow!("Unknown error code '{code}'. Run `meowda explain` to list all error codes")
+    })?;
+    println!("{}: {}\n", error.code().bold(), error.summary().bold());
+    println!("{}\n", error.explanation());
+    println!("Exit code: {}", error.exit_code());
+    Ok(())
+}
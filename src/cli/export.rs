@@ -0,0 +1,39 @@
+use crate::cli::activate::resolve_env_name;
+use crate::cli::args::{ExportArgs, ExportFormat};
+use crate::cli::utils::search_venv;
+use crate::config::Config;
+use crate::store::venv_store::VenvStore;
+use crate::venv::{VenvService, pinned_requirements};
+use anstream::{eprintln, print};
+use anyhow::{Context, Result};
+
+/// Writes the installed packages of an environment as pinned requirements or a
+/// `pylock.toml`, which `meowda import --from` reads back.
+pub async fn export(args: ExportArgs, config: &Config, venv_service: &VenvService) -> Result<()> {
+    let env_ref = resolve_env_name(args.name, config)?;
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = search_venv(scope_type, &env_ref)?;
+    let venv_path = VenvStore::from_specified_scope(detected_venv_scope)?.env_path(name);
+    let packages = pinned_requirements(&venv_path);
+    let content = match args.export_format {
+        // Packages installed from git or a local directory are required from there
+        ExportFormat::Requirements => packages
+            .iter()
+            .map(|pinned| format!("{}\n", pinned.requirement))
+            .collect(),
+        ExportFormat::Pylock => venv_service.write_pylock(&venv_path).await?,
+    };
+
+    match &args.output {
+        Some(output) => {
+            std::fs::write(output, content)
+                .with_context(|| format!("Failed to write '{output}'"))?;
+            eprintln!(
+                "Exported {} packages of '{name}' to {output}.",
+                packages.len()
+            );
+        }
+        None => print!("{content}"),
+    }
+    Ok(())
+}
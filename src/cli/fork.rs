@@ -1,4 +1,5 @@
 use crate::cli::args::ForkArgs;
+use crate::cli::utils::confirm;
 use crate::store::venv_store::VenvStore;
 use crate::venv::{ForkOptions, VenvService};
 use anstream::println;
@@ -8,6 +9,15 @@ pub async fn fork(args: ForkArgs, venv_service: &VenvService) -> Result<()> {
     let scope_type = args.scope.try_into_scope_type()?;
     let store = VenvStore::from_scope_type(scope_type)?;
     store.init_if_needed()?;
+    if args.clear && store.exists(&args.name) {
+        confirm(
+            &format!(
+                "Recreate virtual environment '{}', removing all its packages?",
+                args.name
+            ),
+            args.confirm.yes,
+        )?;
+    }
     venv_service
         .fork(
             &store,
@@ -0,0 +1,111 @@
+use crate::backends::format_utc_timestamp;
+use crate::cli::args::HistoryArgs;
+use crate::cli::output;
+use crate::store::history::{self, HistoryEntry, Outcome};
+use crate::store::venv_store::{VenvStore, get_candidate_scopes};
+use anstream::println;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    scope: &'a str,
+    #[serde(flatten)]
+    entry: &'a HistoryEntry,
+}
+
+fn show_entries(entries: &[(String, HistoryEntry)]) {
+    let user = |entry: &HistoryEntry| entry.user.clone().unwrap_or_else(|| "-".to_string());
+    let user_width = entries
+        .iter()
+        .map(|(_, entry)| user(entry).len())
+        .chain(["USER".len()])
+        .max()
+        .unwrap_or_default();
+    let env_width = entries
+        .iter()
+        .map(|(_, entry)| entry.env.len())
+        .chain(["ENV".len()])
+        .max()
+        .unwrap_or_default();
+    let scope_width = entries
+        .iter()
+        .map(|(scope, _)| scope.len())
+        .chain(["SCOPE".len()])
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{}",
+        format!(
+            "{:16}  {:user_width$}  {:9}  {:env_width$}  {:scope_width$}  {:7}  COMMAND",
+            "TIME", "USER", "OPERATION", "ENV", "SCOPE", "OUTCOME"
+        )
+        .dimmed()
+    );
+    for (scope, entry) in entries {
+        let outcome = match entry.outcome {
+            Outcome::Success => format!("{:7}", "ok").green().to_string(),
+            Outcome::Failure => format!("{:7}", "failed").red().to_string(),
+        };
+        let mut line = format!(
+            "{:16}  {:user_width$}  {:9}  {}  {:scope_width$}  {outcome}  meowda {}",
+            format_utc_timestamp(entry.timestamp),
+            user(entry),
+            entry.operation.to_string(),
+            format!("{:env_width$}", entry.env).green(),
+            scope,
+            entry.args.join(" ")
+        );
+        if let Some(error) = &entry.error {
+            line = format!("{line} {}", format!("({error})").dimmed());
+        }
+        println!("{line}");
+    }
+}
+
+/// Shows the recorded operations of the stores of all scopes, oldest first,
+/// `--local`/`--global` narrow it down.
+pub async fn history(args: HistoryArgs) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let mut stores = get_candidate_scopes(scope_type)?
+        .into_iter()
+        .map(VenvStore::from_specified_scope)
+        .collect::<Result<Vec<_>>>()?;
+    if !scope_type.is_restricted() {
+        stores.extend(VenvStore::named_stores());
+    }
+
+    let mut entries = Vec::new();
+    for store in stores.iter().filter(|store| store.is_ready()) {
+        let scope = store
+            .scope()
+            .map(|scope| scope.to_string())
+            .unwrap_or_default();
+        entries.extend(
+            history::read(store.path())?
+                .into_iter()
+                .filter(|entry| args.name.as_ref().is_none_or(|name| &entry.env == name))
+                .map(|entry| (scope.clone(), entry)),
+        );
+    }
+    entries.sort_by_key(|(_, entry)| entry.timestamp);
+    if let Some(limit) = args.limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+
+    if output::is_json(args.json) {
+        for (scope, entry) in &entries {
+            let line = serde_json::to_string(&JsonEntry { scope, entry })
+                .context("Failed to serialize history entry")?;
+            println!("{line}");
+        }
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("No operations recorded yet.");
+        return Ok(());
+    }
+    show_entries(&entries);
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::cli::args::ImportArgs;
+use crate::cli::utils::confirm;
+use crate::store::venv_store::VenvStore;
+use crate::venv::{CreateOptions, ImportedSpec, VenvService, parse_pipfile_lock, parse_pylock};
+use anstream::println;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Reads the lock file `source`, a `Pipfile.lock` or a `pylock.toml`, `dev` also includes
+/// the development packages.
+pub(crate) fn read_lock_file(source: &Path, dev: bool) -> Result<ImportedSpec> {
+    let file_name = source
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    match file_name {
+        "Pipfile.lock" => {
+            let content = std::fs::read_to_string(source)
+                .with_context(|| format!("Failed to read '{}'", source.display()))?;
+            parse_pipfile_lock(&content, dev)
+        }
+        // PEP 751 names lock files `pylock.toml` or `pylock.<name>.toml`
+        name if name.starts_with("pylock.") && name.ends_with(".toml") => {
+            let content = std::fs::read_to_string(source)
+                .with_context(|| format!("Failed to read '{}'", source.display()))?;
+            let base = std::path::absolute(source)
+                .ok()
+                .and_then(|source| source.parent().map(Path::to_path_buf))
+                .unwrap_or_default();
+            parse_pylock(&content, &base, dev)
+        }
+        "Pipfile" => anyhow::bail!(
+            "Importing a Pipfile is not supported, run `pipenv lock` and import Pipfile.lock instead"
+        ),
+        _ => anyhow::bail!(
+            "Unsupported lock file '{}', supported formats: Pipfile.lock, pylock.toml",
+            source.display()
+        ),
+    }
+}
+
+pub async fn import(args: ImportArgs, venv_service: &VenvService) -> Result<()> {
+    let source = Path::new(&args.source);
+    let spec = read_lock_file(source, args.dev)?;
+    let scope_type = args.scope.try_into_scope_type()?;
+    let store = VenvStore::from_scope_type(scope_type)?;
+    store.init_if_needed()?;
+    if args.clear && store.exists(&args.name) {
+        confirm(
+            &format!(
+                "Recreate virtual environment '{}', removing all its packages?",
+                args.name
+            ),
+            args.confirm.yes,
+        )?;
+    }
+    let python = args.python.as_deref().or(spec.python.as_deref());
+    venv_service
+        .create_from_requirements(
+            &store,
+            &args.name,
+            CreateOptions {
+                python,
+                clear: args.clear,
+                arch: None,
+                seed: true,
+                index_url: None,
+                extra_index_urls: &[],
+                system_site_packages: false,
+                prompt: None,
+            },
+            &spec.requirements,
+        )
+        .await?;
+    println!(
+        "Virtual environment '{}' imported from {} successfully.",
+        args.name,
+        source.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use crate::backends::format_utc_timestamp;
+use crate::cli::activate::resolve_env_name;
+use crate::cli::args::InfoArgs;
+use crate::cli::output::{self, OutputFormat};
+use crate::cli::utils::search_venv;
+use crate::config::Config;
+use crate::store::venv_store::VenvStore;
+use crate::venv::{
+    EnvConfig, EnvMetadata, VenvService, created_at, dir_size, installed_packages, last_used,
+    python_path_in_venv,
+};
+use anstream::println;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Everything known about an environment, gathered from `pyvenv.cfg`, its metadata and
+/// the filesystem.
+#[derive(Debug, Serialize)]
+struct EnvDetails {
+    name: String,
+    scope: String,
+    path: PathBuf,
+    active: bool,
+    python: Option<String>,
+    implementation: Option<String>,
+    /// Size on disk in bytes.
+    size: u64,
+    /// Seconds since the Unix epoch.
+    created: Option<u64>,
+    /// Seconds since the Unix epoch of the last activation.
+    last_used: Option<u64>,
+    packages: usize,
+    frozen: bool,
+    arch: Option<String>,
+    /// Path of the interpreter the environment was created from.
+    interpreter: Option<String>,
+    /// Whether that interpreter still exists.
+    interpreter_exists: bool,
+    /// Where that interpreter was found if not by the backend, e.g., `pyenv`.
+    interpreter_source: Option<String>,
+    index_url: Option<String>,
+    extra_index_urls: Vec<String>,
+    adopted_from: Option<String>,
+    tags: Vec<String>,
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Counts the installed distributions, see [`installed_packages`].
+fn count_packages(venv_path: &Path) -> usize {
+    installed_packages(venv_path).len()
+}
+
+/// Asks the interpreter for its implementation, `pyvenv.cfg` only records it for uv.
+fn query_implementation(venv_path: &Path) -> Option<String> {
+    std::process::Command::new(python_path_in_venv(venv_path))
+        .args([
+            "-c",
+            "import platform; print(platform.python_implementation())",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|implementation| !implementation.is_empty())
+}
+
+fn gather(name: String, scope: String, venv_path: PathBuf) -> Result<EnvDetails> {
+    let config = EnvConfig::parse(venv_path.join("pyvenv.cfg")).ok();
+    let metadata = EnvMetadata::load(&venv_path)?;
+    // Measure linked environments, e.g., adopted ones, at their real location
+    let real_path = venv_path
+        .canonicalize()
+        .unwrap_or_else(|_| venv_path.clone());
+    let active = VenvService::detect_current_venv()
+        .and_then(|current| current.canonicalize().ok())
+        .is_some_and(|current| current == real_path);
+    let implementation = config
+        .as_ref()
+        .and_then(|config| config.implementation.clone())
+        .or_else(|| {
+            metadata
+                .interpreter
+                .as_ref()
+                .map(|interpreter| interpreter.implementation.clone())
+        })
+        .or_else(|| query_implementation(&venv_path));
+    let (interpreter, interpreter_source) = metadata
+        .interpreter
+        .map(|interpreter| (Some(interpreter.path), interpreter.source))
+        .unwrap_or_default();
+    Ok(EnvDetails {
+        name,
+        scope,
+        active,
+        python: config.and_then(|config| config.version),
+        implementation,
+        size: dir_size(&real_path),
+        created: created_at(&venv_path),
+        last_used: last_used(&venv_path),
+        packages: count_packages(&real_path),
+        frozen: metadata.frozen,
+        arch: metadata.arch,
+        interpreter_exists: interpreter
+            .as_ref()
+            .is_some_and(|path| Path::new(path).exists()),
+        interpreter,
+        interpreter_source,
+        index_url: metadata.index_url,
+        extra_index_urls: metadata.extra_index_urls,
+        adopted_from: metadata.adopted_from,
+        tags: metadata.tags,
+        path: venv_path,
+    })
+}
+
+pub async fn info(args: InfoArgs, config: &Config) -> Result<()> {
+    let env_ref = resolve_env_name(args.name, config)?;
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = search_venv(scope_type, &env_ref)?;
+    let store = VenvStore::from_specified_scope(detected_venv_scope.clone())?;
+    let details = gather(
+        name.to_string(),
+        detected_venv_scope.to_string(),
+        store.env_path(name),
+    )?;
+    if output::is_json(args.json) {
+        return output::print_json(&details);
+    }
+
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let date = |secs: Option<u64>| secs.map(format_utc_timestamp);
+    let mut rows = vec![
+        ("name", details.name),
+        ("scope", details.scope),
+        ("path", details.path.display().to_string()),
+        (
+            "active",
+            if details.active { "yes" } else { "no" }.to_string(),
+        ),
+        ("python", or_dash(details.python)),
+        ("implementation", or_dash(details.implementation)),
+        ("size", format_size(details.size)),
+        ("created", or_dash(date(details.created))),
+        (
+            "last used",
+            date(details.last_used).unwrap_or_else(|| "never activated".to_string()),
+        ),
+        ("packages", details.packages.to_string()),
+        (
+            "frozen",
+            if details.frozen { "yes" } else { "no" }.to_string(),
+        ),
+        ("arch", or_dash(details.arch)),
+    ];
+    if let Some(mut interpreter) = details.interpreter {
+        if let Some(source) = details.interpreter_source {
+            interpreter = format!("{interpreter} (from {source})");
+        }
+        if details.interpreter_exists {
+            rows.push(("interpreter", interpreter));
+        } else {
+            rows.push(("interpreter", format!("{interpreter} (missing)")));
+        }
+    }
+    if let Some(index_url) = details.index_url {
+        rows.push(("index", index_url));
+    }
+    for url in details.extra_index_urls {
+        rows.push(("extra index", url));
+    }
+    if let Some(adopted_from) = details.adopted_from {
+        rows.push(("adopted from", adopted_from));
+    }
+    if !details.tags.is_empty() {
+        rows.push(("tags", details.tags.join(", ")));
+    }
+    if output::format() == OutputFormat::Plain {
+        for (key, value) in rows {
+            output::print_plain_row(&[key, &value]);
+        }
+        return Ok(());
+    }
+    let width = rows
+        .iter()
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or_default()
+        + 1;
+    for (key, value) in rows {
+        println!("{:width$} {value}", format!("{key}:"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_size_picks_binary_units() {
+        let cases = [
+            (0, "0 B"),
+            (1023, "1023 B"),
+            (1536, "1.5 KiB"),
+            (5 * 1024 * 1024, "5.0 MiB"),
+            (3 * 1024 * 1024 * 1024, "3.0 GiB"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(format_size(bytes), expected);
+        }
+    }
+
+    #[test]
+    fn count_packages_reads_dist_info() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let site_packages = temp.path().join("lib/python3.12/site-packages");
+        for dir in ["numpy-2.0.0.dist-info", "numpy", "pip-24.0.dist-info"] {
+            std::fs::create_dir_all(site_packages.join(dir))?;
+        }
+        std::fs::write(site_packages.join("numpy/__init__.py"), "x = 1\n")?;
+        assert_eq!(count_packages(temp.path()), 2);
+        assert_eq!(dir_size(temp.path()), 6);
+        Ok(())
+    }
+}
@@ -1,18 +1,39 @@
-use crate::cli::args::InitArgs;
+use crate::cli::args::{HookArgs, InitArgs};
+use crate::envs::EnvVars;
+use crate::venv::shim_bin_dir;
 use anstream::println;
 use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 use std::env;
-use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Maximum shell startup latency the hook may add, checked by `meowda hook --benchmark`.
+pub const HOOK_STARTUP_BUDGET: Duration = Duration::from_millis(5);
+
+/// Appends the shims directory to PATH, once, after everything else, so tools never shadow
+/// the system or an active environment.
+fn tools_path_snippet() -> String {
+    let Ok(dir) = shim_bin_dir() else {
+        return String::new();
+    };
+    let dir = format!("'{}'", dir.display().to_string().replace('\'', r"'\''"));
+    format!(r#"case ":$PATH:" in *:{dir}:*) ;; *) export PATH="$PATH:"{dir} ;; esac"#)
+}
 
 fn get_init_script_content() -> Result<String> {
     let exe_path = env::current_exe()
         .context("Could not get current executable path")?
         .display()
         .to_string();
+    let frozen_var = EnvVars::MEOWDA_FROZEN;
+    let tools_path = tools_path_snippet();
     let script = format!(
         r#"
 # Generated initialization script for virtual environment
+{tools_path}
+
 function __meowda_exe() {{
     {exe_path} "$@"
 }}
@@ -30,22 +51,38 @@ function __meowda_hashr() {{
 function __meowda_activate() {{
     # Remove the first argument ("meowda activate") from "$@"
     local activate_args=("${{@:2}}")
-    local venv_path
-    venv_path=$({exe_path} detect-activate-venv-path "${{activate_args[@]}}")
+    local script
+    script=$({exe_path} detect-activate-venv-path --print --shell bash "${{activate_args[@]}}")
     local ret=$?
     if [ $ret -ne 0 ]; then
         echo "Virtual environment not found or activation failed."
         return 1
     fi
-    if [ -d "$venv_path" ]; then
-        source "$venv_path/bin/activate"
-        echo "Activated virtual environment: $venv_path"
-    else
-        echo "Virtual environment not found: $venv_path"
+    __meowda_source_venv "$script"
+}}
+
+function __meowda_switch() {{
+    # Resolve the new environment first, a failure leaves the current one active
+    local switch_args=("${{@:2}}")
+    local script
+    script=$({exe_path} detect-activate-venv-path --print --shell bash "${{switch_args[@]}}")
+    local ret=$?
+    if [ $ret -ne 0 ]; then
+        echo "Virtual environment not found, keeping the current one active."
+        return 1
     fi
+    __meowda_source_venv "$script"
+}}
+
+function __meowda_source_venv() {{
+    # The activation commands of `meowda activate --print`, which deactivate the current
+    # environment first, also for conda environments without an activate script
+    eval "$1"
+    echo "Activated virtual environment: $VIRTUAL_ENV"
 }}
 
 function __meowda_deactivate() {{
+    unset {frozen_var}
     deactivate
     echo "Deactivated virtual environment."
 }}
@@ -53,7 +90,21 @@ function __meowda_deactivate() {{
 function meowda() {{
     local cmd="${{1-__missing__}}"
     case "$cmd" in
-        (activate) __meowda_activate "$@" ;;
+        (activate)
+            # `--print` only prints the activation commands, which is handled by the binary
+            if [[ " $* " == *" --print "* ]]; then
+                __meowda_exe "$@"
+            else
+                __meowda_activate "$@"
+            fi
+            ;;
+        (switch)
+            if [[ " $* " == *" --print "* ]]; then
+                __meowda_exe "$@"
+            else
+                __meowda_switch "$@"
+            fi
+            ;;
         (deactivate) __meowda_deactivate ;;
         (*) __meowda_exe "$@" ;;
     esac
@@ -66,39 +117,215 @@ function meowda() {{
     Ok(script)
 }
 
-fn inject_init_script(shell_profile: &str) -> Result<()> {
-    // Append the initialization script to the shell profile
-    let meowda_init_comment = "Meowda initialization script";
-    let init_script = format!(
-        r#"
-# {meowda_init_comment}
-MEOWDA_TMP_SCRIPT="/tmp/meowda-init-tmp-$RANDOM.sh"
-meowda generate-init-script > "$MEOWDA_TMP_SCRIPT"
-source "$MEOWDA_TMP_SCRIPT"
-rm -f "$MEOWDA_TMP_SCRIPT"
+pub const INIT_SCRIPT_MARKER: &str = "Meowda initialization script";
+
+/// Last line of the blocks injected before the BEGIN/END markers, which sourced the output
+/// of `meowda generate-init-script` through a temporary file.
+const LEGACY_BLOCK_END: &str = r#"rm -f "$MEOWDA_TMP_SCRIPT""#;
+
+/// Guesses the shell profile of the current user from `$SHELL`.
+pub fn default_shell_profile() -> Option<PathBuf> {
+    let shell = env::var("SHELL").ok()?;
+    let profile = match Path::new(&shell).file_name()?.to_str()? {
+        "zsh" => ".zshrc",
+        "bash" => ".bashrc",
+        _ => return None,
+    };
+    etcetera::home_dir().ok().map(|home| home.join(profile))
+}
+
+/// Checks whether the initialization script is already injected into `shell_profile`.
+pub fn is_init_script_installed(shell_profile: &Path) -> bool {
+    std::fs::read_to_string(shell_profile).is_ok_and(|content| content.contains(INIT_SCRIPT_MARKER))
+}
+
+/// The body of the hook sourced at shell startup. It only defines a thin `meowda` function,
+/// which replaces itself with the full initialization script on first use, so starting a
+/// shell never runs the meowda binary.
+fn hook_body() -> String {
+    let tools_path = tools_path_snippet();
+    format!(
+        r#"{tools_path}
+function meowda() {{
+    unset -f meowda
+    eval "$(command meowda generate-init-script)" || return
+    meowda "$@"
+}}
 "#
-    );
-    let mut file = std::fs::OpenOptions::new()
-        .append(true)
-        .create(true)
-        .read(true)
-        .open(shell_profile)
-        .context("Failed to open shell profile")?;
-
-    let mut buf = String::new();
-    file.read_to_string(&mut buf)
-        .context("Failed to read shell profile")?;
-
-    if buf.contains(meowda_init_comment) {
-        println!(
-            "{}",
-            format!("Initialization script already exists in {shell_profile}. Skipping injection.")
-                .yellow()
-        );
-    } else {
-        file.write_all(init_script.as_bytes())
+    )
+}
+
+fn get_hook_script() -> String {
+    format!("\n# {INIT_SCRIPT_MARKER}\n{}", hook_body())
+}
+
+/// The hook as injected into shell profiles, between BEGIN/END markers so that it can be
+/// updated in place or removed later. The BEGIN marker records the meowda version.
+fn get_init_block() -> String {
+    format!(
+        "# BEGIN {INIT_SCRIPT_MARKER} (v{})\n{}# END {INIT_SCRIPT_MARKER}\n",
+        env!("CARGO_PKG_VERSION"),
+        hook_body()
+    )
+}
+
+/// Finds the byte range of the initialization block in a shell profile, including the
+/// blank line separating it from the content before.
+///
+/// Blocks injected by older versions have no END marker, they end with the line removing
+/// the temporary script they sourced, see [`LEGACY_BLOCK_END`].
+fn find_init_block(content: &str) -> Result<Option<std::ops::Range<usize>>> {
+    let begin_marker = format!("# BEGIN {INIT_SCRIPT_MARKER}");
+    let end_marker = format!("# END {INIT_SCRIPT_MARKER}");
+    let legacy_marker = format!("# {INIT_SCRIPT_MARKER}");
+    let mut start = None;
+    let mut legacy = false;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let line_end = offset + line.len();
+        let trimmed = line.trim();
+        match start {
+            None if trimmed.starts_with(&begin_marker) => start = Some(offset),
+            None if trimmed == legacy_marker => {
+                start = Some(offset);
+                legacy = true;
+            }
+            Some(start)
+                if (!legacy && trimmed == end_marker)
+                    || (legacy && trimmed == LEGACY_BLOCK_END) =>
+            {
+                let start = if content[..start].ends_with("\n\n") {
+                    start - 1
+                } else {
+                    start
+                };
+                return Ok(Some(start..line_end));
+            }
+            _ => {}
+        }
+        offset = line_end;
+    }
+    match start {
+        Some(_) => {
+            anyhow::bail!("The {INIT_SCRIPT_MARKER} block has no end, please remove it manually")
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum InitChange {
+    Installed,
+    Updated,
+    Unchanged,
+}
+
+/// Inserts `block` into the profile `content`, replacing an existing initialization block.
+fn upsert_init_block(content: &str, block: &str) -> Result<(String, InitChange)> {
+    let separated_block = format!("\n{block}");
+    match find_init_block(content)? {
+        Some(range)
+            if content[range.clone()] == *block || content[range.clone()] == separated_block =>
+        {
+            Ok((content.to_string(), InitChange::Unchanged))
+        }
+        Some(range) => {
+            let mut updated = content.to_string();
+            let replacement = if content[range.clone()].starts_with('\n') {
+                &separated_block
+            } else {
+                block
+            };
+            updated.replace_range(range, replacement);
+            Ok((updated, InitChange::Updated))
+        }
+        None => {
+            let mut updated = content.to_string();
+            if !updated.is_empty() && !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            if !updated.is_empty() {
+                updated.push('\n');
+            }
+            updated.push_str(block);
+            Ok((updated, InitChange::Installed))
+        }
+    }
+}
+
+/// Removes the initialization block from the profile `content`, `None` if there is none.
+fn remove_init_block(content: &str) -> Result<Option<String>> {
+    Ok(find_init_block(content)?.map(|range| {
+        let mut updated = content.to_string();
+        updated.replace_range(range, "");
+        updated
+    }))
+}
+
+fn read_shell_profile(shell_profile: &Path) -> Result<String> {
+    match std::fs::read_to_string(shell_profile) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e).context("Failed to read shell profile"),
+    }
+}
+
+fn inject_init_script(shell_profile: &Path) -> Result<()> {
+    let content = read_shell_profile(shell_profile)?;
+    let (updated, change) = upsert_init_block(&content, &get_init_block())?;
+    if change != InitChange::Unchanged {
+        std::fs::write(shell_profile, updated)
             .context("Failed to write initialization script to shell profile")?;
     }
+    let message = match change {
+        InitChange::Installed => format!(
+            "Initialization script injected into {}.",
+            shell_profile.display()
+        )
+        .green()
+        .to_string(),
+        InitChange::Updated => format!(
+            "Initialization script in {} updated to v{}.",
+            shell_profile.display(),
+            env!("CARGO_PKG_VERSION")
+        )
+        .green()
+        .to_string(),
+        InitChange::Unchanged => format!(
+            "Initialization script in {} is already up to date.",
+            shell_profile.display()
+        )
+        .yellow()
+        .to_string(),
+    };
+    println!("{message}");
+    Ok(())
+}
+
+fn uninstall_init_script(shell_profile: &Path) -> Result<()> {
+    let content = read_shell_profile(shell_profile)?;
+    match remove_init_block(&content)? {
+        Some(updated) => {
+            std::fs::write(shell_profile, updated)
+                .context("Failed to remove initialization script from shell profile")?;
+            println!(
+                "{}",
+                format!(
+                    "Initialization script removed from {}, restart the shell to drop the meowda function.",
+                    shell_profile.display()
+                )
+                .green()
+            );
+        }
+        None => println!(
+            "{}",
+            format!(
+                "No initialization script found in {}.",
+                shell_profile.display()
+            )
+            .yellow()
+        ),
+    }
     Ok(())
 }
 
@@ -109,6 +336,145 @@ pub async fn generate_init_script() -> Result<()> {
 }
 
 pub async fn init(args: InitArgs) -> Result<()> {
-    inject_init_script(args.shell_profile.as_str())?;
+    let shell_profile = Path::new(&args.shell_profile);
+    if args.uninstall {
+        uninstall_init_script(shell_profile)
+    } else {
+        inject_init_script(shell_profile)
+    }
+}
+
+fn detect_benchmark_shell() -> String {
+    env::var("SHELL")
+        .ok()
+        .filter(|shell| {
+            Path::new(shell)
+                .file_name()
+                .is_some_and(|name| name == "bash" || name == "zsh")
+        })
+        .unwrap_or_else(|| "bash".to_string())
+}
+
+/// Average wall time of starting `shell` to run `script`.
+fn measure_startup(shell: &str, script: &str, iterations: u32) -> Result<Duration> {
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let status = Command::new(shell)
+            .arg("-c")
+            .arg(script)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .with_context(|| format!("Failed to execute '{shell}'"))?;
+        total += start.elapsed();
+        if !status.success() {
+            anyhow::bail!("Benchmark script failed in '{shell}'");
+        }
+    }
+    Ok(total / iterations.max(1))
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+async fn benchmark_hook(args: &HookArgs) -> Result<()> {
+    let shell = args.shell.clone().unwrap_or_else(detect_benchmark_shell);
+    let iterations = args.iterations.max(1);
+    let baseline = measure_startup(&shell, "true", iterations)?;
+    let hook = measure_startup(&shell, &format!("{}\ntrue", get_hook_script()), iterations)?;
+    let added = hook.saturating_sub(baseline);
+
+    println!("Shell: {shell} ({iterations} iterations)");
+    println!("  baseline startup: {}", format_duration(baseline));
+    println!("  with hook:        {}", format_duration(hook));
+    println!(
+        "  added latency:    {} (budget {})",
+        format_duration(added),
+        format_duration(HOOK_STARTUP_BUDGET)
+    );
+    if added > HOOK_STARTUP_BUDGET {
+        anyhow::bail!(
+            "The shell hook exceeds the startup budget, make sure the profile uses the hook of `meowda init`"
+        );
+    }
+    println!("{}", "The shell hook is within the startup budget.".green());
     Ok(())
 }
+
+pub async fn hook(args: HookArgs) -> Result<()> {
+    if args.benchmark {
+        return benchmark_hook(&args).await;
+    }
+    println!("{}", get_hook_script());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_block_is_idempotent() -> Result<()> {
+        let block = get_init_block();
+        let (installed, change) = upsert_init_block("export EDITOR=vim", &block)?;
+        assert_eq!(change, InitChange::Installed);
+        assert_eq!(installed, format!("export EDITOR=vim\n\n{block}"));
+
+        let (again, change) = upsert_init_block(&installed, &block)?;
+        assert_eq!(change, InitChange::Unchanged);
+        assert_eq!(again, installed);
+
+        assert_eq!(
+            remove_init_block(&installed)?.as_deref(),
+            Some("export EDITOR=vim\n")
+        );
+        assert_eq!(remove_init_block("export EDITOR=vim\n")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn init_block_updates_older_versions_in_place() -> Result<()> {
+        let block = get_init_block();
+        let old = format!(
+            "alias ll='ls -l'\n\n# BEGIN {INIT_SCRIPT_MARKER} (v0.1.0)\nold hook\n# END {INIT_SCRIPT_MARKER}\nexport A=1\n"
+        );
+        let (updated, change) = upsert_init_block(&old, &block)?;
+        assert_eq!(change, InitChange::Updated);
+        assert_eq!(updated, format!("alias ll='ls -l'\n\n{block}export A=1\n"));
+
+        // The block injected by older versions, without markers
+        let legacy = r#"alias ll='ls -l'
+
+# Meowda initialization script
+MEOWDA_TMP_SCRIPT="/tmp/meowda-init-tmp-$RANDOM.sh"
+meowda generate-init-script > "$MEOWDA_TMP_SCRIPT"
+source "$MEOWDA_TMP_SCRIPT"
+rm -f "$MEOWDA_TMP_SCRIPT"
+export A=1
+my_func() {
+  echo hi
+}
+"#;
+        let user_content = "export A=1\nmy_func() {\n  echo hi\n}\n";
+        let (updated, change) = upsert_init_block(legacy, &block)?;
+        assert_eq!(change, InitChange::Updated);
+        assert_eq!(
+            updated,
+            format!("alias ll='ls -l'\n\n{block}{user_content}")
+        );
+        assert_eq!(
+            remove_init_block(legacy)?,
+            Some(format!("alias ll='ls -l'\n{user_content}"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unterminated_init_block_is_an_error() {
+        let content = format!("# BEGIN {INIT_SCRIPT_MARKER} (v0.2.0)\nfunction meowda() {{\n");
+        assert!(upsert_init_block(&content, &get_init_block()).is_err());
+        assert!(remove_init_block(&content).is_err());
+    }
+}
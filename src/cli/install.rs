@@ -1,15 +1,55 @@
-use crate::cli::args::{InstallArgs, UninstallArgs};
-use crate::venv::VenvService;
+use crate::cli::args::{AddArgs, InstallArgs, RemoveDepArgs, UninstallArgs};
+use crate::venv::{PackageOptions, VenvService};
 use anyhow::Result;
 
 pub async fn install(args: InstallArgs, venv_service: &VenvService) -> Result<()> {
     let extra_args: Vec<&str> = args.extra_args.iter().map(|s| s.as_str()).collect();
-    venv_service.install(&extra_args).await?;
+    let options = PackageOptions {
+        freeze: args.freeze_env,
+        thaw: args.thaw,
+    };
+    if args.tools {
+        venv_service.install_tools(&extra_args, options).await?;
+    } else {
+        venv_service.install(&extra_args, options).await?;
+    }
     Ok(())
 }
 
 pub async fn uninstall(args: UninstallArgs, venv_service: &VenvService) -> Result<()> {
     let extra_args: Vec<&str> = args.extra_args.iter().map(|s| s.as_str()).collect();
-    venv_service.uninstall(&extra_args).await?;
+    venv_service
+        .uninstall(
+            &extra_args,
+            PackageOptions {
+                freeze: args.freeze_env,
+                thaw: args.thaw,
+            },
+        )
+        .await?;
     Ok(())
 }
+
+pub async fn add(args: AddArgs, venv_service: &VenvService) -> Result<()> {
+    venv_service
+        .add_deps(
+            &args.requirements,
+            PackageOptions {
+                freeze: args.freeze_env,
+                thaw: args.thaw,
+            },
+        )
+        .await
+}
+
+pub async fn remove_dep(args: RemoveDepArgs, venv_service: &VenvService) -> Result<()> {
+    venv_service
+        .remove_deps(
+            &args.names,
+            PackageOptions {
+                freeze: args.freeze_env,
+                thaw: args.thaw,
+            },
+        )
+        .await
+}
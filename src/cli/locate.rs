@@ -0,0 +1,74 @@
+use crate::cli::args::LocateArgs;
+use crate::cli::utils::glob_match;
+use crate::store::venv_store::{ScopeType, VenvScope, VenvStore, get_candidate_scopes};
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+
+struct Location {
+    scope: VenvScope,
+    path: PathBuf,
+}
+
+fn explain_winner(winner: &VenvScope, others: &[Location]) -> String {
+    match (winner, others.is_empty()) {
+        (_, true) => "only match".to_string(),
+        (VenvScope::Local(_), false) => {
+            "nearest local store takes precedence over outer local stores and the global store"
+                .to_string()
+        }
+        (VenvScope::Global, false) => "global store".to_string(),
+        (VenvScope::Named(name), false) => format!("extra store '{name}'"),
+    }
+}
+
+pub async fn locate(args: LocateArgs) -> Result<()> {
+    // Candidate scopes are ordered by precedence, the first match of a name wins
+    let mut matches: Vec<(String, Vec<Location>)> = Vec::new();
+    for scope in get_candidate_scopes(ScopeType::Unspecified)? {
+        let store = VenvStore::from_specified_scope(scope.clone())?;
+        if !store.is_ready() {
+            continue;
+        }
+        let names = store
+            .env_names()
+            .into_iter()
+            .filter(|name| glob_match(&args.pattern, name));
+        for name in names {
+            let location = Location {
+                scope: scope.clone(),
+                path: store.env_path(&name),
+            };
+            match matches.iter_mut().find(|(existing, _)| existing == &name) {
+                Some((_, locations)) => locations.push(location),
+                None => matches.push((name, vec![location])),
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        anyhow::bail!("No virtual environment matches '{}'", args.pattern);
+    }
+
+    for (name, locations) in matches {
+        let (winner, shadowed) = locations.split_first().expect("at least one location");
+        println!("{}", name.bold());
+        println!(
+            "  {} {} {} ({})",
+            "*".green().bold(),
+            winner.scope.to_string().green(),
+            winner.path.display().blue(),
+            explain_winner(&winner.scope, shadowed)
+        );
+        for location in shadowed {
+            println!(
+                "    {} {} (shadowed by the {} environment)",
+                location.scope.to_string().dimmed(),
+                location.path.display().dimmed(),
+                winner.scope
+            );
+        }
+    }
+    Ok(())
+}
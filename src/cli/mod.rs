@@ -1,8 +1,36 @@
 pub mod activate;
 pub mod args;
+pub mod cache_key;
+pub mod check;
+pub mod completion_data;
+pub mod daemon;
+pub mod diff;
+pub mod direnv;
+pub mod dockerize;
+pub mod doctor;
 pub mod env;
+pub mod events;
+pub mod explain;
+pub mod export;
 pub mod fork;
+pub mod history;
+pub mod import;
+pub mod info;
 pub mod init;
 pub mod install;
 pub mod link;
+pub mod locate;
+pub mod output;
+pub mod pack;
+pub mod prune;
+pub mod run_script;
+pub mod shim;
+pub mod status;
+pub mod stores;
+pub mod sync;
+pub mod test_fixture;
+pub mod tools;
 mod utils;
+pub mod verify_setup;
+pub mod vscode;
+pub mod which;
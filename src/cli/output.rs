@@ -0,0 +1,120 @@
+/// Output format of the reporting commands, e.g., `list`, `info`, `diff` and `doctor`,
+/// selected once for the process with the global `--format` flag.
+///
+/// `table` is the aligned, colored output for humans, `json` prints one pretty-printed JSON
+/// document and `plain` prints tab-separated rows without headers or colors for scripts.
+use anstream::println;
+use anyhow::{Context, Result};
+use comfy_table::{ContentArrangement, Table, presets};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns with headers and colors.
+    #[default]
+    #[value(alias = "text")]
+    Table,
+    /// A single JSON document.
+    Json,
+    /// Tab-separated rows without headers or colors.
+    Plain,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Selects the output format for this process, called once at startup.
+pub fn set_format(format: OutputFormat) {
+    FORMAT.set(format).ok();
+}
+
+pub fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// The format of commands printing rows: tables are only drawn on a terminal, scripts
+/// reading stdout get the rows of the `plain` format.
+pub fn row_format() -> OutputFormat {
+    match format() {
+        OutputFormat::Table if !std::io::stdout().is_terminal() => OutputFormat::Plain,
+        format => format,
+    }
+}
+
+/// Whether to print JSON, either for `--format json` or the `--json` flag of a command.
+pub fn is_json(json_flag: bool) -> bool {
+    json_flag || format() == OutputFormat::Json
+}
+
+pub fn print_json(value: &impl Serialize) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).context("Failed to serialize output")?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Prints one row of the `plain` format, tabs and newlines in fields are replaced by spaces
+/// so every row stays one line of the same number of fields.
+pub fn print_plain_row<S: AsRef<str>>(fields: &[S]) {
+    let row = fields
+        .iter()
+        .map(|field| field.as_ref().replace(['\t', '\n'], " "))
+        .collect::<Vec<_>>()
+        .join("\t");
+    println!("{row}");
+}
+
+/// A table without borders, its columns separated by two spaces under a dimmed header.
+/// Cells may be colored, the escape codes do not count towards the column widths.
+pub fn table<S: AsRef<str>>(header: &[S]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::NOTHING)
+        .set_content_arrangement(ContentArrangement::Disabled)
+        .set_header(header.iter().map(|title| match title.as_ref() {
+            "" => String::new(),
+            title => title.dimmed().to_string(),
+        }));
+    for column in table.column_iter_mut() {
+        column.set_padding((0, 2));
+    }
+    table
+}
+
+pub fn print_table(table: &Table) {
+    println!("{}", table.trim_fmt());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_aligns_colored_cells() {
+        let mut table = table(&["", "NAME", "PYTHON"]);
+        table.add_row([
+            "*".to_string(),
+            "app".green().bold().to_string(),
+            "3.12".into(),
+        ]);
+        table.add_row([
+            String::new(),
+            "tools-long".into(),
+            "3.13".cyan().to_string(),
+        ]);
+        let lines = anstream::adapter::strip_str(&table.trim_fmt())
+            .to_string()
+            .lines()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "   NAME        PYTHON",
+                "*  app         3.12",
+                "   tools-long  3.13",
+            ]
+        );
+    }
+}
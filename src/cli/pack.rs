@@ -0,0 +1,69 @@
+use crate::cli::args::{PackArgs, UnpackArgs, validate_env_name};
+use crate::cli::utils::{confirm, search_venv};
+use crate::store::venv_store::VenvStore;
+use crate::venv::{CreateOptions, VenvService};
+use anstream::println;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+pub async fn pack(args: PackArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = search_venv(scope_type, &args.name)?;
+    let store = VenvStore::from_specified_scope(detected_venv_scope)?;
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{name}.tar.zst")));
+    let output = std::path::absolute(&output)
+        .with_context(|| format!("Invalid output path '{}'", output.display()))?;
+    venv_service
+        .pack(&store, name, &output, args.with_wheels)
+        .await?;
+    println!(
+        "Virtual environment '{name}' packed into {}.",
+        output.display()
+    );
+    Ok(())
+}
+
+pub async fn unpack(args: UnpackArgs, venv_service: &VenvService) -> Result<()> {
+    let (extracted, manifest) = venv_service.extract_archive(&args.archive).await?;
+    let name = match args.name {
+        Some(name) => name,
+        None => {
+            validate_env_name(&manifest.name)?;
+            manifest.name.clone()
+        }
+    };
+
+    let scope_type = args.scope.try_into_scope_type()?;
+    let store = VenvStore::from_scope_type(scope_type)?;
+    store.init_if_needed()?;
+    if args.clear && store.exists(&name) {
+        confirm(
+            &format!("Recreate virtual environment '{name}', removing all its packages?"),
+            args.confirm.yes,
+        )?;
+    }
+    venv_service
+        .unpack(
+            &store,
+            &name,
+            CreateOptions {
+                python: args.python.as_deref().or(manifest.python.as_deref()),
+                clear: args.clear,
+                arch: None,
+                seed: true,
+                index_url: manifest.index_url.as_deref(),
+                extra_index_urls: &manifest.extra_index_urls,
+                system_site_packages: false,
+                prompt: None,
+            },
+            extracted.path(),
+        )
+        .await?;
+    println!(
+        "Virtual environment '{name}' unpacked from {} successfully.",
+        args.archive.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use crate::backends::format_utc_timestamp;
+use crate::cli::args::PruneArgs;
+use crate::cli::utils::{confirm, report_check};
+use crate::store::events::now_timestamp;
+use crate::store::venv_store::{VenvStore, get_candidate_scopes};
+use crate::venv::{EnvMetadata, VenvService, created_at, last_used};
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use std::path::Path;
+
+/// When the environment at `venv_path` was last touched: its last activation, install or
+/// uninstall, or its creation if it was never used.
+fn last_touched(venv_path: &Path) -> Option<u64> {
+    last_used(venv_path).or_else(|| created_at(venv_path))
+}
+
+/// Removes the environments of the local and global stores untouched for
+/// `--unused-for`. The active environment and frozen ones are kept.
+pub async fn prune(args: PruneArgs, venv_service: &VenvService) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let cutoff = now_timestamp().saturating_sub(args.unused_for);
+    let mut stores = Vec::new();
+    for scope in get_candidate_scopes(scope_type)? {
+        let store = VenvStore::from_specified_scope(scope)?;
+        if !store.is_ready() {
+            continue;
+        }
+        let mut names = Vec::new();
+        for env in VenvService::list_venvs_in_store(&store, None)? {
+            let Some(touched) = last_touched(&env.path) else {
+                continue;
+            };
+            if touched > cutoff {
+                continue;
+            }
+            if env.is_active || EnvMetadata::load(&env.path).is_ok_and(|metadata| metadata.frozen) {
+                println!(
+                    "Keeping '{}', it is {}.",
+                    env.name,
+                    if env.is_active { "active" } else { "frozen" }
+                );
+                continue;
+            }
+            names.push((env.name, touched));
+        }
+        if !names.is_empty() {
+            names.sort();
+            stores.push((store, names));
+        }
+    }
+    if stores.is_empty() {
+        println!("No unused virtual environments found.");
+        return Ok(());
+    }
+
+    let planned = stores
+        .iter()
+        .flat_map(|(store, names)| {
+            names.iter().map(move |(name, touched)| {
+                format!(
+                    "  {} {}",
+                    store.env_path(name).display(),
+                    format!("(last used {})", format_utc_timestamp(*touched)).dimmed()
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    if args.dry_run {
+        println!("Would remove {} virtual environments:", planned.len());
+        for line in &planned {
+            println!("{line}");
+        }
+        return Ok(());
+    }
+    confirm(
+        &format!(
+            "Remove {} unused virtual environments?\n{}\n",
+            planned.len(),
+            planned.join("\n")
+        ),
+        args.confirm.yes,
+    )?;
+
+    let mut failed = 0;
+    for (store, names) in &stores {
+        let names = names
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let results = venv_service.remove_many(store, &names).await?;
+        for (name, result) in names.iter().zip(results) {
+            match result {
+                Ok(()) => report_check(true, name, "removed"),
+                Err(e) => {
+                    failed += 1;
+                    report_check(false, name, format!("{e:#}"));
+                }
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} virtual environments failed to be removed",
+            planned.len()
+        );
+    }
+    println!(
+        "All {} unused virtual environments removed successfully.",
+        planned.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use crate::cli::args::{RunScriptArgs, XArgs};
+use crate::interrupt::Interrupts;
+use crate::venv::{
+    ScriptMetadata, VenvService, bin_dir_in_venv, list_tools, python_path_in_venv, scripts_store,
+};
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::path::Path;
+use tracing::info;
+
+/// Runs a script with the dependencies of its inline `# /// script` block, in a cached
+/// environment provisioned on first use.
+pub async fn run_script(args: RunScriptArgs, venv_service: &VenvService) -> Result<()> {
+    let content = std::fs::read_to_string(&args.script)
+        .with_context(|| format!("Failed to read script '{}'", args.script))?;
+    let metadata = ScriptMetadata::parse(&content)
+        .with_context(|| format!("Failed to parse the metadata of '{}'", args.script))?;
+    let store = scripts_store()?;
+    store.init_if_needed()?;
+    if venv_service
+        .provision_script_env(&store, &metadata, args.refresh)
+        .await?
+    {
+        info!(
+            "Provisioned the environment of '{}' with {} dependencies",
+            args.script,
+            metadata.dependencies.len()
+        );
+    }
+
+    let venv_path = store.env_path(&metadata.env_name());
+    let python = python_path_in_venv(&venv_path);
+    let mut script_args = vec![OsString::from(&args.script)];
+    script_args.extend(args.args.iter().map(OsString::from));
+    run_in_env(&venv_path, &python, &script_args).await
+}
+
+/// The executable of a requirement like `ruff==0.5.0`, its name as written.
+fn command_of_requirement(requirement: &str) -> &str {
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    &requirement[..end]
+}
+
+/// Runs a command of a package, e.g., `cowsay` or `ruff==0.5.0`, in a cached environment
+/// with the package, provisioned on first use like those of scripts.
+pub async fn x(args: XArgs, venv_service: &VenvService) -> Result<()> {
+    let (requirement, command) = match &args.from {
+        Some(from) => (from.as_str(), args.command.as_str()),
+        None => (args.command.as_str(), command_of_requirement(&args.command)),
+    };
+    let metadata = ScriptMetadata {
+        requires_python: None,
+        dependencies: vec![requirement.to_string()],
+        python: args.python.clone(),
+    };
+    let store = scripts_store()?;
+    store.init_if_needed()?;
+    if venv_service
+        .provision_script_env(&store, &metadata, args.refresh)
+        .await?
+    {
+        info!("Provisioned an environment with '{requirement}'");
+    }
+
+    let venv_path = store.env_path(&metadata.env_name());
+    let bin_dir = bin_dir_in_venv(&venv_path);
+    let executable = if cfg!(windows) {
+        bin_dir.join(format!("{command}.exe"))
+    } else {
+        bin_dir.join(command)
+    };
+    if !executable.is_file() {
+        let commands = list_tools(&bin_dir);
+        anyhow::bail!(
+            "'{command}' is not a command of '{requirement}', {}",
+            if commands.is_empty() {
+                "it provides none".to_string()
+            } else {
+                format!("it provides: {}", commands.join(", "))
+            }
+        );
+    }
+    let command_args = args.args.iter().map(OsString::from).collect::<Vec<_>>();
+    run_in_env(&venv_path, &executable, &command_args).await
+}
+
+/// Runs `program` with the environment at `venv_path` active and exits with its exit code
+/// if it fails.
+async fn run_in_env(venv_path: &Path, program: &Path, args: &[OsString]) -> Result<()> {
+    let mut path = vec![bin_dir_in_venv(venv_path)];
+    path.extend(
+        std::env::var_os("PATH")
+            .iter()
+            .flat_map(std::env::split_paths),
+    );
+    let mut command = tokio::process::Command::new(program);
+    command
+        .args(args)
+        .env("VIRTUAL_ENV", venv_path)
+        .env("PATH", std::env::join_paths(path)?)
+        .env_remove("PYTHONHOME");
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to execute '{}'", program.display()))?;
+    // Ctrl-C reaches the child too, which decides how to stop
+    let mut interrupts = Interrupts::subscribe();
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status.with_context(|| format!("Failed to wait for '{}'", program.display()))?,
+            _ = interrupts.next() => continue,
+        }
+    };
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_named_after_the_requirement() {
+        assert_eq!(command_of_requirement("cowsay"), "cowsay");
+        assert_eq!(command_of_requirement("ruff==0.5.0"), "ruff");
+        assert_eq!(command_of_requirement("black[d]>=24"), "black");
+    }
+}
@@ -0,0 +1,66 @@
+use crate::cli::args::{ShimAddArgs, ShimListArgs, ShimRemoveArgs};
+use crate::cli::utils::search_venv;
+use crate::store::venv_store::{VenvStore, scope_label};
+use crate::venv::{add_shim, list_shims, remove_shim, shim_bin_dir, shim_target_exists};
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+
+pub fn add(args: ShimAddArgs) -> Result<()> {
+    let scope_type = args.scope.try_into_scope_type()?;
+    let (detected_venv_scope, name) = search_venv(scope_type, &args.env)?;
+    let env_path = VenvStore::from_specified_scope(detected_venv_scope)?.env_path(name);
+    for command in &args.commands {
+        let path = add_shim(&env_path, command, args.force)?;
+        println!(
+            "Added shim '{}' of '{}' ({})",
+            command.green(),
+            name.green(),
+            path.display().blue()
+        );
+    }
+    Ok(())
+}
+
+pub fn list(args: ShimListArgs) -> Result<()> {
+    if args.bin_dir {
+        println!("{}", shim_bin_dir()?.display());
+        return Ok(());
+    }
+    let shims = list_shims()?;
+    if shims.is_empty() {
+        println!("No shims yet, add one with `meowda shim add <env_name> <executable>`.");
+        return Ok(());
+    }
+    for shim in shims {
+        let env_name = shim
+            .env_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let target = format!(
+            "{} of {env_name} ({})",
+            shim.command,
+            scope_label(&shim.env_path)
+        );
+        if shim_target_exists(&shim) {
+            println!("{} -> {}", shim.name.green(), target.blue());
+        } else {
+            println!(
+                "{} -> {} {}",
+                shim.name.green(),
+                target.dimmed(),
+                "[broken: the executable no longer exists]".red()
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn remove(args: ShimRemoveArgs) -> Result<()> {
+    for name in &args.names {
+        remove_shim(name)?;
+        println!("Removed '{}'", name.green());
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::cli::args::StatusArgs;
+use crate::cli::output;
+use crate::envs::EnvVars;
+use crate::store::venv_store::scope_label;
+use crate::venv::{EnvConfig, VenvService};
+use anstream::println;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ActiveEnv {
+    name: String,
+    scope: String,
+    python: Option<String>,
+    path: PathBuf,
+    frozen: bool,
+}
+
+/// The active environment, read from the environment variables of the shell and
+/// `pyvenv.cfg` only: no locks, no store discovery, no interpreter.
+fn active_env() -> Option<ActiveEnv> {
+    active_env_at(
+        VenvService::detect_current_venv()?,
+        std::env::var_os(EnvVars::MEOWDA_FROZEN).is_some(),
+    )
+}
+
+/// The environment at `path`, activated with `VIRTUAL_ENV` pointing at it.
+pub(crate) fn active_env_at(path: PathBuf, frozen: bool) -> Option<ActiveEnv> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let python = EnvConfig::parse(path.join("pyvenv.cfg"))
+        .ok()
+        .and_then(|config| config.version);
+    Some(ActiveEnv {
+        name,
+        scope: scope_label(&path),
+        python,
+        frozen,
+        path,
+    })
+}
+
+/// Prints the active environment for prompt integrations, e.g., a starship custom segment.
+/// Prints nothing in `--porcelain` mode and `null` as JSON when none is active.
+pub async fn status(args: StatusArgs) -> Result<()> {
+    let env = active_env();
+    if output::is_json(args.json) {
+        let json = serde_json::to_string(&env).context("Failed to serialize status")?;
+        println!("{json}");
+        return Ok(());
+    }
+    if args.porcelain || output::format() == output::OutputFormat::Plain {
+        if let Some(env) = env {
+            println!(
+                "{}\t{}\t{}",
+                env.name,
+                env.scope,
+                env.python.unwrap_or_default()
+            );
+        }
+        return Ok(());
+    }
+    match env {
+        Some(env) => {
+            let python = env
+                .python
+                .map(|python| format!(", Python {python}"))
+                .unwrap_or_default();
+            let frozen = if env.frozen { ", frozen" } else { "" };
+            println!("{} ({}{python}{frozen})", env.name.green(), env.scope);
+        }
+        None => println!("No virtual environment is active."),
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use crate::cli::args::StoresArgs;
+use crate::cli::output;
+use crate::envs::EnvVars;
+use crate::store::venv_store::{ScopeType, VenvScope, VenvStore, get_candidate_scopes};
+use crate::venv::VenvService;
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize)]
+struct StoreEntry {
+    scope: String,
+    path: PathBuf,
+    exists: bool,
+    envs: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct StoreHierarchy {
+    /// The `MEOWDA_*_VENV_DIR` variables in effect, keyed by name.
+    overrides: BTreeMap<String, String>,
+    /// The stores in resolution order: local ones nearest first, the global one, extra ones.
+    stores: Vec<StoreEntry>,
+}
+
+fn store_entry(scope: String, store: &VenvStore) -> Result<StoreEntry> {
+    let exists = store.is_ready();
+    let envs = if exists {
+        VenvService::list_venvs_in_store(store, None)?.len()
+    } else {
+        0
+    };
+    Ok(StoreEntry {
+        scope,
+        path: store.path().clone(),
+        exists,
+        envs,
+    })
+}
+
+fn gather() -> Result<StoreHierarchy> {
+    let overrides = [
+        EnvVars::MEOWDA_LOCAL_VENV_DIR,
+        EnvVars::MEOWDA_GLOBAL_VENV_DIR,
+    ]
+    .into_iter()
+    .filter_map(|var| {
+        std::env::var_os(var)
+            .filter(|value| !value.is_empty())
+            .map(|value| (var.to_string(), value.to_string_lossy().into_owned()))
+    })
+    .collect();
+
+    let mut stores = Vec::new();
+    for scope in get_candidate_scopes(ScopeType::Unspecified)? {
+        let store = VenvStore::from_specified_scope(scope.clone())?;
+        // Every ancestor is a candidate, only the discovered local stores are of interest.
+        // An absolute MEOWDA_LOCAL_VENV_DIR is the same store at every level.
+        if matches!(scope, VenvScope::Local(_))
+            && (!store.is_ready()
+                || stores
+                    .iter()
+                    .any(|entry: &StoreEntry| &entry.path == store.path()))
+        {
+            continue;
+        }
+        stores.push(store_entry(scope.to_string(), &store)?);
+    }
+    for store in VenvStore::named_stores() {
+        let scope = store.scope().map(ToString::to_string).unwrap_or_default();
+        stores.push(store_entry(scope, &store)?);
+    }
+    Ok(StoreHierarchy { overrides, stores })
+}
+
+/// Prints the stores environment references are resolved against, in resolution order,
+/// with the variables that moved them.
+pub async fn stores(args: StoresArgs) -> Result<()> {
+    let hierarchy = gather()?;
+    if output::is_json(args.json) {
+        return output::print_json(&hierarchy);
+    }
+
+    if hierarchy.overrides.is_empty() {
+        println!("{}", "No MEOWDA_*_VENV_DIR overrides in effect.".dimmed());
+    } else {
+        println!("Overrides:");
+        for (var, value) in &hierarchy.overrides {
+            println!("  {var}={value}");
+        }
+    }
+    println!();
+    let scope_width = hierarchy
+        .stores
+        .iter()
+        .map(|store| store.scope.len())
+        .max()
+        .unwrap_or_default();
+    let path_width = hierarchy
+        .stores
+        .iter()
+        .map(|store| store.path.display().to_string().len())
+        .max()
+        .unwrap_or_default();
+    println!("Stores, nearest first:");
+    for store in &hierarchy.stores {
+        let count = match (store.exists, store.envs) {
+            (false, _) => "not created yet".to_string(),
+            (true, 1) => "1 env".to_string(),
+            (true, envs) => format!("{envs} envs"),
+        };
+        println!(
+            "  {:scope_width$}  {}  {}",
+            store.scope,
+            format!("{:path_width$}", store.path.display()).blue(),
+            count.dimmed()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use crate::cli::args::{SyncAllArgs, SyncArgs, validate_env_name};
+use crate::cli::import::read_lock_file;
+use crate::cli::utils::{confirm, print_plan, report_check, search_venv};
+use crate::config::{Config, DeclaredScope, EnvDeclaration, find_project_config};
+use crate::hash::KeyHasher;
+use crate::store::venv_store::{ScopeType, VenvStore};
+use crate::venv::{CreateOptions, SyncOutcome, VenvService};
+use anstream::println;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Resolves the requirements files of a declaration, returning the spec installing them
+/// and a fingerprint of the declaration including the content of the files.
+fn resolve_requirements(declaration: &EnvDeclaration, base_dir: &Path) -> Result<(String, String)> {
+    let mut hasher = KeyHasher::default();
+    hasher.optional_field(declaration.python.as_deref());
+    let mut spec = String::new();
+    for requirements in &declaration.requirements {
+        let path = base_dir.join(requirements);
+        let content = std::fs::read(&path)
+            .with_context(|| format!("Failed to read requirements file '{}'", path.display()))?;
+        hasher
+            .field(path.as_os_str().as_encoded_bytes())
+            .field(content);
+        spec.push_str(&format!("-r {}\n", path.display()));
+    }
+    Ok((spec, hasher.finish()))
+}
+
+/// The directory requirements files of declarations are relative to, that of the project
+/// config declaring them.
+pub(crate) fn declarations_base_dir() -> Result<PathBuf> {
+    Ok(match find_project_config()? {
+        Some(path) => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        None => PathBuf::from("."),
+    })
+}
+
+fn store_of(declaration: &EnvDeclaration) -> Result<VenvStore> {
+    VenvStore::from_scope_type(match declaration.scope {
+        DeclaredScope::Local => ScopeType::Local,
+        DeclaredScope::Global => ScopeType::Global,
+    })
+}
+
+fn create_options(declaration: &EnvDeclaration) -> CreateOptions<'_> {
+    CreateOptions {
+        python: declaration.python.as_deref(),
+        clear: false,
+        arch: None,
+        seed: true,
+        index_url: None,
+        extra_index_urls: &[],
+        system_site_packages: false,
+        prompt: None,
+    }
+}
+
+pub async fn sync_all(
+    args: SyncAllArgs,
+    config: &Config,
+    venv_service: &VenvService,
+) -> Result<()> {
+    let Some(envs) = config.envs.as_ref().filter(|envs| !envs.is_empty()) else {
+        println!("No environments declared, add them to the `[envs]` section of meowda.toml.");
+        return Ok(());
+    };
+    let base_dir = declarations_base_dir()?;
+    for name in envs.keys() {
+        validate_env_name(name)?;
+    }
+
+    if args.dry_run {
+        for (name, declaration) in envs {
+            let store = store_of(declaration)?;
+            let (spec, fingerprint) = resolve_requirements(declaration, &base_dir)?;
+            let (outcome, plan) = venv_service.plan_sync(
+                &store,
+                name,
+                create_options(declaration),
+                &spec,
+                &fingerprint,
+            )?;
+            if outcome == SyncOutcome::UpToDate {
+                println!("'{name}' is up to date.");
+            }
+            print_plan(&plan);
+        }
+        return Ok(());
+    }
+
+    let recreated = envs
+        .iter()
+        .filter_map(|(name, declaration)| {
+            let store = store_of(declaration).ok()?;
+            VenvService::needs_recreate(&store, name, declaration.python.as_deref())
+                .then_some(name.as_str())
+        })
+        .collect::<Vec<_>>();
+    if !recreated.is_empty() {
+        confirm(
+            &format!(
+                "Recreate virtual environments {} with another Python version, removing all their packages?",
+                recreated.join(", ")
+            ),
+            args.confirm.yes,
+        )?;
+    }
+
+    let mut failed = 0;
+    for (name, declaration) in envs {
+        let result = async {
+            let store = store_of(declaration)?;
+            store.init_if_needed()?;
+            let (spec, fingerprint) = resolve_requirements(declaration, &base_dir)?;
+            venv_service
+                .sync(
+                    &store,
+                    name,
+                    create_options(declaration),
+                    &spec,
+                    &fingerprint,
+                )
+                .await
+        }
+        .await;
+        match result {
+            Ok(outcome) => {
+                let detail = match outcome {
+                    SyncOutcome::Created => "created",
+                    SyncOutcome::Recreated => "recreated",
+                    SyncOutcome::Updated => "updated",
+                    SyncOutcome::UpToDate => "up to date",
+                };
+                report_check(true, name, detail);
+            }
+            Err(e) => {
+                failed += 1;
+                report_check(false, name, format!("{e:#}"));
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} declared virtual environments failed to sync",
+            envs.len()
+        );
+    }
+    println!(
+        "All {} declared virtual environments are in sync.",
+        envs.len()
+    );
+    Ok(())
+}
+
+/// Creates the environment `name` from a lock file, or updates it when the lock file
+/// changed since the last sync. An environment using another Python version than the lock
+/// file requires is recreated after a confirmation.
+pub async fn sync(args: SyncArgs, venv_service: &VenvService) -> Result<()> {
+    let source = Path::new(&args.source);
+    let spec = read_lock_file(source, args.dev)?;
+    let python = args.python.as_deref().or(spec.python.as_deref());
+    let scope_type = args.scope.try_into_scope_type()?;
+    // An existing environment is synced where it is, a new one created in the default store
+    let store = match search_venv(scope_type, &args.name) {
+        Ok((scope, _)) => VenvStore::from_specified_scope(scope)?,
+        Err(_) => VenvStore::from_scope_type(scope_type)?,
+    };
+    let options = CreateOptions {
+        python,
+        clear: false,
+        arch: None,
+        seed: true,
+        index_url: None,
+        extra_index_urls: &[],
+        system_site_packages: false,
+        prompt: None,
+    };
+    let mut hasher = KeyHasher::default();
+    hasher.optional_field(python).field(&spec.requirements);
+    let fingerprint = hasher.finish();
+
+    if args.dry_run {
+        let (outcome, plan) = venv_service.plan_sync(
+            &store,
+            &args.name,
+            options,
+            &spec.requirements,
+            &fingerprint,
+        )?;
+        if outcome == SyncOutcome::UpToDate {
+            println!("'{}' is up to date.", args.name);
+        }
+        print_plan(&plan);
+        return Ok(());
+    }
+
+    store.init_if_needed()?;
+    if VenvService::needs_recreate(&store, &args.name, python) {
+        confirm(
+            &format!(
+                "Recreate virtual environment '{}' with another Python version, removing all its packages?",
+                args.name
+            ),
+            args.confirm.yes,
+        )?;
+    }
+
+    let outcome = venv_service
+        .sync(
+            &store,
+            &args.name,
+            options,
+            &spec.requirements,
+            &fingerprint,
+        )
+        .await?;
+    let name = &args.name;
+    let source = source.display();
+    match outcome {
+        SyncOutcome::Created => println!("Virtual environment '{name}' created from {source}."),
+        SyncOutcome::Recreated => println!("Virtual environment '{name}' recreated from {source}."),
+        SyncOutcome::Updated => println!("Virtual environment '{name}' synced to {source}."),
+        SyncOutcome::UpToDate => {
+            println!("Virtual environment '{name}' is up to date with {source}.")
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::cli::args::TestFixtureCreateArgs;
+use crate::store::fixture::create_fixture_store;
+use crate::store::venv_store::VenvStore;
+use anstream::println;
+use anyhow::Result;
+use std::path::PathBuf;
+
+pub async fn create(args: TestFixtureCreateArgs) -> Result<()> {
+    let store = match &args.dir {
+        Some(dir) => VenvStore::from_path(std::path::absolute(PathBuf::from(dir))?),
+        None => VenvStore::from_scope_type(args.scope.try_into_scope_type()?)?,
+    };
+    store.init_if_needed()?;
+    let _lock = store.lock().await?;
+    for path in create_fixture_store(&store, &args.names, &args.python)? {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::cli::args::{ToolInstallArgs, ToolListArgs, ToolNameArgs, ToolsArgs};
+use crate::venv::{VenvService, list_tools, shim_bin_dir, tools_bin_dir};
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+
+pub async fn tools(args: ToolsArgs) -> Result<()> {
+    let bin_dir = tools_bin_dir()?;
+    if args.bin_dir {
+        println!("{}", bin_dir.display());
+        return Ok(());
+    }
+    let tools = list_tools(&bin_dir);
+    if tools.is_empty() {
+        println!("No tools installed yet, install one with `meowda install --tools <package>`.");
+        return Ok(());
+    }
+    for tool in tools {
+        println!("{} ({})", tool, bin_dir.join(&tool).display().blue());
+    }
+    Ok(())
+}
+
+fn print_commands(name: &str, commands: &[String]) -> Result<()> {
+    println!(
+        "Commands of '{}' exposed on PATH by the shell hook: {}",
+        name.green(),
+        commands.join(", ")
+    );
+    println!(
+        "{}",
+        format!("(linked in {})", shim_bin_dir()?.display()).dimmed()
+    );
+    Ok(())
+}
+
+pub async fn install(args: ToolInstallArgs, venv_service: &VenvService) -> Result<()> {
+    let commands = venv_service
+        .install_tool(&args.requirement, args.python.as_deref(), args.force)
+        .await?;
+    print_commands(&args.requirement, &commands)
+}
+
+pub fn list(args: ToolListArgs, venv_service: &VenvService) -> Result<()> {
+    if args.shims_dir {
+        println!("{}", shim_bin_dir()?.display());
+        return Ok(());
+    }
+    let tools = venv_service.installed_tools()?;
+    if tools.is_empty() {
+        println!("No tools installed yet, install one with `meowda tool install <package>`.");
+        return Ok(());
+    }
+    for tool in tools {
+        println!(
+            "{} {} ({})",
+            tool.name.green(),
+            tool.version.unwrap_or_else(|| "unknown".to_string()),
+            tool.commands.join(", ").blue()
+        );
+    }
+    Ok(())
+}
+
+pub async fn upgrade(args: ToolNameArgs, venv_service: &VenvService) -> Result<()> {
+    let commands = venv_service.upgrade_tool(&args.name).await?;
+    print_commands(&args.name, &commands)
+}
+
+pub async fn uninstall(args: ToolNameArgs, venv_service: &VenvService) -> Result<()> {
+    venv_service.uninstall_tool(&args.name).await?;
+    println!("Tool '{}' uninstalled.", args.name.green());
+    Ok(())
+}
@@ -1,24 +1,331 @@
-use crate::store::venv_store::{ScopeType, VenvScope, VenvStore, get_candidate_scopes};
+use crate::cli::args::validate_env_name;
+use crate::error::ErrorCode;
+use crate::store::backend::Discovery;
+use crate::store::venv_store::{
+    ScopeType, VenvScope, VenvStore, get_candidate_scopes, get_candidate_scopes_in, parse_env_ref,
+    resolve_named_ref,
+};
+use crate::ui;
+use crate::venv::PlannedOperation;
+use anstream::println;
+use anyhow::Context;
+use owo_colors::OwoColorize;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
-pub fn search_venv(scope_type: ScopeType, env_name: &str) -> anyhow::Result<VenvScope> {
-    let search_local = matches!(scope_type, ScopeType::Local | ScopeType::Unspecified);
-    let search_global = matches!(scope_type, ScopeType::Global | ScopeType::Unspecified);
-    let scopes = get_candidate_scopes(scope_type)?;
+/// Prints what `--dry-run` would do, one operation per line.
+pub fn print_plan(plan: &[PlannedOperation]) {
+    for operation in plan {
+        println!("Would {operation}");
+    }
+}
+
+/// Prints a single check result line, e.g., `✓ uv: uv 0.8.0`, or `OK uv: uv 0.8.0`
+/// in the accessible mode.
+pub fn report_check(ok: bool, label: &str, detail: impl AsRef<str>) {
+    let marker = if ui::is_plain() {
+        if ok { "OK" } else { "ERROR" }.to_string()
+    } else if ok {
+        "✓".green().bold().to_string()
+    } else {
+        "✗".red().bold().to_string()
+    };
+    println!("{marker} {}: {}", label.bold(), detail.as_ref());
+}
+
+/// Prints a check result line which does not fail, e.g., `! uv: not available`, or
+/// `WARNING uv: not available` in the accessible mode.
+pub fn report_warning(label: &str, detail: impl AsRef<str>) {
+    let marker = if ui::is_plain() {
+        "WARNING".to_string()
+    } else {
+        "!".yellow().bold().to_string()
+    };
+    println!("{marker} {}: {}", label.bold(), detail.as_ref());
+}
+
+/// Asks the user to confirm a destructive action, e.g., removing an environment.
+///
+/// `yes` (i.e., `--yes`) proceeds directly. Without a TTY to prompt on, e.g., in the CI mode
+/// or a script, nobody can confirm, so it fails rather than destroying anything unasked.
+pub fn confirm(prompt: &str, yes: bool) -> anyhow::Result<()> {
+    if yes {
+        return Ok(());
+    }
+    if ui::is_ci() || !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        anyhow::bail!(
+            "{prompt} Cannot ask for a confirmation in a non-interactive session, pass --yes to proceed"
+        );
+    }
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact()
+        .context("Failed to read the confirmation")?;
+    if !confirmed {
+        anyhow::bail!("Aborted, pass --yes to skip the confirmation");
+    }
+    Ok(())
+}
+
+/// Matches `name` against a glob `pattern` supporting `*` and `?` wildcards.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Finds the nearest store containing the environment `env_ref`, which may be
+/// scope-qualified, e.g., `global:myenv`.
+///
+/// Returns the scope of the store and the unqualified environment name. Names which are not
+/// valid environment names, e.g., `..` or `store.toml`, are rejected before touching any
+/// store, as callers go on to remove or modify the directory they name.
+pub fn search_venv(scope_type: ScopeType, env_ref: &str) -> anyhow::Result<(VenvScope, &str)> {
+    search_venv_in(&Discovery::current(), scope_type, env_ref)
+}
+
+/// Like [`search_venv`], discovering the stores as given by `discovery`.
+pub fn search_venv_in<'a>(
+    discovery: &Discovery,
+    scope_type: ScopeType,
+    env_ref: &'a str,
+) -> anyhow::Result<(VenvScope, &'a str)> {
+    if let Some((scope, env_name)) = resolve_named_ref(discovery, env_ref) {
+        validate_env_name(env_name)?;
+        let store = VenvStore::from_scope_in(discovery, scope.clone())?;
+        if scope_type.is_restricted() {
+            return Err(ErrorCode::InvalidScope.err(format!(
+                "'{env_ref}' selects the {store}, which contradicts the scope flag"
+            )));
+        }
+        if !store.is_ready() || !store.exists(env_name) {
+            return Err(ErrorCode::EnvNotFound.err_with_hint(
+                format!("Virtual environment '{env_name}' not found in the {store}."),
+                not_found_hint(env_name),
+            ));
+        }
+        return Ok((scope, env_name));
+    }
+    let (scope_type, env_name) = parse_env_ref(env_ref, scope_type)?;
+    validate_env_name(env_name)?;
+    let search_local = scope_type != ScopeType::Global;
+    let search_global = scope_type != ScopeType::Local;
+    let scopes = get_candidate_scopes_in(discovery, scope_type)?;
 
     for scope in scopes {
-        let venv_store = VenvStore::from_specified_scope(scope.clone())?;
+        let venv_store = VenvStore::from_scope_in(discovery, scope.clone())?;
         if venv_store.is_ready() && venv_store.exists(env_name) {
-            return Ok(scope);
+            return Ok((scope, env_name));
         }
     }
 
-    anyhow::bail!(if search_local && search_global {
-        format!("Virtual environment '{env_name}' not found in local or global scope.")
-    } else if search_local {
-        format!("Virtual environment '{env_name}' not found in local scope.")
-    } else if search_global {
-        format!("Virtual environment '{env_name}' not found in global scope.")
-    } else {
-        unreachable!("Unexpected scope combination")
-    })
+    Err(ErrorCode::EnvNotFound.err_with_hint(
+        if search_local && search_global {
+            format!("Virtual environment '{env_name}' not found in local or global scope.")
+        } else if search_local {
+            format!("Virtual environment '{env_name}' not found in local scope.")
+        } else if search_global {
+            format!("Virtual environment '{env_name}' not found in global scope.")
+        } else {
+            unreachable!("Unexpected scope combination")
+        },
+        not_found_hint(env_name),
+    ))
+}
+
+/// The number of single-character insertions, deletions, substitutions and transpositions
+/// of adjacent characters turning `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// The environments among `envs`, pairs of name and scope, whose name is close enough to
+/// `name` to be a typo of it, closest first. Case is ignored, and up to a third of the
+/// characters may differ.
+fn close_matches(name: &str, envs: Vec<(String, String)>) -> Vec<(String, String)> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut matches = envs
+        .into_iter()
+        .map(|env| (edit_distance(&name, &env.0.to_lowercase()), env))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    // Stable, so equally close environments stay in resolution order
+    matches.sort_by_key(|(distance, _)| *distance);
+    matches.into_iter().map(|(_, env)| env).collect()
+}
+
+/// The `help:` hint of a missing environment: the similarly named environments of every
+/// store, including exact matches in a scope that was not searched.
+fn not_found_hint(env_name: &str) -> String {
+    let stores = get_candidate_scopes(ScopeType::Unspecified)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|scope| VenvStore::from_specified_scope(scope).ok())
+        .chain(VenvStore::named_stores())
+        .filter(|store| store.is_ready());
+    let envs = stores
+        .flat_map(|store| {
+            let scope = store.scope().map(ToString::to_string).unwrap_or_default();
+            store
+                .env_names()
+                .into_iter()
+                .map(move |name| (name, scope.clone()))
+        })
+        .collect::<Vec<_>>();
+    let mut seen = std::collections::HashSet::new();
+    let suggestions = close_matches(env_name, envs)
+        .into_iter()
+        // Shadowed environments cannot be activated by name anyway
+        .filter(|(name, _)| seen.insert(name.clone()))
+        .take(3)
+        .map(|(name, scope)| format!("'{name}' in {scope}"))
+        .collect::<Vec<_>>();
+    match suggestions.as_slice() {
+        [] => {
+            "run `meowda list` to see the available environments, or create it with `meowda create`"
+                .to_string()
+        }
+        [suggestion] => format!("did you mean {suggestion}?"),
+        [rest @ .., last] => format!("did you mean {} or {last}?", rest.join(", ")),
+    }
+}
+
+/// The paths of the environments named `env_name` in the local stores outside the store
+/// `found` was resolved from, which `found` shadows. The global store is not reported,
+/// local environments taking precedence over it is the point.
+pub fn shadowed_envs(
+    discovery: &Discovery,
+    scope_type: ScopeType,
+    found: &VenvScope,
+    env_name: &str,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut shadowed = Vec::new();
+    for scope in get_candidate_scopes_in(discovery, scope_type)?
+        .into_iter()
+        .skip_while(|scope| scope != found)
+        .skip(1)
+    {
+        if !matches!(scope, VenvScope::Local(_)) {
+            continue;
+        }
+        let store = VenvStore::from_scope_in(discovery, scope)?;
+        if store.is_ready() && store.exists(env_name) {
+            shadowed.push(store.env_path(env_name));
+        }
+    }
+    Ok(shadowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_venv_rejects_invalid_names() {
+        for env_ref in [
+            "..",
+            ".trash",
+            "global:..",
+            "local:store.toml",
+            "history.jsonl",
+            "a/b",
+        ] {
+            let error = search_venv(ScopeType::Unspecified, env_ref).unwrap_err();
+            assert_eq!(
+                ErrorCode::of(&error),
+                Some(ErrorCode::InvalidEnvName),
+                "{env_ref:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn glob_match_supports_wildcards() {
+        let cases = [
+            ("foo", "foo", true),
+            ("foo", "foobar", false),
+            ("foo*", "foobar", true),
+            ("*bar", "foobar", true),
+            ("f?o*r", "foobar", true),
+            ("*", "", true),
+            ("?", "", false),
+            ("a*b*c", "aXXbYYc", true),
+            ("a*b*c", "aXXbYY", false),
+        ];
+        for (pattern, name, expected) in cases {
+            assert_eq!(
+                glob_match(pattern, name),
+                expected,
+                "pattern: {pattern}, name: {name}"
+            );
+        }
+    }
+
+    #[test]
+    fn close_matches_catch_typos() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("torhc", "torch"), 1);
+        let env = |name: &str, scope: &str| (name.to_string(), scope.to_string());
+        let envs = vec![
+            env("numpy-dev", "local"),
+            env("torch", "local"),
+            env("Torch", "global"),
+            env("tensorflow", "global"),
+        ];
+        assert_eq!(
+            close_matches("torhc", envs.clone()),
+            [env("torch", "local"), env("Torch", "global")]
+        );
+        assert_eq!(
+            close_matches("numpy-dv", envs.clone()),
+            [env("numpy-dev", "local")]
+        );
+        assert!(close_matches("jax", envs).is_empty());
+    }
 }
@@ -0,0 +1,196 @@
+use crate::backends::{BackendKind, MIN_UV_VERSION, UvBackend, UvVersionReq, query_uv_version};
+use crate::cli::args::VerifySetupArgs;
+use crate::cli::init::{default_shell_profile, is_init_script_installed};
+use crate::cli::utils::{report_check, report_warning};
+use crate::config::{Config, find_project_config};
+use anstream::println;
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+
+/// A failed check with the command fixing it.
+struct Fix {
+    check: String,
+    command: String,
+}
+
+/// Reports a failed uv check, with the command fixing it when uv is `required`. The `auto`
+/// backend falls back to the `venv` backend without uv, so it only gets a warning.
+fn report_uv_failure(
+    required: bool,
+    label: &str,
+    detail: String,
+    command: String,
+    fixes: &mut Vec<Fix>,
+) {
+    if !required {
+        report_warning(
+            label,
+            format!("{detail}, the `auto` backend falls back to `venv`"),
+        );
+        return;
+    }
+    report_check(false, label, detail);
+    fixes.push(Fix {
+        check: label.to_string(),
+        command,
+    });
+}
+
+fn check_uv(config: &Config, fixes: &mut Vec<Fix>) {
+    let required = match BackendKind::resolve(config) {
+        Ok(BackendKind::Uv) => true,
+        Ok(BackendKind::Auto) => false,
+        _ => {
+            report_check(true, "uv", "not required by the selected backend");
+            return;
+        }
+    };
+    if let Some(req) = &config.uv_version {
+        match UvVersionReq::parse(req) {
+            Ok(req) => {
+                if UvBackend::resolve_pinned_path(config).is_err() {
+                    report_uv_failure(
+                        required,
+                        "uv version",
+                        format!("{req} is pinned, but no matching uv was found"),
+                        UvBackend::bootstrap_command(&req),
+                        fixes,
+                    );
+                    return;
+                }
+                report_check(true, "uv version", format!("{req} is pinned"));
+            }
+            Err(e) => {
+                report_uv_failure(
+                    required,
+                    "uv version",
+                    e.to_string(),
+                    "set `uv_version` in meowda.toml to e.g. \"0.5.x\"".to_string(),
+                    fixes,
+                );
+                return;
+            }
+        }
+    }
+    let (uv_path, source) = match UvBackend::resolve_pinned_path(config) {
+        Ok(resolved) => resolved,
+        Err(_) => UvBackend::resolve_path(config),
+    };
+    let version = query_uv_version(&uv_path);
+    let (major, minor, patch) = MIN_UV_VERSION;
+    match version {
+        Some(version) if version >= MIN_UV_VERSION => report_check(
+            true,
+            "uv",
+            format!(
+                "{}.{}.{} ({source}) >= {major}.{minor}.{patch}",
+                version.0, version.1, version.2
+            ),
+        ),
+        Some(version) => {
+            report_uv_failure(
+                required,
+                "uv",
+                format!(
+                    "{}.{}.{} ({source}) is older than {major}.{minor}.{patch}",
+                    version.0, version.1, version.2
+                ),
+                "uv self update".to_string(),
+                fixes,
+            );
+        }
+        None => {
+            report_uv_failure(
+                required,
+                "uv",
+                format!("{uv_path} ({source}) is not available"),
+                "curl -LsSf https://astral.sh/uv/install.sh | sh".to_string(),
+                fixes,
+            );
+        }
+    }
+}
+
+fn check_shell_hook(args: &VerifySetupArgs, fixes: &mut Vec<Fix>) {
+    let profile = args
+        .shell_profile
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(default_shell_profile);
+    match profile {
+        Some(profile) if is_init_script_installed(&profile) => report_check(
+            true,
+            "shell hook",
+            format!("installed in {}", profile.display()),
+        ),
+        Some(profile) => {
+            report_check(
+                false,
+                "shell hook",
+                format!("not installed in {}", profile.display()),
+            );
+            fixes.push(Fix {
+                check: "shell hook".to_string(),
+                command: format!("meowda init {}", profile.display()),
+            });
+        }
+        None => {
+            report_check(
+                false,
+                "shell hook",
+                "unable to detect the shell profile, pass --shell-profile",
+            );
+            fixes.push(Fix {
+                check: "shell hook".to_string(),
+                command: "meowda init <shell_profile>".to_string(),
+            });
+        }
+    }
+}
+
+fn check_env_vars(config: &Config, fixes: &mut Vec<Fix>) {
+    let required = config.required_env_vars.clone().unwrap_or_default();
+    if required.is_empty() {
+        report_check(true, "environment variables", "none required");
+        return;
+    }
+    for var in required {
+        if std::env::var_os(&var).is_some_and(|value| !value.is_empty()) {
+            report_check(true, "environment variable", format!("{var} is set"));
+        } else {
+            report_check(false, "environment variable", format!("{var} is not set"));
+            fixes.push(Fix {
+                check: var.clone(),
+                command: format!("export {var}=<value>"),
+            });
+        }
+    }
+}
+
+pub async fn verify_setup(args: VerifySetupArgs, config: &Config) -> Result<()> {
+    let mut fixes = Vec::new();
+
+    match find_project_config()? {
+        Some(path) => report_check(true, "project config", path.display().to_string()),
+        None => report_check(true, "project config", "not found, using defaults"),
+    }
+    check_uv(config, &mut fixes);
+    check_shell_hook(&args, &mut fixes);
+    check_env_vars(config, &mut fixes);
+
+    if fixes.is_empty() {
+        println!(
+            "\n{}",
+            "Everything is set up, happy hacking!".green().bold()
+        );
+        return Ok(());
+    }
+
+    println!("\n{}", "To fix the failed checks, run:".yellow().bold());
+    for fix in &fixes {
+        println!("  {}", format!("# {}", fix.check).dimmed());
+        println!("  {}", fix.command.cyan());
+    }
+    anyhow::bail!("{} check(s) failed", fixes.len());
+}
@@ -0,0 +1,226 @@
+use crate::cli::activate::{detect_venv_path, resolve_env_name};
+use crate::cli::args::VscodeArgs;
+use crate::config::Config;
+use crate::venv::python_path_in_venv;
+use anstream::println;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::path::Path;
+
+pub const INTERPRETER_SETTING: &str = "python.defaultInterpreterPath";
+
+/// The interpreter path as written into the settings, relative to `${workspaceFolder}` for
+/// environments inside the workspace, e.g., local ones, so the settings can be shared.
+fn interpreter_setting(python: &Path, workspace: &Path) -> String {
+    match python.strip_prefix(workspace) {
+        Ok(relative) => format!(
+            "${{workspaceFolder}}/{}",
+            relative.display().to_string().replace('\\', "/")
+        ),
+        Err(_) => python.display().to_string(),
+    }
+}
+
+/// Finds the end of the JSON string starting at `start`, i.e., the index after the
+/// closing quote.
+fn string_end(content: &str, start: usize) -> Option<usize> {
+    let mut escaped = false;
+    for (offset, c) in content[start + 1..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(start + 1 + offset + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Skips the whitespace and comments starting at `index`, returning the index of the next
+/// token.
+fn skip_trivia(content: &str, mut index: usize) -> Result<usize> {
+    loop {
+        let rest = &content[index..];
+        let trimmed = rest.trim_start();
+        index += rest.len() - trimmed.len();
+        if trimmed.starts_with("//") {
+            index += trimmed.find('\n').unwrap_or(trimmed.len());
+        } else if let Some(comment) = trimmed.strip_prefix("/*") {
+            let end = comment
+                .find("*/")
+                .context("Malformed settings.json, unterminated comment")?;
+            index += "/*".len() + end + "*/".len();
+        } else {
+            return Ok(index);
+        }
+    }
+}
+
+/// Finds the value of the top-level `key` in the settings `content`, returning the index of
+/// the opening brace of the settings and the index of the value if the key is set.
+fn find_setting(content: &str, key: &str) -> Result<(usize, Option<usize>)> {
+    let open = skip_trivia(content, 0)?;
+    if !content[open..].starts_with('{') {
+        anyhow::bail!("Malformed settings.json, expected an object");
+    }
+    let mut depth = 0;
+    let mut index = open;
+    loop {
+        index = skip_trivia(content, index)?;
+        let c = content[index..]
+            .chars()
+            .next()
+            .context("Malformed settings.json, unterminated object")?;
+        match c {
+            '"' => {
+                let end = string_end(content, index)
+                    .context("Malformed settings.json, unterminated string")?;
+                let after = skip_trivia(content, end)?;
+                // Only strings directly in the settings object followed by `:` are its keys
+                if depth == 1
+                    && content[after..].starts_with(':')
+                    && serde_json::from_str::<String>(&content[index..end]).is_ok_and(|k| k == key)
+                {
+                    return Ok((open, Some(skip_trivia(content, after + 1)?)));
+                }
+                index = end;
+            }
+            '{' | '[' => {
+                depth += 1;
+                index += 1;
+            }
+            '}' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((open, None));
+                }
+                index += 1;
+            }
+            c => index += c.len_utf8(),
+        }
+    }
+}
+
+/// Sets `key` to the string `value` in the settings `content`.
+///
+/// `settings.json` is JSON with comments, so it is edited as text rather than parsed, which
+/// keeps the comments and the order of the other settings.
+fn set_setting(content: &str, key: &str, value: &str) -> Result<String> {
+    let key_json = serde_json::to_string(key)?;
+    let value_json = serde_json::to_string(value)?;
+    let (open, value_start) = find_setting(content, key)?;
+    if let Some(value_start) = value_start {
+        if !content[value_start..].starts_with('"') {
+            anyhow::bail!("Malformed settings.json, `{key}` is not a string");
+        }
+        let value_end = string_end(content, value_start)
+            .context("Malformed settings.json, unterminated string")?;
+        return Ok(format!(
+            "{}{value_json}{}",
+            &content[..value_start],
+            &content[value_end..]
+        ));
+    }
+
+    let rest = &content[open + 1..];
+    let separator = if rest.trim_start().starts_with('}') {
+        ""
+    } else {
+        ","
+    };
+    Ok(format!(
+        "{}{{\n    {key_json}: {value_json}{separator}{}",
+        &content[..open],
+        if separator.is_empty() {
+            format!("\n{}", rest.trim_start())
+        } else {
+            rest.to_string()
+        }
+    ))
+}
+
+/// Points the Python extension of VS Code at an environment by setting
+/// `python.defaultInterpreterPath` in `.vscode/settings.json` of the current directory.
+pub async fn vscode(args: VscodeArgs, config: &Config) -> Result<()> {
+    let env_ref = resolve_env_name(args.name, config)?;
+    let (name, venv_path) = detect_venv_path(&args.scope, &env_ref, config)?;
+    let workspace = std::env::current_dir().context("Failed to get current working directory")?;
+    let python = std::path::absolute(python_path_in_venv(&venv_path))
+        .context("Failed to resolve the interpreter path")?;
+    let value = interpreter_setting(&python, &workspace);
+
+    let settings_dir = workspace.join(".vscode");
+    let settings_path = settings_dir.join("settings.json");
+    let content = match std::fs::read_to_string(&settings_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "{}\n".to_string(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read '{}'", settings_path.display()));
+        }
+    };
+    let updated = set_setting(&content, INTERPRETER_SETTING, &value)
+        .with_context(|| format!("Failed to update '{}'", settings_path.display()))?;
+    std::fs::create_dir_all(&settings_dir)
+        .with_context(|| format!("Failed to create '{}'", settings_dir.display()))?;
+    std::fs::write(&settings_path, updated)
+        .with_context(|| format!("Failed to write '{}'", settings_path.display()))?;
+    println!(
+        "{}",
+        format!(
+            "VS Code now uses '{name}' ({value}), reload the window if the interpreter does not change."
+        )
+        .green()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_setting_keeps_comments_and_order() -> Result<()> {
+        let key = INTERPRETER_SETTING;
+        assert_eq!(
+            set_setting("{}\n", key, "/venvs/app/bin/python")?,
+            "{\n    \"python.defaultInterpreterPath\": \"/venvs/app/bin/python\"\n}\n"
+        );
+
+        let content = "// Project settings\n{\n    \"editor.tabSize\": 4, // spaces\n}\n";
+        assert_eq!(
+            set_setting(content, key, "/venvs/app/bin/python")?,
+            "// Project settings\n{\n    \"python.defaultInterpreterPath\": \"/venvs/app/bin/python\",\n    \"editor.tabSize\": 4, // spaces\n}\n"
+        );
+
+        let content =
+            "{\n    \"python.defaultInterpreterPath\" : \"C:\\\\old\\\"\",\n    \"a\": 1\n}";
+        assert_eq!(
+            set_setting(content, key, "/new")?,
+            "{\n    \"python.defaultInterpreterPath\" : \"/new\",\n    \"a\": 1\n}"
+        );
+
+        // The key in comments, nested objects and values is not the setting
+        let content = "/* { \"python.defaultInterpreterPath\": \"a\" } */\n{\n    // \"python.defaultInterpreterPath\": \"b\",\n    \"[python]\": { \"python.defaultInterpreterPath\": \"c\" },\n    \"note\": \"python.defaultInterpreterPath\"\n}\n";
+        assert_eq!(
+            set_setting(content, key, "/new")?,
+            "/* { \"python.defaultInterpreterPath\": \"a\" } */\n{\n    \"python.defaultInterpreterPath\": \"/new\",\n    // \"python.defaultInterpreterPath\": \"b\",\n    \"[python]\": { \"python.defaultInterpreterPath\": \"c\" },\n    \"note\": \"python.defaultInterpreterPath\"\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn interpreter_setting_is_relative_inside_the_workspace() {
+        let workspace = Path::new("/work/project");
+        assert_eq!(
+            interpreter_setting(
+                Path::new("/work/project/.meowda/venvs/app/bin/python"),
+                workspace
+            ),
+            "${workspaceFolder}/.meowda/venvs/app/bin/python"
+        );
+        assert_eq!(
+            interpreter_setting(Path::new("/venvs/app/bin/python"), workspace),
+            "/venvs/app/bin/python"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use crate::cli::activate::{detect_venv_path, resolve_env_name};
+use crate::cli::args::{PythonPathArgs, WhichArgs};
+use crate::cli::output;
+use crate::config::Config;
+use crate::venv::{bin_dir_in_venv, python_path_in_venv};
+use anstream::println;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The paths of an environment, with the platform layout resolved.
+#[derive(Debug, Serialize)]
+struct EnvPaths {
+    name: String,
+    path: PathBuf,
+    python: PathBuf,
+    bin_dir: PathBuf,
+}
+
+pub async fn which(args: WhichArgs, config: &Config) -> Result<()> {
+    let env_ref = resolve_env_name(args.name, config)?;
+    let (name, venv_path) = detect_venv_path(&args.scope, &env_ref, config)?;
+    let paths = EnvPaths {
+        name,
+        python: python_path_in_venv(&venv_path),
+        bin_dir: bin_dir_in_venv(&venv_path),
+        path: venv_path,
+    };
+    if output::is_json(args.json) {
+        return output::print_json(&paths);
+    }
+    println!("path:    {}", paths.path.display());
+    println!("python:  {}", paths.python.display());
+    println!("bin_dir: {}", paths.bin_dir.display());
+    Ok(())
+}
+
+pub async fn python_path(args: PythonPathArgs, config: &Config) -> Result<()> {
+    let env_ref = resolve_env_name(args.name, config)?;
+    let (_, venv_path) = detect_venv_path(&args.scope, &env_ref, config)?;
+    println!("{}", python_path_in_venv(&venv_path).display());
+    Ok(())
+}
@@ -0,0 +1,212 @@
+/// User and project configuration for meowda.
+///
+/// Configuration is read from two layers, later layers override earlier ones:
+///
+/// 1. The user-level config file, e.g., `~/.config/meowda/meowda.toml`
+/// 2. The nearest `meowda.toml` found walking up from the current working directory
+use anyhow::{Context, Result};
+use etcetera::BaseStrategy;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = "meowda.toml";
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Backend used to create environments and manage packages, e.g., `uv` or `venv`.
+    pub backend: Option<String>,
+    /// Path to the uv executable used by the uv backend.
+    pub uv_path: Option<String>,
+    /// Extra arguments passed to every uv invocation, e.g., `["--offline"]`.
+    pub uv_args: Option<Vec<String>>,
+    /// Pinned uv version, e.g., `0.5.x` or `0.5.3`, as resolver behavior differs between versions.
+    pub uv_version: Option<String>,
+    /// Environment variables that must be set, checked by `meowda verify-setup`.
+    pub required_env_vars: Option<Vec<String>>,
+    /// Timeout in seconds for child processes (e.g., uv), no timeout if unset.
+    pub command_timeout: Option<u64>,
+    /// Timeout in seconds for waiting on a lock held by another meowda process, forever if
+    /// unset (5 minutes in CI mode).
+    pub lock_timeout: Option<u64>,
+    /// Store backend deciding where stores live, e.g., `filesystem` or `volume`.
+    pub store_backend: Option<String>,
+    /// Root directory of the `volume` store backend.
+    pub store_root: Option<PathBuf>,
+    /// Share the local store of the main worktree with all linked git worktrees.
+    pub share_worktree_store: Option<bool>,
+    /// Stop local store discovery at the root of the git repository instead of walking up
+    /// to the filesystem root. A `.meowda/root` marker always stops it.
+    pub stop_at_git_root: Option<bool>,
+    /// Scope of `create`, `list` and the other commands run without `--local`/`--global`,
+    /// `local`, `global` or `auto` for the nearest local store up the tree if any.
+    pub default_scope: Option<String>,
+    /// Days removed environments are kept in the trash of their store for `meowda restore`,
+    /// 7 unless set, 0 deletes them right away.
+    pub trash_days: Option<u64>,
+    /// Append debug logs, e.g., of uv invocations, to a rotated `meowda.log` in the user
+    /// state directory, `MEOWDA_LOG_FILE` overrides it.
+    pub log_file: Option<bool>,
+    /// Environment used by `meowda activate`/`meowda shell` when no name is given,
+    /// e.g., set per worktree in an untracked `meowda.toml`.
+    pub default_env: Option<String>,
+    /// Prefix the shell prompt with the active environment, on unless disabled.
+    pub prompt: Option<bool>,
+    /// Format of the prompt prefix, `{name}` and `{scope}` are replaced, e.g., `({scope}:{name}) `.
+    pub prompt_format: Option<String>,
+    /// Extra named stores, e.g., `team = "/mnt/team/venvs"`, referenced as `team:<env>`.
+    pub stores: Option<BTreeMap<String, PathBuf>>,
+    /// Extra stores of `stores` whose environments must not be changed, e.g., centrally
+    /// provisioned ones on a shared server. A `.readonly` file in any store has the same effect.
+    pub read_only_stores: Option<Vec<String>>,
+    /// Defaults of `meowda create` per scope, e.g., `[create.local]`.
+    pub create: Option<ScopeCreateDefaults>,
+    /// Declared environments, e.g., `[envs.dev]`, created or updated by `meowda sync-all`.
+    pub envs: Option<BTreeMap<String, EnvDeclaration>>,
+}
+
+/// Defaults of `meowda create` in one scope, explicit arguments win.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct CreateDefaults {
+    /// Python version/path, like `--python`.
+    pub python: Option<String>,
+    /// Install seed packages, like `--seed`/`--no-seed`.
+    pub seed: Option<bool>,
+}
+
+/// An environment declared in config, e.g., `[envs.dev]`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnvDeclaration {
+    /// Python version/path, like `--python`.
+    pub python: Option<String>,
+    /// Requirements files installed into the environment, relative paths are relative
+    /// to the directory of the project `meowda.toml`.
+    pub requirements: Vec<PathBuf>,
+    /// Store of the environment, the local one unless set to `global`.
+    pub scope: DeclaredScope,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeclaredScope {
+    #[default]
+    Local,
+    Global,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct ScopeCreateDefaults {
+    pub local: Option<CreateDefaults>,
+    pub global: Option<CreateDefaults>,
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        let current_dir =
+            std::env::current_dir().context("Failed to get current working directory")?;
+        Self::load_in(&current_dir)
+    }
+
+    /// Loads the config applying in `dir`, the user config and the nearest project config
+    /// of `dir` or its parents.
+    pub fn load_in(dir: &Path) -> Result<Self> {
+        let mut config = Config::default();
+        if let Some(user_config) = user_config_path()
+            && user_config.is_file()
+        {
+            config = config.merge(Self::from_file(&user_config)?);
+        }
+        if let Some(project_config) = find_project_config_in(dir) {
+            config = config.merge(Self::from_file(&project_config)?);
+        }
+        Ok(config)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file '{}'", path.display()))
+    }
+
+    /// The `meowda create` defaults of the local or global scope.
+    pub fn create_defaults(&self, local: bool) -> CreateDefaults {
+        self.create
+            .as_ref()
+            .and_then(|create| {
+                if local {
+                    create.local.clone()
+                } else {
+                    create.global.clone()
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Merges `overrides` on top of `self`, fields set in `overrides` win.
+    fn merge(self, overrides: Config) -> Config {
+        Config {
+            backend: overrides.backend.or(self.backend),
+            uv_path: overrides.uv_path.or(self.uv_path),
+            uv_args: overrides.uv_args.or(self.uv_args),
+            uv_version: overrides.uv_version.or(self.uv_version),
+            required_env_vars: overrides.required_env_vars.or(self.required_env_vars),
+            command_timeout: overrides.command_timeout.or(self.command_timeout),
+            lock_timeout: overrides.lock_timeout.or(self.lock_timeout),
+            store_backend: overrides.store_backend.or(self.store_backend),
+            store_root: overrides.store_root.or(self.store_root),
+            share_worktree_store: overrides.share_worktree_store.or(self.share_worktree_store),
+            stop_at_git_root: overrides.stop_at_git_root.or(self.stop_at_git_root),
+            default_scope: overrides.default_scope.or(self.default_scope),
+            trash_days: overrides.trash_days.or(self.trash_days),
+            log_file: overrides.log_file.or(self.log_file),
+            default_env: overrides.default_env.or(self.default_env),
+            prompt: overrides.prompt.or(self.prompt),
+            prompt_format: overrides.prompt_format.or(self.prompt_format),
+            read_only_stores: overrides.read_only_stores.or(self.read_only_stores),
+            create: overrides.create.or(self.create),
+            // Stores and envs of both layers are kept, the project wins on conflicting names
+            stores: merge_maps(self.stores, overrides.stores),
+            envs: merge_maps(self.envs, overrides.envs),
+        }
+    }
+}
+
+fn merge_maps<V>(
+    base: Option<BTreeMap<String, V>>,
+    overrides: Option<BTreeMap<String, V>>,
+) -> Option<BTreeMap<String, V>> {
+    match (base, overrides) {
+        (Some(mut base), Some(overrides)) => {
+            base.extend(overrides);
+            Some(base)
+        }
+        (base, overrides) => overrides.or(base),
+    }
+}
+
+/// Returns the user-level config file path.
+///
+/// Corresponds to `$XDG_CONFIG_HOME/meowda/meowda.toml` on Unix.
+pub fn user_config_path() -> Option<PathBuf> {
+    etcetera::base_strategy::choose_base_strategy()
+        .ok()
+        .map(|dirs| dirs.config_dir().join("meowda").join(CONFIG_FILE_NAME))
+}
+
+pub fn find_project_config() -> Result<Option<PathBuf>> {
+    let current_dir = std::env::current_dir().context("Failed to get current working directory")?;
+    Ok(find_project_config_in(&current_dir))
+}
+
+/// The nearest project config of `dir` or its parents.
+pub fn find_project_config_in(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
@@ -3,4 +3,20 @@ pub struct EnvVars;
 impl EnvVars {
     pub const MEOWDA_LOCAL_VENV_DIR: &'static str = "MEOWDA_LOCAL_VENV_DIR";
     pub const MEOWDA_GLOBAL_VENV_DIR: &'static str = "MEOWDA_GLOBAL_VENV_DIR";
+    pub const MEOWDA_BACKEND: &'static str = "MEOWDA_BACKEND";
+    pub const MEOWDA_STORE_BACKEND: &'static str = "MEOWDA_STORE_BACKEND";
+    pub const MEOWDA_STORE_ROOT: &'static str = "MEOWDA_STORE_ROOT";
+    pub const MEOWDA_FROZEN: &'static str = "MEOWDA_FROZEN";
+    pub const MEOWDA_SHELL: &'static str = "MEOWDA_SHELL";
+    pub const MEOWDA_UV_PATH: &'static str = "MEOWDA_UV_PATH";
+    pub const MEOWDA_UV_ARGS: &'static str = "MEOWDA_UV_ARGS";
+    pub const MEOWDA_ACCESSIBLE: &'static str = "MEOWDA_ACCESSIBLE";
+    pub const MEOWDA_CI: &'static str = "MEOWDA_CI";
+    pub const MEOWDA_OFFLINE: &'static str = "MEOWDA_OFFLINE";
+    pub const MEOWDA_NO_RECURSE: &'static str = "MEOWDA_NO_RECURSE";
+    pub const MEOWDA_LOG_FILE: &'static str = "MEOWDA_LOG_FILE";
+    pub const MEOWDA_DEFAULT_SCOPE: &'static str = "MEOWDA_DEFAULT_SCOPE";
+    pub const PYENV_ROOT: &'static str = "PYENV_ROOT";
+    pub const NO_COLOR: &'static str = "NO_COLOR";
+    pub const CLICOLOR_FORCE: &'static str = "CLICOLOR_FORCE";
 }
@@ -0,0 +1,352 @@
+/// Typed meowda errors carrying a stable error code.
+///
+/// Each code has a longer explanation available through `meowda explain <code>`.
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    EnvNotFound,
+    EnvAlreadyExists,
+    BackendUnavailable,
+    NoActiveEnv,
+    EnvNotManaged,
+    InvalidScope,
+    EnvFrozen,
+    Interrupted,
+    CommandTimeout,
+    InvalidEnvName,
+    LockTimeout,
+    BackendFailed,
+    StoreReadOnly,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 13] = [
+        ErrorCode::EnvNotFound,
+        ErrorCode::EnvAlreadyExists,
+        ErrorCode::BackendUnavailable,
+        ErrorCode::NoActiveEnv,
+        ErrorCode::EnvNotManaged,
+        ErrorCode::InvalidScope,
+        ErrorCode::EnvFrozen,
+        ErrorCode::Interrupted,
+        ErrorCode::CommandTimeout,
+        ErrorCode::InvalidEnvName,
+        ErrorCode::LockTimeout,
+        ErrorCode::BackendFailed,
+        ErrorCode::StoreReadOnly,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorCode::EnvNotFound => "M001",
+            ErrorCode::EnvAlreadyExists => "M002",
+            ErrorCode::BackendUnavailable => "M003",
+            ErrorCode::NoActiveEnv => "M004",
+            ErrorCode::EnvNotManaged => "M005",
+            ErrorCode::InvalidScope => "M006",
+            ErrorCode::EnvFrozen => "M007",
+            ErrorCode::Interrupted => "M008",
+            ErrorCode::CommandTimeout => "M009",
+            ErrorCode::InvalidEnvName => "M010",
+            ErrorCode::LockTimeout => "M011",
+            ErrorCode::BackendFailed => "M012",
+            ErrorCode::StoreReadOnly => "M013",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_ascii_uppercase();
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+
+    pub fn summary(&self) -> &'static str {
+        match self {
+            ErrorCode::EnvNotFound => "Virtual environment not found",
+            ErrorCode::EnvAlreadyExists => "Virtual environment already exists",
+            ErrorCode::BackendUnavailable => "Backend is not available",
+            ErrorCode::NoActiveEnv => "No virtual environment is activated",
+            ErrorCode::EnvNotManaged => "Virtual environment is not managed by meowda",
+            ErrorCode::InvalidScope => "Invalid scope selection",
+            ErrorCode::EnvFrozen => "Virtual environment is frozen",
+            ErrorCode::Interrupted => "Operation interrupted",
+            ErrorCode::CommandTimeout => "Command timed out",
+            ErrorCode::InvalidEnvName => "Invalid virtual environment name",
+            ErrorCode::LockTimeout => "Timed out waiting for a lock",
+            ErrorCode::BackendFailed => "Backend command failed",
+            ErrorCode::StoreReadOnly => "Store is read-only",
+        }
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            ErrorCode::EnvNotFound => {
+                r#"The requested virtual environment does not exist in any of the searched stores.
+
+Meowda searches local stores (`.meowda/venvs` in the current directory and its parents)
+first, then the global store. Passing `--local` or `--global` restricts the search to
+that scope only.
+
+Common causes:
+  - A typo in the environment name
+  - The environment lives in a local store of another project
+  - `MEOWDA_LOCAL_VENV_DIR` or `MEOWDA_GLOBAL_VENV_DIR` points to another directory
+
+Fixes:
+  - Run `meowda env list` to see the available environments
+  - Create it with `meowda create <name>`"#
+            }
+            ErrorCode::EnvAlreadyExists => {
+                r#"A virtual environment with the same name already exists in the target store.
+
+Fixes:
+  - Pass `--clear` to remove the existing environment and recreate it
+  - Choose another name, or target another scope with `--local` / `--global`"#
+            }
+            ErrorCode::BackendUnavailable => {
+                r#"The backend used to create environments and manage packages could not be found.
+
+Meowda uses uv by default. The backend can be selected with the `MEOWDA_BACKEND`
+environment variable or the `backend` key in `meowda.toml`.
+
+Fixes:
+  - Install uv, see https://docs.astral.sh/uv/getting-started/installation/
+  - Or switch to the fallback backend with `MEOWDA_BACKEND=venv`, or with
+    `MEOWDA_BACKEND=auto` to keep using uv wherever it is installed
+  - If `uv_version` pins a uv version, install that version with the command in the
+    error message, meowda then picks the bootstrapped copy up automatically"#
+            }
+            ErrorCode::NoActiveEnv => {
+                r#"The command operates on the currently activated virtual environment, but none is
+activated (`VIRTUAL_ENV` is not set).
+
+Fixes:
+  - Activate an environment first with `meowda activate <name>`
+  - Make sure the shell integration is installed with `meowda init <shell_profile>`"#
+            }
+            ErrorCode::EnvNotManaged => {
+                r#"The currently activated virtual environment is not located in any meowda store,
+so meowda refuses to modify it.
+
+Fixes:
+  - Deactivate it and activate a meowda-managed environment instead
+  - Fork it into meowda with `meowda fork <name> --from <path>`"#
+            }
+            ErrorCode::InvalidScope => {
+                r#"The scope selection is invalid, e.g., both `--local` and `--global` were passed, or a
+scope-qualified name such as `local:myenv` contradicts `--global`.
+
+Fixes:
+  - Pass at most one of `--local` and `--global`
+  - Drop the scope flag when qualifying the name with `local:` or `global:`"#
+            }
+            ErrorCode::EnvFrozen => {
+                r#"The virtual environment has been frozen, e.g., with `meowda install --freeze-env`
+or `meowda env freeze`, so its packages must not change anymore. This keeps blessed
+environments (e.g., for CI) from drifting.
+
+While a frozen environment is activated, `MEOWDA_FROZEN=1` is exported so that other
+tools wrapping `pip install` can refuse changes too.
+
+Fixes:
+  - Pass `--thaw` to `meowda install` / `meowda uninstall` to change it anyway
+  - Thaw it permanently with `meowda env thaw <name>`"#
+            }
+            ErrorCode::Interrupted => {
+                r#"The operation was interrupted with Ctrl-C while a child process (e.g., uv) was
+running. The child process was killed, and a partially created environment is removed.
+
+Fixes:
+  - Run the command again"#
+            }
+            ErrorCode::CommandTimeout => {
+                r#"A child process (e.g., uv) ran longer than the configured `command_timeout` and
+was killed. A partially created environment is removed.
+
+Common causes:
+  - A slow or unreachable package index
+  - Large packages being built from source
+
+Fixes:
+  - Increase `command_timeout` (in seconds) in `meowda.toml`, or remove it
+  - Check the network connection and the package index configuration"#
+            }
+            ErrorCode::InvalidEnvName => {
+                r#"The virtual environment name cannot be used as a directory name inside a store.
+
+Environment names must be a single, portable path component, so that an environment
+can never be created outside its store or clash with hidden files such as `.lock`.
+
+Rejected names:
+  - Names containing `/` or `\`, or equal to `..`
+  - Names starting with `.`
+  - Names containing `< > : " | ? *` or control characters, or ending with `.` or a space
+  - Names reserved on Windows, e.g., `CON`, `NUL`, `COM1`, `LPT1`
+
+Fixes:
+  - Choose a name made of letters, digits, `-`, `_` and `.`, e.g., `my-project-3.12`"#
+            }
+            ErrorCode::LockTimeout => {
+                r#"Another meowda process held the lock of the store or environment for longer than
+meowda was willing to wait. Locks are only waited on for a limited time in the CI mode.
+
+Common causes:
+  - Another meowda command, e.g., a long `meowda install`, running in parallel
+  - A meowda process that hangs, e.g., on a slow package index
+
+Fixes:
+  - Wait for the other command to finish and run the command again
+  - Avoid running meowda commands on the same environment in parallel"#
+            }
+            ErrorCode::BackendFailed => {
+                r#"The backend (uv, or `python -m venv` and pip) ran but failed to create the
+environment or to install or uninstall packages. Its output is printed above the error,
+or written to the log file with `--log-dir`.
+
+Common causes:
+  - A typo in a package name or version specifier
+  - A Python version that is neither installed nor downloadable
+  - An unreachable package index, or missing packages in the cache in offline mode
+
+Fixes:
+  - Check the output of the backend and run the command again
+  - Run with `--verbose` to see the full output"#
+            }
+            ErrorCode::StoreReadOnly => {
+                r#"The store is read-only, so meowda refuses to create, remove or change its
+environments. Read-only stores hold centrally provisioned environments, e.g., on a shared
+server, which can still be listed, activated and run.
+
+A store is read-only if its root contains a `.readonly` file, or if it is one of the
+extra stores listed in `read_only_stores` in `meowda.toml`.
+
+Fixes:
+  - Fork the environment into a writable store with `meowda fork <name> --from <path>`
+  - Ask the owners of the store to make the change"#
+            }
+        }
+    }
+
+    /// Exit code of the process, distinct for each error code so that scripts can branch on
+    /// the kind of failure. Other errors exit with 1 and usage errors with 2. Timeouts and
+    /// interruptions follow the conventions of `timeout` and shells.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCode::EnvNotFound => 3,
+            ErrorCode::LockTimeout => 4,
+            ErrorCode::BackendFailed => 5,
+            ErrorCode::BackendUnavailable => 6,
+            ErrorCode::EnvAlreadyExists => 7,
+            ErrorCode::NoActiveEnv => 8,
+            ErrorCode::EnvNotManaged => 9,
+            ErrorCode::InvalidScope => 10,
+            ErrorCode::EnvFrozen => 11,
+            ErrorCode::InvalidEnvName => 12,
+            ErrorCode::StoreReadOnly => 13,
+            ErrorCode::CommandTimeout => 124,
+            ErrorCode::Interrupted => 130,
+        }
+    }
+
+    /// Finds the error code of `error` or of any error it wraps.
+    pub fn of(error: &anyhow::Error) -> Option<Self> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<MeowdaError>())
+            .map(|error| error.code)
+    }
+
+    /// Builds an error with this code and the given message.
+    pub fn err(self, message: impl Into<String>) -> anyhow::Error {
+        anyhow::Error::new(MeowdaError {
+            code: self,
+            message: message.into(),
+            hint: None,
+        })
+    }
+
+    /// Builds an error with this code, the given message and a hint on how to fix this
+    /// particular occurrence, printed as `help: <hint>`.
+    pub fn err_with_hint(
+        self,
+        message: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> anyhow::Error {
+        anyhow::Error::new(MeowdaError {
+            code: self,
+            message: message.into(),
+            hint: Some(hint.into()),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct MeowdaError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl Display for MeowdaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(hint) = &self.hint {
+            write!(f, "\nhelp: {hint}")?;
+        }
+        write!(
+            f,
+            "\n(error {}, run `meowda explain {}` for details)",
+            self.code.code(),
+            self.code.code()
+        )
+    }
+}
+
+impl std::error::Error for MeowdaError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_codes_are_unique_and_round_trip() {
+        for error in ErrorCode::ALL {
+            assert_eq!(ErrorCode::from_code(error.code()), Some(error));
+            assert_eq!(
+                ErrorCode::from_code(&error.code().to_ascii_lowercase()),
+                Some(error)
+            );
+        }
+        assert_eq!(ErrorCode::from_code("M999"), None);
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let mut exit_codes = ErrorCode::ALL.map(|error| error.exit_code()).to_vec();
+        exit_codes.sort();
+        exit_codes.dedup();
+        assert_eq!(exit_codes.len(), ErrorCode::ALL.len());
+        assert!(exit_codes.iter().all(|&code| code > 2 && code <= 130));
+        assert_eq!(ErrorCode::EnvNotFound.exit_code(), 3);
+        assert_eq!(ErrorCode::LockTimeout.exit_code(), 4);
+        assert_eq!(ErrorCode::BackendFailed.exit_code(), 5);
+
+        let error = ErrorCode::EnvFrozen
+            .err("frozen")
+            .context("Failed to install packages");
+        assert_eq!(ErrorCode::of(&error), Some(ErrorCode::EnvFrozen));
+        assert_eq!(ErrorCode::of(&anyhow::anyhow!("other")), None);
+    }
+
+    #[test]
+    fn hints_are_printed_before_the_code() {
+        let error = ErrorCode::EnvNotFound.err_with_hint(
+            "Virtual environment 'ap' not found in local or global scope.",
+            "did you mean 'app' in global?",
+        );
+        assert_eq!(
+            error.to_string(),
+            "Virtual environment 'ap' not found in local or global scope.\nhelp: did you mean 'app' in global?\n(error M001, run `meowda explain M001` for details)"
+        );
+    }
+}
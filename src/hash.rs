@@ -0,0 +1,84 @@
+//! Hashes which have to stay the same across meowda and Rust versions and platforms, e.g.,
+//! cache keys of CI systems and keys persisted to disk, unlike those of `std::hash`.
+
+use sha2::{Digest, Sha256};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The SHA-256 digest of `data` as lowercase hex.
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// Builds a short key of several fields, e.g., a directory name or a fingerprint. Fields are
+/// length-prefixed, so moving bytes from one field to the next changes the key.
+#[derive(Default)]
+pub struct KeyHasher(Sha256);
+
+impl KeyHasher {
+    pub fn field(&mut self, field: impl AsRef<[u8]>) -> &mut Self {
+        let field = field.as_ref();
+        self.0.update((field.len() as u64).to_le_bytes());
+        self.0.update(field);
+        self
+    }
+
+    /// Adds a field which may be missing, a missing field differs from an empty one.
+    pub fn optional_field(&mut self, field: Option<impl AsRef<[u8]>>) -> &mut Self {
+        match field {
+            Some(field) => {
+                self.0.update([1]);
+                self.field(field)
+            }
+            None => {
+                self.0.update([0]);
+                self
+            }
+        }
+    }
+
+    /// The key, the first 16 hex digits of the SHA-256 digest of the fields.
+    pub fn finish(self) -> String {
+        to_hex(&self.0.finalize()[..8])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_matches_the_reference_digests() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn key_hasher_separates_fields() {
+        let key = |fields: &[Option<&str>]| {
+            let mut hasher = KeyHasher::default();
+            for field in fields {
+                hasher.optional_field(*field);
+            }
+            hasher.finish()
+        };
+        let cases = [
+            (vec![Some("ab"), Some("c")], vec![Some("a"), Some("bc")]),
+            (vec![None], vec![Some("")]),
+            (vec![Some("a"), None], vec![None, Some("a")]),
+        ];
+        for (a, b) in cases {
+            assert_ne!(key(&a), key(&b), "{a:?} {b:?}");
+        }
+        assert_eq!(key(&[Some("a")]), key(&[Some("a")]));
+        assert_eq!(key(&[Some("a")]).len(), 16);
+    }
+}
@@ -0,0 +1,48 @@
+//! Ctrl-C handling. Listening for Ctrl-C replaces the default behavior of the process for
+//! good, so a single listener installed at startup owns it: it exits with the exit code of
+//! [`ErrorCode::Interrupted`] unless some code subscribed to handle the interrupt itself,
+//! e.g., to kill a child process and clean up a half-created environment.
+
+use crate::error::ErrorCode;
+use std::sync::OnceLock;
+use tokio::sync::watch;
+
+static INTERRUPTS: OnceLock<watch::Sender<u64>> = OnceLock::new();
+
+fn sender() -> &'static watch::Sender<u64> {
+    INTERRUPTS.get_or_init(|| watch::channel(0).0)
+}
+
+/// Installs the Ctrl-C listener of the process, called once at startup. Without it, e.g.,
+/// in tests, Ctrl-C keeps its default behavior and subscriptions never fire.
+pub fn install() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if sender().receiver_count() == 0 {
+                std::process::exit(ErrorCode::Interrupted.exit_code());
+            }
+            sender().send_modify(|count| *count += 1);
+        }
+    });
+}
+
+/// A subscription to Ctrl-C: while it lives, Ctrl-C is delivered to it instead of exiting.
+pub struct Interrupts(watch::Receiver<u64>);
+
+impl Interrupts {
+    pub fn subscribe() -> Self {
+        Interrupts(sender().subscribe())
+    }
+
+    /// Resolves on the next Ctrl-C.
+    pub async fn next(&mut self) {
+        if self.0.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Resolves on the next Ctrl-C, which does not exit the process while this is awaited.
+pub async fn ctrl_c() {
+    Interrupts::subscribe().next().await;
+}
@@ -0,0 +1,190 @@
+use crate::redact::redact_credentials;
+use crate::store::venv_store::user_state_dir;
+use crate::ui;
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, warn};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
+
+/// Log filter of the log file, independent of `--quiet`, `-v` and `RUST_LOG`.
+const LOG_FILE_FILTER: &str = "meowda=debug";
+
+/// Maps `--quiet` and the number of `-v` flags to a log filter of meowda.
+fn default_filter(quiet: bool, verbose: u8) -> &'static str {
+    match (quiet, verbose) {
+        (true, _) => "meowda=error",
+        (false, 0) => "meowda=warn",
+        (false, 1) => "meowda=info",
+        (false, 2) => "meowda=debug",
+        (false, _) => "meowda=trace",
+    }
+}
+
+/// The log file is rotated once it grows beyond this size.
+const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Rotated log files kept next to the current one, `meowda.log.1` being the newest.
+const ROTATED_LOG_FILES: usize = 3;
+
+/// The log file enabled by `MEOWDA_LOG_FILE` (`1` for the default location, or a path) or
+/// `log_file` in config, `MEOWDA_LOG_FILE=0` disables it. The default location is
+/// `meowda.log` in the `logs` directory of the user state directory.
+pub fn log_file_path(
+    env_value: Option<OsString>,
+    enabled_in_config: Option<bool>,
+) -> Option<PathBuf> {
+    let default_path = || user_state_dir().map(|dir| dir.join("logs").join("meowda.log"));
+    match env_value {
+        Some(value) if value.is_empty() || value == "0" => None,
+        Some(value) if value == "1" => default_path(),
+        Some(path) => Some(PathBuf::from(path)),
+        None if enabled_in_config == Some(true) => default_path(),
+        None => None,
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+/// Shifts `path` to `path.1`, `path.1` to `path.2` and so on once it exceeds `max_size`,
+/// dropping the oldest. Concurrent meowda processes may race here, at worst losing one
+/// rotated file.
+fn rotate(path: &Path, max_size: u64) -> io::Result<()> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.len() >= max_size => {}
+        Ok(_) => return Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    }
+    for index in (1..ROTATED_LOG_FILES).rev() {
+        match std::fs::rename(rotated_path(path, index), rotated_path(path, index + 1)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+}
+
+fn open_log_file(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    rotate(path, MAX_LOG_FILE_SIZE)?;
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Installs the log subscriber writing to stderr, `RUST_LOG` overrides the CLI flags.
+///
+/// With a `log_file`, debug logs are also appended to it as JSON lines with timestamps,
+/// whatever the flags, so failures in CI or on user machines can be diagnosed afterwards,
+/// e.g., from the uv command lines and their exit codes.
+pub fn init(quiet: bool, verbose: u8, log_file: Option<PathBuf>) {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(default_filter(quiet, verbose)));
+    let stderr_layer = tracing_subscriber::fmt::layer()
+        // anstream also strips the colors of messages, e.g., highlighted names
+        .with_writer(anstream::stderr)
+        .with_ansi(ui::use_color(std::io::stderr().is_terminal()))
+        .without_time()
+        .with_target(verbose >= 2)
+        .with_filter(filter);
+    let (file_layer, file_error) = match log_file.map(|path| (open_log_file(&path), path)) {
+        Some((Ok(file), _)) => {
+            let layer = tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(Mutex::new(file))
+                .with_filter(EnvFilter::new(LOG_FILE_FILTER));
+            (Some(layer), None)
+        }
+        Some((Err(e), path)) => (None, Some(format!("'{}': {e}", path.display()))),
+        None => (None, None),
+    };
+    let logs_to_file = file_layer.is_some();
+    tracing_subscriber::registry()
+        .with(stderr_layer)
+        .with(file_layer)
+        .init();
+    if let Some(error) = file_error {
+        warn!("Failed to open log file {error}");
+    }
+    if logs_to_file {
+        let argv = std::env::args()
+            .map(|arg| redact_credentials(&arg))
+            .collect::<Vec<_>>();
+        debug!(?argv, "Started");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_filter() {
+        let cases = [
+            (true, 2, "meowda=error"),
+            (false, 0, "meowda=warn"),
+            (false, 1, "meowda=info"),
+            (false, 2, "meowda=debug"),
+            (false, 5, "meowda=trace"),
+        ];
+        for (quiet, verbose, expected) in cases {
+            assert_eq!(default_filter(quiet, verbose), expected);
+        }
+    }
+
+    #[test]
+    fn log_file_is_enabled_by_env_or_config() {
+        let default_path = user_state_dir().map(|dir| dir.join("logs").join("meowda.log"));
+        let cases = [
+            (None, None, None),
+            (None, Some(false), None),
+            (None, Some(true), default_path.clone()),
+            (Some("1"), None, default_path.clone()),
+            (Some("0"), Some(true), None),
+            (Some(""), Some(true), None),
+            (
+                Some("/tmp/meowda.log"),
+                Some(false),
+                Some(PathBuf::from("/tmp/meowda.log")),
+            ),
+        ];
+        for (env_value, config, expected) in cases {
+            assert_eq!(
+                log_file_path(env_value.map(OsString::from), config),
+                expected,
+                "{env_value:?} {config:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rotate_keeps_the_newest_files() -> io::Result<()> {
+        let temp = tempfile::tempdir()?;
+        let path = temp.path().join("meowda.log");
+        for generation in 0..5 {
+            std::fs::write(&path, format!("{generation}"))?;
+            rotate(&path, 1)?;
+        }
+        assert!(!path.exists());
+        let kept = (1..=ROTATED_LOG_FILES + 1)
+            .map(|index| std::fs::read_to_string(rotated_path(&path, index)).ok())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kept,
+            [Some("4".into()), Some("3".into()), Some("2".into()), None]
+        );
+
+        std::fs::write(&path, "small")?;
+        rotate(&path, MAX_LOG_FILE_SIZE)?;
+        assert!(path.exists());
+        Ok(())
+    }
+}
@@ -1,70 +1,252 @@
+mod backends;
 mod cli;
+mod config;
 mod envs;
+mod error;
+mod hash;
+mod interrupt;
+mod logging;
+mod redact;
 mod store;
+mod ui;
 mod venv;
 use anstream::eprintln;
 use clap::Parser;
+use cli::args::{
+    Commands, EnvCommandsArgs, MatrixCommandsArgs, ShimCommandsArgs, TagCommandsArgs,
+    TestFixtureCommandsArgs, ToolCommandsArgs,
+};
+use std::time::Duration;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = cli::args::Args::parse();
-    let venv_service = match venv::VenvService::new() {
-        Ok(venv_service) => venv_service,
-        Err(e) => {
-            eprintln!("{e}");
-            std::process::exit(1);
-        }
+/// How long the CI mode waits for locks held by other processes.
+const CI_LOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Prints `error` and exits with the exit code of its error code, 1 for other errors. The
+/// CI mode prints a single `ERROR <code>: <message>` line.
+fn exit_with_error(error: &anyhow::Error, message: String) -> ! {
+    let code = error::ErrorCode::of(error);
+    let exit_code = code.map_or(1, |code| code.exit_code());
+    // Only recorded in the log file, unless debug logs are shown
+    tracing::debug!(exit_code, "Failed: {error:#}");
+    if !ui::is_ci() {
+        eprintln!("{}{message}", ui::error_prefix());
+        std::process::exit(exit_code);
+    }
+    // The message of a coded error already carries the code, print the bare message
+    let message = error
+        .downcast_ref::<error::MeowdaError>()
+        .map_or(message, |error| match &error.hint {
+            Some(hint) => format!("{} (help: {hint})", error.message),
+            None => error.message.clone(),
+        });
+    let message = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    match code {
+        Some(code) => eprintln!("ERROR {}: {message}", code.code()),
+        None => eprintln!("ERROR: {message}"),
+    }
+    std::process::exit(exit_code);
+}
+
+async fn run(args: cli::args::Args, config: &config::Config) -> anyhow::Result<()> {
+    let output = match backends::OutputMode::from_flags(args.quiet, args.verbose > 0) {
+        // Spinners redraw the line, which screen readers announce over and over, and which
+        // garbles CI logs
+        backends::OutputMode::Progress if ui::is_plain() => backends::OutputMode::Quiet,
+        output => output,
     };
+    // The backend is only resolved for commands that need it
+    let venv_service = || venv::VenvService::new(config, output);
 
-    let result = match args.command {
-        cli::args::Commands::Create(create_args) => {
-            cli::env::create(create_args, &venv_service).await
+    match args.command {
+        Commands::Create(create_args) => {
+            cli::env::create(create_args, config, &venv_service()?).await
         }
-        cli::args::Commands::Fork(fork_args) => cli::fork::fork(fork_args, &venv_service).await,
-        cli::args::Commands::Remove(remove_args) => {
-            cli::env::remove(remove_args, &venv_service).await
+        Commands::Fork(fork_args) => cli::fork::fork(fork_args, &venv_service()?).await,
+        Commands::Adopt(adopt_args) => cli::env::adopt(adopt_args, &venv_service()?).await,
+        Commands::Register(register_args) => {
+            cli::env::register(register_args, &venv_service()?).await
         }
-        cli::args::Commands::Env(env_args) => match env_args {
-            cli::args::EnvCommandsArgs::Create(create_args) => {
-                cli::env::create(create_args, &venv_service).await
+        Commands::Import(import_args) => cli::import::import(import_args, &venv_service()?).await,
+        Commands::Export(export_args) => {
+            cli::export::export(export_args, config, &venv_service()?).await
+        }
+        Commands::Dockerize(dockerize_args) => {
+            cli::dockerize::dockerize(dockerize_args, config).await
+        }
+        Commands::Pack(pack_args) => cli::pack::pack(pack_args, &venv_service()?).await,
+        Commands::Unpack(unpack_args) => cli::pack::unpack(unpack_args, &venv_service()?).await,
+        Commands::CacheKey(cache_key_args) => cli::cache_key::cache_key(cache_key_args, config),
+        Commands::Sync(sync_args) => cli::sync::sync(sync_args, &venv_service()?).await,
+        Commands::SyncAll(sync_args) => {
+            cli::sync::sync_all(sync_args, config, &venv_service()?).await
+        }
+        Commands::CreateMatrix(matrix_args) => {
+            cli::env::create_matrix(matrix_args, &venv_service()?).await
+        }
+        Commands::Matrix(matrix_args) => match matrix_args {
+            MatrixCommandsArgs::Create(create_args) => {
+                cli::env::matrix_create(create_args, &venv_service()?).await
+            }
+        },
+        Commands::Tag(tag_args) => match tag_args {
+            TagCommandsArgs::Add(add_args) => {
+                cli::env::set_tags(add_args, true, &venv_service()?).await
+            }
+            TagCommandsArgs::Remove(remove_args) => {
+                cli::env::set_tags(remove_args, false, &venv_service()?).await
+            }
+        },
+        Commands::Restore(restore_args) => cli::env::restore(restore_args, &venv_service()?).await,
+        Commands::Prune(prune_args) => cli::prune::prune(prune_args, &venv_service()?).await,
+        Commands::Move(move_args) => cli::env::move_env(move_args, &venv_service()?).await,
+        Commands::Rebuild(rebuild_args) => cli::env::rebuild(rebuild_args, &venv_service()?).await,
+        Commands::Remove(remove_args) => cli::env::remove(remove_args, &venv_service()?).await,
+        Commands::List(list_args) => cli::env::list(list_args, &venv_service()?).await,
+        Commands::History(history_args) => cli::history::history(history_args).await,
+        Commands::Env(env_args) => match env_args {
+            EnvCommandsArgs::Create(create_args) => {
+                cli::env::create(create_args, config, &venv_service()?).await
             }
-            cli::args::EnvCommandsArgs::Fork(fork_args) => {
-                cli::fork::fork(fork_args, &venv_service).await
+            EnvCommandsArgs::Fork(fork_args) => cli::fork::fork(fork_args, &venv_service()?).await,
+            EnvCommandsArgs::Remove(remove_args) => {
+                cli::env::remove(remove_args, &venv_service()?).await
             }
-            cli::args::EnvCommandsArgs::Remove(remove_args) => {
-                cli::env::remove(remove_args, &venv_service).await
+            EnvCommandsArgs::List(list_args) => cli::env::list(list_args, &venv_service()?).await,
+            EnvCommandsArgs::Dir(dir_args) => cli::env::dir(dir_args, &venv_service()?).await,
+            EnvCommandsArgs::Freeze(freeze_args) => {
+                cli::env::set_frozen(freeze_args, true, &venv_service()?).await
             }
-            cli::args::EnvCommandsArgs::List(list_args) => {
-                cli::env::list(list_args, &venv_service).await
+            EnvCommandsArgs::Thaw(thaw_args) => {
+                cli::env::set_frozen(thaw_args, false, &venv_service()?).await
             }
-            cli::args::EnvCommandsArgs::Dir(dir_args) => {
-                cli::env::dir(dir_args, &venv_service).await
+        },
+        Commands::Init(init_args) => cli::init::init(init_args).await,
+        Commands::Hook(hook_args) => cli::init::hook(hook_args).await,
+        Commands::_GenerateInitScript => cli::init::generate_init_script().await,
+        Commands::Activate(activate_args) => cli::activate::activate(activate_args, config).await,
+        Commands::Deactivate => cli::activate::deactivate().await,
+        Commands::Switch(switch_args) => cli::activate::switch(switch_args, config).await,
+        Commands::Shell(shell_args) => cli::activate::shell(shell_args, config).await,
+        Commands::RunScript(run_script_args) => {
+            cli::run_script::run_script(run_script_args, &venv_service()?).await
+        }
+        Commands::X(x_args) => cli::run_script::x(x_args, &venv_service()?).await,
+        Commands::_DetectActivateVenvPath(activate_args) => {
+            cli::activate::detect_activate_venv_path(activate_args, config).await
+        }
+        Commands::Tools(tools_args) => cli::tools::tools(tools_args).await,
+        Commands::Tool(tool_args) => match tool_args {
+            ToolCommandsArgs::Install(install_args) => {
+                cli::tools::install(install_args, &venv_service()?).await
+            }
+            ToolCommandsArgs::List(list_args) => cli::tools::list(list_args, &venv_service()?),
+            ToolCommandsArgs::Upgrade(name_args) => {
+                cli::tools::upgrade(name_args, &venv_service()?).await
+            }
+            ToolCommandsArgs::Uninstall(name_args) => {
+                cli::tools::uninstall(name_args, &venv_service()?).await
             }
         },
-        cli::args::Commands::Init(init_args) => cli::init::init(init_args).await,
-        cli::args::Commands::_GenerateInitScript => cli::init::generate_init_script().await,
-        cli::args::Commands::Activate(activate_args) => {
-            cli::activate::activate(activate_args).await
+        Commands::Shim(shim_args) => match shim_args {
+            ShimCommandsArgs::Add(add_args) => cli::shim::add(add_args),
+            ShimCommandsArgs::List(list_args) => cli::shim::list(list_args),
+            ShimCommandsArgs::Remove(remove_args) => cli::shim::remove(remove_args),
+        },
+        Commands::Install(install_args) => {
+            cli::install::install(install_args, &venv_service()?).await
+        }
+        Commands::Uninstall(uninstall_args) => {
+            cli::install::uninstall(uninstall_args, &venv_service()?).await
         }
-        cli::args::Commands::Deactivate => cli::activate::deactivate().await,
-        cli::args::Commands::_DetectActivateVenvPath(activate_args) => {
-            cli::activate::detect_activate_venv_path(activate_args).await
+        Commands::Add(add_args) => cli::install::add(add_args, &venv_service()?).await,
+        Commands::RemoveDep(remove_dep_args) => {
+            cli::install::remove_dep(remove_dep_args, &venv_service()?).await
         }
-        cli::args::Commands::Install(install_args) => {
-            cli::install::install(install_args, &venv_service).await
+        Commands::Link(link_args) => cli::link::link(link_args, &venv_service()?).await,
+        Commands::Unlink(unlink_args) => cli::link::unlink(unlink_args, &venv_service()?).await,
+        Commands::Which(which_args) => cli::which::which(which_args, config).await,
+        Commands::Info(info_args) => cli::info::info(info_args, config).await,
+        Commands::Status(status_args) => cli::status::status(status_args).await,
+        Commands::Stores(stores_args) => cli::stores::stores(stores_args).await,
+        Commands::Daemon(daemon_args) => cli::daemon::daemon(daemon_args).await,
+        Commands::Direnv(direnv_args) => cli::direnv::direnv(direnv_args, config).await,
+        Commands::Vscode(vscode_args) => cli::vscode::vscode(vscode_args, config).await,
+        Commands::PythonPath(python_path_args) => {
+            cli::which::python_path(python_path_args, config).await
         }
-        cli::args::Commands::Uninstall(uninstall_args) => {
-            cli::install::uninstall(uninstall_args, &venv_service).await
+        Commands::Locate(locate_args) => cli::locate::locate(locate_args).await,
+        Commands::Diff(diff_args) => cli::diff::diff(diff_args).await,
+        Commands::Check(check_args) => cli::check::check(check_args, config).await,
+        Commands::Explain(explain_args) => cli::explain::explain(explain_args).await,
+        Commands::TestFixture(TestFixtureCommandsArgs::Create(create_args)) => {
+            cli::test_fixture::create(create_args).await
         }
-        cli::args::Commands::Link(link_args) => cli::link::link(link_args, &venv_service).await,
-        cli::args::Commands::Unlink(unlink_args) => {
-            cli::link::unlink(unlink_args, &venv_service).await
+        Commands::CompletionData(completion_args) => {
+            cli::completion_data::completion_data(completion_args, config).await
         }
+        Commands::WatchEvents(watch_args) => cli::events::watch_events(watch_args).await,
+        Commands::VerifySetup(verify_args) => {
+            cli::verify_setup::verify_setup(verify_args, config).await
+        }
+        Commands::Doctor(doctor_args) => cli::doctor::doctor(doctor_args, config).await,
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = cli::args::Args::parse();
+    interrupt::install();
+    ui::set_accessible(
+        args.accessible
+            || std::env::var_os(envs::EnvVars::MEOWDA_ACCESSIBLE).is_some_and(|v| v == "1"),
+    );
+    ui::set_ci(args.ci || std::env::var_os(envs::EnvVars::MEOWDA_CI).is_some_and(|v| v == "1"));
+    backends::set_offline(
+        args.offline || std::env::var_os(envs::EnvVars::MEOWDA_OFFLINE).is_some_and(|v| v == "1"),
+    );
+    store::venv_store::set_no_recurse(
+        args.no_recurse
+            || std::env::var_os(envs::EnvVars::MEOWDA_NO_RECURSE).is_some_and(|v| v == "1"),
+    );
+    cli::output::set_format(args.format);
+    // Escape codes would end up in the documents and rows parsed by scripts
+    ui::set_color(args.color, args.format != cli::output::OutputFormat::Table);
+    let config = match config::Config::load() {
+        Ok(config) => config,
+        Err(e) => exit_with_error(&e, format!("{e:#}")),
     };
+    logging::init(
+        args.quiet,
+        args.verbose,
+        logging::log_file_path(
+            std::env::var_os(envs::EnvVars::MEOWDA_LOG_FILE),
+            config.log_file,
+        ),
+    );
+    let lock_timeout = config
+        .lock_timeout
+        .map(Duration::from_secs)
+        .or(ui::is_ci().then_some(CI_LOCK_TIMEOUT));
+    if let Some(timeout) = lock_timeout {
+        store::file_lock::set_wait_timeout(timeout);
+    }
+    if let Err(e) = store::backend::install(&config) {
+        exit_with_error(&e, format!("{e:#}"));
+    }
+    if let Some(log_dir) = &args.log_dir {
+        backends::set_log_dir(log_dir.clone());
+    }
+    if !args.command.is_hot_path() {
+        store::migrate::migrate_stores().await;
+    }
 
-    if let Err(e) = result {
-        eprintln!("{e}");
-        std::process::exit(1);
+    if let Err(e) = run(args, &config).await {
+        exit_with_error(&e, e.to_string());
     }
 
     Ok(())
@@ -0,0 +1,307 @@
+/// Index of resolved activation paths, so that activating an environment skips the
+/// store discovery, which walks every parent directory and is slow on network filesystems.
+///
+/// The index is lock-free: readers never block, writers replace the file atomically and
+/// the last writer wins. Hits are verified with a single `stat`, a miss falls back to the
+/// full discovery, and creating, forking or removing an environment drops the whole index
+/// as it may change which store wins. Hits in local stores are dropped as well once a
+/// discovery boundary, e.g., a `.meowda/root` marker, puts their store out of reach.
+use crate::config::Config;
+use crate::envs::EnvVars;
+use crate::hash::KeyHasher;
+use crate::store::atomic::write_atomic;
+use crate::store::backend::Discovery;
+use crate::store::venv_store::{ScopeType, VenvStore, is_local_scope_reachable, user_state_dir};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Bumped on incompatible changes of the index format.
+const INDEX_FORMAT_VERSION: u32 = 3;
+
+/// The index is dropped instead of growing beyond this many entries.
+const MAX_INDEX_ENTRIES: usize = 512;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ActivateIndex {
+    version: u32,
+    /// Resolved environments keyed by [`index_key`].
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+/// A resolved environment, its name is the one it was resolved by, e.g., `gpu` of the
+/// environment registered as `gpu` at `/disk/gpu-env`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub path: PathBuf,
+    /// The parent level of the local store the environment was found in, `None` for the
+    /// other stores.
+    pub local_level: Option<u8>,
+}
+
+fn index_path() -> Option<PathBuf> {
+    user_state_dir().map(|dir| dir.join("activate-index.json"))
+}
+
+/// Identifies a lookup by everything the discovery depends on: the working directory,
+/// the scope selection, the reference and the store settings from config and environment.
+fn index_key(config: &Config, cwd: &Path, scope_type: ScopeType, env_ref: &str) -> String {
+    let flag = |value: Option<bool>| value.map(|value| if value { "true" } else { "false" });
+    let mut hasher = KeyHasher::default();
+    hasher
+        .optional_field(config.store_backend.as_deref())
+        .optional_field(
+            config
+                .store_root
+                .as_ref()
+                .map(|root| root.as_os_str().as_encoded_bytes()),
+        )
+        .optional_field(flag(config.share_worktree_store))
+        .optional_field(flag(config.stop_at_git_root));
+    let stores = config.stores.clone().unwrap_or_default();
+    hasher.field(stores.len().to_string());
+    for (name, path) in &stores {
+        hasher
+            .field(name)
+            .field(path.as_os_str().as_encoded_bytes());
+    }
+    for var in [
+        EnvVars::MEOWDA_LOCAL_VENV_DIR,
+        EnvVars::MEOWDA_GLOBAL_VENV_DIR,
+        EnvVars::MEOWDA_STORE_BACKEND,
+        EnvVars::MEOWDA_STORE_ROOT,
+    ] {
+        hasher.optional_field(std::env::var_os(var).map(|value| value.into_encoded_bytes()));
+    }
+    format!(
+        "{} {scope_type:?} {env_ref} {}",
+        hasher.finish(),
+        cwd.display()
+    )
+}
+
+fn read_index(path: &Path) -> Option<ActivateIndex> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<ActivateIndex>(&content)
+        .ok()
+        .filter(|index| index.version == INDEX_FORMAT_VERSION)
+}
+
+/// Returns the indexed environment of `env_ref`, if it still looks like a complete
+/// environment and its store is still searched from the working directory of `discovery`.
+pub fn lookup(
+    config: &Config,
+    discovery: &Discovery,
+    scope_type: ScopeType,
+    env_ref: &str,
+) -> Option<IndexEntry> {
+    lookup_at(&index_path()?, config, discovery, scope_type, env_ref)
+}
+
+/// Like [`lookup`], with the index at `index_path`.
+fn lookup_at(
+    index_path: &Path,
+    config: &Config,
+    discovery: &Discovery,
+    scope_type: ScopeType,
+    env_ref: &str,
+) -> Option<IndexEntry> {
+    let mut index = read_index(index_path)?;
+    let cwd = discovery.cwd().ok()?;
+    let entry = index
+        .entries
+        .remove(&index_key(config, &cwd, scope_type, env_ref))?;
+    if entry
+        .local_level
+        .is_some_and(|level| !is_local_scope_reachable(discovery, level))
+    {
+        return None;
+    }
+    let path = &entry.path;
+    let being_created = path
+        .parent()
+        .zip(path.file_name().and_then(|name| name.to_str()))
+        .is_some_and(|(store, name)| {
+            VenvStore::from_path(store.to_path_buf()).is_being_created(name)
+        });
+    (path.join("pyvenv.cfg").is_file() && !being_created).then_some(entry)
+}
+
+fn write_index(path: &Path, index: &ActivateIndex) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+    }
+    let content = serde_json::to_string(index).context("Failed to serialize activate index")?;
+    write_atomic(path, content)
+}
+
+/// Records the environment `env_ref` resolved to, failures only cost the next lookup a miss.
+pub fn record(
+    config: &Config,
+    cwd: &Path,
+    scope_type: ScopeType,
+    env_ref: &str,
+    entry: IndexEntry,
+) {
+    if let Some(path) = index_path() {
+        record_at(&path, config, cwd, scope_type, env_ref, entry);
+    }
+}
+
+/// Like [`record`], with the index at `path`.
+fn record_at(
+    path: &Path,
+    config: &Config,
+    cwd: &Path,
+    scope_type: ScopeType,
+    env_ref: &str,
+    entry: IndexEntry,
+) {
+    let mut index = read_index(path).unwrap_or_default();
+    if index.entries.len() >= MAX_INDEX_ENTRIES {
+        index.entries.clear();
+    }
+    index.version = INDEX_FORMAT_VERSION;
+    index
+        .entries
+        .insert(index_key(config, cwd, scope_type, env_ref), entry);
+    if let Err(e) = write_index(path, &index) {
+        debug!("Failed to update the activate index: {e:#}");
+    }
+}
+
+/// Drops the index, e.g., after an environment was created or removed.
+pub fn invalidate() {
+    if let Some(path) = index_path()
+        && path.exists()
+        && let Err(e) = std::fs::remove_file(&path)
+    {
+        debug!("Failed to drop the activate index: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::backend::StoreSettings;
+    use crate::store::fixture::create_fixture_env;
+    use crate::store::venv_store::ROOT_MARKER_PATH;
+
+    #[test]
+    fn hits_keep_the_name_of_registered_envs() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        store.init_if_needed()?;
+        let venv_path = temp.path().join("disk").join("gpu-env");
+        create_fixture_env(&venv_path, "3.13.1")?;
+        store.register("gpu", venv_path.clone())?;
+
+        let index_path = temp.path().join("activate-index.json");
+        let config = Config::default();
+        let settings = StoreSettings::default();
+        let discovery = Discovery::new(temp.path().to_path_buf(), &settings);
+        let lookup = || {
+            lookup_at(
+                &index_path,
+                &config,
+                &discovery,
+                ScopeType::Unspecified,
+                "gpu",
+            )
+        };
+        assert_eq!(lookup(), None);
+        let entry = || IndexEntry {
+            name: "gpu".to_string(),
+            path: store.env_path("gpu"),
+            local_level: None,
+        };
+        record_at(
+            &index_path,
+            &config,
+            temp.path(),
+            ScopeType::Unspecified,
+            "gpu",
+            entry(),
+        );
+        assert_eq!(
+            lookup().map(|entry| (entry.name, entry.path)),
+            Some(("gpu".to_string(), venv_path))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hits_beyond_a_new_discovery_boundary_are_dropped() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let project = temp.path().join("project");
+        let venv_path = temp.path().join(".meowda").join("venvs").join("app");
+        create_fixture_env(&venv_path, "3.13.1")?;
+        std::fs::create_dir_all(&project)?;
+
+        let index_path = temp.path().join("activate-index.json");
+        let config = Config::default();
+        let settings = StoreSettings::default();
+        let discovery = Discovery::new(project.clone(), &settings);
+        let entry = IndexEntry {
+            name: "app".to_string(),
+            path: venv_path,
+            local_level: Some(1),
+        };
+        record_at(
+            &index_path,
+            &config,
+            &project,
+            ScopeType::Unspecified,
+            "app",
+            entry,
+        );
+        let lookup = || {
+            lookup_at(
+                &index_path,
+                &config,
+                &discovery,
+                ScopeType::Unspecified,
+                "app",
+            )
+        };
+        assert!(lookup().is_some());
+        std::fs::create_dir_all(project.join(".meowda"))?;
+        std::fs::write(project.join(ROOT_MARKER_PATH), "")?;
+        assert!(lookup().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn index_key_distinguishes_lookups() {
+        let config = Config::default();
+        let cwd = Path::new("/work/project");
+        let key = index_key(&config, cwd, ScopeType::Unspecified, "app");
+        assert_eq!(key, index_key(&config, cwd, ScopeType::Unspecified, "app"));
+        let others = [
+            index_key(
+                &config,
+                Path::new("/work/other"),
+                ScopeType::Unspecified,
+                "app",
+            ),
+            index_key(&config, cwd, ScopeType::Global, "app"),
+            index_key(&config, cwd, ScopeType::Unspecified, "global:app"),
+            index_key(
+                &Config {
+                    store_backend: Some("volume".to_string()),
+                    ..Config::default()
+                },
+                cwd,
+                ScopeType::Unspecified,
+                "app",
+            ),
+        ];
+        for other in others {
+            assert_ne!(key, other);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+/// Atomic replacement of the small state files of meowda, e.g., the registry of a store or
+/// the snapshot of the environments.
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+/// Writes `content` to `path` through a temporary file next to it which is renamed over
+/// `path`, so readers never observe a partial file. Every writer, of this or another
+/// process, uses a temporary file of its own, the last rename wins.
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let prefix = format!(
+        ".{}.",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix).suffix(".tmp");
+    // Readable like files written by `std::fs::write`, e.g., by a team sharing a store
+    #[cfg(unix)]
+    builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o666));
+    let mut file = builder
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create a temporary file in '{}'", dir.display()))?;
+    file.write_all(content.as_ref())
+        .with_context(|| format!("Failed to write '{}'", file.path().display()))?;
+    file.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_writers_replace_the_whole_file() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let path = &temp.path().join("state.json");
+        let contents = (0..8)
+            .map(|i| i.to_string().repeat(4096))
+            .collect::<Vec<_>>();
+        std::thread::scope(|scope| {
+            let writers = contents
+                .iter()
+                .map(|content| scope.spawn(move || write_atomic(path, content)))
+                .collect::<Vec<_>>();
+            writers
+                .into_iter()
+                .try_for_each(|writer| writer.join().expect("writer panicked"))
+        })?;
+        assert!(contents.contains(&std::fs::read_to_string(path)?));
+        // No temporary files are left behind
+        assert_eq!(std::fs::read_dir(temp.path())?.count(), 1);
+        Ok(())
+    }
+}
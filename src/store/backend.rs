@@ -0,0 +1,456 @@
+/// Pluggable store backends, deciding where the local and global stores live.
+use crate::config::Config;
+use crate::envs::EnvVars;
+use crate::store::git::main_worktree_root;
+use crate::store::venv_store::{DefaultScope, resolve_parent_path, user_state_dir};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// Resolves the root directories of the stores.
+pub trait StoreBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Root of the local store `parent_level` directories above `cwd`.
+    fn local_root(&self, cwd: &Path, parent_level: u8) -> Result<PathBuf>;
+
+    fn global_root(&self) -> Result<PathBuf>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreBackendKind {
+    Filesystem,
+    Volume,
+}
+
+impl FromStr for StoreBackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "filesystem" | "fs" => Ok(StoreBackendKind::Filesystem),
+            "volume" => Ok(StoreBackendKind::Volume),
+            other => {
+                anyhow::bail!(
+                    "Unknown store backend '{other}', expected one of: filesystem, volume"
+                )
+            }
+        }
+    }
+}
+
+impl StoreBackendKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoreBackendKind::Filesystem => "filesystem",
+            StoreBackendKind::Volume => "volume",
+        }
+    }
+
+    /// Resolves the store backend kind, prefer, in order:
+    ///
+    /// 1. The store backend specified by the user, i.e., `MEOWDA_STORE_BACKEND`
+    /// 2. The `store_backend` key in the config file
+    /// 3. filesystem
+    pub fn resolve(config: &Config) -> Result<Self> {
+        if let Some(backend) = std::env::var(EnvVars::MEOWDA_STORE_BACKEND)
+            .ok()
+            .filter(|s| !s.is_empty())
+        {
+            return backend.parse();
+        }
+        match config.store_backend.as_deref() {
+            Some(backend) => backend.parse(),
+            None => Ok(StoreBackendKind::Filesystem),
+        }
+    }
+}
+
+/// Stores next to the projects (`./.meowda/venvs`) and in the user data directory.
+#[derive(Debug, Default)]
+pub struct FilesystemStore {
+    /// Anchor local stores of linked git worktrees at the main worktree, so all
+    /// worktrees of a repository share one store.
+    pub share_worktree_store: bool,
+}
+
+impl StoreBackend for FilesystemStore {
+    fn name(&self) -> &'static str {
+        StoreBackendKind::Filesystem.name()
+    }
+
+    /// Prefer, in order:
+    /// 1. The specific tool directory specified by the user, i.e., `MEOWDA_LOCAL_VENV_DIR`
+    /// 2. A directory in the local data directory, e.g., `./.meowda/venvs`
+    fn local_root(&self, cwd: &Path, parent_level: u8) -> Result<PathBuf> {
+        let local_venv_dir_path = if let Some(local_venv_dir) =
+            std::env::var_os(EnvVars::MEOWDA_LOCAL_VENV_DIR).filter(|s| !s.is_empty())
+        {
+            if PathBuf::from(local_venv_dir.clone()).is_absolute() {
+                return std::path::absolute(local_venv_dir).with_context(|| {
+                    "Invalid path for `MEOWDA_LOCAL_VENV_DIR` environment variable".to_string()
+                });
+            }
+            PathBuf::from(local_venv_dir)
+        } else {
+            PathBuf::from(".meowda").join("venvs")
+        };
+        let mut project_dir = resolve_parent_path(cwd, parent_level)?;
+        if self.share_worktree_store
+            && let Some(main_root) = main_worktree_root(&project_dir)
+        {
+            debug!(
+                "Using the store of the main worktree `{}`",
+                main_root.display()
+            );
+            project_dir = main_root;
+        }
+        Ok(project_dir.join(local_venv_dir_path))
+    }
+
+    /// Prefer, in order:
+    ///
+    /// 1. The specific tool directory specified by the user, i.e., `MEOWDA_GLOBAL_VENV_DIR`
+    /// 2. A directory in the system-appropriate user-level data directory, e.g., `~/.local/meowda/venvs`
+    fn global_root(&self) -> Result<PathBuf> {
+        if let Some(tool_dir) =
+            std::env::var_os(EnvVars::MEOWDA_GLOBAL_VENV_DIR).filter(|s| !s.is_empty())
+        {
+            std::path::absolute(tool_dir).with_context(|| {
+                "Invalid path for `MEOWDA_GLOBAL_VENV_DIR` environment variable".to_string()
+            })
+        } else {
+            user_state_dir()
+                .map(|dir| dir.join("venvs"))
+                .ok_or_else(|| anyhow::anyhow!("Failed to determine user state directory"))
+        }
+    }
+}
+
+/// All stores rooted in a single directory, e.g., a dev-container volume, keeping
+/// environments out of the (possibly bind-mounted) project directories.
+///
+/// The global store is `<root>/global`, the local store of a directory is
+/// `<root>/local/<slug of the directory path>`.
+pub struct VolumeStore {
+    root: PathBuf,
+}
+
+impl VolumeStore {
+    pub fn new(root: PathBuf) -> Self {
+        VolumeStore { root }
+    }
+
+    /// Resolves the volume root, prefer `MEOWDA_STORE_ROOT`, then the `store_root` key in the config file.
+    pub fn resolve_root(config: &Config) -> Result<PathBuf> {
+        let root = std::env::var_os(EnvVars::MEOWDA_STORE_ROOT)
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| config.store_root.clone())
+            .context(
+                "The volume store backend requires a root directory, set `store_root` in meowda.toml or `MEOWDA_STORE_ROOT`",
+            )?;
+        std::path::absolute(&root)
+            .with_context(|| format!("Invalid store root '{}'", root.display()))
+    }
+}
+
+/// Flattens `path` into a single directory name, e.g., `/work/app` into `work-app`.
+pub(crate) fn path_slug(path: &Path) -> String {
+    let slug = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "root".to_string()
+    } else {
+        slug
+    }
+}
+
+impl StoreBackend for VolumeStore {
+    fn name(&self) -> &'static str {
+        StoreBackendKind::Volume.name()
+    }
+
+    fn local_root(&self, cwd: &Path, parent_level: u8) -> Result<PathBuf> {
+        let project_dir = resolve_parent_path(cwd, parent_level)?;
+        Ok(self.root.join("local").join(path_slug(&project_dir)))
+    }
+
+    fn global_root(&self) -> Result<PathBuf> {
+        Ok(self.root.join("global"))
+    }
+}
+
+pub fn select_store_backend(config: &Config) -> Result<Box<dyn StoreBackend>> {
+    Ok(match StoreBackendKind::resolve(config)? {
+        StoreBackendKind::Filesystem => Box::new(FilesystemStore {
+            share_worktree_store: config.share_worktree_store.unwrap_or(false),
+        }),
+        StoreBackendKind::Volume => Box::new(VolumeStore::new(VolumeStore::resolve_root(config)?)),
+    })
+}
+
+static SETTINGS: OnceLock<StoreSettings> = OnceLock::new();
+
+/// Resolves the scope of commands run without `--local`/`--global`, prefer, in order:
+///
+/// 1. The scope specified by the user, i.e., `MEOWDA_DEFAULT_SCOPE`
+/// 2. The `default_scope` key in the config file
+///
+/// `None` keeps the built-in behavior: creating in the global store, listing all scopes.
+pub fn resolve_default_scope(config: &Config) -> Result<Option<DefaultScope>> {
+    if let Some(scope) = std::env::var(EnvVars::MEOWDA_DEFAULT_SCOPE)
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        return scope.parse().map(Some);
+    }
+    config
+        .default_scope
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .context("Invalid `default_scope` in config")
+}
+
+/// Checks the names of the extra stores in config, which prefix environment references.
+fn validate_named_stores(stores: &BTreeMap<String, PathBuf>) -> Result<()> {
+    for name in stores.keys() {
+        if matches!(name.as_str(), "local" | "global") {
+            anyhow::bail!("Store name '{name}' in `stores` is reserved for the built-in scopes");
+        }
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!(
+                "Invalid store name '{name}' in `stores`, only ASCII letters, digits, `-` and `_` are allowed"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Resolves the roots of the extra stores listed in `read_only_stores`.
+fn resolve_read_only_roots(
+    names: &[String],
+    stores: &BTreeMap<String, PathBuf>,
+) -> Result<Vec<PathBuf>> {
+    names
+        .iter()
+        .map(|name| {
+            stores.get(name).cloned().with_context(|| {
+                format!("Unknown store '{name}' in `read_only_stores`, expected one of `stores`")
+            })
+        })
+        .collect()
+}
+
+/// The store settings of config, everything store discovery depends on besides the
+/// directory it starts from.
+pub struct StoreSettings {
+    backend: Box<dyn StoreBackend>,
+    named_stores: BTreeMap<String, PathBuf>,
+    read_only_roots: Vec<PathBuf>,
+    stop_at_git_root: bool,
+    default_scope: Option<DefaultScope>,
+}
+
+impl Default for StoreSettings {
+    fn default() -> Self {
+        StoreSettings {
+            backend: Box::new(FilesystemStore::default()),
+            named_stores: BTreeMap::new(),
+            read_only_roots: Vec::new(),
+            stop_at_git_root: false,
+            default_scope: None,
+        }
+    }
+}
+
+impl StoreSettings {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let backend = select_store_backend(config)?;
+        let named_stores = config.stores.clone().unwrap_or_default();
+        validate_named_stores(&named_stores)?;
+        let read_only_roots = resolve_read_only_roots(
+            config.read_only_stores.as_deref().unwrap_or_default(),
+            &named_stores,
+        )?;
+        Ok(StoreSettings {
+            backend,
+            named_stores,
+            read_only_roots,
+            stop_at_git_root: config.stop_at_git_root.unwrap_or(false),
+            default_scope: resolve_default_scope(config)?,
+        })
+    }
+
+    pub fn backend(&self) -> &dyn StoreBackend {
+        self.backend.as_ref()
+    }
+
+    /// The extra named stores from config, keyed by name.
+    pub fn named_stores(&self) -> &BTreeMap<String, PathBuf> {
+        &self.named_stores
+    }
+
+    /// Whether local store discovery stops at the root of the git repository.
+    pub fn stop_at_git_root(&self) -> bool {
+        self.stop_at_git_root
+    }
+}
+
+/// Selects the store settings for this process, called once at startup.
+pub fn install(config: &Config) -> Result<()> {
+    let settings = StoreSettings::from_config(config)?;
+    // Settings installed before win, e.g., in tests
+    let _ = SETTINGS.set(settings);
+    Ok(())
+}
+
+/// The store settings of this process, the built-in ones if none were installed.
+pub fn settings() -> &'static StoreSettings {
+    SETTINGS.get_or_init(StoreSettings::default)
+}
+
+/// Returns the extra named stores from config, keyed by name.
+pub fn named_stores() -> &'static BTreeMap<String, PathBuf> {
+    settings().named_stores()
+}
+
+/// The roots of the stores configured as read-only.
+pub fn read_only_roots() -> &'static [PathBuf] {
+    &settings().read_only_roots
+}
+
+/// The scope of commands run without `--local`/`--global`, `None` if not configured.
+pub fn default_scope() -> Option<DefaultScope> {
+    settings().default_scope
+}
+
+/// Where stores are discovered from: a directory, the working directory unless given, and
+/// the store settings. Discovery for another directory and config, e.g., for requests of
+/// `meowda daemon`, neither changes the working directory nor the settings of the process.
+pub struct Discovery<'a> {
+    cwd: Option<PathBuf>,
+    settings: &'a StoreSettings,
+}
+
+impl<'a> Discovery<'a> {
+    pub fn new(cwd: PathBuf, settings: &'a StoreSettings) -> Self {
+        Discovery {
+            cwd: Some(cwd),
+            settings,
+        }
+    }
+
+    /// Discovery from the working directory with the settings of this process.
+    pub fn current() -> Discovery<'static> {
+        Discovery {
+            cwd: None,
+            settings: settings(),
+        }
+    }
+
+    pub fn cwd(&self) -> Result<PathBuf> {
+        match &self.cwd {
+            Some(cwd) => Ok(cwd.clone()),
+            None => std::env::current_dir().context("Failed to get current working directory"),
+        }
+    }
+
+    pub fn settings(&self) -> &'a StoreSettings {
+        self.settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_backend_kind_parse() {
+        let cases = [
+            ("filesystem", Some(StoreBackendKind::Filesystem)),
+            ("FS", Some(StoreBackendKind::Filesystem)),
+            ("volume", Some(StoreBackendKind::Volume)),
+            ("s3", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<StoreBackendKind>().ok(), expected);
+        }
+    }
+
+    #[test]
+    fn test_default_scope_parse() {
+        let cases = [
+            ("local", Some(DefaultScope::Local)),
+            (" Global ", Some(DefaultScope::Global)),
+            ("AUTO", Some(DefaultScope::Auto)),
+            ("unspecified", None),
+            ("team", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                input.parse::<DefaultScope>().ok(),
+                expected,
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_path_slug() {
+        let cases = [
+            ("/work/app", "work-app"),
+            ("/", "root"),
+            ("/a/b/c", "a-b-c"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(path_slug(Path::new(path)), expected);
+        }
+    }
+
+    #[test]
+    fn volume_store_roots() -> Result<()> {
+        let store = VolumeStore::new(PathBuf::from("/volume"));
+        assert_eq!(store.global_root()?, PathBuf::from("/volume/global"));
+        assert_eq!(
+            store.local_root(Path::new("/work/app"), 0)?,
+            PathBuf::from("/volume/local/work-app")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate_named_stores_rejects_reserved_and_invalid_names() {
+        let cases = [
+            ("team", true),
+            ("team-store_2", true),
+            ("local", false),
+            ("global", false),
+            ("", false),
+            ("team:a", false),
+            ("team store", false),
+        ];
+        for (name, valid) in cases {
+            let stores = BTreeMap::from([(name.to_string(), PathBuf::from("/tmp"))]);
+            assert_eq!(
+                validate_named_stores(&stores).is_ok(),
+                valid,
+                "name: {name}"
+            );
+        }
+    }
+}
@@ -0,0 +1,135 @@
+/// Read-only discovery of conda/mamba environments, listed by `meowda list --include-conda`
+/// and activated as `conda:<name>`, to ease migrating off conda. Meowda never writes into
+/// them.
+use std::path::{Path, PathBuf};
+
+/// Prefix of environment references selecting a conda environment, e.g., `conda:base`.
+pub const CONDA_REF_PREFIX: &str = "conda:";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CondaEnv {
+    /// Directory name of the environment, `base` for the root prefix.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl CondaEnv {
+    /// The Python version installed in the environment, from the conda package records,
+    /// e.g., `conda-meta/python-3.12.4-h5148396_1.json`.
+    pub fn python_version(&self) -> Option<String> {
+        std::fs::read_dir(self.path.join("conda-meta"))
+            .ok()?
+            .filter_map(Result::ok)
+            .find_map(|entry| {
+                let file_name = entry.file_name();
+                let record = file_name.to_str()?.strip_suffix(".json")?;
+                let (version, _build) = record.strip_prefix("python-")?.split_once('-')?;
+                version
+                    .starts_with(|c: char| c.is_ascii_digit())
+                    .then(|| version.to_string())
+            })
+    }
+}
+
+/// The environment name of a `conda:<name>` reference.
+pub fn strip_conda_ref(env_ref: &str) -> Option<&str> {
+    env_ref.strip_prefix(CONDA_REF_PREFIX)
+}
+
+/// Whether `path` is a conda environment, which has a `conda-meta` directory.
+pub fn is_conda_env(path: &Path) -> bool {
+    path.join("conda-meta").is_dir()
+}
+
+/// The file conda and mamba record the prefix of every environment they create in.
+fn environments_file() -> Option<PathBuf> {
+    etcetera::home_dir()
+        .ok()
+        .map(|home| home.join(".conda").join("environments.txt"))
+}
+
+/// Whether `path` is the root prefix of a conda installation, its `base` environment.
+fn is_root_prefix(path: &Path) -> bool {
+    path.join("condabin").is_dir()
+}
+
+/// Parses `environments.txt`, one environment prefix per line. Environments are named
+/// after their directory, except the root prefix, which is `base`.
+fn parse_environments(content: &str, is_root: impl Fn(&Path) -> bool) -> Vec<CondaEnv> {
+    let mut envs: Vec<CondaEnv> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let path = PathBuf::from(line);
+        let name = match path.file_name() {
+            Some(name) if !is_root(&path) => name.to_string_lossy().into_owned(),
+            _ => "base".to_string(),
+        };
+        if !envs.iter().any(|env| env.path == path) {
+            envs.push(CondaEnv { name, path });
+        }
+    }
+    envs
+}
+
+/// Lists the conda environments which still exist, empty if conda was never used.
+pub fn list_conda_envs() -> Vec<CondaEnv> {
+    let Some(content) = environments_file().and_then(|file| std::fs::read_to_string(file).ok())
+    else {
+        return Vec::new();
+    };
+    parse_environments(&content, is_root_prefix)
+        .into_iter()
+        .filter(|env| is_conda_env(&env.path))
+        .collect()
+}
+
+/// Finds the existing conda environment `name`, e.g., `base`.
+pub fn find_conda_env(name: &str) -> Option<CondaEnv> {
+    list_conda_envs().into_iter().find(|env| env.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn parse_environments_names_envs_after_their_directory_except_base() {
+        let content = "/opt/miniconda3\n\n/opt/miniconda3/envs/ml\n/home/me/projects/app\n/opt/miniconda3/envs/ml\n";
+        let envs = parse_environments(content, |path| path == Path::new("/opt/miniconda3"))
+            .into_iter()
+            .map(|env| (env.name, env.path))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            envs,
+            [
+                ("base".to_string(), PathBuf::from("/opt/miniconda3")),
+                ("ml".to_string(), PathBuf::from("/opt/miniconda3/envs/ml")),
+                ("app".to_string(), PathBuf::from("/home/me/projects/app")),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_version_comes_from_the_package_records() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let conda_meta = temp.path().join("conda-meta");
+        std::fs::create_dir_all(&conda_meta)?;
+        let env = CondaEnv {
+            name: "ml".to_string(),
+            path: temp.path().to_path_buf(),
+        };
+        assert!(is_conda_env(&env.path));
+        assert_eq!(env.python_version(), None);
+
+        std::fs::write(
+            conda_meta.join("python-dateutil-2.9.0-pyhd8ed1ab_0.json"),
+            "{}",
+        )?;
+        std::fs::write(conda_meta.join("python-3.12.4-h5148396_1.json"), "{}")?;
+        assert_eq!(env.python_version(), Some("3.12.4".to_string()));
+        Ok(())
+    }
+}
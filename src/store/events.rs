@@ -0,0 +1,144 @@
+/// Environment change events for external consumers, e.g., a tray/GUI app.
+///
+/// Two files in the user state directory form a stable subscription endpoint:
+///
+/// - `events.jsonl`: one JSON event per line, appended on every environment change
+/// - `snapshot.json`: the environments of every store seen so far, rewritten after each event
+use crate::store::activate_index;
+use crate::store::atomic::write_atomic;
+use crate::store::file_lock::FileLock;
+use crate::store::index;
+use crate::store::venv_store::{VenvStore, user_state_dir};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Bumped on incompatible changes of the event or snapshot format.
+pub const EVENTS_FORMAT_VERSION: u32 = 1;
+
+/// The events file is rotated to `events.jsonl.1` once it grows beyond this size.
+const MAX_EVENTS_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    Forked,
+    Removed,
+    Restored,
+    PackagesChanged,
+    Frozen,
+    Thawed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub version: u32,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub kind: EventKind,
+    pub env: String,
+    pub store: PathBuf,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub timestamp: u64,
+    /// Environment names keyed by store path.
+    pub stores: BTreeMap<PathBuf, Vec<String>>,
+}
+
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn events_path() -> Option<PathBuf> {
+    user_state_dir().map(|dir| dir.join("events.jsonl"))
+}
+
+pub fn snapshot_path() -> Option<PathBuf> {
+    user_state_dir().map(|dir| dir.join("snapshot.json"))
+}
+
+fn append_event(path: &Path, event: &Event) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+    }
+    if std::fs::metadata(path).is_ok_and(|meta| meta.len() > MAX_EVENTS_FILE_SIZE) {
+        std::fs::rename(path, path.with_extension("jsonl.1"))
+            .with_context(|| format!("Failed to rotate '{}'", path.display()))?;
+    }
+    let mut line = serde_json::to_string(event).context("Failed to serialize event")?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Failed to append event to '{}'", path.display()))
+}
+
+pub fn read_snapshot(path: &Path) -> Result<Snapshot> {
+    if !path.exists() {
+        return Ok(Snapshot {
+            version: EVENTS_FORMAT_VERSION,
+            ..Snapshot::default()
+        });
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read '{}'", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse '{}'", path.display()))
+}
+
+/// Adds the environments of `store` to the snapshot at `path`. Concurrent updates are
+/// serialized by a lock next to it, so none of them is lost.
+fn update_snapshot(path: &Path, store: &VenvStore) -> Result<()> {
+    let _lock = FileLock::acquire_blocking(path.with_extension("json.lock"), "snapshot")
+        .with_context(|| format!("Failed to lock '{}'", path.display()))?;
+    let mut snapshot = read_snapshot(path).unwrap_or_default();
+    snapshot.version = EVENTS_FORMAT_VERSION;
+    snapshot.timestamp = now_timestamp();
+    // Forget stores which have been deleted since
+    snapshot.stores.retain(|store_path, _| store_path.is_dir());
+    snapshot
+        .stores
+        .insert(store.path().clone(), store.env_names());
+    let content =
+        serde_json::to_string_pretty(&snapshot).context("Failed to serialize snapshot")?;
+    write_atomic(path, content)
+}
+
+/// Publishes an event for `env` in `store`, failures are only logged.
+pub fn emit(kind: EventKind, store: &VenvStore, env: &str) {
+    if matches!(
+        kind,
+        EventKind::Created | EventKind::Forked | EventKind::Removed | EventKind::Restored
+    ) {
+        activate_index::invalidate();
+        index::refresh(store);
+    }
+    let (Some(events_path), Some(snapshot_path)) = (events_path(), snapshot_path()) else {
+        return;
+    };
+    let event = Event {
+        version: EVENTS_FORMAT_VERSION,
+        timestamp: now_timestamp(),
+        kind,
+        env: env.to_string(),
+        store: store.path().clone(),
+    };
+    if let Err(e) =
+        append_event(&events_path, &event).and_then(|_| update_snapshot(&snapshot_path, store))
+    {
+        warn!("Failed to publish environment event: {e:#}");
+    }
+}
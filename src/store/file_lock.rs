@@ -1,10 +1,130 @@
 use fs2::FileExt;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 use tempfile::NamedTempFile;
 
+use super::events::now_timestamp;
+
 use tracing::{debug, error, info, trace, warn};
 
+/// How long to wait for a lock held by another process, forever if unset.
+static WAIT_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+/// Interval of the attempts to acquire a lock while waiting with a timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Limits how long locks held by other processes are waited on, called once at startup.
+pub fn set_wait_timeout(timeout: Duration) {
+    let _ = WAIT_TIMEOUT.set(timeout);
+}
+
+/// The process holding a lock, recorded in the lock file while it is held, so that waiting
+/// processes can tell who they wait for and detect stale locks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub host: Option<String>,
+    /// Seconds since the Unix epoch the lock was acquired at.
+    pub since: u64,
+    /// The locked resource, e.g., `venv_store`.
+    pub operation: String,
+}
+
+impl LockHolder {
+    fn current(operation: &str) -> Self {
+        LockHolder {
+            pid: std::process::id(),
+            host: host_name(),
+            since: now_timestamp(),
+            operation: operation.to_string(),
+        }
+    }
+
+    /// Reads the holder recorded in the lock file at `path`, if any.
+    pub fn read(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(content.trim()).ok()
+    }
+
+    /// Whether the holder is a process of this host which is no longer running, e.g., one
+    /// that crashed while the lock file lives on a network filesystem.
+    pub fn is_stale(&self) -> bool {
+        self.host.is_some()
+            && self.host == host_name()
+            && self.pid != std::process::id()
+            && is_process_alive(self.pid) == Some(false)
+    }
+}
+
+impl Display for LockHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PID {}", self.pid)?;
+        if let Some(host) = &self.host {
+            write!(f, " on {host}")?;
+        }
+        write!(
+            f,
+            " (`{}`) for {}s",
+            self.operation,
+            now_timestamp().saturating_sub(self.since)
+        )
+    }
+}
+
+#[cfg(unix)]
+fn host_name() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for its length, the name is truncated to fit
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    let name = String::from_utf8_lossy(&buffer[..end]).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(unix))]
+fn host_name() -> Option<String> {
+    std::env::var("COMPUTERNAME")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Checks whether the process `pid` is running, `None` if that cannot be told.
+#[cfg(target_os = "linux")]
+fn is_process_alive(pid: u32) -> Option<bool> {
+    Some(Path::new("/proc").join(pid.to_string()).exists())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_process_alive(pid: u32) -> Option<bool> {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .ok()
+        .map(|status| status.success())
+}
+
+#[cfg(not(unix))]
+fn is_process_alive(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// State of a lock file, see [`FileLock::inspect`].
+#[derive(Debug)]
+pub enum LockState {
+    Free,
+    /// Held by another process, the holder is unknown for lock files of older versions.
+    Held(Option<LockHolder>),
+}
+
 /// A file lock that is automatically released when dropped.
 /// This implementation is copied from uv-fs https://github.com/astral-sh/uv/blob/main/crates/uv-fs/src/lib.rs
 #[derive(Debug)]
@@ -20,17 +140,30 @@ impl FileLock {
         match file.file().try_lock_exclusive() {
             Ok(()) => {
                 debug!("Acquired lock for `{resource}`");
-                Ok(Self(file))
+                Ok(Self::with_holder(file, resource))
             }
             Err(err) => {
                 // Log error code and enum kind to help debugging more exotic failures.
                 if err.kind() != std::io::ErrorKind::WouldBlock {
                     debug!("Try lock error: {err:?}");
                 }
-                info!(
-                    "Waiting to acquire lock for `{resource}` at `{}`",
-                    file.path().display(),
-                );
+                match LockHolder::read(file.path()) {
+                    Some(holder) if holder.is_stale() => warn!(
+                        "Waiting to acquire lock for `{resource}` at `{}`, held by {holder}, which is no longer running. The lock looks stale, run `meowda doctor --fix-locks` to remove it",
+                        file.path().display(),
+                    ),
+                    Some(holder) => warn!(
+                        "Waiting to acquire lock for `{resource}` at `{}`, held by {holder}",
+                        file.path().display(),
+                    ),
+                    None => info!(
+                        "Waiting to acquire lock for `{resource}` at `{}`",
+                        file.path().display(),
+                    ),
+                }
+                if let Some(timeout) = WAIT_TIMEOUT.get() {
+                    return Self::poll_lock(file, resource, *timeout);
+                }
                 file.file().lock_exclusive().map_err(|err| {
                     // Not an fs_err method, we need to build our own path context
                     std::io::Error::other(format!(
@@ -41,11 +174,84 @@ impl FileLock {
                 })?;
 
                 debug!("Acquired lock for `{resource}`");
-                Ok(Self(file))
+                Ok(Self::with_holder(file, resource))
             }
         }
     }
 
+    /// Records the current process as the holder of the acquired lock, best effort.
+    fn with_holder(file: fs_err::File, resource: &str) -> Self {
+        let holder = LockHolder::current(resource);
+        let result = serde_json::to_string(&holder)
+            .map_err(std::io::Error::other)
+            .and_then(|content| {
+                let mut handle = file.file();
+                handle.set_len(0)?;
+                handle.seek(SeekFrom::Start(0))?;
+                handle.write_all(content.as_bytes())
+            });
+        if let Err(err) = result {
+            debug!("Failed to record the holder of `{resource}`: {err}");
+        }
+        Self(file)
+    }
+
+    /// Checks whether the lock file at `path` is held by another process, without waiting.
+    pub fn inspect(path: &Path) -> std::io::Result<LockState> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        match file.try_lock_exclusive() {
+            Ok(()) => {
+                fs2::FileExt::unlock(&file)?;
+                Ok(LockState::Free)
+            }
+            Err(_) => Ok(LockState::Held(LockHolder::read(path))),
+        }
+    }
+
+    /// Removes the lock file at `path` if the lock can be acquired without waiting, returning
+    /// whether it was removed. A lock still held by some process is kept.
+    pub fn remove_if_free(path: &Path) -> std::io::Result<bool> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        if file.try_lock_exclusive().is_err() {
+            return Ok(false);
+        }
+        // Removed while held, no other process can acquire it in between
+        let result = std::fs::remove_file(path);
+        fs2::FileExt::unlock(&file)?;
+        result.map(|()| true)
+    }
+
+    /// Retries to acquire the lock until `timeout` elapses, failing with
+    /// [`std::io::ErrorKind::TimedOut`].
+    fn poll_lock(
+        file: fs_err::File,
+        resource: &str,
+        timeout: Duration,
+    ) -> Result<Self, std::io::Error> {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            std::thread::sleep(POLL_INTERVAL);
+            if file.file().try_lock_exclusive().is_ok() {
+                debug!("Acquired lock for `{resource}`");
+                return Ok(Self::with_holder(file, resource));
+            }
+        }
+        Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!(
+                "Timed out after {}s waiting for the lock of `{resource}` at `{}`",
+                timeout.as_secs(),
+                file.path().display()
+            ),
+        ))
+    }
+
     /// Acquire a cross-process lock for a resource using a file at the provided path.
     // #[cfg(feature = "tokio")]
     pub async fn acquire(
@@ -57,6 +263,27 @@ impl FileLock {
         tokio::task::spawn_blocking(move || Self::lock_file_blocking(file, &resource)).await?
     }
 
+    /// Acquires the lock without waiting, `None` while another process holds it.
+    pub fn try_acquire(
+        path: impl AsRef<Path>,
+        resource: impl Display,
+    ) -> Result<Option<Self>, std::io::Error> {
+        let file = Self::create(path)?;
+        if file.file().try_lock_exclusive().is_err() {
+            return Ok(None);
+        }
+        Ok(Some(Self::with_holder(file, &resource.to_string())))
+    }
+
+    /// Like [`FileLock::acquire`], blocking the current thread, for locks held only briefly.
+    pub fn acquire_blocking(
+        path: impl AsRef<Path>,
+        resource: impl Display,
+    ) -> Result<Self, std::io::Error> {
+        let file = Self::create(path)?;
+        Self::lock_file_blocking(file, &resource.to_string())
+    }
+
     #[cfg(unix)]
     fn create(path: impl AsRef<Path>) -> Result<fs_err::File, std::io::Error> {
         use std::os::unix::fs::PermissionsExt;
@@ -112,6 +339,8 @@ impl FileLock {
 
 impl Drop for FileLock {
     fn drop(&mut self) {
+        // Forget the holder first, the lock file is empty while the lock is free
+        let _ = self.0.file().set_len(0);
         if let Err(err) = fs2::FileExt::unlock(self.0.file()) {
             error!(
                 "Failed to unlock {}; program may be stuck: {}",
@@ -123,3 +352,51 @@ impl Drop for FileLock {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lock_records_its_holder() -> anyhow::Result<()> {
+        let temp = tempfile::tempdir()?;
+        let path = temp.path().join(".lock");
+        let lock = FileLock::acquire(&path, "venv_store").await?;
+        let holder = LockHolder::read(&path).expect("holder recorded");
+        assert_eq!(holder.pid, std::process::id());
+        assert_eq!(holder.operation, "venv_store");
+        assert!(!holder.is_stale());
+        assert!(matches!(
+            FileLock::inspect(&path)?,
+            LockState::Held(Some(LockHolder { pid, .. })) if pid == std::process::id()
+        ));
+
+        assert!(!FileLock::remove_if_free(&path)?);
+        assert!(path.exists());
+
+        drop(lock);
+        assert!(LockHolder::read(&path).is_none());
+        assert!(matches!(FileLock::inspect(&path)?, LockState::Free));
+        assert!(FileLock::remove_if_free(&path)?);
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn holders_of_dead_processes_are_stale() {
+        let holder = LockHolder {
+            // Above the default pid_max, never a running process
+            pid: u32::MAX - 1,
+            host: host_name(),
+            since: 0,
+            operation: "venv_store".to_string(),
+        };
+        assert!(holder.is_stale());
+        let remote = LockHolder {
+            host: Some("some-other-host".to_string()),
+            ..holder
+        };
+        assert!(!remote.is_stale());
+    }
+}
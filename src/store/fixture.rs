@@ -0,0 +1,156 @@
+/// Fake-but-valid environments for hermetic tests, created without uv or the network.
+///
+/// A fixture environment has the layout of a real virtual environment (`pyvenv.cfg`,
+/// `bin/python`, `bin/activate`, `site-packages`), the `python` executable is a
+/// shell script stub which only answers `--version`.
+use crate::store::venv_store::VenvStore;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Splits `3.12.1` into the `3.12` used by the `site-packages` directory.
+fn short_version(python_version: &str) -> &str {
+    match python_version.match_indices('.').nth(1) {
+        Some((index, _)) => &python_version[..index],
+        None => python_version,
+    }
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    std::fs::write(path, content).with_context(|| format!("Failed to write '{}'", path.display()))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("Failed to set permissions of '{}'", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Creates a fixture environment at `env_path` reporting `python_version`.
+pub fn create_fixture_env(env_path: &Path, python_version: &str) -> Result<()> {
+    let bin_dir = env_path.join("bin");
+    let site_packages = env_path
+        .join("lib")
+        .join(format!("python{}", short_version(python_version)))
+        .join("site-packages");
+    for dir in [&bin_dir, &site_packages] {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create '{}'", dir.display()))?;
+    }
+
+    write_file(
+        &env_path.join("pyvenv.cfg"),
+        &format!(
+            "home = {}\ninclude-system-site-packages = false\nversion_info = {python_version}\nmeowda-fixture = true\n",
+            bin_dir.display()
+        ),
+    )?;
+
+    let python = bin_dir.join("python");
+    write_file(
+        &python,
+        &format!(
+            r#"#!/bin/sh
+# Python stub of a meowda test fixture
+case "$1" in
+    --version|-V) echo "Python {python_version}" ;;
+    *) echo "meowda test fixture: python is a stub" >&2; exit 1 ;;
+esac
+"#
+        ),
+    )?;
+    make_executable(&python)?;
+    #[cfg(unix)]
+    {
+        let python3 = bin_dir.join("python3");
+        if !python3.exists() {
+            std::os::unix::fs::symlink("python", &python3)
+                .with_context(|| format!("Failed to create '{}'", python3.display()))?;
+        }
+    }
+
+    write_file(
+        &bin_dir.join("activate"),
+        &format!(
+            r#"# Activation script of a meowda test fixture
+deactivate () {{
+    if [ -n "${{_OLD_VIRTUAL_PATH:-}}" ]; then
+        PATH="$_OLD_VIRTUAL_PATH"
+        export PATH
+        unset _OLD_VIRTUAL_PATH
+    fi
+    unset VIRTUAL_ENV
+    unset -f deactivate
+}}
+VIRTUAL_ENV="{env}"
+export VIRTUAL_ENV
+_OLD_VIRTUAL_PATH="$PATH"
+PATH="$VIRTUAL_ENV/bin:$PATH"
+export PATH
+"#,
+            env = env_path.display()
+        ),
+    )
+}
+
+/// Creates a fixture environment for each of `names` in `store`, returning their paths.
+pub fn create_fixture_store(
+    store: &VenvStore,
+    names: &[String],
+    python_version: &str,
+) -> Result<Vec<PathBuf>> {
+    store.init_if_needed()?;
+    names
+        .iter()
+        .map(|name| {
+            let env_path = store.path().join(name);
+            create_fixture_env(&env_path, python_version)?;
+            Ok(env_path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venv::EnvConfig;
+
+    #[test]
+    fn test_short_version() {
+        let cases = [("3.12.1", "3.12"), ("3.12", "3.12"), ("3", "3")];
+        for (version, expected) in cases {
+            assert_eq!(short_version(version), expected);
+        }
+    }
+
+    #[test]
+    fn create_fixture_store_creates_valid_envs() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        let names = ["a".to_string(), "b".to_string()];
+        let paths = create_fixture_store(&store, &names, "3.11.4")?;
+
+        assert!(store.is_ready());
+        for path in paths {
+            let config = EnvConfig::parse(path.join("pyvenv.cfg"))?;
+            assert_eq!(config.version.as_deref(), Some("3.11.4"));
+            assert!(path.join("lib/python3.11/site-packages").is_dir());
+            #[cfg(unix)]
+            {
+                let output = std::process::Command::new(path.join("bin/python3"))
+                    .arg("--version")
+                    .output()?;
+                assert_eq!(
+                    String::from_utf8_lossy(&output.stdout).trim(),
+                    "Python 3.11.4"
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+/// Git worktree detection, read from the `.git` files directly instead of running git.
+use std::path::{Path, PathBuf};
+
+/// Returns the root of the main worktree if `dir` is the root of a linked worktree.
+///
+/// A linked worktree has a `.git` file containing `gitdir: <main>/.git/worktrees/<name>`,
+/// whose `commondir` file points back to the `.git` directory of the main worktree.
+pub fn main_worktree_root(dir: &Path) -> Option<PathBuf> {
+    let dot_git = dir.join(".git");
+    if !dot_git.is_file() {
+        return None;
+    }
+    let content = std::fs::read_to_string(&dot_git).ok()?;
+    let gitdir = dir.join(content.trim().strip_prefix("gitdir:")?.trim());
+    let common_dir = match std::fs::read_to_string(gitdir.join("commondir")) {
+        Ok(common_dir) => gitdir.join(common_dir.trim()),
+        // Submodules also use a `.git` file, but without a common dir
+        Err(_) => return None,
+    };
+    let common_dir = common_dir.canonicalize().ok()?;
+    if common_dir.file_name()? != ".git" {
+        // Worktrees of a bare repository have no main worktree
+        return None;
+    }
+    common_dir.parent().map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+
+    #[test]
+    fn main_worktree_root_resolves_linked_worktree() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let main = temp.path().join("repo");
+        let linked = temp.path().join("repo-feature");
+        let gitdir = main.join(".git").join("worktrees").join("repo-feature");
+        fs::create_dir_all(&gitdir)?;
+        fs::create_dir_all(&linked)?;
+        fs::write(gitdir.join("commondir"), "../..\n")?;
+        fs::write(
+            linked.join(".git"),
+            format!("gitdir: {}\n", gitdir.display()),
+        )?;
+
+        assert_eq!(main_worktree_root(&linked), Some(main.canonicalize()?));
+        // The main worktree itself has a `.git` directory
+        assert_eq!(main_worktree_root(&main), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+/// Append-only audit log of the operations on the environments of a store, e.g., to see who
+/// changed an environment of a store shared by a team: `history.jsonl` in the store, one
+/// JSON entry per line.
+use crate::redact::redact_credentials;
+use crate::store::events::now_timestamp;
+use crate::store::venv_store::VenvStore;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Write;
+use std::path::Path;
+use tracing::{debug, warn};
+
+pub const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Create,
+    Remove,
+    Restore,
+    Install,
+    Uninstall,
+    Rebuild,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Operation::Create => "create",
+            Operation::Remove => "remove",
+            Operation::Restore => "restore",
+            Operation::Install => "install",
+            Operation::Uninstall => "uninstall",
+            Operation::Rebuild => "rebuild",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub user: Option<String>,
+    pub operation: Operation,
+    pub env: String,
+    /// Arguments of the meowda invocation, e.g., `["install", "numpy"]`.
+    pub args: Vec<String>,
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn current_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|user| !user.is_empty())
+}
+
+fn append_entry(path: &Path, entry: &HistoryEntry) -> Result<()> {
+    let mut line = serde_json::to_string(entry).context("Failed to serialize history entry")?;
+    line.push('\n');
+    // A single append of a whole line keeps concurrent writers from interleaving
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Failed to append to history '{}'", path.display()))
+}
+
+/// Records the outcome of `operation` on `env` in the history of `store`, failures are
+/// only logged.
+pub fn record<T>(store: &VenvStore, operation: Operation, env: &str, result: &Result<T>) {
+    let entry = HistoryEntry {
+        timestamp: now_timestamp(),
+        user: current_user(),
+        operation,
+        env: env.to_string(),
+        args: std::env::args()
+            .skip(1)
+            .map(|arg| redact_credentials(&arg))
+            .collect(),
+        outcome: match result {
+            Ok(_) => Outcome::Success,
+            Err(_) => Outcome::Failure,
+        },
+        error: result
+            .as_ref()
+            .err()
+            .map(|e| redact_credentials(&e.to_string())),
+    };
+    if let Err(e) = append_entry(&store.path().join(HISTORY_FILE_NAME), &entry) {
+        warn!("Failed to record the history of '{env}': {e:#}");
+    }
+}
+
+/// Reads the history of the store at `store_path` in recording order, empty if there is
+/// none. Malformed lines, e.g., of a future format, are skipped.
+pub fn read(store_path: &Path) -> Result<Vec<HistoryEntry>> {
+    let path = store_path.join(HISTORY_FILE_NAME);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read history '{}'", path.display()));
+        }
+    };
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                debug!("Skipping malformed history entry: {e}");
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_records_outcomes() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().to_path_buf());
+        assert!(read(temp.path())?.is_empty());
+
+        record(&store, Operation::Create, "app", &Ok(()));
+        record::<()>(
+            &store,
+            Operation::Install,
+            "app",
+            &Err(anyhow::anyhow!("Failed to install packages")),
+        );
+        let entries = read(temp.path())?;
+        let summary = entries
+            .iter()
+            .map(|entry| (entry.operation, entry.env.as_str(), entry.outcome))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (Operation::Create, "app", Outcome::Success),
+                (Operation::Install, "app", Outcome::Failure)
+            ]
+        );
+        assert_eq!(
+            entries[1].error.as_deref(),
+            Some("Failed to install packages")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+/// Index of the environments of a store, so listing reads one small file instead of
+/// stating every directory and parsing every `pyvenv.cfg`, which is slow for large stores
+/// and on network filesystems.
+///
+/// The index records the modification time of the store directory, which changes whenever
+/// an environment directory is created, removed or renamed, so an outdated index is
+/// detected with a single `stat` and rebuilt. Creating or removing an environment also
+/// rebuilds it right away. Registered environments live outside the store directory and are
+/// never indexed.
+///
+/// The indexes are kept in the user state directory next to the events snapshot, one file
+/// per store, rather than in the stores: replacing them never changes the modification time
+/// of a store directory, and read-only stores are indexed too.
+use crate::hash::KeyHasher;
+use crate::store::atomic::write_atomic;
+use crate::store::venv_store::{VenvStore, user_state_dir};
+use crate::venv::EnvConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::debug;
+
+/// Bumped on incompatible changes of the index format.
+const INDEX_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreIndex {
+    version: u32,
+    /// The indexed store, indexes of stores which no longer exist are removed.
+    #[serde(default)]
+    store: PathBuf,
+    /// Modification time of the store directory the index is up to date with.
+    modified: Option<SystemTime>,
+    /// The parsed `pyvenv.cfg` of each environment, `None` if it could not be read.
+    envs: BTreeMap<String, Option<EnvConfig>>,
+}
+
+fn store_modified(root: &Path) -> Option<SystemTime> {
+    std::fs::metadata(root)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reads the environment directories of the store, skipping those of meowda, e.g., `.trash`,
+/// and environments still being created.
+fn scan(store: &VenvStore) -> BTreeMap<String, Option<EnvConfig>> {
+    store
+        .path()
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let path = entry.path();
+            (!name.starts_with('.') && path.is_dir() && !store.is_being_created(&name))
+                .then(|| (name, EnvConfig::parse(path.join("pyvenv.cfg")).ok()))
+        })
+        .collect()
+}
+
+fn read_index(path: &Path) -> Option<StoreIndex> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<StoreIndex>(&content)
+        .ok()
+        .filter(|index| index.version == INDEX_FORMAT_VERSION)
+}
+
+/// Where the index of `store` is kept, keyed by the path of the store.
+fn index_path(store: &VenvStore) -> Option<PathBuf> {
+    let mut hasher = KeyHasher::default();
+    hasher.field(store.path().as_os_str().as_encoded_bytes());
+    user_state_dir().map(|dir| {
+        dir.join("indexes")
+            .join(format!("{}.json", hasher.finish()))
+    })
+}
+
+/// Removes the indexes in `dir` of stores which no longer exist, e.g., of removed projects.
+fn remove_orphaned_indexes(dir: &Path) {
+    let orphans = dir
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+                && read_index(path).is_none_or(|index| !index.store.is_dir())
+        });
+    for path in orphans {
+        if let Err(e) = std::fs::remove_file(&path) {
+            debug!("Failed to remove '{}': {e}", path.display());
+        }
+    }
+}
+
+fn write_index(path: &Path, index: &StoreIndex) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+        remove_orphaned_indexes(parent);
+    }
+    let content = serde_json::to_string(index).context("Failed to serialize store index")?;
+    write_atomic(path, content)
+}
+
+/// Scans the store and writes the index to `path`, best effort as the index only saves time.
+fn rebuild(store: &VenvStore, path: Option<&Path>) -> BTreeMap<String, Option<EnvConfig>> {
+    // Taken before scanning, so changes during the scan outdate the index
+    let modified = store_modified(store.path());
+    let envs = scan(store);
+    if let Some(path) = path
+        && store.is_ready()
+    {
+        let index = StoreIndex {
+            version: INDEX_FORMAT_VERSION,
+            store: store.path().clone(),
+            modified,
+            envs,
+        };
+        if let Err(e) = write_index(path, &index) {
+            debug!("Failed to update the index of the {store}: {e:#}");
+        }
+        return index.envs;
+    }
+    envs
+}
+
+/// Like [`indexed_envs`], with the index at `path`.
+fn indexed_envs_at(store: &VenvStore, path: Option<&Path>) -> BTreeMap<String, Option<EnvConfig>> {
+    if let Some(index) = path.and_then(read_index)
+        && index.modified.is_some()
+        && index.modified == store_modified(store.path())
+    {
+        return index.envs;
+    }
+    rebuild(store, path)
+}
+
+/// The environments in the store directory with their parsed `pyvenv.cfg`, from the index
+/// while it is up to date.
+pub fn indexed_envs(store: &VenvStore) -> BTreeMap<String, Option<EnvConfig>> {
+    indexed_envs_at(store, index_path(store).as_deref())
+}
+
+/// Rebuilds the index after an environment of the store was created or removed.
+pub fn refresh(store: &VenvStore) {
+    rebuild(store, index_path(store).as_deref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+
+    #[test]
+    fn index_is_used_until_the_store_changes() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        create_fixture_store(&store, &["app".to_string()], "3.13.1")?;
+        let path = temp.path().join("index.json");
+        let indexed_envs = |store: &VenvStore| indexed_envs_at(store, Some(&path));
+        let names = |envs: BTreeMap<String, Option<EnvConfig>>| {
+            envs.into_iter()
+                .map(|(name, config)| (name, config.and_then(|config| config.version)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(indexed_envs(&store)),
+            [("app".to_string(), Some("3.13.1".to_string()))]
+        );
+
+        // An up-to-date index is answered from, without reading the environments
+        let mut index = read_index(&path).expect("index is written");
+        index.envs.insert("cached".to_string(), None);
+        std::fs::write(&path, serde_json::to_string(&index)?)?;
+        assert_eq!(names(indexed_envs(&store)).len(), 2);
+
+        // Indexes of stores which no longer exist are removed
+        let orphan = temp.path().join("orphan.json");
+        std::fs::write(
+            &orphan,
+            serde_json::to_string(&StoreIndex {
+                version: INDEX_FORMAT_VERSION,
+                store: temp.path().join("removed"),
+                ..StoreIndex::default()
+            })?,
+        )?;
+        create_fixture_store(&store, &["tools".to_string()], "3.12.0")?;
+        assert_eq!(
+            names(indexed_envs(&store)),
+            [
+                ("app".to_string(), Some("3.13.1".to_string())),
+                ("tools".to_string(), Some("3.12.0".to_string())),
+            ]
+        );
+        assert!(!orphan.exists() && path.exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+/// Versioning of the store layout: every store records its format in `store.toml`, and
+/// stores of an older layout are migrated at startup, so layout changes never break the
+/// environments users already have.
+use crate::store::venv_store::{ScopeType, VenvStore, get_candidate_scopes};
+use crate::venv::{EnvMetadata, METADATA_FILE_NAME};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::Path;
+use tracing::{debug, info, warn};
+
+pub const STORE_FILE_NAME: &str = "store.toml";
+
+/// Bumped with a new entry in [`MIGRATIONS`] on every change of the store layout.
+pub const STORE_FORMAT_VERSION: u32 = 1;
+
+/// Migrations of the store layout, the one at index `i` upgrades format `i` to `i + 1`.
+/// Stores created before `store.toml` existed are format 0.
+const MIGRATIONS: [fn(&VenvStore) -> Result<()>; STORE_FORMAT_VERSION as usize] =
+    [add_missing_metadata];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoreFile {
+    format_version: u32,
+}
+
+fn store_file_content(format_version: u32) -> Result<String> {
+    toml::to_string(&StoreFile { format_version }).context("Failed to serialize store file")
+}
+
+/// The format of the store rooted at `root`, 0 for stores without `store.toml`.
+pub fn format_version(root: &Path) -> Result<u32> {
+    let path = root.join(STORE_FILE_NAME);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read '{}'", path.display()));
+        }
+    };
+    let store_file: StoreFile = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse '{}'", path.display()))?;
+    Ok(store_file.format_version)
+}
+
+/// Records the current format in a new store, keeping the file of an existing one.
+pub(crate) fn write_initial_format(root: &Path) -> io::Result<()> {
+    let content = store_file_content(STORE_FORMAT_VERSION).map_err(io::Error::other)?;
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(root.join(STORE_FILE_NAME))
+    {
+        Ok(mut file) => file.write_all(content.as_bytes()),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Fails for stores written by a newer meowda, whose layout this one does not know.
+pub fn ensure_known_format(root: &Path) -> Result<()> {
+    let version = format_version(root)?;
+    if version > STORE_FORMAT_VERSION {
+        anyhow::bail!(
+            "The store {} has format {version}, newer than {STORE_FORMAT_VERSION} supported by this meowda, upgrade meowda to change it",
+            root.display()
+        );
+    }
+    Ok(())
+}
+
+/// Format 0 to 1: environments created before metadata was recorded get an empty
+/// metadata file, so every environment of a store has one.
+fn add_missing_metadata(store: &VenvStore) -> Result<()> {
+    let registered = store.registered_envs();
+    for name in store.env_names() {
+        // Registered environments belong to their owners, they are never written to
+        if registered.contains_key(&name) {
+            continue;
+        }
+        let env_path = store.env_path(&name);
+        if env_path.join("pyvenv.cfg").is_file() && !env_path.join(METADATA_FILE_NAME).exists() {
+            EnvMetadata::default().save(&env_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs the migrations from the format of `store` up to the current one, returning whether
+/// any ran. Must be called with the lock of the store held.
+fn migrate_locked(store: &VenvStore) -> Result<bool> {
+    let version = format_version(store.path())?;
+    if version >= STORE_FORMAT_VERSION {
+        return Ok(false);
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        debug!("Migrating {store} from format {from} to {}", from + 1);
+        migration(store).with_context(|| {
+            format!(
+                "Failed to migrate the {store} from format {from} to {}",
+                from + 1
+            )
+        })?;
+    }
+    let path = store.path().join(STORE_FILE_NAME);
+    std::fs::write(&path, store_file_content(STORE_FORMAT_VERSION)?)
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    Ok(true)
+}
+
+/// Migrates `store` to the current format if it has an older one. Only reads `store.toml`
+/// when it is up to date, the store lock is taken for migrating.
+pub async fn migrate(store: &VenvStore) -> Result<()> {
+    if !store.is_ready()
+        || store.is_read_only()
+        || format_version(store.path())? >= STORE_FORMAT_VERSION
+    {
+        return Ok(());
+    }
+    let _lock = store.lock().await?;
+    if migrate_locked(store)? {
+        info!("Migrated the {store} to format {STORE_FORMAT_VERSION}");
+    }
+    Ok(())
+}
+
+/// Migrates the local and global stores commands resolve environments from, called at
+/// startup. Failures are only logged, so a broken store never blocks other commands.
+pub async fn migrate_stores() {
+    let scopes = match get_candidate_scopes(ScopeType::Unspecified) {
+        Ok(scopes) => scopes,
+        Err(e) => {
+            debug!("Skipping store migration: {e:#}");
+            return;
+        }
+    };
+    for scope in scopes {
+        let result = match VenvStore::from_specified_scope(scope) {
+            Ok(store) => migrate(&store).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!("{e:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+
+    #[tokio::test]
+    async fn stores_without_a_format_are_migrated() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        let paths = create_fixture_store(&store, &["old".to_string()], "3.12.0")?;
+        assert_eq!(format_version(store.path())?, STORE_FORMAT_VERSION);
+
+        // A store created before `store.toml` existed
+        std::fs::remove_file(store.path().join(STORE_FILE_NAME))?;
+        std::fs::remove_file(paths[0].join(METADATA_FILE_NAME)).ok();
+        assert_eq!(format_version(store.path())?, 0);
+
+        migrate(&store).await?;
+        assert_eq!(format_version(store.path())?, STORE_FORMAT_VERSION);
+        assert!(paths[0].join(METADATA_FILE_NAME).is_file());
+        assert!(ensure_known_format(store.path()).is_ok());
+
+        std::fs::write(
+            store.path().join(STORE_FILE_NAME),
+            store_file_content(STORE_FORMAT_VERSION + 1)?,
+        )?;
+        assert!(ensure_known_format(store.path()).is_err());
+        Ok(())
+    }
+}
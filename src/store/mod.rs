@@ -1,2 +1,14 @@
+pub mod activate_index;
+pub mod atomic;
+pub mod backend;
+pub mod conda;
+pub mod events;
 pub mod file_lock;
+pub mod fixture;
+pub mod git;
+pub mod history;
+pub mod index;
+pub mod migrate;
+pub mod registry;
+pub mod trash;
 pub mod venv_store;
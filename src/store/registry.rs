@@ -0,0 +1,57 @@
+/// Registry of external environments, which stay where they are, e.g., on another disk,
+/// but are listed and activated like the environments inside the store.
+use crate::store::atomic::write_atomic;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The registry file inside the store, environment names cannot start with `.`.
+pub const REGISTRY_FILE_NAME: &str = ".registry.toml";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Registry {
+    /// Paths of the registered environments keyed by their names.
+    pub envs: BTreeMap<String, PathBuf>,
+}
+
+impl Registry {
+    /// Loads the registry of the store at `store_path`, empty if there is none.
+    pub fn load(store_path: &Path) -> Result<Self> {
+        let path = store_path.join(REGISTRY_FILE_NAME);
+        if !path.exists() {
+            return Ok(Registry::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read registry '{}'", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse registry '{}'", path.display()))
+    }
+
+    pub fn save(&self, store_path: &Path) -> Result<()> {
+        let path = store_path.join(REGISTRY_FILE_NAME);
+        let content = toml::to_string(self).context("Failed to serialize registry")?;
+        write_atomic(&path, content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_round_trips() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        assert!(Registry::load(temp.path())?.envs.is_empty());
+
+        let mut registry = Registry::default();
+        registry
+            .envs
+            .insert("gpu-env".to_string(), PathBuf::from("/mnt/data/gpu-env"));
+        registry.save(temp.path())?;
+        let loaded = Registry::load(temp.path())?;
+        assert_eq!(loaded.envs, registry.envs);
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+/// Removed environments are moved into `.trash/` of their store instead of being deleted,
+/// so `meowda restore` can bring back large, slow-to-rebuild environments removed by
+/// mistake. Entries are named `<env>.<removal timestamp>` and deleted once expired.
+use crate::error::ErrorCode;
+use crate::store::events::now_timestamp;
+use crate::store::venv_store::VenvStore;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+pub const TRASH_DIR_NAME: &str = ".trash";
+
+/// Days removed environments are kept in the trash unless `trash_days` is set.
+pub const DEFAULT_TRASH_DAYS: u64 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashEntry {
+    pub name: String,
+    /// Seconds since the Unix epoch.
+    pub removed_at: u64,
+    pub path: PathBuf,
+}
+
+pub fn trash_dir(store: &VenvStore) -> PathBuf {
+    store.path().join(TRASH_DIR_NAME)
+}
+
+/// Splits a trash entry file name into the environment name and its removal timestamp.
+fn parse_entry_name(file_name: &str) -> Option<(&str, u64)> {
+    let (name, removed_at) = file_name.rsplit_once('.')?;
+    Some((name, removed_at.parse().ok()?)).filter(|(name, _)| !name.is_empty())
+}
+
+/// Lists the environments in the trash of `store`, oldest first.
+pub fn list_trash(store: &VenvStore) -> Vec<TrashEntry> {
+    let mut entries = trash_dir(store)
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let (name, removed_at) = parse_entry_name(file_name.to_str()?)?;
+            Some(TrashEntry {
+                name: name.to_string(),
+                removed_at,
+                path: entry.path(),
+            })
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| (a.removed_at, &a.name).cmp(&(b.removed_at, &b.name)));
+    entries
+}
+
+/// Moves the environment `name` into the trash of its store. Environments stay on the same
+/// filesystem, so this is a rename however large they are.
+pub fn move_to_trash(store: &VenvStore, name: &str) -> Result<PathBuf> {
+    let trash = trash_dir(store);
+    std::fs::create_dir_all(&trash)
+        .with_context(|| format!("Failed to create trash directory '{}'", trash.display()))?;
+    let mut removed_at = now_timestamp();
+    // Removing an environment twice within a second must not overwrite the first one
+    while trash.join(format!("{name}.{removed_at}")).exists() {
+        removed_at += 1;
+    }
+    let target = trash.join(format!("{name}.{removed_at}"));
+    std::fs::rename(store.path().join(name), &target)
+        .context("Failed to move virtual environment to the trash")?;
+    debug!("Moved '{name}' to {}", target.display());
+    Ok(target)
+}
+
+/// Moves the most recently removed environment `name` back from the trash of `store`.
+pub fn restore(store: &VenvStore, name: &str) -> Result<TrashEntry> {
+    if store.exists(name) {
+        return Err(ErrorCode::EnvAlreadyExists.err_with_hint(
+            format!("Virtual environment '{name}' already exists."),
+            format!("remove it first with `meowda remove {name}`"),
+        ));
+    }
+    let Some(entry) = list_trash(store)
+        .into_iter()
+        .rev()
+        .find(|entry| entry.name == name)
+    else {
+        return Err(ErrorCode::EnvNotFound.err_with_hint(
+            format!("Virtual environment '{name}' is not in the trash of the {store}"),
+            "list the removed environments with `meowda restore --list`",
+        ));
+    };
+    std::fs::rename(&entry.path, store.path().join(name))
+        .context("Failed to restore virtual environment from the trash")?;
+    Ok(entry)
+}
+
+/// Deletes the environments removed more than `days` days ago from the trash of `store`,
+/// failures are only logged.
+pub fn purge_expired(store: &VenvStore, days: u64) {
+    let cutoff = now_timestamp().saturating_sub(days.saturating_mul(24 * 60 * 60));
+    for entry in list_trash(store) {
+        if entry.removed_at > cutoff {
+            continue;
+        }
+        match std::fs::remove_dir_all(&entry.path) {
+            Ok(()) => debug!("Deleted expired trash entry {}", entry.path.display()),
+            Err(e) => warn!("Failed to delete '{}': {e}", entry.path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+
+    #[test]
+    fn removed_envs_can_be_restored_until_they_expire() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        create_fixture_store(&store, &["app".to_string()], "3.13.1")?;
+
+        move_to_trash(&store, "app")?;
+        assert!(!store.exists("app"));
+        assert_eq!(store.env_names(), Vec::<String>::new());
+        create_fixture_store(&store, &["app".to_string()], "3.13.1")?;
+        move_to_trash(&store, "app")?;
+        let entries = list_trash(&store);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].removed_at < entries[1].removed_at);
+
+        create_fixture_store(&store, &["app".to_string()], "3.13.1")?;
+        assert!(restore(&store, "app").is_err());
+        std::fs::remove_dir_all(store.env_path("app"))?;
+        assert_eq!(restore(&store, "app")?, entries[1]);
+        assert!(store.env_path("app").join("pyvenv.cfg").is_file());
+        assert_eq!(list_trash(&store), entries[..1]);
+
+        purge_expired(&store, DEFAULT_TRASH_DAYS);
+        assert_eq!(list_trash(&store).len(), 1);
+        purge_expired(&store, 0);
+        assert!(list_trash(&store).is_empty());
+        assert!(restore(&store, "missing").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn entry_names_split_at_the_last_dot() {
+        let cases = [
+            ("app.1700000000", Some(("app", 1700000000))),
+            ("py3.12.1700000000", Some(("py3.12", 1700000000))),
+            ("app", None),
+            (".1700000000", None),
+            ("app.tmp", None),
+        ];
+        for (file_name, expected) in cases {
+            assert_eq!(parse_entry_name(file_name), expected, "{file_name}");
+        }
+    }
+}
@@ -1,16 +1,34 @@
 /// Provides a user-level directory for storing application state.
 /// Heavy inspiration from the uv implementation.
-use crate::envs::EnvVars;
+use crate::error::ErrorCode;
+use crate::store::backend::{self, Discovery};
 use crate::store::file_lock::FileLock;
+use crate::store::migrate;
+use crate::store::registry::Registry;
 use anyhow::{Context, Result};
 use etcetera::BaseStrategy;
+use std::collections::BTreeMap;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use tracing::{debug, info, instrument, warn};
+
+static NO_RECURSE: AtomicBool = AtomicBool::new(false);
+
+/// Restricts local discovery to the nearest local store, called once at startup.
+pub fn set_no_recurse(no_recurse: bool) {
+    NO_RECURSE.store(no_recurse, Ordering::Relaxed);
+}
+
+pub fn is_no_recurse() -> bool {
+    NO_RECURSE.load(Ordering::Relaxed)
+}
 
 /// Returns an appropriate user-level directory for storing application state.
 ///
 /// Corresponds to `$XDG_DATA_HOME/meowda` on Unix.
-fn user_state_dir() -> Option<PathBuf> {
+pub fn user_state_dir() -> Option<PathBuf> {
     etcetera::base_strategy::choose_base_strategy()
         .ok()
         .map(|dirs| dirs.data_dir().join("meowda"))
@@ -30,106 +48,317 @@ pub fn resolve_parent_path(path: &Path, parent_level: u8) -> Result<PathBuf> {
     Ok(current.to_path_buf())
 }
 
+/// Marker file, relative to a project directory, stopping local store discovery there.
+pub const ROOT_MARKER_PATH: &str = ".meowda/root";
+
+/// A file in the root of a store marking it read-only, e.g., a centrally provisioned one.
+pub const READ_ONLY_MARKER: &str = ".readonly";
+
+/// Whether the store rooted at `root` is read-only: marked by a `.readonly` file or listed
+/// in `read_only_stores`. Its environments are listed, activated and run, never changed.
+pub fn is_read_only_root(root: &Path) -> bool {
+    root.join(READ_ONLY_MARKER).is_file()
+        || backend::read_only_roots()
+            .iter()
+            .any(|read_only| read_only == root)
+}
+
+/// Whether local store discovery stops at `dir` rather than continuing with its parent,
+/// so that a stray store above a project, e.g., `~/.meowda/venvs`, doesn't leak into it.
+fn is_discovery_boundary(dir: &Path, stop_at_git_root: bool) -> bool {
+    dir.join(ROOT_MARKER_PATH).exists() || (stop_at_git_root && dir.join(".git").exists())
+}
+
+/// Whether the local store `parent_level` levels above the working directory is still
+/// among the candidate stores, i.e., no discovery boundary was added below it. Only the
+/// boundary markers are checked, unlike [`get_candidate_scopes_in`].
+pub fn is_local_scope_reachable(discovery: &Discovery, parent_level: u8) -> bool {
+    let Ok(cwd) = discovery.cwd() else {
+        return false;
+    };
+    (0..parent_level).all(|level| {
+        resolve_parent_path(&cwd, level)
+            .is_ok_and(|dir| !is_discovery_boundary(&dir, discovery.settings().stop_at_git_root()))
+    })
+}
+
+#[instrument(level = "debug")]
 pub fn get_candidate_scopes(scope_type: ScopeType) -> Result<Vec<VenvScope>> {
-    let current_dir = std::env::current_dir().context("Failed to get current working directory")?;
-    let search_local = matches!(scope_type, ScopeType::Local | ScopeType::Unspecified);
-    let search_global = matches!(scope_type, ScopeType::Global | ScopeType::Unspecified);
+    get_candidate_scopes_in(&Discovery::current(), scope_type)
+}
+
+/// Like [`get_candidate_scopes`], discovering local stores as given by `discovery`.
+pub fn get_candidate_scopes_in(
+    discovery: &Discovery,
+    scope_type: ScopeType,
+) -> Result<Vec<VenvScope>> {
+    let current_dir = discovery.cwd()?;
+    let search_local = scope_type != ScopeType::Global;
+    let search_global = scope_type != ScopeType::Local;
 
     let mut scopes = Vec::new();
     if search_local {
         for parent_level in 0.. {
             match resolve_parent_path(current_dir.as_path(), parent_level) {
-                Ok(_) => scopes.push(VenvScope::Local(parent_level)),
+                Ok(dir) => {
+                    scopes.push(VenvScope::Local(parent_level));
+                    if is_discovery_boundary(&dir, discovery.settings().stop_at_git_root()) {
+                        debug!("Stopping local store discovery at `{}`", dir.display());
+                        break;
+                    }
+                }
                 Err(_) => break, // Stop if we can't resolve any further parent directories
             }
         }
+        if is_no_recurse() {
+            // Only the nearest existing store, the outer ones are out of reach
+            scopes = scopes
+                .into_iter()
+                .find(|scope| {
+                    VenvStore::from_scope_in(discovery, scope.clone())
+                        .is_ok_and(|store| store.is_ready())
+                })
+                .into_iter()
+                .collect();
+        }
     }
     if search_global {
         scopes.push(VenvScope::Global);
     }
+    debug!("Found {} candidate scopes", scopes.len());
     Ok(scopes)
 }
 
+/// The scope of the environment at `venv_path` for display, e.g., in the prompt: `global`,
+/// the name of an extra store, or `local` for everything else. Only compares paths, so it
+/// is cheap enough for every prompt.
+pub fn scope_label(venv_path: &Path) -> String {
+    let Some(store_path) = venv_path.parent() else {
+        return "local".to_string();
+    };
+    VenvStore::from_specified_scope(VenvScope::Global)
+        .into_iter()
+        .chain(VenvStore::named_stores())
+        .find(|store| store.path() == store_path)
+        .and_then(|store| store.scope().map(|scope| scope.to_string()))
+        .unwrap_or_else(|| "local".to_string())
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ScopeType {
     Local,
     Global,
+    /// The nearest local store up the tree if there is one, the global store otherwise.
+    /// Searching it looks in all local stores and the global store, like `Unspecified`.
+    Auto,
     Unspecified,
 }
 
-#[derive(PartialEq, Eq, Clone)]
+/// The scope of commands run without `--local`/`--global`, from `default_scope` in config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefaultScope {
+    Local,
+    Global,
+    /// The nearest local store up the tree if there is one, the global store otherwise.
+    Auto,
+}
+
+impl std::str::FromStr for DefaultScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "local" => Ok(DefaultScope::Local),
+            "global" => Ok(DefaultScope::Global),
+            "auto" => Ok(DefaultScope::Auto),
+            other => {
+                anyhow::bail!("Unknown scope '{other}', expected one of: local, global, auto")
+            }
+        }
+    }
+}
+
+/// The nearest existing local store of the current directory or its parents, which the
+/// `auto` scope prefers.
+pub fn nearest_local_scope() -> Option<VenvScope> {
+    let scope = get_candidate_scopes(ScopeType::Local)
+        .ok()?
+        .into_iter()
+        .find(|scope| {
+            VenvStore::from_specified_scope(scope.clone()).is_ok_and(|store| store.is_ready())
+        });
+    match &scope {
+        Some(scope) => info!("Auto scope: using the {scope} store"),
+        None => info!("Auto scope: no local store found, using the global store"),
+    }
+    scope
+}
+
+impl ScopeType {
+    /// Replaces `Unspecified` with the default scope from config, see
+    /// [`backend::default_scope`], other scopes are kept.
+    pub fn or_default_scope(self) -> Self {
+        match (self, backend::default_scope()) {
+            (ScopeType::Unspecified, Some(DefaultScope::Local)) => ScopeType::Local,
+            (ScopeType::Unspecified, Some(DefaultScope::Global)) => ScopeType::Global,
+            (ScopeType::Unspecified, Some(DefaultScope::Auto)) => ScopeType::Auto,
+            _ => self,
+        }
+    }
+
+    /// Whether `--local`/`--global` or a prefix like `global:` restrict it to one scope.
+    pub fn is_restricted(self) -> bool {
+        matches!(self, ScopeType::Local | ScopeType::Global)
+    }
+}
+
+/// Splits a scope-qualified environment reference, e.g., `global:myenv` or `local:myenv`,
+/// into the scope type it selects and the environment name.
+///
+/// Unqualified names keep `scope_type`, a prefix contradicting `--local`/`--global` is an error.
+pub fn parse_env_ref(env_ref: &str, scope_type: ScopeType) -> Result<(ScopeType, &str)> {
+    let Some((prefix, name)) = env_ref.split_once(':') else {
+        return Ok((scope_type, env_ref));
+    };
+    let prefix_scope = match prefix {
+        "local" => ScopeType::Local,
+        "global" => ScopeType::Global,
+        // Not a scope prefix, environment names cannot contain `:` anyway
+        _ => return Ok((scope_type, env_ref)),
+    };
+    if scope_type.is_restricted() && scope_type != prefix_scope {
+        return Err(ErrorCode::InvalidScope.err(format!(
+            "'{env_ref}' selects the {prefix} scope, which contradicts the scope flag"
+        )));
+    }
+    Ok((prefix_scope, name))
+}
+
+/// Splits a reference into an extra store from config, e.g., `team:myenv`, into the
+/// scope of the store and the environment name, `None` for other references.
+pub fn resolve_named_ref<'a>(
+    discovery: &Discovery,
+    env_ref: &'a str,
+) -> Option<(VenvScope, &'a str)> {
+    let (prefix, name) = env_ref.split_once(':')?;
+    discovery
+        .settings()
+        .named_stores()
+        .contains_key(prefix)
+        .then(|| (VenvScope::Named(prefix.to_string()), name))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum VenvScope {
     Local(u8),
     Global,
+    /// An extra store from the `stores` config key.
+    Named(String),
+}
+
+impl std::fmt::Display for VenvScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VenvScope::Local(0) => write!(f, "local"),
+            VenvScope::Local(1) => write!(f, "local (1 level up)"),
+            VenvScope::Local(parent_level) => write!(f, "local ({parent_level} levels up)"),
+            VenvScope::Global => write!(f, "global"),
+            VenvScope::Named(name) => write!(f, "{name}"),
+        }
+    }
 }
 
 pub struct VenvStore {
     path: PathBuf,
+    /// The scope the store was resolved from, `None` for a store at an explicit path.
+    scope: Option<VenvScope>,
+    /// The registered environments, loaded once per store as an operation looks up many
+    /// names.
+    registry: Mutex<Option<BTreeMap<String, PathBuf>>>,
+}
+
+impl std::fmt::Display for VenvStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.scope() {
+            Some(scope) => write!(f, "{scope} store {}", self.path.display()),
+            None => write!(f, "store {}", self.path.display()),
+        }
+    }
 }
 
 impl VenvStore {
-    /// Detects the local venv directory in the current working directory.
-    ///
-    /// Prefer, in order:
-    /// 1. The specific tool directory specified by the user, i.e., `MEOWDA_LOCAL_VENV_DIR`
-    /// 2. A directory in the local data directory, e.g., `./.meowda/venvs`
-    fn local_path(parent_level: u8) -> Result<PathBuf> {
-        let local_venv_dir_path = if let Some(local_venv_dir) =
-            std::env::var_os(EnvVars::MEOWDA_LOCAL_VENV_DIR).filter(|s| !s.is_empty())
-        {
-            if PathBuf::from(local_venv_dir.clone()).is_absolute() {
-                return std::path::absolute(local_venv_dir).with_context(|| {
-                    "Invalid path for `MEOWDA_LOCAL_VENV_DIR` environment variable".to_string()
-                });
+    #[instrument(level = "debug")]
+    pub fn from_specified_scope(scope: VenvScope) -> Result<Self> {
+        Self::from_scope_in(&Discovery::current(), scope)
+    }
+
+    /// Like [`Self::from_specified_scope`], resolving local stores as given by `discovery`.
+    pub fn from_scope_in(discovery: &Discovery, scope: VenvScope) -> Result<Self> {
+        let backend = discovery.settings().backend();
+        let path = match &scope {
+            VenvScope::Local(parent_level) => {
+                backend.local_root(&discovery.cwd()?, *parent_level)?
             }
-            PathBuf::from(local_venv_dir)
-        } else {
-            PathBuf::from(".meowda").join("venvs")
+            VenvScope::Global => backend.global_root()?,
+            VenvScope::Named(name) => discovery
+                .settings()
+                .named_stores()
+                .get(name)
+                .cloned()
+                .ok_or_else(|| {
+                    ErrorCode::InvalidScope.err(format!("Unknown store '{name}' in `stores`"))
+                })?,
         };
-        let current_dir =
-            std::env::current_dir().context("Failed to get current working directory")?;
-        Ok(resolve_parent_path(&current_dir, parent_level)?.join(local_venv_dir_path))
+        debug!("Using store at `{}`", path.display());
+        Ok(VenvStore {
+            path,
+            scope: Some(scope),
+            registry: Mutex::default(),
+        })
     }
 
-    /// Detects the global venv directory in the current working directory.
-    ///
-    /// Prefer, in order:
-    ///
-    /// 1. The specific tool directory specified by the user, i.e., `MEOWDA_GLOBAL_VENV_DIR`
-    /// 2. A directory in the system-appropriate user-level data directory, e.g., `~/.local/meowda/venvs`
-    fn global_path() -> Result<PathBuf> {
-        if let Some(tool_dir) =
-            std::env::var_os(EnvVars::MEOWDA_GLOBAL_VENV_DIR).filter(|s| !s.is_empty())
-        {
-            std::path::absolute(tool_dir).with_context(|| {
-                "Invalid path for `MEOWDA_GLOBAL_VENV_DIR` environment variable".to_string()
-            })
-        } else {
-            user_state_dir()
-                .map(|dir| dir.join("venvs"))
-                .ok_or_else(|| anyhow::anyhow!("Failed to determine user state directory"))
+    /// Uses the extra store `name` rooted at `root`, e.g., a shared team directory.
+    pub fn from_named_root(name: &str, root: PathBuf) -> Self {
+        VenvStore {
+            path: root,
+            scope: Some(VenvScope::Named(name.to_string())),
+            registry: Mutex::default(),
         }
     }
 
-    pub fn from_specified_scope(scope: VenvScope) -> Result<Self> {
-        let path = match scope {
-            VenvScope::Local(parent_level) => Self::local_path(parent_level)?,
-            VenvScope::Global => Self::global_path()?,
-        };
-        Ok(VenvStore { path })
+    /// The extra stores from the `stores` config key.
+    pub fn named_stores() -> Vec<Self> {
+        backend::named_stores()
+            .iter()
+            .map(|(name, root)| Self::from_named_root(name, root.clone()))
+            .collect()
+    }
+
+    /// Uses `path` as the store directory, bypassing scope detection.
+    pub fn from_path(path: PathBuf) -> Self {
+        VenvStore {
+            path,
+            scope: None,
+            registry: Mutex::default(),
+        }
     }
 
+    #[instrument(level = "debug")]
     pub fn from_scope_type(scope_type: ScopeType) -> Result<Self> {
-        let path = match scope_type {
-            ScopeType::Local => Self::local_path(0)?,
-            ScopeType::Global => Self::global_path()?,
-            ScopeType::Unspecified => Self::global_path()?,
+        let scope = match scope_type.or_default_scope() {
+            ScopeType::Local => VenvScope::Local(0),
+            // The store found up the tree, not one in the current directory
+            ScopeType::Auto => nearest_local_scope().unwrap_or(VenvScope::Global),
+            ScopeType::Global | ScopeType::Unspecified => VenvScope::Global,
         };
-        Ok(VenvStore { path })
+        Self::from_specified_scope(scope)
     }
 
     pub fn is_ready(&self) -> bool {
-        self.path.exists() && self.path.is_dir() && self.path.join(".gitignore").exists()
+        // Extra stores are managed by their owners, e.g., a team, not initialized by meowda
+        self.path.is_dir()
+            && (matches!(self.scope, Some(VenvScope::Named(_)))
+                || self.path.join(".gitignore").exists())
     }
 
     pub fn init(&self) -> io::Result<()> {
@@ -141,13 +370,15 @@ impl VenvStore {
             .create_new(true)
             .open(self.path.join(".gitignore"))
         {
-            Ok(mut file) => file.write_all(b"*"),
-            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(()),
-            Err(err) => Err(err),
+            Ok(mut file) => file.write_all(b"*")?,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
         }
+        migrate::write_initial_format(&self.path)
     }
 
     pub fn init_if_needed(&self) -> Result<()> {
+        self.ensure_writable()?;
         if !self.is_ready() {
             self.init().context("Failed to initialize venv store")?;
         }
@@ -158,18 +389,389 @@ impl VenvStore {
         &self.path
     }
 
+    pub fn is_read_only(&self) -> bool {
+        is_read_only_root(&self.path)
+    }
+
+    /// Fails if the store is read-only or of a newer format. Every change takes a lock of the store, which checks
+    /// this, so commands only call it themselves to fail before prompting.
+    pub fn ensure_writable(&self) -> Result<()> {
+        if !self.is_read_only() {
+            return migrate::ensure_known_format(&self.path);
+        }
+        Err(ErrorCode::StoreReadOnly.err_with_hint(
+            format!("The {self} is read-only, its environments cannot be changed"),
+            "fork an environment into a writable store with `meowda fork <name> --from <path>`",
+        ))
+    }
+
+    /// The lock files of the store, `.lock` and `.<name>.lock` of its environments.
+    pub fn lock_paths(&self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(&self.path) else {
+            return Vec::new();
+        };
+        let mut paths = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with('.') && name.ends_with(".lock"))
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    /// Checks whether the environment `name` exists, inside the store or registered.
+    /// Environments still being created, or whose creation was killed, do not count.
     pub fn exists(&self, name: &str) -> bool {
-        self.path.join(name).exists()
+        self.env_path(name).exists() && !self.is_being_created(name)
+    }
+
+    /// Marker in the store next to the environment `name` while it is being created.
+    pub fn creating_marker_path(&self, name: &str) -> PathBuf {
+        self.path.join(format!(".{name}.creating"))
+    }
+
+    pub fn is_being_created(&self, name: &str) -> bool {
+        self.creating_marker_path(name).exists()
+    }
+
+    /// Path of the environment `name`, the registered path for external environments.
+    pub fn env_path(&self, name: &str) -> PathBuf {
+        self.with_registry(|envs| envs.get(name).cloned())
+            .unwrap_or_else(|| self.path.join(name))
+    }
+
+    /// Runs `f` on the registered environments, loading the registry on first use.
+    fn with_registry<T>(&self, f: impl FnOnce(&BTreeMap<String, PathBuf>) -> T) -> T {
+        let mut registry = self.registry.lock().unwrap_or_else(PoisonError::into_inner);
+        let envs = registry.get_or_insert_with(|| match Registry::load(&self.path) {
+            Ok(registry) => registry.envs,
+            Err(e) => {
+                warn!("Ignoring the registered environments of the {self}: {e:#}");
+                BTreeMap::new()
+            }
+        });
+        f(envs)
+    }
+
+    /// External environments registered with `meowda register`, keyed by their names.
+    pub fn registered_envs(&self) -> BTreeMap<String, PathBuf> {
+        self.with_registry(Clone::clone)
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.with_registry(|envs| envs.contains_key(name))
     }
 
+    /// Registers the external environment at `path` as `name`, callers hold the store lock.
+    pub fn register(&self, name: &str, path: PathBuf) -> Result<()> {
+        self.update_registry(|envs| {
+            envs.insert(name.to_string(), path);
+        })
+    }
+
+    /// Forgets the registered environment `name`, leaving the environment itself untouched.
+    pub fn unregister(&self, name: &str) -> Result<()> {
+        self.update_registry(|envs| {
+            envs.remove(name);
+        })
+    }
+
+    /// Applies `update` to the registry freshly loaded under the store lock, saves it and
+    /// keeps it as the loaded registry.
+    fn update_registry(&self, update: impl FnOnce(&mut BTreeMap<String, PathBuf>)) -> Result<()> {
+        let mut registry = Registry::load(&self.path)?;
+        update(&mut registry.envs);
+        registry.save(&self.path)?;
+        *self.registry.lock().unwrap_or_else(PoisonError::into_inner) = Some(registry.envs);
+        Ok(())
+    }
+
+    /// Names of the environments inside the store and the registered ones, sorted.
+    pub fn env_names(&self) -> Vec<String> {
+        let mut names = self
+            .path
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            // Environment names never start with `.`, such directories belong to meowda
+            .filter(|name| !name.starts_with('.') && !self.is_being_created(name))
+            .chain(self.registered_envs().into_keys())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn scope(&self) -> Option<&VenvScope> {
+        self.scope.as_ref()
+    }
+
+    /// Checks whether `path` is inside the store, comparing path components rather
+    /// than strings so separators and symlinks do not matter on any platform.
+    ///
+    /// Environments linked into the store by `meowda adopt` or registered with
+    /// `meowda register` count as inside it.
     pub fn contains(&self, path: impl AsRef<Path>) -> Result<bool> {
-        Ok(path.as_ref().starts_with(self.path()))
+        let path = path.as_ref();
+        if path.starts_with(self.path()) {
+            return Ok(true);
+        }
+        let Ok(path) = path.canonicalize() else {
+            return Ok(false);
+        };
+        match self.path.canonicalize() {
+            Ok(store_path) if path.starts_with(&store_path) => Ok(true),
+            Ok(_) => Ok(self
+                .external_env_paths()
+                .any(|target| path.starts_with(target))),
+            Err(_) => Ok(false),
+        }
     }
 
+    /// Resolved paths of the environments linked into the store or registered.
+    fn external_env_paths(&self) -> impl Iterator<Item = PathBuf> {
+        self.path
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_symlink()))
+            .map(|entry| entry.path())
+            .chain(self.registered_envs().into_values())
+            .filter_map(|path| path.canonicalize().ok())
+    }
+
+    /// Acquires the lock of the whole store, guarding the registry. Operations on a single
+    /// environment only take [`Self::lock_env`], and reading the store takes no lock at all.
+    #[instrument(level = "debug", skip(self), fields(store = %self.path.display()))]
     pub async fn lock(&self) -> Result<FileLock> {
+        self.ensure_writable()?;
         let lock_path = self.path.join(".lock");
         FileLock::acquire(lock_path, "venv_store")
             .await
+            .map_err(lock_error)
             .context("Failed to acquire lock for VenvStore")
     }
+
+    /// Acquires the lock of a single environment, allowing operations on
+    /// different environments of the same store to run concurrently.
+    #[instrument(level = "debug", skip(self), fields(store = %self.path.display()))]
+    pub async fn lock_env(&self, name: &str) -> Result<FileLock> {
+        self.ensure_writable()?;
+        FileLock::acquire(self.env_lock_path(name), format!("venv `{name}`"))
+            .await
+            .map_err(lock_error)
+            .with_context(|| format!("Failed to acquire lock for virtual environment '{name}'"))
+    }
+
+    /// Like [`Self::lock_env`] without waiting, `None` while another process holds it.
+    pub fn try_lock_env(&self, name: &str) -> Result<Option<FileLock>> {
+        self.ensure_writable()?;
+        FileLock::try_acquire(self.env_lock_path(name), format!("venv `{name}`"))
+            .with_context(|| format!("Failed to acquire lock for virtual environment '{name}'"))
+    }
+
+    /// The lock file of the environment `name`.
+    pub fn env_lock_path(&self, name: &str) -> PathBuf {
+        self.path.join(format!(".{name}.lock"))
+    }
+}
+
+/// Tags lock waits which timed out with their error code.
+fn lock_error(err: io::Error) -> anyhow::Error {
+    if err.kind() == io::ErrorKind::TimedOut {
+        ErrorCode::LockTimeout.err(err.to_string())
+    } else {
+        err.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_ref_splits_scope_prefix() -> Result<()> {
+        let cases = [
+            (
+                "myenv",
+                ScopeType::Unspecified,
+                ScopeType::Unspecified,
+                "myenv",
+            ),
+            ("myenv", ScopeType::Local, ScopeType::Local, "myenv"),
+            (
+                "global:myenv",
+                ScopeType::Unspecified,
+                ScopeType::Global,
+                "myenv",
+            ),
+            (
+                "local:myenv",
+                ScopeType::Unspecified,
+                ScopeType::Local,
+                "myenv",
+            ),
+            ("local:myenv", ScopeType::Local, ScopeType::Local, "myenv"),
+            (
+                "other:myenv",
+                ScopeType::Global,
+                ScopeType::Global,
+                "other:myenv",
+            ),
+        ];
+        for (env_ref, scope_type, expected_scope, expected_name) in cases {
+            assert_eq!(
+                parse_env_ref(env_ref, scope_type)?,
+                (expected_scope, expected_name),
+                "env_ref: {env_ref}"
+            );
+        }
+        assert!(parse_env_ref("local:myenv", ScopeType::Global).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn contains_compares_path_components() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join(".meowda").join("venvs"));
+        store.init()?;
+        std::fs::create_dir_all(store.path().join("app"))?;
+
+        let cases = [
+            (store.path().join("app"), true),
+            (store.path().join("app").join("bin"), true),
+            (
+                temp.path().join(".meowda").join("venvs-other").join("app"),
+                false,
+            ),
+            (temp.path().join("app"), false),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(store.contains(&path)?, expected, "{}", path.display());
+        }
+        assert_eq!(store.scope(), None);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn contains_follows_linked_envs() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        store.init()?;
+        let external = temp.path().join("project").join(".venv");
+        std::fs::create_dir_all(external.join("bin"))?;
+        std::os::unix::fs::symlink(&external, store.path().join("project"))?;
+
+        assert!(store.contains(&external)?);
+        assert!(store.contains(external.join("bin"))?);
+        assert!(!store.contains(temp.path().join("project"))?);
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn contains_handles_windows_paths() -> Result<()> {
+        let store = VenvStore::from_path(PathBuf::from(r"C:\work\app\.meowda\venvs"));
+        let cases = [
+            (r"C:\work\app\.meowda\venvs\dev", true),
+            (r"C:/work/app/.meowda/venvs/dev", true),
+            (r"C:\work\app\.meowda\venvs-other\dev", false),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(store.contains(path)?, expected, "{path}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn registered_envs_resolve_outside_the_store() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        store.init()?;
+        std::fs::create_dir_all(store.path().join("local-env"))?;
+        let external = temp.path().join("disk").join("gpu-env");
+        std::fs::create_dir_all(external.join("bin"))?;
+        store.register("gpu-env", external.clone())?;
+
+        assert!(store.exists("gpu-env"));
+        assert!(store.is_registered("gpu-env"));
+        assert_eq!(store.env_path("gpu-env"), external);
+        assert_eq!(store.env_path("local-env"), store.path().join("local-env"));
+        assert_eq!(store.env_names(), ["gpu-env", "local-env"]);
+        assert!(store.contains(external.join("bin"))?);
+
+        store.unregister("gpu-env")?;
+        assert!(!store.exists("gpu-env"));
+        assert!(external.is_dir());
+        Ok(())
+    }
+
+    #[test]
+    fn from_specified_scope_records_scope() -> Result<()> {
+        for scope in [VenvScope::Local(0), VenvScope::Global] {
+            let store = VenvStore::from_specified_scope(scope.clone())?;
+            assert_eq!(store.scope(), Some(&scope));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn env_locks_are_independent() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().to_path_buf());
+        let _lock = store.lock().await?;
+        let _first = store.lock_env("first").await?;
+        let second =
+            tokio::time::timeout(std::time::Duration::from_secs(5), store.lock_env("second")).await;
+        assert!(second.is_ok_and(|lock| lock.is_ok()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_only_marker_refuses_changes() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().to_path_buf());
+        store.init_if_needed()?;
+        assert!(store.ensure_writable().is_ok());
+
+        std::fs::write(temp.path().join(READ_ONLY_MARKER), "")?;
+        assert!(store.is_read_only());
+        for result in [
+            store.ensure_writable(),
+            store.init_if_needed(),
+            store.lock().await.map(|_| ()),
+            store.lock_env("app").await.map(|_| ()),
+        ] {
+            let error = result.expect_err("read-only store must refuse changes");
+            assert_eq!(ErrorCode::of(&error), Some(ErrorCode::StoreReadOnly));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn discovery_stops_at_root_marker_or_git_root() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let project = temp.path();
+        assert!(!is_discovery_boundary(project, true));
+
+        std::fs::create_dir(project.join(".git"))?;
+        assert!(!is_discovery_boundary(project, false));
+        assert!(is_discovery_boundary(project, true));
+
+        std::fs::create_dir(project.join(".meowda"))?;
+        std::fs::write(project.join(ROOT_MARKER_PATH), "")?;
+        assert!(is_discovery_boundary(project, false));
+        Ok(())
+    }
 }
@@ -0,0 +1,132 @@
+/// Output style of the CLI, e.g., the screen-reader-friendly accessible mode.
+///
+/// The accessible mode avoids color-only signaling, spinners and symbols, using explicit
+/// text markers (e.g., `ACTIVE`, `ERROR`) and stable line-oriented output instead. The CI
+/// mode uses the same plain output, and additionally never prompts.
+///
+/// Colors follow one policy for the whole process, see [`set_color`]: everything printed
+/// through anstream is stripped accordingly, the rest asks [`use_color`].
+use crate::envs::EnvVars;
+use std::ffi::OsString;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+static CI: AtomicBool = AtomicBool::new(false);
+
+/// Switches to the accessible mode for this process, called once at startup.
+pub fn set_accessible(accessible: bool) {
+    ACCESSIBLE.store(accessible, Ordering::Relaxed);
+}
+
+pub fn is_accessible() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+/// Switches to the non-interactive CI mode for this process, called once at startup.
+pub fn set_ci(ci: bool) {
+    CI.store(ci, Ordering::Relaxed);
+}
+
+pub fn is_ci() -> bool {
+    CI.load(Ordering::Relaxed)
+}
+
+/// Whether to use plain output without colors, spinners and symbols.
+pub fn is_plain() -> bool {
+    is_accessible() || is_ci()
+}
+
+/// Prefix of error messages, an explicit `ERROR: ` marker in the accessible mode.
+pub fn error_prefix() -> &'static str {
+    if is_accessible() { "ERROR: " } else { "" }
+}
+
+/// The `--color` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorMode {
+    /// Colors on terminals, unless `NO_COLOR` is set, or forced with `CLICOLOR_FORCE`.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Decides the colors of the process: `--color always|never` wins, otherwise the plain
+/// modes and `NO_COLOR` disable them, `CLICOLOR_FORCE` forces them, and else each stream
+/// is colored if it is a terminal.
+fn resolve_color(
+    mode: ColorMode,
+    plain: bool,
+    no_color: Option<OsString>,
+    clicolor_force: Option<OsString>,
+) -> anstream::ColorChoice {
+    let is_set = |value: Option<OsString>| value.is_some_and(|value| !value.is_empty());
+    match mode {
+        ColorMode::Always => anstream::ColorChoice::Always,
+        ColorMode::Never => anstream::ColorChoice::Never,
+        ColorMode::Auto if plain || is_set(no_color) => anstream::ColorChoice::Never,
+        ColorMode::Auto if is_set(clicolor_force.filter(|value| value != "0")) => {
+            anstream::ColorChoice::Always
+        }
+        ColorMode::Auto => anstream::ColorChoice::Auto,
+    }
+}
+
+/// Applies the color policy for this process, called once at startup after the output
+/// style and format are known. `plain` is set for outputs parsed by scripts, e.g., JSON.
+pub fn set_color(mode: ColorMode, plain: bool) {
+    resolve_color(
+        mode,
+        plain || is_plain(),
+        std::env::var_os(EnvVars::NO_COLOR),
+        std::env::var_os(EnvVars::CLICOLOR_FORCE),
+    )
+    .write_global();
+}
+
+/// Whether to color output not printed through anstream, e.g., spinners and logs, written
+/// to a stream which `is_terminal`.
+pub fn use_color(is_terminal: bool) -> bool {
+    match anstream::ColorChoice::global() {
+        anstream::ColorChoice::Always | anstream::ColorChoice::AlwaysAnsi => true,
+        anstream::ColorChoice::Never => false,
+        anstream::ColorChoice::Auto => is_terminal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_flag_wins_over_environment() {
+        use anstream::ColorChoice;
+
+        let set = |value: &str| Some(OsString::from(value));
+        let cases = [
+            (ColorMode::Auto, false, None, None, ColorChoice::Auto),
+            (ColorMode::Auto, true, None, None, ColorChoice::Never),
+            (ColorMode::Auto, false, set("1"), None, ColorChoice::Never),
+            (ColorMode::Auto, false, set(""), None, ColorChoice::Auto),
+            (ColorMode::Auto, false, None, set("1"), ColorChoice::Always),
+            (ColorMode::Auto, false, None, set("0"), ColorChoice::Auto),
+            (
+                ColorMode::Auto,
+                false,
+                set("1"),
+                set("1"),
+                ColorChoice::Never,
+            ),
+            (ColorMode::Always, true, set("1"), None, ColorChoice::Always),
+            (ColorMode::Never, false, None, set("1"), ColorChoice::Never),
+        ];
+        for (mode, plain, no_color, clicolor_force, expected) in cases {
+            let case = format!("{mode:?} plain={plain} {no_color:?} {clicolor_force:?}");
+            assert_eq!(
+                resolve_color(mode, plain, no_color, clicolor_force),
+                expected,
+                "{case}"
+            );
+        }
+    }
+}
@@ -1,33 +1,93 @@
-use anyhow::{Context, Result};
+use crate::backends::{Backend, OutputMode, ProcessOptions, VenvOptions, is_offline, run_command};
+use crate::error::ErrorCode;
+use anyhow::Result;
 use std::path::Path;
-use std::process::Command;
+use tracing::debug;
 
-pub(super) fn create_uv_venv(
-    uv_path: &str,
-    venv_path: &Path,
-    python: &str,
-    seed: bool,
-    include_system_site_packages: bool,
-) -> Result<()> {
-    let venv_path_str = venv_path
-        .to_str()
-        .ok_or_else(|| anyhow::anyhow!("Invalid path for virtual environment"))?;
-
-    let mut command = Command::new(uv_path);
-    command.args(["venv", venv_path_str, "--python", python]);
-    if seed {
-        command.arg("--seed");
+/// Explains which downloads creating an environment with uv may have attempted, the
+/// likely cause of a failure in offline mode. `None` if nothing had to be downloaded.
+fn offline_failure(backend: &str, options: &VenvOptions<'_>) -> Option<String> {
+    if backend != "uv" {
+        return None;
     }
-    if include_system_site_packages {
-        command.arg("--system-site-packages");
+    // An interpreter given by path is used as is, a version request may need a download
+    let toolchain = !Path::new(options.python).is_absolute();
+    let downloads = match (toolchain, options.seed) {
+        (true, true) => format!(
+            "the Python {} toolchain and the seed packages (pip)",
+            options.python
+        ),
+        (true, false) => format!("the Python {} toolchain", options.python),
+        (false, true) => "the seed packages (pip)".to_string(),
+        (false, false) => return None,
+    };
+    let mut message = format!(
+        "Failed to create virtual environment in offline mode, it may have needed to download {downloads}, which only works if they are already cached"
+    );
+    if toolchain {
+        message.push_str(&format!(
+            ".\nInstall the toolchain beforehand with `uv python install {}`, or run without --offline",
+            options.python
+        ));
     }
+    Some(message)
+}
 
-    let status = command.status().context("Failed to execute uv command")?;
-    if !status.success() {
-        anyhow::bail!(
-            "Failed to create virtual environment. Check Python version/source environment and try again"
-        );
+pub(super) async fn create_venv(
+    backend: &dyn Backend,
+    venv_path: &Path,
+    options: &VenvOptions<'_>,
+    process: ProcessOptions,
+) -> Result<()> {
+    let command = backend.venv_command(venv_path, options)?;
+    debug!(
+        "Creating virtual environment with {}: {command:?}",
+        backend.name()
+    );
+    let output = run_command(command, process).await?;
+    if !output.status.success() {
+        // Other modes have already printed the output of the failed command
+        let stderr = output.stderr.trim();
+        if let Some(message) = is_offline()
+            .then(|| offline_failure(backend.name(), options))
+            .flatten()
+        {
+            if process.output == OutputMode::Capture && !stderr.is_empty() {
+                return Err(ErrorCode::BackendFailed.err(format!("{message}\n{stderr}")));
+            }
+            return Err(ErrorCode::BackendFailed.err(message));
+        }
+        if process.output == OutputMode::Capture && !stderr.is_empty() {
+            return Err(ErrorCode::BackendFailed
+                .err(format!("Failed to create virtual environment: {stderr}")));
+        }
+        return Err(ErrorCode::BackendFailed.err(
+            "Failed to create virtual environment. Check Python version/source environment and try again",
+        ));
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_failure_names_the_downloads() {
+        let options = |python, seed| VenvOptions {
+            python,
+            seed,
+            include_system_site_packages: false,
+            arch: None,
+            prompt: None,
+        };
+        let message = offline_failure("uv", &options("3.12", true)).unwrap_or_default();
+        assert!(message.contains("the Python 3.12 toolchain and the seed packages (pip)"));
+        assert!(message.contains("uv python install 3.12"));
+        let message = offline_failure("uv", &options("/usr/bin/python3", true)).unwrap_or_default();
+        assert!(message.contains("download the seed packages (pip),"));
+        assert!(offline_failure("uv", &options("/usr/bin/python3", false)).is_none());
+        assert!(offline_failure("venv", &options("3.12", true)).is_none());
+    }
+}
@@ -1,5 +1,9 @@
-use super::{EnvConfig, VenvService, create::create_uv_venv};
-use crate::store::venv_store::{ScopeType, VenvStore, get_candidate_scopes};
+use super::{EnvConfig, VenvService, create::create_venv, python_path_in_venv};
+use crate::backends::{Backend, ProcessOptions, VenvOptions, run_command};
+use crate::store::backend::Discovery;
+use crate::store::venv_store::{
+    ScopeType, VenvStore, get_candidate_scopes, parse_env_ref, resolve_named_ref,
+};
 use anyhow::{Context, Result};
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -55,35 +59,48 @@ enum ForkSource {
     Python(String),
 }
 
-pub(super) fn resolve_current_source() -> Result<PythonEnvLayout> {
-    inspect_resolved_source(resolve_current_source_spec()?)
+pub(super) async fn resolve_current_source(process: ProcessOptions) -> Result<PythonEnvLayout> {
+    inspect_resolved_source(resolve_current_source_spec()?, process).await
 }
 
-pub(super) fn resolve_named_source(source: &str, scope_type: ScopeType) -> Result<PythonEnvLayout> {
-    inspect_resolved_source(resolve_source_spec(source, scope_type)?).with_context(|| {
-        format!(
-            "Failed to resolve fork source '{source}' in the selected scope or as a Python executable"
-        )
-    })
+pub(super) async fn resolve_named_source(
+    source: &str,
+    scope_type: ScopeType,
+    process: ProcessOptions,
+) -> Result<PythonEnvLayout> {
+    inspect_resolved_source(resolve_source_spec(source, scope_type)?, process)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to resolve fork source '{source}' in the selected scope or as a Python executable"
+            )
+        })
 }
 
-pub(super) fn create_with_source(
-    uv_path: &str,
+pub(super) async fn create_with_source(
+    backend: &dyn Backend,
     source: &PythonEnvLayout,
     target_path: &Path,
+    process: ProcessOptions,
 ) -> Result<()> {
     let target_path = ensure_distinct_source_target(source, target_path)?;
 
-    create_uv_venv(
-        uv_path,
+    create_venv(
+        backend,
         &target_path,
-        source.base_python.to_string_lossy().as_ref(),
-        false,
-        source.include_system_site_packages,
-    )?;
+        &VenvOptions {
+            python: source.base_python.to_string_lossy().as_ref(),
+            seed: false,
+            include_system_site_packages: source.include_system_site_packages,
+            arch: None,
+            prompt: None,
+        },
+        process,
+    )
+    .await?;
 
     let target_python = python_path_in_venv(&target_path);
-    let mut target = inspect_python_env(target_python.to_string_lossy().as_ref())?;
+    let mut target = inspect_python_env(target_python.to_string_lossy().as_ref(), process).await?;
     target.requested_prefix = Some(target_path.clone());
     let rewrite = build_rewrite_paths(source, &target);
     copy_site_packages(source, &target, &rewrite)?;
@@ -114,18 +131,6 @@ fn normalize_path(path: impl AsRef<Path>) -> Result<PathBuf> {
     }
 }
 
-fn python_path_in_venv(venv_path: &Path) -> PathBuf {
-    #[cfg(windows)]
-    {
-        return venv_path.join("Scripts").join("python.exe");
-    }
-
-    #[cfg(not(windows))]
-    {
-        venv_path.join("bin").join("python")
-    }
-}
-
 fn is_text_file(path: &Path) -> Result<bool> {
     let bytes =
         fs::read(path).with_context(|| format!("Failed to read file '{}'", path.display()))?;
@@ -404,21 +409,19 @@ fn parse_python_layout(output: &str) -> Result<PythonEnvLayout> {
     })
 }
 
-fn inspect_python_env(python: &str) -> Result<PythonEnvLayout> {
-    let output = Command::new(python)
-        .args(["-c", PYTHON_INFO_SCRIPT])
-        .output()
-        .with_context(|| format!("Failed to inspect Python environment with '{python}'"))?;
+async fn inspect_python_env(python: &str, process: ProcessOptions) -> Result<PythonEnvLayout> {
+    let mut command = Command::new(python);
+    command.args(["-c", PYTHON_INFO_SCRIPT]);
+    let output = run_command(command, process.capture()).await?;
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
         anyhow::bail!(
             "Failed to inspect Python environment with '{}': {}",
             python,
-            stderr.trim()
+            output.stderr.trim()
         );
     }
 
-    parse_python_layout(String::from_utf8_lossy(&output.stdout).as_ref())
+    parse_python_layout(&output.stdout)
 }
 
 fn find_python_in_directory(dir: &Path) -> Result<Option<PathBuf>> {
@@ -470,11 +473,16 @@ fn find_python_in_directory(dir: &Path) -> Result<Option<PathBuf>> {
     }
 }
 
-fn resolve_managed_env(name: &str, scope_type: ScopeType) -> Result<Option<PathBuf>> {
+fn resolve_managed_env(env_ref: &str, scope_type: ScopeType) -> Result<Option<PathBuf>> {
+    if let Some((scope, name)) = resolve_named_ref(&Discovery::current(), env_ref) {
+        let store = VenvStore::from_specified_scope(scope)?;
+        return Ok((store.is_ready() && store.exists(name)).then(|| store.env_path(name)));
+    }
+    let (scope_type, name) = parse_env_ref(env_ref, scope_type)?;
     for scope in get_candidate_scopes(scope_type)? {
         let store = VenvStore::from_specified_scope(scope)?;
         if store.is_ready() && store.exists(name) {
-            return Ok(Some(store.path().join(name)));
+            return Ok(Some(store.env_path(name)));
         }
     }
     Ok(None)
@@ -533,7 +541,10 @@ fn resolve_source_spec(source: &str, scope_type: ScopeType) -> Result<ForkSource
     Ok(ForkSource::Python(source.to_string()))
 }
 
-fn inspect_resolved_source(source: ForkSource) -> Result<PythonEnvLayout> {
+async fn inspect_resolved_source(
+    source: ForkSource,
+    process: ProcessOptions,
+) -> Result<PythonEnvLayout> {
     match source {
         ForkSource::Directory(path) => {
             if path.join("pyvenv.cfg").exists() {
@@ -543,13 +554,13 @@ fn inspect_resolved_source(source: ForkSource) -> Result<PythonEnvLayout> {
                         path.display()
                     )
                 })?;
-                let mut layout = inspect_python_env(&python.to_string_lossy())?;
+                let mut layout = inspect_python_env(&python.to_string_lossy(), process).await?;
                 layout.requested_prefix = Some(path);
                 return Ok(layout);
             }
 
             if let Some(python) = find_python_in_directory(&path)? {
-                let mut layout = inspect_python_env(&python.to_string_lossy())?;
+                let mut layout = inspect_python_env(&python.to_string_lossy(), process).await?;
                 layout.requested_prefix = Some(path);
                 return Ok(layout);
             }
@@ -560,7 +571,7 @@ fn inspect_resolved_source(source: ForkSource) -> Result<PythonEnvLayout> {
             )
         }
         ForkSource::Python(python) => {
-            let mut layout = inspect_python_env(&python)?;
+            let mut layout = inspect_python_env(&python, process).await?;
             layout.requested_prefix = env_root_from_python_path(Path::new(&python));
             Ok(layout)
         }
@@ -0,0 +1,244 @@
+use super::spec::normalize_name;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::debug;
+
+pub const METADATA_FILE_NAME: &str = "meowda-env.toml";
+
+/// Requirements file recording the dependency spec of an environment.
+pub const SPEC_FILE_NAME: &str = "requirements.meowda.txt";
+
+/// Marker file inside each environment, touched whenever it is activated or packages are
+/// installed into or uninstalled from it.
+///
+/// It is kept out of [`EnvMetadata`], so activating never races with a concurrent
+/// `install` rewriting the metadata.
+pub const LAST_USED_FILE_NAME: &str = ".meowda-last-used";
+
+/// Records that the environment at `env_path` is being used, failures are only logged.
+pub fn touch_last_used(env_path: &Path) {
+    if let Err(e) = std::fs::write(env_path.join(LAST_USED_FILE_NAME), "") {
+        debug!("Failed to record the usage of {}: {e}", env_path.display());
+    }
+}
+
+/// Seconds since the Unix epoch the environment at `env_path` was last activated, if ever.
+pub fn last_used(env_path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(env_path.join(LAST_USED_FILE_NAME))
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    modified
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs())
+}
+
+/// Seconds since the Unix epoch the environment at `env_path` was created at, i.e., when
+/// its `pyvenv.cfg` was written.
+pub fn created_at(env_path: &Path) -> Option<u64> {
+    std::fs::metadata(env_path.join("pyvenv.cfg"))
+        .and_then(|metadata| metadata.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs())
+}
+
+/// Total size of the files below `path`, symlinks are not followed.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some(if metadata.is_dir() {
+                dir_size(&entry.path())
+            } else {
+                metadata.len()
+            })
+        })
+        .sum()
+}
+
+/// The installed distributions of the environment at `venv_path`, their normalized names
+/// mapped to their versions, see [`dist_infos`].
+pub fn installed_packages(venv_path: &Path) -> BTreeMap<String, String> {
+    dist_infos(venv_path)
+        .into_iter()
+        .map(|(name, version, _)| (name, version))
+        .collect()
+}
+
+/// The normalized name, version and `*.dist-info` directory of each distribution installed
+/// in the site-packages directories of both the POSIX and the Windows layout.
+pub(crate) fn dist_infos(venv_path: &Path) -> Vec<(String, String, PathBuf)> {
+    let mut site_packages = vec![venv_path.join("Lib").join("site-packages")];
+    for lib in ["lib", "lib64"] {
+        let Ok(entries) = std::fs::read_dir(venv_path.join(lib)) else {
+            continue;
+        };
+        site_packages.extend(
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with("python"))
+                .map(|entry| entry.path().join("site-packages")),
+        );
+    }
+    // `lib64` is usually a link to `lib`
+    let mut site_packages = site_packages
+        .into_iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect::<Vec<_>>();
+    site_packages.sort();
+    site_packages.dedup();
+    site_packages
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            // Both parts are escaped, so the first `-` separates them
+            let (name, version) = file_name.strip_suffix(".dist-info")?.split_once('-')?;
+            Some((normalize_name(name), version.to_string(), entry.path()))
+        })
+        .collect()
+}
+
+/// Meowda-specific metadata stored inside each environment, next to `pyvenv.cfg`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvMetadata {
+    /// Frozen environments refuse package changes until thawed.
+    pub frozen: bool,
+    /// Set while the spec of a new environment is being installed, an interrupted
+    /// installation is resumed by running the same command again.
+    pub installing: bool,
+    /// Architecture of the interpreter, e.g., `arm64` or `x86_64`, recorded at creation.
+    pub arch: Option<String>,
+    /// Original path of an environment adopted with `meowda adopt`.
+    pub adopted_from: Option<String>,
+    /// Summary of the declaration applied by the last `meowda sync-all`, an unchanged
+    /// declaration is skipped.
+    pub sync_fingerprint: Option<String>,
+    /// Package index of the environment, e.g., a private registry, used by every install.
+    pub index_url: Option<String>,
+    /// Extra package indexes of the environment, used by every install.
+    pub extra_index_urls: Vec<String>,
+    /// Free-form labels of `meowda tag`, e.g., `gpu`, sorted.
+    pub tags: Vec<String>,
+    /// The base interpreter the environment was created from, recorded at creation.
+    pub interpreter: Option<Interpreter>,
+}
+
+/// The exact interpreter an environment was created from, the backend may have picked any
+/// matching the requested version.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Interpreter {
+    /// Python implementation, e.g., `CPython`.
+    pub implementation: String,
+    /// Full version, e.g., `3.12.4`.
+    pub version: String,
+    /// Path of the interpreter, outside of the environment.
+    pub path: String,
+    /// Where the interpreter was found if not by the backend, e.g., `pyenv`.
+    pub source: Option<String>,
+}
+
+impl EnvMetadata {
+    /// Loads the metadata of the environment at `env_path`, falling back to
+    /// defaults for environments created before metadata existed.
+    pub fn load(env_path: &Path) -> Result<Self> {
+        let path = env_path.join(METADATA_FILE_NAME);
+        if !path.exists() {
+            return Ok(EnvMetadata::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read metadata file '{}'", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse metadata file '{}'", path.display()))
+    }
+
+    /// Index arguments of pip/uv pip installs into the environment.
+    pub fn index_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(index_url) = &self.index_url {
+            args.extend(["--index-url".to_string(), index_url.clone()]);
+        }
+        for url in &self.extra_index_urls {
+            args.extend(["--extra-index-url".to_string(), url.clone()]);
+        }
+        args
+    }
+
+    /// Adds `tags`, keeping the tags sorted and unique.
+    pub fn add_tags(&mut self, tags: &[String]) {
+        self.tags.extend(tags.iter().cloned());
+        self.tags.sort();
+        self.tags.dedup();
+    }
+
+    pub fn remove_tags(&mut self, tags: &[String]) {
+        self.tags.retain(|tag| !tags.contains(tag));
+    }
+
+    pub fn save(&self, env_path: &Path) -> Result<()> {
+        let path = env_path.join(METADATA_FILE_NAME);
+        let content = toml::to_string(self).context("Failed to serialize metadata")?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write metadata file '{}'", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_args_follow_the_recorded_indexes() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        assert!(EnvMetadata::load(temp.path())?.index_args().is_empty());
+
+        let metadata = EnvMetadata {
+            index_url: Some("https://pypi.example.com/simple".to_string()),
+            extra_index_urls: vec!["https://extra.example.com/simple".to_string()],
+            ..Default::default()
+        };
+        metadata.save(temp.path())?;
+        assert_eq!(
+            EnvMetadata::load(temp.path())?.index_args(),
+            [
+                "--index-url",
+                "https://pypi.example.com/simple",
+                "--extra-index-url",
+                "https://extra.example.com/simple"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tags_stay_sorted_and_unique() {
+        let mut metadata = EnvMetadata::default();
+        metadata.add_tags(&["ml".to_string(), "gpu".to_string()]);
+        metadata.add_tags(&["gpu".to_string(), "cuda".to_string()]);
+        assert_eq!(metadata.tags, ["cuda", "gpu", "ml"]);
+        metadata.remove_tags(&["gpu".to_string(), "unknown".to_string()]);
+        assert_eq!(metadata.tags, ["cuda", "ml"]);
+    }
+
+    #[test]
+    fn last_used_follows_activations() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        assert_eq!(last_used(temp.path()), None);
+        touch_last_used(temp.path());
+        assert!(last_used(temp.path()).is_some_and(|secs| secs > 0));
+        Ok(())
+    }
+}
@@ -1,17 +1,56 @@
 mod create;
 mod fork;
+mod metadata;
+mod pack;
+mod pipenv;
+mod plan;
+mod pylock;
+mod rebuild;
+mod script;
+mod shim;
+mod spec;
+mod tools;
+mod version;
 
+use crate::backends::{
+    Arch, Backend, OutputMode, PYENV_PREFIX, ProcessOptions, VenvOptions, current_shell_arch,
+    is_offline, requested_implementation, resolve_pyenv_python, run_command, select_backend,
+};
+use crate::config::Config;
+use crate::error::ErrorCode;
+use crate::interrupt;
+use crate::store::events::{self, EventKind};
+use crate::store::history::{self, Operation};
+use crate::store::index;
+use crate::store::trash;
 use crate::store::venv_store::{ScopeType, VenvScope, VenvStore, get_candidate_scopes};
 use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use tracing::info;
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
-use self::create::create_uv_venv;
+use self::create::create_venv;
 use self::fork::{
     create_with_source, ensure_distinct_source_target, resolve_current_source, resolve_named_source,
 };
+use self::metadata::Interpreter;
+pub use self::metadata::{
+    EnvMetadata, METADATA_FILE_NAME, SPEC_FILE_NAME, created_at, dir_size, installed_packages,
+    last_used, touch_last_used,
+};
+pub use self::pack::PackManifest;
+pub use self::pipenv::{ImportedSpec, parse_pipfile_lock};
+pub use self::plan::PlannedOperation;
+pub use self::pylock::{PinnedRequirement, parse_pylock, pinned_requirements};
+pub use self::rebuild::RebuildSource;
+pub use self::script::{ScriptMetadata, scripts_store};
+pub use self::shim::{add_shim, list_shims, remove_shim, shim_bin_dir, shim_target_exists};
+pub use self::spec::{Requirement, normalize_name, parse_requirement};
+pub use self::tools::{TOOLS_ENV_NAME, check_not_reserved, list_tools, tools_bin_dir};
+pub use self::version::minor_version;
 
 #[derive(Debug, Clone)]
 pub struct EnvInfo {
@@ -21,20 +60,65 @@ pub struct EnvInfo {
     pub config: Option<EnvConfig>,
 }
 
+/// Tells whether environments are the active one, comparing symlink-resolved paths. The
+/// active environment is canonicalized once and the parent directories of the environments,
+/// e.g., their store, once each, so an environment costs at most a single `lstat` unless it
+/// is a symlink itself, and nothing when no environment is active.
+#[derive(Debug, Default, Clone)]
+pub struct ActiveEnvMatcher {
+    current: Option<PathBuf>,
+    canonical_parents: HashMap<PathBuf, Option<PathBuf>>,
+}
+
+impl ActiveEnvMatcher {
+    pub fn new(current_venv: Option<&PathBuf>) -> Self {
+        ActiveEnvMatcher {
+            current: current_venv.and_then(|current| current.canonicalize().ok()),
+            canonical_parents: HashMap::new(),
+        }
+    }
+
+    pub fn matches(&mut self, env_path: &Path) -> bool {
+        let Some(current) = &self.current else {
+            return false;
+        };
+        let is_symlink = std::fs::symlink_metadata(env_path)
+            .is_ok_and(|metadata| metadata.file_type().is_symlink());
+        if is_symlink {
+            return env_path.canonicalize().is_ok_and(|path| &path == current);
+        }
+        // The directory itself is not a symlink, its name must be the one of the active one
+        let (Some(parent), Some(name)) = (env_path.parent(), env_path.file_name()) else {
+            return false;
+        };
+        if current.file_name() != Some(name) {
+            return false;
+        }
+        self.canonical_parents
+            .entry(parent.to_path_buf())
+            .or_insert_with(|| parent.canonicalize().ok())
+            .as_ref()
+            .is_some_and(|parent| parent.join(name) == *current)
+    }
+}
+
 /// Parsed `pyvenv.cfg`, refer to uv `PyVenvConfiguration`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvConfig {
     #[allow(dead_code)]
     pub virtualenv: bool,
     #[allow(dead_code)]
     pub uv: bool,
-    #[allow(dead_code)]
     pub relocatable: bool,
     #[allow(dead_code)]
     pub seed: bool,
     #[allow(dead_code)]
     pub include_system_site_packages: bool,
     pub version: Option<String>,
+    /// Python implementation, e.g., `CPython`, only recorded by uv.
+    pub implementation: Option<String>,
+    /// Prompt prefix given at creation, e.g., by `--prompt`.
+    pub prompt: Option<String>,
 }
 
 impl EnvConfig {
@@ -45,6 +129,8 @@ impl EnvConfig {
         let mut seed = false;
         let mut include_system_site_packages = true;
         let mut version = None;
+        let mut implementation = None;
+        let mut prompt = None;
 
         let cfg_path = cfg.as_ref();
         if !cfg_path.exists() {
@@ -76,6 +162,14 @@ impl EnvConfig {
                 "version" | "version_info" => {
                     version = Some(value.to_string());
                 }
+                "implementation" => implementation = Some(value.to_string()),
+                // The `venv` module writes the prompt as a Python string literal
+                "prompt" => {
+                    let unquoted = ['\'', '"']
+                        .into_iter()
+                        .find_map(|quote| value.strip_prefix(quote)?.strip_suffix(quote));
+                    prompt = Some(unquoted.unwrap_or(value).to_string());
+                }
                 _ => continue,
             }
         }
@@ -87,17 +181,213 @@ impl EnvConfig {
             seed,
             include_system_site_packages,
             version,
+            implementation,
+            prompt,
         })
     }
 }
 
+/// Prints the architecture, implementation, full version and base interpreter of an
+/// environment, one per line. The interpreter of an environment links to the base one on
+/// POSIX, `sys._base_executable` is only reliable since 3.11 there.
+const INSPECT_INTERPRETER_SCRIPT: &str = r#"import os, platform, sys
+base = getattr(sys, "_base_executable", sys.executable)
+if os.path.normcase(base) == os.path.normcase(sys.executable):
+    base = os.path.realpath(sys.executable)
+print(platform.machine())
+print(platform.python_implementation())
+print(platform.python_version())
+print(base)"#;
+
+/// Warns if the interpreter of the environment at `venv_path` does not match the
+/// architecture of the current shell, e.g., an x86_64 environment in a native
+/// arm64 shell on Apple Silicon, which mixes architectures of native extensions.
+pub fn warn_on_arch_mismatch(venv_path: &Path) {
+    let Some(shell_arch) = current_shell_arch() else {
+        return;
+    };
+    let env_arch = EnvMetadata::load(venv_path)
+        .ok()
+        .and_then(|metadata| metadata.arch)
+        .and_then(|arch| Arch::from_machine(&arch));
+    if let Some(env_arch) = env_arch
+        && env_arch != shell_arch
+    {
+        warn!(
+            "Virtual environment {} uses a {} interpreter, but the current shell runs as {}. Packages with native extensions may fail to load, recreate it with `meowda create --clear --arch {}`",
+            venv_path.display(),
+            env_arch.name(),
+            shell_arch.name(),
+            shell_arch.name()
+        );
+    }
+}
+
+/// Warns if the interpreter the environment at `venv_path` was created from is gone, e.g.,
+/// uninstalled by a system upgrade, which breaks the environment.
+pub fn warn_on_missing_interpreter(venv_path: &Path) {
+    let Some(interpreter) = EnvMetadata::load(venv_path)
+        .ok()
+        .and_then(|metadata| metadata.interpreter)
+    else {
+        return;
+    };
+    if !Path::new(&interpreter.path).exists() {
+        let name = venv_name(venv_path).unwrap_or_default();
+        warn!(
+            "The {} {} interpreter {} of '{name}' no longer exists, recreate it with `meowda rebuild {name}` once Python {} is available again",
+            interpreter.implementation, interpreter.version, interpreter.path, interpreter.version
+        );
+    }
+}
+
+/// Copies the metadata and the recorded spec of an environment, which a fork leaves out.
+fn copy_meowda_files(source_path: &Path, target_path: &Path) -> Result<()> {
+    for file_name in [METADATA_FILE_NAME, SPEC_FILE_NAME] {
+        let source = source_path.join(file_name);
+        if source.is_file() {
+            std::fs::copy(&source, target_path.join(file_name))
+                .with_context(|| format!("Failed to copy '{}'", source.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path of the Python executable inside a virtual environment.
+pub(crate) fn python_path_in_venv(venv_path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        return bin_dir_in_venv(venv_path).join("python.exe");
+    }
+
+    #[cfg(not(windows))]
+    {
+        bin_dir_in_venv(venv_path).join("python")
+    }
+}
+
+/// The error of an installation which failed in offline mode, `packages` names what had
+/// to be downloaded.
+fn offline_install_failure(packages: &str) -> anyhow::Error {
+    ErrorCode::BackendFailed.err_with_hint(
+        format!(
+            "Failed to install {packages} in offline mode, only packages already in the cache or local wheels passed with --find-links can be installed."
+        ),
+        "run without --offline to download them",
+    )
+}
+
+/// Name of the environment at `venv_path`, which also names its lock in the store.
+fn venv_name(venv_path: &Path) -> Result<&str> {
+    venv_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid virtual environment name"))
+}
+
+/// Returns the directory of the executables inside a virtual environment, i.e., `bin`
+/// or `Scripts` on Windows.
+pub(crate) fn bin_dir_in_venv(venv_path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        return venv_path.join("Scripts");
+    }
+
+    #[cfg(not(windows))]
+    {
+        venv_path.join("bin")
+    }
+}
+
 pub struct VenvService {
-    uv_path: String,
+    backend: Box<dyn Backend>,
+    process: ProcessOptions,
+    /// Days removed environments are kept in the trash, deleted right away if 0.
+    trash_days: u64,
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct CreateOptions<'a> {
     pub python: Option<&'a str>,
     pub clear: bool,
+    pub arch: Option<Arch>,
+    /// Install seed packages, e.g., pip.
+    pub seed: bool,
+    /// Package index recorded in the environment, used by all its installs.
+    pub index_url: Option<&'a str>,
+    /// Extra package indexes recorded in the environment.
+    pub extra_index_urls: &'a [String],
+    /// Give the environment access to the packages of the base interpreter.
+    pub system_site_packages: bool,
+    /// Prompt prefix of the activated environment instead of its name.
+    pub prompt: Option<&'a str>,
+}
+
+impl CreateOptions<'_> {
+    /// The Python request passed to the backend and where its interpreter was found, a
+    /// pyenv interpreter replaces the request if available, see [`resolve_pyenv_python`].
+    fn resolve_python(&self) -> Result<(String, Option<&'static str>)> {
+        let python = self.python.unwrap_or("3.14");
+        // An architecture selects an interpreter managed by uv
+        if self.arch.is_some() && !python.starts_with(PYENV_PREFIX) {
+            return Ok((python.to_string(), None));
+        }
+        Ok(match resolve_pyenv_python(python)? {
+            Some(path) => (path.display().to_string(), Some("pyenv")),
+            None => (python.to_string(), None),
+        })
+    }
+
+    fn venv_options<'a>(&'a self, python: &'a str) -> VenvOptions<'a> {
+        VenvOptions {
+            python,
+            seed: self.seed,
+            include_system_site_packages: self.system_site_packages,
+            arch: self.arch,
+            prompt: self.prompt,
+        }
+    }
+
+    /// The metadata recorded in a new environment, e.g., its package indexes.
+    fn metadata(&self) -> EnvMetadata {
+        EnvMetadata {
+            index_url: self.index_url.map(str::to_string),
+            extra_index_urls: self.extra_index_urls.to_vec(),
+            ..EnvMetadata::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PackageOptions {
+    /// Mark the environment as frozen after the operation succeeds.
+    pub freeze: bool,
+    /// Allow changing a frozen environment, which is thawed afterwards.
+    pub thaw: bool,
+}
+
+/// What `VenvService::sync` did to an environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncOutcome {
+    Created,
+    /// Recreated as it used another Python version than declared.
+    Recreated,
+    Updated,
+    UpToDate,
+}
+
+/// Checks whether an environment with Python `actual`, from `pyvenv.cfg`, satisfies the
+/// requested `python`, e.g., `3.12` is satisfied by `3.12.4`.
+///
+/// Requests which are not plain versions, e.g., paths, cannot be checked and always match.
+pub fn python_matches(python: Option<&str>, actual: Option<&str>) -> bool {
+    let (Some(python), Some(actual)) = (python, actual) else {
+        return true;
+    };
+    if python.is_empty() || !python.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return true;
+    }
+    actual == python || actual.starts_with(&format!("{python}."))
 }
 
 pub struct ForkOptions<'a> {
@@ -107,41 +397,141 @@ pub struct ForkOptions<'a> {
 }
 
 impl VenvService {
-    pub fn new() -> Result<Self> {
-        let uv_path = "uv";
-        if !Self::check_uv_available(uv_path) {
-            anyhow::bail!(
-                "uv is not available, please install it first.\nSee https://docs.astral.sh/uv/getting-started/installation/ for installation instructions"
-            );
-        }
-
+    pub fn new(config: &Config, output: OutputMode) -> Result<Self> {
         Ok(VenvService {
-            uv_path: uv_path.to_string(),
+            backend: select_backend(config)?,
+            process: ProcessOptions {
+                output,
+                timeout: config.command_timeout.map(Duration::from_secs),
+            },
+            trash_days: config.trash_days.unwrap_or(trash::DEFAULT_TRASH_DAYS),
         })
     }
 
-    fn check_uv_available(uv_path: &str) -> bool {
-        Command::new(uv_path)
-            .arg("--version")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+    /// Whether removed environments are moved into the trash, to be restored by
+    /// `meowda restore`.
+    pub fn uses_trash(&self) -> bool {
+        self.trash_days > 0
     }
 
+    /// Removes the environment `name`, only forgetting it if it is registered.
     fn remove_venv(store: &VenvStore, name: &str) -> Result<()> {
+        if store.is_registered(name) {
+            return store.unregister(name);
+        }
         std::fs::remove_dir_all(store.path().join(name))
             .context("Failed to remove virtual environment")?;
         Ok(())
     }
 
-    pub(super) fn detect_current_venv() -> Option<PathBuf> {
+    /// Checks that the environment `name` can be created, returning whether an existing one
+    /// has to be removed first, which `clear` allows.
+    fn ensure_creatable(store: &VenvStore, name: &str, clear: bool) -> Result<bool> {
+        if !store.exists(name) {
+            return Ok(false);
+        }
+        if !clear {
+            return Err(ErrorCode::EnvAlreadyExists.err_with_hint(
+                format!("Virtual environment '{name}' already exists."),
+                "pass `--clear` to recreate it",
+            ));
+        }
+        Ok(true)
+    }
+
+    /// Records the architecture and the base interpreter of the environment, found by
+    /// `source` unless by the backend, in the metadata, best effort as interpreters which
+    /// cannot report them are still usable.
+    async fn record_interpreter(
+        venv_path: &Path,
+        requested: Option<Arch>,
+        python: Option<&str>,
+        source: Option<&str>,
+        process: ProcessOptions,
+    ) -> Result<()> {
+        let mut command = std::process::Command::new(python_path_in_venv(venv_path));
+        command.args(["-c", INSPECT_INTERPRETER_SCRIPT]);
+        let stdout = match run_command(command, process.capture()).await {
+            Ok(output) if output.status.success() => output.stdout,
+            _ => String::new(),
+        };
+        let mut lines = stdout.lines().map(str::trim);
+        let (Some(machine), Some(implementation), Some(version), Some(path)) =
+            (lines.next(), lines.next(), lines.next(), lines.next())
+        else {
+            debug!(
+                "Failed to inspect the interpreter of {}",
+                venv_path.display()
+            );
+            return Ok(());
+        };
+        let arch = Arch::from_machine(machine);
+        if let (Some(requested), Some(arch)) = (requested, arch)
+            && requested != arch
+        {
+            warn!(
+                "Requested a {} interpreter, but {} uses {}",
+                requested.name(),
+                venv_path.display(),
+                arch.name()
+            );
+        }
+        if let Some(requested) = python.and_then(requested_implementation)
+            && !requested.eq_ignore_ascii_case(implementation)
+        {
+            warn!(
+                "Requested {requested}, but {} uses {implementation}",
+                venv_path.display()
+            );
+        }
+        let mut metadata = EnvMetadata::load(venv_path)?;
+        if let Some(arch) = arch {
+            metadata.arch = Some(arch.name().to_string());
+        }
+        metadata.interpreter = Some(Interpreter {
+            implementation: implementation.to_string(),
+            version: version.to_string(),
+            path: path.to_string(),
+            source: source.map(str::to_string),
+        });
+        metadata.save(venv_path)
+    }
+
+    /// Removes the half-built environment `name` of a creation whose process was killed,
+    /// e.g., by SIGKILL or a power loss, which had no chance to clean up. Only called with
+    /// the lock of the environment held, so the creation is not running anymore.
+    fn discard_interrupted_creation(store: &VenvStore, name: &str) {
+        let marker = store.creating_marker_path(name);
+        if !marker.exists() {
+            return;
+        }
+        warn!("Removing '{name}', its creation was interrupted");
+        Self::discard_partial_venv(&store.path().join(name));
+        if let Err(e) = std::fs::remove_file(&marker) {
+            warn!("Failed to remove '{}': {e}", marker.display());
+        }
+    }
+
+    /// Removes a partially created environment after a failed or interrupted creation.
+    fn discard_partial_venv(venv_path: &Path) {
+        if venv_path.exists()
+            && let Err(e) = std::fs::remove_dir_all(venv_path)
+        {
+            warn!(
+                "Failed to remove partially created environment '{}': {e}",
+                venv_path.display()
+            );
+        }
+    }
+
+    pub(crate) fn detect_current_venv() -> Option<PathBuf> {
         std::env::var("VIRTUAL_ENV")
             .ok()
             .and_then(|s| std::path::absolute(PathBuf::from(s)).ok())
     }
 
     fn get_site_package_dir(&self, env_name: &str, store: &VenvStore) -> Result<PathBuf> {
-        let lib_dir = store.path().join(env_name).join("lib");
+        let lib_dir = store.env_path(env_name).join("lib");
         let site_package_dir = lib_dir
             .read_dir()
             .context("Failed to read lib directory")?
@@ -168,29 +558,310 @@ impl VenvService {
         name: &str,
         options: CreateOptions<'_>,
     ) -> Result<()> {
-        let _lock = store.lock().await?;
-        if store.exists(name) {
-            if options.clear {
-                Self::remove_venv(store, name)?;
-            } else {
+        let _env_lock = store.lock_env(name).await?;
+        let result = self.create_locked(store, name, options, self.process).await;
+        history::record(store, Operation::Create, name, &result);
+        result
+    }
+
+    /// Creates an environment and installs the given requirements file content,
+    /// which is recorded as the environment spec.
+    ///
+    /// The environment is marked as installing until the requirements are installed,
+    /// if that is interrupted or fails, calling this again resumes the installation
+    /// instead of starting over.
+    pub async fn create_from_requirements(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        options: CreateOptions<'_>,
+        requirements: &str,
+    ) -> Result<()> {
+        let _env_lock = store.lock_env(name).await?;
+        let result = self
+            .create_from_requirements_locked(store, name, options, requirements, self.process)
+            .await;
+        history::record(store, Operation::Create, name, &result);
+        result
+    }
+
+    async fn create_from_requirements_locked(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        options: CreateOptions<'_>,
+        requirements: &str,
+        process: ProcessOptions,
+    ) -> Result<()> {
+        let venv_path = store.env_path(name);
+        let resuming = !options.clear
+            && store.exists(name)
+            && EnvMetadata::load(&venv_path).is_ok_and(|metadata| metadata.installing);
+        if resuming {
+            info!("Resuming the installation of '{}'", name.green());
+        } else {
+            self.create_locked(store, name, options, process).await?;
+        }
+
+        let result = self
+            .install_spec_locked(store, name, requirements, process)
+            .await;
+        if result.is_err() {
+            warn!(
+                "The installation of '{name}' is incomplete, run the same command again to resume it, or pass --clear to start over"
+            );
+        }
+        result
+    }
+
+    /// Records `requirements` as the spec of the environment `name` and installs it, the
+    /// environment is marked as installing until that succeeds.
+    async fn install_spec_locked(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        requirements: &str,
+        process: ProcessOptions,
+    ) -> Result<()> {
+        let venv_path = store.env_path(name);
+        let mut metadata = EnvMetadata::load(&venv_path)?;
+        metadata.installing = true;
+        metadata.save(&venv_path)?;
+        let spec_path = venv_path.join(SPEC_FILE_NAME);
+        let result = async {
+            std::fs::write(&spec_path, requirements)
+                .with_context(|| format!("Failed to write spec file '{}'", spec_path.display()))?;
+            // Installing is incremental, already installed packages are skipped on resume
+            let mut command = self.backend.pip_command(&venv_path, "install")?;
+            command
+                .args(metadata.index_args())
+                .arg("-r")
+                .arg(&spec_path);
+            let output = run_command(command, process).await?;
+            if !output.status.success() {
+                if is_offline() {
+                    return Err(offline_install_failure(&format!(
+                        "the requirements in '{}'",
+                        spec_path.display()
+                    )));
+                }
+                return Err(ErrorCode::BackendFailed
+                    .err(format!("Failed to install the requirements of '{name}'")));
+            }
+            Ok(())
+        }
+        .await;
+        if result.is_ok() {
+            metadata.installing = false;
+            metadata.save(&venv_path)?;
+        }
+        events::emit(EventKind::PackagesChanged, store, name);
+        result
+    }
+
+    /// Packs the environment `name` into the archive `output`: its Python version, its
+    /// pinned packages and, with `with_wheels`, their wheels for offline machines.
+    pub async fn pack(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        output: &Path,
+        with_wheels: bool,
+    ) -> Result<()> {
+        // Packing only reads the environment, which nothing changes in a read-only store
+        let _env_lock = match store.is_read_only() {
+            true => None,
+            false => Some(store.lock_env(name).await?),
+        };
+        if !store.exists(name) {
+            return Err(
+                ErrorCode::EnvNotFound.err(format!("Virtual environment '{name}' does not exist"))
+            );
+        }
+        let venv_path = store.env_path(name);
+        let metadata = EnvMetadata::load(&venv_path)?;
+        let mut command = self.backend.pip_command(&venv_path, "freeze")?;
+        command.arg("--exclude-editable");
+        let freeze = run_command(command, self.process.capture()).await?;
+        if !freeze.status.success() {
+            anyhow::bail!(
+                "Failed to list the packages of '{name}': {}",
+                freeze.stderr.trim()
+            );
+        }
+
+        let staging = tempfile::tempdir().context("Failed to create a temporary directory")?;
+        let requirements_path = staging.path().join(pack::REQUIREMENTS_FILE_NAME);
+        std::fs::write(&requirements_path, &freeze.stdout)
+            .with_context(|| format!("Failed to write '{}'", requirements_path.display()))?;
+        PackManifest {
+            version: pack::PACK_FORMAT_VERSION,
+            name: name.to_string(),
+            python: EnvConfig::parse(venv_path.join("pyvenv.cfg"))
+                .ok()
+                .and_then(|config| config.version),
+            index_url: metadata.index_url.clone(),
+            extra_index_urls: metadata.extra_index_urls.clone(),
+        }
+        .save(staging.path())?;
+        if with_wheels && !freeze.stdout.trim().is_empty() {
+            // uv has no download command, the pip of the environment fetches the wheels
+            let mut command = std::process::Command::new(python_path_in_venv(&venv_path));
+            command
+                .args(["-m", "pip", "download", "--no-deps", "-d"])
+                .arg(staging.path().join(pack::WHEELS_DIR_NAME))
+                .args(metadata.index_args())
+                .arg("-r")
+                .arg(&requirements_path);
+            let output = run_command(command, self.process).await?;
+            if !output.status.success() {
                 anyhow::bail!(
-                    "Virtual environment '{}' already exists. Use --clear to recreate it",
-                    name
+                    "Failed to download the wheels of '{name}', --with-wheels needs pip in the environment (e.g., created with --seed)"
                 );
             }
         }
-        let venv_path = store.path().join(name);
-        create_uv_venv(
-            &self.uv_path,
+        pack::write_archive(staging.path(), output, self.process).await
+    }
+
+    /// Extracts an archive of [`Self::pack`] into a temporary directory, which is removed
+    /// when dropped.
+    pub async fn extract_archive(
+        &self,
+        archive: &Path,
+    ) -> Result<(tempfile::TempDir, PackManifest)> {
+        let dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+        pack::extract_archive(archive, dir.path(), self.process).await?;
+        let manifest = PackManifest::load(dir.path())?;
+        Ok((dir, manifest))
+    }
+
+    /// Creates the environment `name` from an archive extracted to `dir` by
+    /// [`Self::extract_archive`], installing from the bundled wheels if there are any.
+    pub async fn unpack(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        options: CreateOptions<'_>,
+        dir: &Path,
+    ) -> Result<()> {
+        let requirements_path = dir.join(pack::REQUIREMENTS_FILE_NAME);
+        let requirements = std::fs::read_to_string(&requirements_path)
+            .with_context(|| format!("Failed to read '{}'", requirements_path.display()))?;
+        self.create(store, name, options).await?;
+        if requirements.trim().is_empty() {
+            return Ok(());
+        }
+        let venv_path = store.env_path(name);
+        let spec_path = venv_path.join(SPEC_FILE_NAME);
+        std::fs::write(&spec_path, &requirements)
+            .with_context(|| format!("Failed to write spec file '{}'", spec_path.display()))?;
+        let spec_path = spec_path.to_string_lossy();
+        let wheels = pack::bundled_wheels(dir).map(|wheels| wheels.to_string_lossy().into_owned());
+        let mut args = vec!["-r", spec_path.as_ref()];
+        if let Some(wheels) = &wheels {
+            args.extend(["--find-links", wheels.as_str()]);
+        }
+        self.install_into(
+            store,
             &venv_path,
-            options.python.unwrap_or("3.14"),
-            true,
-            false,
-        )?;
+            &args,
+            PackageOptions {
+                freeze: false,
+                thaw: false,
+            },
+        )
+        .await
+    }
+
+    /// Creates several environments concurrently, each guarded by its own lock, and installs
+    /// `requirements` into each of them if given.
+    ///
+    /// Returns the result of each creation in the order of `envs`.
+    pub async fn create_many(
+        &self,
+        store: &VenvStore,
+        envs: &[(String, CreateOptions<'_>)],
+        requirements: Option<&str>,
+    ) -> Vec<Result<()>> {
+        // Capture the output, interleaved output of concurrent processes is unreadable
+        let process = self.process.capture();
+        let tasks = envs.iter().map(|(name, options)| async move {
+            let _env_lock = store.lock_env(name).await?;
+            let result = match requirements {
+                Some(requirements) => {
+                    self.create_from_requirements_locked(
+                        store,
+                        name,
+                        *options,
+                        requirements,
+                        process,
+                    )
+                    .await
+                }
+                None => self.create_locked(store, name, *options, process).await,
+            };
+            history::record(store, Operation::Create, name, &result);
+            result
+        });
+        futures::future::join_all(tasks).await
+    }
+
+    async fn create_locked(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        options: CreateOptions<'_>,
+        process: ProcessOptions,
+    ) -> Result<()> {
+        Self::discard_interrupted_creation(store, name);
+        if Self::ensure_creatable(store, name, options.clear)? {
+            Self::remove_venv(store, name)?;
+        }
+        let venv_path = store.path().join(name);
+        // Environments are not relocatable, so they cannot be built elsewhere and moved into
+        // place. The marker identifies a half-built environment left behind by a killed
+        // process instead.
+        let marker = store.creating_marker_path(name);
+        std::fs::write(&marker, "")
+            .with_context(|| format!("Failed to write '{}'", marker.display()))?;
+        let (python, source) = options.resolve_python()?;
+        let populate = async {
+            create_venv(
+                self.backend.as_ref(),
+                &venv_path,
+                &options.venv_options(&python),
+                process,
+            )
+            .await?;
+            Self::record_interpreter(&venv_path, options.arch, options.python, source, process)
+                .await?;
+            if options.index_url.is_some() || !options.extra_index_urls.is_empty() {
+                let mut metadata = EnvMetadata::load(&venv_path)?;
+                metadata.index_url = options.index_url.map(str::to_string);
+                metadata.extra_index_urls = options.extra_index_urls.to_vec();
+                metadata.save(&venv_path)?;
+            }
+            Ok(())
+        };
+        // Covers the steps between the child processes too, which handle Ctrl-C themselves
+        let result = tokio::select! {
+            result = populate => result,
+            _ = interrupt::ctrl_c() => {
+                Err(ErrorCode::Interrupted.err(format!("Interrupted while creating '{name}'")))
+            }
+        };
+        if let Err(e) = result {
+            Self::discard_partial_venv(&venv_path);
+            let _ = std::fs::remove_file(&marker);
+            return Err(e);
+        }
+        std::fs::remove_file(&marker)
+            .with_context(|| format!("Failed to remove '{}'", marker.display()))?;
+        events::emit(EventKind::Created, store, name);
         info!(
-            "Created virtual environment '{}' at {}",
+            "Created virtual environment '{}' in the {}",
             name.green(),
-            venv_path.display().to_string().blue()
+            store.to_string().blue()
         );
         Ok(())
     }
@@ -201,25 +872,28 @@ impl VenvService {
         name: &str,
         options: ForkOptions<'_>,
     ) -> Result<()> {
-        let source_layout = options
-            .source
-            .map(|source| resolve_named_source(source, options.scope_type))
-            .transpose()?
-            .unwrap_or(resolve_current_source()?);
+        let source_layout = match options.source {
+            Some(source) => resolve_named_source(source, options.scope_type, self.process).await?,
+            None => resolve_current_source(self.process).await?,
+        };
         let venv_path = store.path().join(name);
-        let _lock = store.lock().await?;
+        let _env_lock = store.lock_env(name).await?;
         ensure_distinct_source_target(&source_layout, &venv_path)?;
-        if store.exists(name) {
-            if options.clear {
-                Self::remove_venv(store, name)?;
-            } else {
-                anyhow::bail!(
-                    "Virtual environment '{}' already exists. Use --clear to recreate it",
-                    name
-                );
-            }
+        if Self::ensure_creatable(store, name, options.clear)? {
+            Self::remove_venv(store, name)?;
         }
-        create_with_source(&self.uv_path, &source_layout, &venv_path)?;
+        if let Err(e) = create_with_source(
+            self.backend.as_ref(),
+            &source_layout,
+            &venv_path,
+            self.process,
+        )
+        .await
+        {
+            Self::discard_partial_venv(&venv_path);
+            return Err(e);
+        }
+        events::emit(EventKind::Forked, store, name);
         info!(
             "Forked virtual environment '{}' from {} to {}",
             name.green(),
@@ -229,140 +903,739 @@ impl VenvService {
         Ok(())
     }
 
-    pub async fn remove(&self, store: &VenvStore, name: &str) -> Result<()> {
+    /// Moves the environment `name` from `source_store` to `target_store`. Environments are
+    /// not relocatable, so it is recreated with the same Python and packages, like a fork,
+    /// then the original is removed.
+    pub async fn move_env(
+        &self,
+        source_store: &VenvStore,
+        target_store: &VenvStore,
+        name: &str,
+    ) -> Result<()> {
+        if source_store.path() == target_store.path() {
+            anyhow::bail!("Virtual environment '{name}' is already in the {target_store}");
+        }
+        // Both stores are locked in a fixed order, so opposite moves cannot deadlock
+        let (first, second) = if source_store.path() <= target_store.path() {
+            (source_store, target_store)
+        } else {
+            (target_store, source_store)
+        };
+        let _first_lock = first.lock_env(name).await?;
+        let _second_lock = second.lock_env(name).await?;
+        if !source_store.exists(name) {
+            return Err(
+                ErrorCode::EnvNotFound.err(format!("Virtual environment '{name}' does not exist"))
+            );
+        }
+        if target_store.exists(name) {
+            return Err(ErrorCode::EnvAlreadyExists.err(format!(
+                "Virtual environment '{name}' already exists in the {target_store}"
+            )));
+        }
+
+        let source_path = source_store.env_path(name);
+        let target_path = target_store.path().join(name);
+        let source_layout = resolve_named_source(
+            &source_path.to_string_lossy(),
+            ScopeType::Unspecified,
+            self.process,
+        )
+        .await?;
+        if let Err(e) = create_with_source(
+            self.backend.as_ref(),
+            &source_layout,
+            &target_path,
+            self.process,
+        )
+        .await
+        .and_then(|_| copy_meowda_files(&source_path, &target_path))
+        {
+            Self::discard_partial_venv(&target_path);
+            return Err(e);
+        }
+        events::emit(EventKind::Created, target_store, name);
+        Self::remove_venv(source_store, name)?;
+        events::emit(EventKind::Removed, source_store, name);
+        info!(
+            "Moved virtual environment '{}' from the {} to the {}",
+            name.green(),
+            source_store.to_string().blue(),
+            target_store.to_string().blue()
+        );
+        Ok(())
+    }
+
+    /// Checks that `source` is a virtual environment outside `store`, returning its absolute
+    /// path and its parsed `pyvenv.cfg`.
+    fn check_external_venv(store: &VenvStore, source: &Path) -> Result<(PathBuf, EnvConfig)> {
+        let source = source
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve path '{}'", source.display()))?;
+        let config = EnvConfig::parse(source.join("pyvenv.cfg")).map_err(|_| {
+            anyhow::anyhow!(
+                "'{}' is not a virtual environment, it has no pyvenv.cfg",
+                source.display()
+            )
+        })?;
+        if !python_path_in_venv(&source).exists() {
+            anyhow::bail!(
+                "'{}' is not a virtual environment, it has no Python executable",
+                source.display()
+            );
+        }
+        if store.contains(&source)? {
+            anyhow::bail!("'{}' is already managed by the {store}", source.display());
+        }
+        Ok((source, config))
+    }
+
+    /// Adopts the hand-made environment at `source` into `store` as `name`, linking it
+    /// into the store, or moving it there if `move_env` is set and it is relocatable.
+    pub async fn adopt(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        source: &Path,
+        move_env: bool,
+    ) -> Result<()> {
+        let (source, config) = Self::check_external_venv(store, source)?;
+        if move_env && !config.relocatable {
+            anyhow::bail!(
+                "'{}' is not relocatable, its scripts would still point to the old path. Adopt it without --move to link it, or fork it with `meowda fork {name} --from {}`",
+                source.display(),
+                source.display()
+            );
+        }
+        let _env_lock = store.lock_env(name).await?;
+        if store.exists(name) {
+            return Err(ErrorCode::EnvAlreadyExists.err(format!(
+                "Virtual environment '{name}' already exists in the {store}"
+            )));
+        }
+
+        let venv_path = store.path().join(name);
+        if move_env {
+            std::fs::rename(&source, &venv_path).with_context(|| {
+                format!(
+                    "Failed to move '{}' to '{}', e.g., they are on different disks",
+                    source.display(),
+                    venv_path.display()
+                )
+            })?;
+        } else {
+            #[cfg(unix)]
+            let linked = std::os::unix::fs::symlink(&source, &venv_path);
+            #[cfg(windows)]
+            let linked = std::os::windows::fs::symlink_dir(&source, &venv_path);
+            linked.with_context(|| {
+                format!("Failed to link '{}' into the {store}", source.display())
+            })?;
+        }
+        let mut metadata = EnvMetadata::load(&venv_path)?;
+        metadata.adopted_from = Some(source.display().to_string());
+        metadata.save(&venv_path)?;
+        Self::record_interpreter(&venv_path, None, None, None, self.process).await?;
+        events::emit(EventKind::Created, store, name);
+        info!(
+            "Adopted '{}' as virtual environment '{}' in the {}",
+            source.display().to_string().blue(),
+            name.green(),
+            store.to_string().blue()
+        );
+        Ok(())
+    }
+
+    /// Registers the external environment at `source` in `store` as `name`, e.g., one on
+    /// another disk, which stays in place but is listed and activated like the others.
+    pub async fn register(&self, store: &VenvStore, name: &str, source: &Path) -> Result<()> {
+        let (source, _) = Self::check_external_venv(store, source)?;
+        // The store lock guards the registry
         let _lock = store.lock().await?;
+        let _env_lock = store.lock_env(name).await?;
+        if store.exists(name) {
+            return Err(ErrorCode::EnvAlreadyExists.err(format!(
+                "Virtual environment '{name}' already exists in the {store}"
+            )));
+        }
+        store.register(name, source.clone())?;
+        Self::record_interpreter(&source, None, None, None, self.process).await?;
+        events::emit(EventKind::Created, store, name);
+        info!(
+            "Registered '{}' as virtual environment '{}' in the {}",
+            source.display().to_string().blue(),
+            name.green(),
+            store.to_string().blue()
+        );
+        Ok(())
+    }
+
+    /// Checks whether the existing environment `name` would be recreated by a sync, as it
+    /// uses another Python version than `python`.
+    pub fn needs_recreate(store: &VenvStore, name: &str, python: Option<&str>) -> bool {
         if !store.exists(name) {
-            anyhow::bail!("Virtual environment '{}' does not exist", name);
+            return false;
         }
-        Self::remove_venv(store, name)?;
-        info!("Removed virtual environment '{}'", name.green());
+        let version = EnvConfig::parse(store.env_path(name).join("pyvenv.cfg"))
+            .ok()
+            .and_then(|config| config.version);
+        !python_matches(python, version.as_deref())
+    }
+
+    /// What syncing the environment `name` to a declaration of Python `python` and
+    /// `fingerprint` does.
+    fn sync_outcome(
+        store: &VenvStore,
+        name: &str,
+        python: Option<&str>,
+        fingerprint: &str,
+    ) -> Result<SyncOutcome> {
+        if !store.exists(name) {
+            return Ok(SyncOutcome::Created);
+        }
+        if Self::needs_recreate(store, name, python) {
+            return Ok(SyncOutcome::Recreated);
+        }
+        let metadata = EnvMetadata::load(&store.env_path(name))?;
+        if !metadata.installing && metadata.sync_fingerprint.as_deref() == Some(fingerprint) {
+            return Ok(SyncOutcome::UpToDate);
+        }
+        Ok(SyncOutcome::Updated)
+    }
+
+    /// Creates or updates the environment `name` to match a declaration, installing the
+    /// given requirements file content, which is recorded as the environment spec.
+    ///
+    /// Nothing is done if `fingerprint`, summarizing the declaration, is unchanged since the
+    /// last sync. An environment using another Python version than declared is recreated.
+    pub async fn sync(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        options: CreateOptions<'_>,
+        requirements: &str,
+        fingerprint: &str,
+    ) -> Result<SyncOutcome> {
+        let outcome = Self::sync_outcome(store, name, options.python, fingerprint)?;
+        if outcome == SyncOutcome::UpToDate {
+            return Ok(outcome);
+        }
+        let options = CreateOptions {
+            clear: outcome == SyncOutcome::Recreated,
+            ..options
+        };
+        match outcome {
+            SyncOutcome::Created | SyncOutcome::Recreated if requirements.is_empty() => {
+                self.create(store, name, options).await?;
+            }
+            SyncOutcome::Created | SyncOutcome::Recreated => {
+                self.create_from_requirements(store, name, options, requirements)
+                    .await?;
+            }
+            _ => {
+                let venv_path = store.env_path(name);
+                let spec_path = venv_path.join(SPEC_FILE_NAME);
+                std::fs::write(&spec_path, requirements).with_context(|| {
+                    format!("Failed to write spec file '{}'", spec_path.display())
+                })?;
+                if !requirements.is_empty() {
+                    let spec_path = spec_path.to_string_lossy();
+                    self.install_into(
+                        store,
+                        &venv_path,
+                        &["-r", &spec_path],
+                        PackageOptions {
+                            freeze: false,
+                            thaw: false,
+                        },
+                    )
+                    .await?;
+                }
+            }
+        }
+        let venv_path = store.env_path(name);
+        let mut metadata = EnvMetadata::load(&venv_path)?;
+        metadata.sync_fingerprint = Some(fingerprint.to_string());
+        metadata.save(&venv_path)?;
+        Ok(outcome)
+    }
+
+    pub async fn remove(&self, store: &VenvStore, name: &str) -> Result<()> {
+        self.remove_locked(store, name).await
+    }
+
+    /// Removes several environments of `store`, one environment lock at a time.
+    ///
+    /// Returns the result of each removal in the order of `names`.
+    pub async fn remove_many(
+        &self,
+        store: &VenvStore,
+        names: &[String],
+    ) -> Result<Vec<Result<()>>> {
+        let mut results = Vec::with_capacity(names.len());
+        for name in names {
+            results.push(self.remove_locked(store, name).await);
+        }
+        Ok(results)
+    }
+
+    async fn remove_locked(&self, store: &VenvStore, name: &str) -> Result<()> {
+        // Unregistering rewrites the registry, which is guarded by the store lock
+        let _registry_lock = match store.is_registered(name) {
+            true => Some(store.lock().await?),
+            false => None,
+        };
+        let _env_lock = store.lock_env(name).await?;
+        let result = if !store.exists(name) {
+            Err(ErrorCode::EnvNotFound.err(format!("Virtual environment '{name}' does not exist")))
+        } else if self.uses_trash() && !store.is_registered(name) {
+            trash::move_to_trash(store, name).map(|_| ())
+        } else {
+            Self::remove_venv(store, name)
+        };
+        history::record(store, Operation::Remove, name, &result);
+        result?;
+        events::emit(EventKind::Removed, store, name);
+        info!(
+            "Removed virtual environment '{}' from the {store}",
+            name.green()
+        );
+        trash::purge_expired(store, self.trash_days);
         Ok(())
     }
 
-    fn list_venvs_in_store(
+    /// Restores the most recently removed environment `name` from the trash of `store`.
+    pub async fn restore(&self, store: &VenvStore, name: &str) -> Result<()> {
+        let _env_lock = store.lock_env(name).await?;
+        let result = trash::restore(store, name);
+        history::record(store, Operation::Restore, name, &result);
+        result?;
+        events::emit(EventKind::Restored, store, name);
+        info!(
+            "Restored virtual environment '{}' in the {store}",
+            name.green()
+        );
+        trash::purge_expired(store, self.trash_days);
+        Ok(())
+    }
+
+    /// Lists the environments of `store` without taking any lock, so it never waits on
+    /// other processes. Results are best effort: environments created or removed meanwhile
+    /// may be missed or shown without their Python version, a vanished store has none.
+    pub(crate) fn list_venvs_in_store(
         store: &VenvStore,
         current_venv: Option<&PathBuf>,
     ) -> Result<Vec<EnvInfo>> {
-        let entries = store
-            .path()
-            .read_dir()
-            .context("Failed to read venv directory")?
-            .filter_map(|entry| {
-                entry.ok().and_then(|e| {
-                    if e.path().is_dir() {
-                        e.file_name().to_str().map(|name| {
-                            let env_path = e.path();
-                            let is_active = if let Some(current) = current_venv {
-                                env_path.canonicalize().ok() == current.canonicalize().ok()
-                            } else {
-                                false
-                            };
-
-                            EnvInfo {
-                                name: name.to_string(),
-                                path: env_path.clone(),
-                                is_active,
-                                config: EnvConfig::parse(env_path.join("pyvenv.cfg")).ok(),
-                            }
-                        })
-                    } else {
-                        None
-                    }
-                })
+        Self::list_venvs_matching(store, &mut ActiveEnvMatcher::new(current_venv))
+    }
+
+    /// Like [`Self::list_venvs_in_store`], sharing `active` across the stores of a listing.
+    pub(crate) fn list_venvs_matching(
+        store: &VenvStore,
+        active: &mut ActiveEnvMatcher,
+    ) -> Result<Vec<EnvInfo>> {
+        let registered = store.registered_envs();
+        let mut entries = index::indexed_envs(store)
+            .into_iter()
+            .filter(|(name, _)| !registered.contains_key(name))
+            .map(|(name, config)| {
+                let path = store.path().join(&name);
+                EnvInfo {
+                    name,
+                    is_active: active.matches(&path),
+                    config,
+                    path,
+                }
             })
-            .collect();
+            .collect::<Vec<_>>();
+        entries.extend(registered.iter().map(|(name, path)| EnvInfo {
+            name: name.clone(),
+            is_active: active.matches(path),
+            config: EnvConfig::parse(path.join("pyvenv.cfg")).ok(),
+            path: path.clone(),
+        }));
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
         Ok(entries)
     }
 
+    /// Lists the environments of every store, nearest first. The stores are scanned
+    /// concurrently on the blocking pool, as each one costs several filesystem round trips,
+    /// which add up with deep directory hierarchies and on network filesystems.
     pub async fn list(&self) -> Result<Vec<(VenvScope, Vec<EnvInfo>)>> {
-        let current_venv = Self::detect_current_venv();
+        let active = ActiveEnvMatcher::new(Self::detect_current_venv().as_ref());
         let scopes = get_candidate_scopes(ScopeType::Unspecified)?;
 
+        let stores = scopes
+            .into_iter()
+            .map(VenvStore::from_specified_scope)
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .chain(VenvStore::named_stores());
+        let tasks = stores.map(|venv_store| {
+            let mut active = active.clone();
+            tokio::task::spawn_blocking(move || {
+                if !venv_store.is_ready() {
+                    return Ok(None);
+                }
+                let Some(scope) = venv_store.scope().cloned() else {
+                    return Ok(None);
+                };
+                Self::list_venvs_matching(&venv_store, &mut active).map(|envs| Some((scope, envs)))
+            })
+        });
         let mut results = Vec::new();
-        for scope in scopes {
-            let venv_store = VenvStore::from_specified_scope(scope.clone())?;
-            if !venv_store.is_ready() {
-                continue;
-            }
-            results.push((
-                scope.clone(),
-                Self::list_venvs_in_store(&venv_store, current_venv.as_ref())?,
-            ));
+        for listing in futures::future::join_all(tasks).await {
+            results.extend(listing.context("Failed to scan a store")??);
         }
         Ok(results)
     }
 
+    /// Resolves the store directory only, without locking or reading the store.
     pub fn dir(&self, store: &VenvStore) -> Result<PathBuf> {
         Ok(store.path().clone())
     }
 
-    fn check_env_is_managed(current_venv: &PathBuf) -> Result<VenvScope> {
+    /// Returns the store managing `current_venv`.
+    fn check_env_is_managed(current_venv: &PathBuf) -> Result<VenvStore> {
         let scopes = get_candidate_scopes(ScopeType::Unspecified)?;
         for scope in scopes {
-            let store = VenvStore::from_specified_scope(scope.clone())?;
+            let store = VenvStore::from_specified_scope(scope)?;
             if store.contains(current_venv)? {
-                return Ok(scope);
+                return Ok(store);
             }
         }
 
-        anyhow::bail!(
-            "Current virtual environment ({}) is not managed by meowda.\nPlease activate a meowda-managed environment first",
-            current_venv.display()
-        );
+        Err(ErrorCode::EnvNotManaged.err_with_hint(
+            format!(
+                "Current virtual environment ({}) is not managed by meowda.",
+                current_venv.display()
+            ),
+            "activate a meowda-managed environment first, or adopt this one with `meowda adopt \"$VIRTUAL_ENV\" --name <name>`",
+        ))
     }
 
-    pub async fn install(&self, extra_args: &[&str]) -> Result<()> {
-        let current_venv = Self::detect_current_venv()
-            .ok_or_else(|| anyhow::anyhow!("No virtual environment is currently activated.\nPlease activate a virtual environment first with: meowda activate <env_name>"))?;
-        let scope = Self::check_env_is_managed(&current_venv)?;
-        let store = VenvStore::from_specified_scope(scope)?;
-        let _lock = store.lock().await?;
+    fn check_frozen(venv_path: &Path, thaw: bool) -> Result<EnvMetadata> {
+        let metadata = EnvMetadata::load(venv_path)?;
+        if metadata.frozen && !thaw {
+            return Err(ErrorCode::EnvFrozen.err(format!(
+                "Virtual environment ({}) is frozen. Pass --thaw to change its packages",
+                venv_path.display()
+            )));
+        }
+        Ok(metadata)
+    }
 
-        let status = Command::new(&self.uv_path)
-            .args(["pip", "install"])
-            .args(extra_args)
-            .status()
-            .context("Failed to execute uv pip install command")?;
+    fn update_frozen(
+        venv_path: &Path,
+        metadata: &mut EnvMetadata,
+        options: PackageOptions,
+    ) -> Result<()> {
+        let frozen = options.freeze || (metadata.frozen && !options.thaw);
+        if frozen != metadata.frozen {
+            metadata.frozen = frozen;
+            metadata.save(venv_path)?;
+        }
+        Ok(())
+    }
 
-        if !status.success() {
-            anyhow::bail!("Failed to install packages. Check package names and try again");
+    fn emit_packages_changed(store: &VenvStore, venv_path: &Path) {
+        if let Some(name) = venv_path.file_name().and_then(|s| s.to_str()) {
+            events::emit(EventKind::PackagesChanged, store, name);
         }
+    }
 
+    pub async fn set_frozen(&self, store: &VenvStore, name: &str, frozen: bool) -> Result<()> {
+        let _env_lock = store.lock_env(name).await?;
+        if !store.exists(name) {
+            return Err(
+                ErrorCode::EnvNotFound.err(format!("Virtual environment '{name}' does not exist"))
+            );
+        }
+        let venv_path = store.env_path(name);
+        let mut metadata = EnvMetadata::load(&venv_path)?;
+        metadata.frozen = frozen;
+        metadata.save(&venv_path)?;
+        let kind = if frozen {
+            EventKind::Frozen
+        } else {
+            EventKind::Thawed
+        };
+        events::emit(kind, store, name);
+        Ok(())
+    }
+
+    /// Adds or removes the `tags` of the environment `name`, returning its tags afterwards.
+    pub async fn set_tags(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        tags: &[String],
+        add: bool,
+    ) -> Result<Vec<String>> {
+        let _env_lock = store.lock_env(name).await?;
+        if !store.exists(name) {
+            return Err(
+                ErrorCode::EnvNotFound.err(format!("Virtual environment '{name}' does not exist"))
+            );
+        }
+        let venv_path = store.env_path(name);
+        let mut metadata = EnvMetadata::load(&venv_path)?;
+        if add {
+            metadata.add_tags(tags);
+        } else {
+            metadata.remove_tags(tags);
+        }
+        metadata.save(&venv_path)?;
+        Ok(metadata.tags)
+    }
+
+    pub async fn install(&self, extra_args: &[&str], options: PackageOptions) -> Result<()> {
+        let current_venv = Self::detect_current_venv().ok_or_else(|| {
+            ErrorCode::NoActiveEnv.err_with_hint(
+                "No virtual environment is currently activated.",
+                "activate one first with `meowda activate <env_name>`",
+            )
+        })?;
+        let store = Self::check_env_is_managed(&current_venv)?;
+        self.install_into(&store, &current_venv, extra_args, options)
+            .await?;
         println!("Packages installed successfully.");
         Ok(())
     }
 
-    pub async fn uninstall(&self, extra_args: &[&str]) -> Result<()> {
-        let current_venv = Self::detect_current_venv()
-            .ok_or_else(|| anyhow::anyhow!("No virtual environment is currently activated.\nPlease activate a virtual environment first with: meowda activate <env_name>"))?;
-        let scope = Self::check_env_is_managed(&current_venv)?;
-        let store = VenvStore::from_specified_scope(scope)?;
-        let _lock = store.lock().await?;
+    /// Installs packages into the global tools environment, creating it on first use, and
+    /// writes shims of its entry points. Shims of entry points no longer installed are removed.
+    pub async fn install_tools(&self, extra_args: &[&str], options: PackageOptions) -> Result<()> {
+        let store = tools::tools_store()?;
+        store.init_if_needed()?;
+        if !store.exists(TOOLS_ENV_NAME) {
+            self.create(
+                &store,
+                TOOLS_ENV_NAME,
+                CreateOptions {
+                    python: None,
+                    clear: false,
+                    arch: None,
+                    seed: true,
+                    index_url: None,
+                    extra_index_urls: &[],
+                    system_site_packages: false,
+                    prompt: None,
+                },
+            )
+            .await?;
+        }
+        let venv_path = store.path().join(TOOLS_ENV_NAME);
+        self.install_into(&store, &venv_path, extra_args, options)
+            .await?;
 
-        let status = Command::new(&self.uv_path)
-            .args(["pip", "uninstall"])
-            .args(extra_args)
-            .status()
-            .context("Failed to execute uv pip uninstall command")?;
+        for shim in list_shims()? {
+            if shim.env_path == venv_path && !shim_target_exists(&shim) {
+                remove_shim(&shim.name)?;
+            }
+        }
+        let tools = list_tools(&tools_bin_dir()?);
+        for tool in &tools {
+            let command = tool.strip_suffix(".exe").unwrap_or(tool);
+            if let Err(e) = add_shim(&venv_path, command, false) {
+                warn!("Skipping the shim of '{command}': {e:#}");
+            }
+        }
+        println!(
+            "Packages installed into the tools environment, exposed on PATH by the shell hook: {}",
+            if tools.is_empty() {
+                "no entry points".to_string()
+            } else {
+                tools.join(", ")
+            }
+        );
+        Ok(())
+    }
+
+    async fn install_into(
+        &self,
+        store: &VenvStore,
+        venv_path: &Path,
+        extra_args: &[&str],
+        options: PackageOptions,
+    ) -> Result<()> {
+        let _env_lock = store.lock_env(venv_name(venv_path)?).await?;
+        self.install_locked(store, venv_path, extra_args, options)
+            .await
+    }
 
-        if !status.success() {
-            anyhow::bail!("Failed to uninstall packages. Check package names and try again");
+    /// [`Self::install_into`] for callers already holding the lock of the environment.
+    async fn install_locked(
+        &self,
+        store: &VenvStore,
+        venv_path: &Path,
+        extra_args: &[&str],
+        options: PackageOptions,
+    ) -> Result<()> {
+        let name = venv_name(venv_path)?;
+        let result = async {
+            let mut metadata = Self::check_frozen(venv_path, options.thaw)?;
+            warn_on_arch_mismatch(venv_path);
+            touch_last_used(venv_path);
+
+            let mut command = self.backend.pip_command(venv_path, "install")?;
+            command.args(metadata.index_args()).args(extra_args);
+            let output = run_command(command, self.process).await?;
+
+            if !output.status.success() {
+                if is_offline() {
+                    return Err(offline_install_failure(&extra_args.join(" ")));
+                }
+                return Err(ErrorCode::BackendFailed
+                    .err("Failed to install packages. Check package names and try again"));
+            }
+
+            Self::update_frozen(venv_path, &mut metadata, options)
         }
+        .await;
+        history::record(store, Operation::Install, name, &result);
+        result?;
+        Self::emit_packages_changed(store, venv_path);
+        Ok(())
+    }
 
+    pub async fn uninstall(&self, extra_args: &[&str], options: PackageOptions) -> Result<()> {
+        let current_venv = Self::detect_current_venv().ok_or_else(|| {
+            ErrorCode::NoActiveEnv.err_with_hint(
+                "No virtual environment is currently activated.",
+                "activate one first with `meowda activate <env_name>`",
+            )
+        })?;
+        let store = Self::check_env_is_managed(&current_venv)?;
+        self.uninstall_from(&store, &current_venv, extra_args, options)
+            .await?;
         println!("Packages uninstalled successfully.");
         Ok(())
     }
 
+    async fn uninstall_from(
+        &self,
+        store: &VenvStore,
+        venv_path: &Path,
+        extra_args: &[&str],
+        options: PackageOptions,
+    ) -> Result<()> {
+        let _env_lock = store.lock_env(venv_name(venv_path)?).await?;
+        self.uninstall_locked(store, venv_path, extra_args, options)
+            .await
+    }
+
+    /// [`Self::uninstall_from`] for callers already holding the lock of the environment.
+    async fn uninstall_locked(
+        &self,
+        store: &VenvStore,
+        venv_path: &Path,
+        extra_args: &[&str],
+        options: PackageOptions,
+    ) -> Result<()> {
+        let name = venv_name(venv_path)?;
+        let result = async {
+            let mut metadata = Self::check_frozen(venv_path, options.thaw)?;
+            warn_on_arch_mismatch(venv_path);
+            touch_last_used(venv_path);
+
+            let mut command = self.backend.pip_command(venv_path, "uninstall")?;
+            command.args(extra_args);
+            let output = run_command(command, self.process).await?;
+
+            if !output.status.success() {
+                return Err(ErrorCode::BackendFailed
+                    .err("Failed to uninstall packages. Check package names and try again"));
+            }
+
+            Self::update_frozen(venv_path, &mut metadata, options)
+        }
+        .await;
+        history::record(store, Operation::Uninstall, name, &result);
+        result?;
+        Self::emit_packages_changed(store, venv_path);
+        Ok(())
+    }
+
+    /// Installs `requirements` into the active environment and records them in its spec,
+    /// replacing the recorded requirements of the same projects.
+    pub async fn add_deps(&self, requirements: &[String], options: PackageOptions) -> Result<()> {
+        let current_venv = Self::detect_current_venv().ok_or_else(|| {
+            ErrorCode::NoActiveEnv.err_with_hint(
+                "No virtual environment is currently activated.",
+                "activate one first with `meowda activate <env_name>`",
+            )
+        })?;
+        let store = Self::check_env_is_managed(&current_venv)?;
+        let extra_args: Vec<&str> = requirements.iter().map(String::as_str).collect();
+        // One lock for both, so no other change lands between the install and the spec
+        let _env_lock = store.lock_env(venv_name(&current_venv)?).await?;
+        self.install_locked(&store, &current_venv, &extra_args, options)
+            .await?;
+        Self::edit_spec(&current_venv, |spec| {
+            spec::add_requirements(spec, requirements)
+        })?;
+        println!(
+            "Packages installed and recorded in {}.",
+            current_venv.join(SPEC_FILE_NAME).display()
+        );
+        Ok(())
+    }
+
+    /// Uninstalls the projects `names` from the active environment and drops them from its
+    /// spec.
+    pub async fn remove_deps(&self, names: &[String], options: PackageOptions) -> Result<()> {
+        let current_venv = Self::detect_current_venv().ok_or_else(|| {
+            ErrorCode::NoActiveEnv.err_with_hint(
+                "No virtual environment is currently activated.",
+                "activate one first with `meowda activate <env_name>`",
+            )
+        })?;
+        let store = Self::check_env_is_managed(&current_venv)?;
+        let extra_args: Vec<&str> = names.iter().map(String::as_str).collect();
+        let _env_lock = store.lock_env(venv_name(&current_venv)?).await?;
+        self.uninstall_locked(&store, &current_venv, &extra_args, options)
+            .await?;
+        let mut missing = Vec::new();
+        Self::edit_spec(&current_venv, |spec| {
+            let (spec, not_recorded) = spec::remove_requirements(spec, names);
+            missing = not_recorded;
+            spec
+        })?;
+        for name in missing {
+            warn!("'{name}' was not recorded in the spec of the environment");
+        }
+        println!("Packages uninstalled and dropped from the environment spec.");
+        Ok(())
+    }
+
+    /// Rewrites the spec of the environment at `venv_path`, a missing spec is empty. The
+    /// caller holds the lock of the environment.
+    fn edit_spec(venv_path: &Path, edit: impl FnOnce(&str) -> String) -> Result<()> {
+        let spec_path = venv_path.join(SPEC_FILE_NAME);
+        let spec = match std::fs::read_to_string(&spec_path) {
+            Ok(spec) => spec,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read spec file '{}'", spec_path.display())
+                });
+            }
+        };
+        std::fs::write(&spec_path, edit(&spec))
+            .with_context(|| format!("Failed to write spec file '{}'", spec_path.display()))
+    }
+
     pub async fn link(&self, project_name: &str, project_path: &str) -> Result<()> {
-        let current_venv = Self::detect_current_venv()
-            .ok_or_else(|| anyhow::anyhow!("No virtual environment is currently activated.\nPlease activate a virtual environment first with: meowda activate <env_name>"))?;
-        let scope = Self::check_env_is_managed(&current_venv)?;
-        let store = VenvStore::from_specified_scope(scope)?;
-        let venv_name = current_venv
-            .file_name()
-            .and_then(|s| s.to_str())
-            .ok_or_else(|| anyhow::anyhow!("Invalid virtual environment name"))?;
-        let _lock = store.lock().await?;
+        let current_venv = Self::detect_current_venv().ok_or_else(|| {
+            ErrorCode::NoActiveEnv.err_with_hint(
+                "No virtual environment is currently activated.",
+                "activate one first with `meowda activate <env_name>`",
+            )
+        })?;
+        let store = Self::check_env_is_managed(&current_venv)?;
+        let venv_name = venv_name(&current_venv)?;
+        let _env_lock = store.lock_env(venv_name).await?;
 
         let site_package_dir = self.get_site_package_dir(venv_name, &store)?;
         let pth_file = site_package_dir.join(format!("meowda_link_{}.pth", project_name));
@@ -376,15 +1649,15 @@ impl VenvService {
     }
 
     pub async fn unlink(&self, project_name: &str) -> Result<()> {
-        let current_venv = Self::detect_current_venv()
-            .ok_or_else(|| anyhow::anyhow!("No virtual environment is currently activated.\nPlease activate a virtual environment first with: meowda activate <env_name>"))?;
-        let scope = Self::check_env_is_managed(&current_venv)?;
-        let store = VenvStore::from_specified_scope(scope)?;
-        let venv_name = current_venv
-            .file_name()
-            .and_then(|s| s.to_str())
-            .ok_or_else(|| anyhow::anyhow!("Invalid virtual environment name"))?;
-        let _lock = store.lock().await?;
+        let current_venv = Self::detect_current_venv().ok_or_else(|| {
+            ErrorCode::NoActiveEnv.err_with_hint(
+                "No virtual environment is currently activated.",
+                "activate one first with `meowda activate <env_name>`",
+            )
+        })?;
+        let store = Self::check_env_is_managed(&current_venv)?;
+        let venv_name = venv_name(&current_venv)?;
+        let _env_lock = store.lock_env(venv_name).await?;
 
         let site_package_dir = self.get_site_package_dir(venv_name, &store)?;
         let pth_file = site_package_dir.join(format!("meowda_link_{}.pth", project_name));
@@ -394,3 +1667,205 @@ impl VenvService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+    use std::process::Command;
+
+    /// A backend refusing every command, for tests which must not run one.
+    struct NoBackend;
+
+    impl Backend for NoBackend {
+        fn name(&self) -> &'static str {
+            "none"
+        }
+
+        fn venv_command(&self, _venv_path: &Path, _options: &VenvOptions<'_>) -> Result<Command> {
+            anyhow::bail!("No backend in tests")
+        }
+
+        fn pip_command(&self, _venv_path: &Path, _subcommand: &str) -> Result<Command> {
+            anyhow::bail!("No backend in tests")
+        }
+    }
+
+    /// A service independent of the Python and uv of the machine.
+    fn test_service() -> VenvService {
+        VenvService {
+            backend: Box::new(NoBackend),
+            process: ProcessOptions::default(),
+            trash_days: trash::DEFAULT_TRASH_DAYS,
+        }
+    }
+
+    #[test]
+    fn list_venvs_in_store_reads_fixture_envs() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().to_path_buf());
+        let paths =
+            create_fixture_store(&store, &["app".to_string(), "tools".to_string()], "3.13.1")?;
+
+        let mut envs = VenvService::list_venvs_in_store(&store, Some(&paths[1]))?;
+        envs.sort_by(|a, b| a.name.cmp(&b.name));
+        let summary = envs
+            .iter()
+            .map(|env| {
+                (
+                    env.name.as_str(),
+                    env.is_active,
+                    env.config
+                        .as_ref()
+                        .and_then(|config| config.version.as_deref()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("app", false, Some("3.13.1")),
+                ("tools", true, Some("3.13.1"))
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn active_env_matcher_resolves_symlinks() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store_dir = temp.path().join("venvs");
+        let external = temp.path().join("project").join(".venv");
+        std::fs::create_dir_all(store_dir.join("app"))?;
+        std::fs::create_dir_all(&external)?;
+        std::os::unix::fs::symlink(&store_dir, temp.path().join("linked-venvs"))?;
+        std::os::unix::fs::symlink(&external, store_dir.join("project"))?;
+
+        let mut active = ActiveEnvMatcher::new(Some(&temp.path().join("linked-venvs/app")));
+        assert!(active.matches(&store_dir.join("app")));
+        assert!(!active.matches(&store_dir.join("project")));
+        let mut active = ActiveEnvMatcher::new(Some(&external));
+        assert!(active.matches(&store_dir.join("project")));
+        assert!(!active.matches(&store_dir.join("app")));
+        assert!(!ActiveEnvMatcher::new(None).matches(&store_dir.join("app")));
+        Ok(())
+    }
+
+    #[test]
+    fn env_config_reads_the_prompt_of_uv_and_venv() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let cfg = temp.path().join("pyvenv.cfg");
+        let cases = [
+            ("home = /usr/bin\n", None),
+            ("prompt = my app\n", Some("my app")),
+            ("prompt = 'my app'\n", Some("my app")),
+            ("prompt = \"it's\"\n", Some("it's")),
+        ];
+        for (content, expected) in cases {
+            std::fs::write(&cfg, content)?;
+            assert_eq!(
+                EnvConfig::parse(&cfg)?.prompt.as_deref(),
+                expected,
+                "content: {content:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn interrupted_creations_are_discarded() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().to_path_buf());
+        create_fixture_store(&store, &["app".to_string(), "half".to_string()], "3.13.1")?;
+        std::fs::write(store.creating_marker_path("half"), "")?;
+        // Hidden from listings and resolution until discarded
+        let names = VenvService::list_venvs_in_store(&store, None)?
+            .into_iter()
+            .map(|env| env.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["app"]);
+        assert_eq!(store.env_names(), ["app"]);
+        assert!(!store.exists("half"));
+
+        VenvService::discard_interrupted_creation(&store, "app");
+        VenvService::discard_interrupted_creation(&store, "half");
+        assert!(store.exists("app"));
+        assert!(!store.exists("half"));
+        assert!(!store.creating_marker_path("half").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn list_venvs_in_store_tolerates_a_vanished_store() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("removed"));
+        assert!(VenvService::list_venvs_in_store(&store, None)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn python_matches_version_prefixes() {
+        let cases = [
+            (Some("3.12"), Some("3.12.4"), true),
+            (Some("3.12.4"), Some("3.12.4"), true),
+            (Some("3.1"), Some("3.12.4"), false),
+            (Some("3.13"), Some("3.12.4"), false),
+            (Some("/usr/bin/python3"), Some("3.12.4"), true),
+            (None, Some("3.12.4"), true),
+            (Some("3.12"), None, true),
+        ];
+        for (python, actual, expected) in cases {
+            assert_eq!(
+                python_matches(python, actual),
+                expected,
+                "{python:?} {actual:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_skips_unchanged_envs() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().to_path_buf());
+        store.init()?;
+        let paths = create_fixture_store(&store, &["app".to_string()], "3.12.4")?;
+        let metadata = EnvMetadata {
+            sync_fingerprint: Some("abc".to_string()),
+            ..EnvMetadata::default()
+        };
+        metadata.save(&paths[0])?;
+        let service = test_service();
+
+        let options = CreateOptions {
+            python: Some("3.12"),
+            clear: false,
+            arch: None,
+            seed: true,
+            index_url: None,
+            extra_index_urls: &[],
+            system_site_packages: false,
+            prompt: None,
+        };
+        let outcome = service.sync(&store, "app", options, "", "abc").await?;
+        assert_eq!(outcome, SyncOutcome::UpToDate);
+        assert!(VenvService::needs_recreate(&store, "app", Some("3.13")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remove_many_reports_each_env() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().to_path_buf());
+        store.init()?;
+        create_fixture_store(&store, &["app".to_string(), "tools".to_string()], "3.13.1")?;
+        let service = test_service();
+
+        let names = ["app", "missing", "tools"].map(str::to_string);
+        let results = service.remove_many(&store, &names).await?;
+        let removed = results.iter().map(Result::is_ok).collect::<Vec<_>>();
+        assert_eq!(removed, [true, false, true]);
+        assert!(!store.exists("app") && !store.exists("tools"));
+        Ok(())
+    }
+}
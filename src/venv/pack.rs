@@ -0,0 +1,117 @@
+/// Portable environment archives of `meowda pack`/`meowda unpack`: a tarball holding the
+/// spec of an environment, i.e., its Python version and pinned packages, and optionally
+/// the wheels of those packages for offline machines.
+use crate::backends::{ProcessOptions, run_command};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Bumped on incompatible changes of the archive layout.
+pub const PACK_FORMAT_VERSION: u32 = 1;
+
+pub const MANIFEST_FILE_NAME: &str = "meowda-pack.toml";
+pub const REQUIREMENTS_FILE_NAME: &str = "requirements.txt";
+pub const WHEELS_DIR_NAME: &str = "wheels";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub version: u32,
+    /// Name of the packed environment, the default name when unpacking.
+    pub name: String,
+    /// Python version of the environment, e.g., `3.12.4`.
+    pub python: Option<String>,
+    #[serde(default)]
+    pub index_url: Option<String>,
+    #[serde(default)]
+    pub extra_index_urls: Vec<String>,
+}
+
+impl PackManifest {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Not a meowda archive, '{MANIFEST_FILE_NAME}' is missing"))?;
+        let manifest: PackManifest = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse '{MANIFEST_FILE_NAME}'"))?;
+        if manifest.version > PACK_FORMAT_VERSION {
+            anyhow::bail!(
+                "The archive uses format version {}, this meowda only supports up to {PACK_FORMAT_VERSION}, please upgrade meowda",
+                manifest.version
+            );
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let content = toml::to_string(self).context("Failed to serialize archive manifest")?;
+        std::fs::write(dir.join(MANIFEST_FILE_NAME), content)
+            .with_context(|| format!("Failed to write '{MANIFEST_FILE_NAME}'"))
+    }
+}
+
+/// Packs the contents of `dir` into the archive `output`, compressed according to its
+/// extension, e.g., `.tar.zst` or `.tar.gz`.
+///
+/// Both GNU tar and bsdtar (macOS, Windows) pick the compression with `-a`.
+pub async fn write_archive(dir: &Path, output: &Path, process: ProcessOptions) -> Result<()> {
+    let mut command = Command::new("tar");
+    command.arg("-caf").arg(output).arg("-C").arg(dir).arg(".");
+    let output_result = run_command(command, process.capture()).await?;
+    if !output_result.status.success() {
+        anyhow::bail!(
+            "Failed to write archive '{}' with tar: {}\n`.tar.zst` archives need zstd installed, `.tar.gz` works everywhere",
+            output.display(),
+            output_result.stderr.trim()
+        );
+    }
+    Ok(())
+}
+
+/// Extracts `archive` into `dir`, tar detects the compression.
+pub async fn extract_archive(archive: &Path, dir: &Path, process: ProcessOptions) -> Result<()> {
+    let mut command = Command::new("tar");
+    command.arg("-xf").arg(archive).arg("-C").arg(dir);
+    let output = run_command(command, process.capture()).await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to extract archive '{}' with tar: {}",
+            archive.display(),
+            output.stderr.trim()
+        );
+    }
+    Ok(())
+}
+
+/// The wheels directory of an extracted archive, if it bundles wheels.
+pub fn bundled_wheels(dir: &Path) -> Option<PathBuf> {
+    Some(dir.join(WHEELS_DIR_NAME)).filter(|wheels| wheels.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_round_trips() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let manifest = PackManifest {
+            version: PACK_FORMAT_VERSION,
+            name: "app".to_string(),
+            python: Some("3.12.4".to_string()),
+            index_url: None,
+            extra_index_urls: vec!["https://extra.example.com/simple".to_string()],
+        };
+        manifest.save(temp.path())?;
+        assert_eq!(PackManifest::load(temp.path())?, manifest);
+
+        let newer = PackManifest {
+            version: PACK_FORMAT_VERSION + 1,
+            ..manifest
+        };
+        newer.save(temp.path())?;
+        assert!(PackManifest::load(temp.path()).is_err());
+        assert!(bundled_wheels(temp.path()).is_none());
+        Ok(())
+    }
+}
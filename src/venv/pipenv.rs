@@ -0,0 +1,179 @@
+/// Conversion of `Pipfile.lock` into a pip requirements file.
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize)]
+struct PipfileLock {
+    #[serde(rename = "_meta", default)]
+    meta: PipfileLockMeta,
+    #[serde(default)]
+    default: BTreeMap<String, PipfileLockEntry>,
+    #[serde(default)]
+    develop: BTreeMap<String, PipfileLockEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PipfileLockMeta {
+    #[serde(default)]
+    requires: PipfileLockRequires,
+    #[serde(default)]
+    sources: Vec<PipfileLockSource>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PipfileLockRequires {
+    python_version: Option<String>,
+    python_full_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PipfileLockSource {
+    url: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PipfileLockEntry {
+    version: Option<String>,
+    hashes: Vec<String>,
+    extras: Vec<String>,
+    markers: Option<String>,
+    git: Option<String>,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    path: Option<String>,
+    file: Option<String>,
+    editable: bool,
+}
+
+/// The environment spec imported from a lock file of another tool.
+#[derive(Debug)]
+pub struct ImportedSpec {
+    pub python: Option<String>,
+    /// Content of a pip requirements file, with hashes preserved.
+    pub requirements: String,
+}
+
+fn format_entry(name: &str, entry: &PipfileLockEntry) -> String {
+    let extras = if entry.extras.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", entry.extras.join(","))
+    };
+    let requirement = if let Some(git) = &entry.git {
+        let git = git.strip_prefix("git+").unwrap_or(git);
+        match &entry.git_ref {
+            Some(git_ref) => format!("{name}{extras} @ git+{git}@{git_ref}"),
+            None => format!("{name}{extras} @ git+{git}"),
+        }
+    } else if let Some(path) = entry.path.as_ref().or(entry.file.as_ref()) {
+        if entry.editable {
+            return format!("-e {path}");
+        }
+        format!("{name}{extras} @ {path}")
+    } else {
+        format!("{name}{extras}{}", entry.version.as_deref().unwrap_or(""))
+    };
+
+    let mut line = match &entry.markers {
+        Some(markers) => format!("{requirement} ; {markers}"),
+        None => requirement,
+    };
+    for hash in &entry.hashes {
+        line.push_str(&format!(" \\\n    --hash={hash}"));
+    }
+    line
+}
+
+/// Converts the content of a `Pipfile.lock`, `include_develop` also imports the `develop` section.
+pub fn parse_pipfile_lock(content: &str, include_develop: bool) -> Result<ImportedSpec> {
+    let lock: PipfileLock = serde_json::from_str(content).context("Invalid Pipfile.lock")?;
+
+    let mut lines = vec!["# Imported from Pipfile.lock by meowda".to_string()];
+    let mut sources = lock.meta.sources.iter();
+    if let Some(source) = sources.next() {
+        lines.push(format!("--index-url {}", source.url));
+    }
+    for source in sources {
+        lines.push(format!("--extra-index-url {}", source.url));
+    }
+
+    let mut packages = lock.default;
+    if include_develop {
+        // Packages in both sections are pinned identically by pipenv
+        for (name, entry) in lock.develop {
+            packages.entry(name).or_insert(entry);
+        }
+    }
+    lines.extend(
+        packages
+            .iter()
+            .map(|(name, entry)| format_entry(name, entry)),
+    );
+
+    Ok(ImportedSpec {
+        python: lock
+            .meta
+            .requires
+            .python_full_version
+            .or(lock.meta.requires.python_version),
+        requirements: lines.join("\n") + "\n",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"{
+        "_meta": {
+            "requires": {"python_version": "3.11"},
+            "sources": [{"name": "pypi", "url": "https://pypi.org/simple", "verify_ssl": true}]
+        },
+        "default": {
+            "requests": {
+                "hashes": ["sha256:aaa", "sha256:bbb"],
+                "version": "==2.31.0",
+                "extras": ["socks"],
+                "markers": "python_version >= '3.7'"
+            },
+            "mylib": {"git": "https://github.com/example/mylib.git", "ref": "abc123"}
+        },
+        "develop": {
+            "pytest": {"hashes": ["sha256:ccc"], "version": "==8.0.0"}
+        }
+    }"#;
+
+    #[test]
+    fn parse_pipfile_lock_preserves_pins_and_hashes() -> Result<()> {
+        let spec = parse_pipfile_lock(LOCK, false)?;
+        assert_eq!(spec.python.as_deref(), Some("3.11"));
+        let expected_lines = [
+            "--index-url https://pypi.org/simple",
+            "mylib @ git+https://github.com/example/mylib.git@abc123",
+            "requests[socks]==2.31.0 ; python_version >= '3.7' \\",
+            "    --hash=sha256:aaa \\",
+            "    --hash=sha256:bbb",
+        ];
+        for line in expected_lines {
+            assert!(
+                spec.requirements.lines().any(|l| l == line),
+                "missing line: {line}\n{}",
+                spec.requirements
+            );
+        }
+        assert!(!spec.requirements.contains("pytest"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_pipfile_lock_includes_develop_on_request() -> Result<()> {
+        let spec = parse_pipfile_lock(LOCK, true)?;
+        assert!(
+            spec.requirements
+                .contains("pytest==8.0.0 \\\n    --hash=sha256:ccc")
+        );
+        Ok(())
+    }
+}
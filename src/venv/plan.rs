@@ -0,0 +1,180 @@
+use super::{CreateOptions, EnvMetadata, SPEC_FILE_NAME, SyncOutcome, VenvService};
+use crate::backends::display_command;
+use crate::error::ErrorCode;
+use crate::store::venv_store::VenvStore;
+use anyhow::Result;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+/// An operation `--dry-run` reports instead of performing it.
+#[derive(Debug, PartialEq)]
+pub enum PlannedOperation {
+    /// Creates a store directory, with its `.gitignore`.
+    InitStore(PathBuf),
+    Run(String),
+    Write(PathBuf),
+    Remove(PathBuf),
+    /// Moves an environment into the trash of its store, see `meowda restore`.
+    Trash(PathBuf),
+    /// Forgets a registered environment, leaving its directory in place.
+    Unregister(String, PathBuf),
+}
+
+impl Display for PlannedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlannedOperation::InitStore(path) => write!(f, "create store {}", path.display()),
+            PlannedOperation::Run(command) => write!(f, "run {command}"),
+            PlannedOperation::Write(path) => write!(f, "write {}", path.display()),
+            PlannedOperation::Remove(path) => write!(f, "remove {}", path.display()),
+            PlannedOperation::Trash(path) => write!(f, "move {} to the trash", path.display()),
+            PlannedOperation::Unregister(name, path) => {
+                write!(
+                    f,
+                    "unregister '{name}', leaving {} in place",
+                    path.display()
+                )
+            }
+        }
+    }
+}
+
+impl VenvService {
+    /// The operations removing the environment `name` performs, moving it into the trash if
+    /// `trash`, see [`VenvService::remove`].
+    pub fn plan_remove(
+        store: &VenvStore,
+        name: &str,
+        trash: bool,
+    ) -> Result<Vec<PlannedOperation>> {
+        if !store.exists(name) {
+            return Err(
+                ErrorCode::EnvNotFound.err(format!("Virtual environment '{name}' does not exist"))
+            );
+        }
+        Ok(vec![if store.is_registered(name) {
+            PlannedOperation::Unregister(name.to_string(), store.env_path(name))
+        } else if trash {
+            PlannedOperation::Trash(store.env_path(name))
+        } else {
+            PlannedOperation::Remove(store.env_path(name))
+        }])
+    }
+
+    /// The operations creating the environment `name` performs, installing `requirements`
+    /// if given, see [`VenvService::create`] and [`VenvService::create_from_requirements`].
+    pub fn plan_create(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        options: CreateOptions<'_>,
+        requirements: Option<&str>,
+    ) -> Result<Vec<PlannedOperation>> {
+        let mut plan = Vec::new();
+        if !store.is_ready() {
+            plan.push(PlannedOperation::InitStore(store.path().clone()));
+        }
+        let venv_path = store.path().join(name);
+        let resuming = requirements.is_some()
+            && !options.clear
+            && store.exists(name)
+            && EnvMetadata::load(&venv_path).is_ok_and(|metadata| metadata.installing);
+        if !resuming {
+            if Self::ensure_creatable(store, name, options.clear)? {
+                plan.extend(Self::plan_remove(store, name, false)?);
+            }
+            let (python, _) = options.resolve_python()?;
+            let command = self
+                .backend
+                .venv_command(&venv_path, &options.venv_options(&python))?;
+            plan.push(PlannedOperation::Run(display_command(&command)));
+        }
+        if requirements.is_some() {
+            plan.extend(self.plan_install_spec(&venv_path, &options.metadata())?);
+        }
+        Ok(plan)
+    }
+
+    /// The outcome and operations of syncing the environment `name` to a declaration, see
+    /// [`VenvService::sync`].
+    pub fn plan_sync(
+        &self,
+        store: &VenvStore,
+        name: &str,
+        options: CreateOptions<'_>,
+        requirements: &str,
+        fingerprint: &str,
+    ) -> Result<(SyncOutcome, Vec<PlannedOperation>)> {
+        let outcome = Self::sync_outcome(store, name, options.python, fingerprint)?;
+        let requirements = Some(requirements).filter(|requirements| !requirements.is_empty());
+        let plan = match outcome {
+            SyncOutcome::Created | SyncOutcome::Recreated => {
+                let options = CreateOptions {
+                    clear: outcome == SyncOutcome::Recreated,
+                    ..options
+                };
+                self.plan_create(store, name, options, requirements)?
+            }
+            SyncOutcome::Updated => {
+                let venv_path = store.env_path(name);
+                match requirements {
+                    Some(_) => {
+                        self.plan_install_spec(&venv_path, &EnvMetadata::load(&venv_path)?)?
+                    }
+                    None => vec![PlannedOperation::Write(venv_path.join(SPEC_FILE_NAME))],
+                }
+            }
+            SyncOutcome::UpToDate => Vec::new(),
+        };
+        Ok((outcome, plan))
+    }
+
+    /// Writing the spec of the environment at `venv_path` and installing it.
+    fn plan_install_spec(
+        &self,
+        venv_path: &Path,
+        metadata: &EnvMetadata,
+    ) -> Result<Vec<PlannedOperation>> {
+        let spec_path = venv_path.join(SPEC_FILE_NAME);
+        let mut command = self.backend.pip_command(venv_path, "install")?;
+        command
+            .args(metadata.index_args())
+            .arg("-r")
+            .arg(&spec_path);
+        Ok(vec![
+            PlannedOperation::Write(spec_path),
+            PlannedOperation::Run(display_command(&command)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+
+    #[test]
+    fn plan_remove_leaves_registered_envs_in_place() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        let paths = create_fixture_store(&store, &["app".to_string()], "3.13.1")?;
+        let external = temp.path().join("external");
+        std::fs::create_dir(&external)?;
+        store.register("ext", external.clone())?;
+
+        assert_eq!(
+            VenvService::plan_remove(&store, "app", false)?,
+            [PlannedOperation::Remove(paths[0].clone())]
+        );
+        assert_eq!(
+            VenvService::plan_remove(&store, "app", true)?,
+            [PlannedOperation::Trash(paths[0].clone())]
+        );
+        assert_eq!(
+            VenvService::plan_remove(&store, "ext", true)?,
+            [PlannedOperation::Unregister("ext".to_string(), external)]
+        );
+        assert!(VenvService::plan_remove(&store, "missing", true).is_err());
+        Ok(())
+    }
+}
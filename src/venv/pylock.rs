@@ -0,0 +1,616 @@
+/// Conversion of PEP 751 lock files, `pylock.toml`, from and into environments.
+use super::metadata::dist_infos;
+use super::pipenv::ImportedSpec;
+use super::spec::normalize_name;
+use super::version::minor_version;
+use super::version::python_from_requires;
+use super::{EnvConfig, EnvMetadata, VenvService, python_path_in_venv};
+use crate::backends::run_command;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// The lock file versions understood, a newer minor version stays readable.
+const LOCK_VERSION: &str = "1.0";
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct PyLock {
+    lock_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requires_python: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_by: Option<String>,
+    #[serde(default)]
+    packages: Vec<PyLockPackage>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct PyLockPackage {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    marker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vcs: Option<PyLockVcs>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    directory: Option<PyLockDirectory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archive: Option<PyLockFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sdist: Option<PyLockFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    wheels: Vec<PyLockFile>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct PyLockVcs {
+    #[serde(rename = "type")]
+    vcs_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requested_revision: Option<String>,
+    commit_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subdirectory: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct PyLockDirectory {
+    path: String,
+    editable: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct PyLockFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    hashes: BTreeMap<String, String>,
+}
+
+impl PyLockFile {
+    fn hashes(&self) -> impl Iterator<Item = String> + '_ {
+        self.hashes
+            .iter()
+            .map(|(algorithm, digest)| format!("{algorithm}:{digest}"))
+    }
+}
+
+/// Whether `marker` selects a package by an extra or a dependency group, which pip cannot
+/// evaluate.
+fn is_optional_marker(marker: &str) -> bool {
+    marker.contains("extras") || marker.contains("dependency_groups")
+}
+
+/// A path of the lock file relative to its directory `base`.
+fn resolve_path(base: &Path, path: &str) -> String {
+    base.join(path).display().to_string()
+}
+
+fn format_package(package: &PyLockPackage, base: &Path) -> Option<String> {
+    let name = &package.name;
+    let mut hashes = Vec::new();
+    let requirement = if let Some(vcs) = &package.vcs {
+        let url = match (&vcs.url, &vcs.path) {
+            (Some(url), _) => url.clone(),
+            (None, Some(path)) => format!("file://{}", resolve_path(base, path)),
+            (None, None) => return None,
+        };
+        let subdirectory = vcs
+            .subdirectory
+            .as_ref()
+            .map(|subdirectory| format!("#subdirectory={subdirectory}"))
+            .unwrap_or_default();
+        format!(
+            "{name} @ {}+{url}@{}{subdirectory}",
+            vcs.vcs_type, vcs.commit_id
+        )
+    } else if let Some(directory) = &package.directory {
+        let path = resolve_path(base, &directory.path);
+        if directory.editable {
+            return Some(format!("-e {path}"));
+        }
+        format!("{name} @ {path}")
+    } else if let Some(archive) = &package.archive {
+        hashes.extend(archive.hashes());
+        match (&archive.url, &archive.path) {
+            (Some(url), _) => format!("{name} @ {url}"),
+            (None, Some(path)) => format!("{name} @ {}", resolve_path(base, path)),
+            (None, None) => return None,
+        }
+    } else {
+        let version = package.version.as_ref()?;
+        hashes.extend(package.sdist.iter().flat_map(PyLockFile::hashes));
+        hashes.extend(package.wheels.iter().flat_map(PyLockFile::hashes));
+        format!("{name}=={version}")
+    };
+
+    let mut line = match &package.marker {
+        Some(marker) if !is_optional_marker(marker) => format!("{requirement} ; {marker}"),
+        _ => requirement,
+    };
+    for hash in hashes {
+        line.push_str(&format!(" \\\n    --hash={hash}"));
+    }
+    Some(line)
+}
+
+/// Converts the content of a `pylock.toml` in the directory `base`, `include_optional`
+/// also imports the packages of extras and dependency groups.
+pub fn parse_pylock(content: &str, base: &Path, include_optional: bool) -> Result<ImportedSpec> {
+    let lock: PyLock = toml::from_str(content).context("Invalid pylock.toml")?;
+    let major = |version: &str| version.split('.').next().map(str::to_string);
+    if major(&lock.lock_version) != major(LOCK_VERSION) {
+        anyhow::bail!(
+            "Unsupported pylock.toml version {}, expected {LOCK_VERSION}",
+            lock.lock_version
+        );
+    }
+
+    let mut lines = vec!["# Imported from pylock.toml by meowda".to_string()];
+    let mut indexes = Vec::new();
+    for package in &lock.packages {
+        if let Some(index) = &package.index
+            && !indexes.contains(index)
+        {
+            indexes.push(index.clone());
+        }
+    }
+    let mut indexes = indexes.iter();
+    if let Some(index) = indexes.next() {
+        lines.push(format!("--index-url {index}"));
+    }
+    for index in indexes {
+        lines.push(format!("--extra-index-url {index}"));
+    }
+    for package in &lock.packages {
+        if !include_optional && package.marker.as_deref().is_some_and(is_optional_marker) {
+            continue;
+        }
+        let line = format_package(package, base).with_context(|| {
+            format!(
+                "Package '{}' of pylock.toml has no usable source",
+                package.name
+            )
+        })?;
+        lines.push(line);
+    }
+
+    Ok(ImportedSpec {
+        python: lock
+            .requires_python
+            .as_deref()
+            .and_then(python_from_requires),
+        requirements: lines.join("\n") + "\n",
+    })
+}
+
+/// Where an installed distribution came from, as recorded in its `direct_url.json` by
+/// installers, see PEP 610. Distributions installed from an index have none.
+#[derive(Debug, Deserialize)]
+struct DirectUrl {
+    url: String,
+    subdirectory: Option<String>,
+    vcs_info: Option<VcsInfo>,
+    dir_info: Option<DirInfo>,
+    archive_info: Option<ArchiveInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VcsInfo {
+    vcs: String,
+    commit_id: String,
+    requested_revision: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DirInfo {
+    editable: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ArchiveInfo {
+    hashes: BTreeMap<String, String>,
+    /// The single hash of older installers, e.g., `sha256=<digest>`.
+    hash: Option<String>,
+}
+
+/// An installed distribution to lock.
+#[derive(Debug)]
+struct InstalledDist {
+    name: String,
+    version: String,
+    direct_url: Option<DirectUrl>,
+}
+
+fn installed_dists(venv_path: &Path) -> Vec<InstalledDist> {
+    let mut dists = dist_infos(venv_path)
+        .into_iter()
+        .map(|(name, version, dist_info)| InstalledDist {
+            direct_url: std::fs::read_to_string(dist_info.join("direct_url.json"))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok()),
+            name,
+            version,
+        })
+        .collect::<Vec<_>>();
+    dists.sort_by(|a, b| a.name.cmp(&b.name));
+    dists
+}
+
+/// A file of a project on a package index, from its JSON simple API, see PEP 691.
+#[derive(Debug, Deserialize)]
+struct IndexFile {
+    filename: String,
+    url: String,
+    #[serde(default)]
+    hashes: BTreeMap<String, String>,
+}
+
+/// Prints the files of the projects named in the arguments after the index URL as a JSON
+/// object, leaving out projects which cannot be looked up.
+const LOOKUP_INDEX_FILES_SCRIPT: &str = r#"import json, sys, urllib.parse, urllib.request
+index = sys.argv[1].rstrip("/")
+files = {}
+for name in sys.argv[2:]:
+    request = urllib.request.Request(
+        f"{index}/{name}/", headers={"Accept": "application/vnd.pypi.simple.v1+json"}
+    )
+    try:
+        with urllib.request.urlopen(request, timeout=30) as response:
+            page = json.load(response)
+            base = response.geturl()
+    except Exception:
+        continue
+    files[name] = [
+        {"filename": f["filename"], "url": urllib.parse.urljoin(base, f["url"]), "hashes": f.get("hashes", {})}
+        for f in page.get("files", [])
+    ]
+print(json.dumps(files))"#;
+
+const DEFAULT_INDEX_URL: &str = "https://pypi.org/simple";
+
+/// Whether `filename` is a wheel of version `version` of the project `name`.
+fn is_wheel_of(filename: &str, name: &str, version: &str) -> bool {
+    let Some(stem) = filename.strip_suffix(".whl") else {
+        return false;
+    };
+    let mut parts = stem.split('-');
+    parts
+        .next()
+        .is_some_and(|dist| normalize_name(dist) == name)
+        && parts.next() == Some(version)
+}
+
+/// Whether `filename` is a source distribution of version `version` of the project `name`.
+fn is_sdist_of(filename: &str, name: &str, version: &str) -> bool {
+    let Some(stem) = filename
+        .strip_suffix(".tar.gz")
+        .or_else(|| filename.strip_suffix(".zip"))
+    else {
+        return false;
+    };
+    stem.rsplit_once('-')
+        .is_some_and(|(dist, dist_version)| normalize_name(dist) == name && dist_version == version)
+}
+
+fn lock_file(file: &IndexFile) -> PyLockFile {
+    PyLockFile {
+        name: Some(file.filename.clone()),
+        url: Some(file.url.clone()),
+        path: None,
+        hashes: file.hashes.clone(),
+    }
+}
+
+/// The lock entry of `dist`: its direct source, or the wheels and the source distribution
+/// of its version among `files` of the index `index`, or only the version if there are none.
+fn locked_package(dist: &InstalledDist, index: &str, files: &[IndexFile]) -> PyLockPackage {
+    let mut package = PyLockPackage {
+        name: dist.name.clone(),
+        version: Some(dist.version.clone()),
+        ..PyLockPackage::default()
+    };
+    match &dist.direct_url {
+        Some(direct_url) if direct_url.vcs_info.is_some() || direct_url.dir_info.is_some() => {
+            let path = direct_url.url.strip_prefix("file://");
+            if let Some(vcs) = &direct_url.vcs_info {
+                package.vcs = Some(PyLockVcs {
+                    vcs_type: vcs.vcs.clone(),
+                    url: path.is_none().then(|| direct_url.url.clone()),
+                    path: path.map(str::to_string),
+                    requested_revision: vcs.requested_revision.clone(),
+                    commit_id: vcs.commit_id.clone(),
+                    subdirectory: direct_url.subdirectory.clone(),
+                });
+            } else {
+                package.directory = Some(PyLockDirectory {
+                    path: path.unwrap_or(&direct_url.url).to_string(),
+                    editable: direct_url.dir_info.as_ref().is_some_and(|dir| dir.editable),
+                });
+            }
+        }
+        Some(direct_url) => {
+            let archive_info = direct_url.archive_info.as_ref();
+            let mut hashes = archive_info
+                .map(|info| info.hashes.clone())
+                .unwrap_or_default();
+            if let Some((algorithm, digest)) = archive_info
+                .and_then(|info| info.hash.as_deref())
+                .and_then(|hash| hash.split_once('='))
+            {
+                hashes
+                    .entry(algorithm.to_string())
+                    .or_insert_with(|| digest.to_string());
+            }
+            let path = direct_url.url.strip_prefix("file://");
+            package.archive = Some(PyLockFile {
+                name: None,
+                url: path.is_none().then(|| direct_url.url.clone()),
+                path: path.map(str::to_string),
+                hashes,
+            });
+        }
+        None => {
+            package.index = Some(index.to_string());
+            package.wheels = files
+                .iter()
+                .filter(|file| is_wheel_of(&file.filename, &dist.name, &dist.version))
+                .map(lock_file)
+                .collect();
+            package.sdist = files
+                .iter()
+                .find(|file| is_sdist_of(&file.filename, &dist.name, &dist.version))
+                .map(lock_file);
+        }
+    }
+    package
+}
+
+/// Renders a `pylock.toml` of the distributions `dists` of an environment with Python
+/// `python`, looked up on the index `index` as `files`, keyed by project name.
+fn render_pylock(
+    python: Option<&str>,
+    index: &str,
+    dists: &[InstalledDist],
+    files: &BTreeMap<String, Vec<IndexFile>>,
+) -> Result<String> {
+    let lock = PyLock {
+        lock_version: LOCK_VERSION.to_string(),
+        requires_python: python.map(|python| format!("=={}.*", minor_version(python))),
+        created_by: Some("meowda".to_string()),
+        packages: dists
+            .iter()
+            .map(|dist| {
+                let files = files.get(&dist.name).map(Vec::as_slice).unwrap_or_default();
+                locked_package(dist, index, files)
+            })
+            .collect(),
+    };
+    toml::to_string(&lock).context("Failed to serialize pylock.toml")
+}
+
+/// A requirement reinstalling an installed distribution, see [`pinned_requirements`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinnedRequirement {
+    pub name: String,
+    pub version: String,
+    /// The requirement, e.g., `numpy==2.1.0`, `app @ git+https://example.com/app@<commit>`
+    /// or `-e /src/app`.
+    pub requirement: String,
+    /// The local directory or archive the distribution was installed from, if any.
+    pub local_path: Option<PathBuf>,
+}
+
+/// The installed distributions of the environment at `venv_path` as requirements
+/// reinstalling each from where it was installed from, like the packages of
+/// [`VenvService::write_pylock`] without looking up the files on the index.
+pub fn pinned_requirements(venv_path: &Path) -> Vec<PinnedRequirement> {
+    installed_dists(venv_path)
+        .iter()
+        .map(|dist| {
+            let package = locked_package(dist, DEFAULT_INDEX_URL, &[]);
+            let local_path = package
+                .directory
+                .as_ref()
+                .map(|directory| &directory.path)
+                .or_else(|| {
+                    package
+                        .archive
+                        .as_ref()
+                        .and_then(|archive| archive.path.as_ref())
+                })
+                .or_else(|| package.vcs.as_ref().and_then(|vcs| vcs.path.as_ref()))
+                .map(PathBuf::from);
+            PinnedRequirement {
+                requirement: format_package(&package, Path::new("/"))
+                    .unwrap_or_else(|| format!("{}=={}", dist.name, dist.version)),
+                name: dist.name.clone(),
+                version: dist.version.clone(),
+                local_path,
+            }
+        })
+        .collect()
+}
+
+impl VenvService {
+    /// Writes a `pylock.toml` of the environment at `venv_path`. Direct installs, e.g., from
+    /// git or a local directory, are locked from what the installer recorded, the files and
+    /// hashes of packages from the index are looked up on the index with the Python of the
+    /// environment. Packages which cannot be looked up, e.g., offline, pin their version only.
+    pub async fn write_pylock(&self, venv_path: &Path) -> Result<String> {
+        let python = EnvConfig::parse(venv_path.join("pyvenv.cfg"))
+            .ok()
+            .and_then(|config| config.version);
+        let metadata = EnvMetadata::load(venv_path)?;
+        let index = metadata
+            .index_url
+            .as_deref()
+            .unwrap_or(DEFAULT_INDEX_URL)
+            .to_string();
+        let dists = installed_dists(venv_path);
+        let from_index = dists
+            .iter()
+            .filter(|dist| dist.direct_url.is_none())
+            .map(|dist| dist.name.as_str())
+            .collect::<Vec<_>>();
+
+        let mut files = BTreeMap::new();
+        if !from_index.is_empty() {
+            let mut command = std::process::Command::new(python_path_in_venv(venv_path));
+            command
+                .args(["-c", LOOKUP_INDEX_FILES_SCRIPT, &index])
+                .args(&from_index);
+            match run_command(command, self.process.capture()).await {
+                Ok(output) if output.status.success() => {
+                    files = serde_json::from_str(&output.stdout).unwrap_or_default();
+                }
+                Ok(output) => debug!("Failed to look up the index files: {}", output.stderr),
+                Err(e) => debug!("Failed to look up the index files: {e:#}"),
+            }
+        }
+        let missing = from_index
+            .iter()
+            .filter(|name| !files.contains_key(**name))
+            .count();
+        if missing > 0 {
+            warn!(
+                "Failed to look up the files of {missing} packages on {index}, they are locked by version only"
+            );
+        }
+        render_pylock(python.as_deref(), &index, &dists, &files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"
+lock-version = "1.0"
+requires-python = ">=3.12"
+created-by = "uv"
+
+[[packages]]
+name = "requests"
+version = "2.32.3"
+index = "https://pypi.org/simple"
+wheels = [{ url = "https://example.com/requests.whl", hashes = { sha256 = "aaa" } }]
+
+[[packages]]
+name = "mylib"
+vcs = { type = "git", url = "https://github.com/example/mylib.git", commit-id = "abc123" }
+
+[[packages]]
+name = "pytest"
+version = "8.3.0"
+marker = "'dev' in dependency_groups"
+"#;
+
+    #[test]
+    fn parse_pylock_preserves_pins_and_hashes() -> Result<()> {
+        let spec = parse_pylock(LOCK, Path::new("/project"), false)?;
+        assert_eq!(spec.python.as_deref(), Some("3.12"));
+        let expected_lines = [
+            "--index-url https://pypi.org/simple",
+            "requests==2.32.3 \\",
+            "    --hash=sha256:aaa",
+            "mylib @ git+https://github.com/example/mylib.git@abc123",
+        ];
+        for line in expected_lines {
+            assert!(
+                spec.requirements.lines().any(|l| l == line),
+                "missing line: {line}\n{}",
+                spec.requirements
+            );
+        }
+        assert!(!spec.requirements.contains("pytest"));
+        assert!(
+            parse_pylock(LOCK, Path::new("/project"), true)?
+                .requirements
+                .contains("pytest==8.3.0\n")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn written_pylocks_record_sources_and_are_imported_back() -> Result<()> {
+        let direct_url = |json: &str| Some(serde_json::from_str::<DirectUrl>(json).unwrap());
+        let dist = |name: &str, version: &str, direct_url| InstalledDist {
+            name: name.to_string(),
+            version: version.to_string(),
+            direct_url,
+        };
+        let dists = [
+            dist(
+                "mylib",
+                "0.1.0",
+                direct_url(
+                    r#"{"url": "https://github.com/example/mylib.git", "vcs_info": {"vcs": "git", "commit_id": "abc123"}}"#,
+                ),
+            ),
+            dist("numpy", "2.0.0", None),
+            dist("pandas", "2.2.0", None),
+            dist(
+                "tool",
+                "1.0",
+                direct_url(
+                    r#"{"url": "https://example.com/tool-1.0.tar.gz", "archive_info": {"hash": "sha256=ccc"}}"#,
+                ),
+            ),
+            dist(
+                "work",
+                "0.0.1",
+                direct_url(r#"{"url": "file:///src/work", "dir_info": {"editable": true}}"#),
+            ),
+        ];
+        let files = serde_json::from_str(
+            r#"{"numpy": [
+                {"filename": "numpy-2.0.0-cp312-cp312-manylinux_2_17_x86_64.whl", "url": "https://files.example/numpy-2.0.0-cp312.whl", "hashes": {"sha256": "aaa"}},
+                {"filename": "numpy-2.0.0.tar.gz", "url": "https://files.example/numpy-2.0.0.tar.gz", "hashes": {"sha256": "bbb"}},
+                {"filename": "numpy-1.26.4-cp312-cp312-manylinux_2_17_x86_64.whl", "url": "https://files.example/numpy-1.26.4.whl", "hashes": {"sha256": "old"}}
+            ]}"#,
+        )?;
+        let lock = render_pylock(Some("3.12.4"), DEFAULT_INDEX_URL, &dists, &files)?;
+        let spec = parse_pylock(&lock, Path::new("/project"), false)?;
+        assert_eq!(spec.python.as_deref(), Some("3.12"));
+        let expected_lines = [
+            "--index-url https://pypi.org/simple",
+            "mylib @ git+https://github.com/example/mylib.git@abc123",
+            "numpy==2.0.0 \\",
+            "    --hash=sha256:bbb \\",
+            "    --hash=sha256:aaa",
+            "pandas==2.2.0",
+            "tool @ https://example.com/tool-1.0.tar.gz \\",
+            "    --hash=sha256:ccc",
+            "-e /src/work",
+        ];
+        for line in expected_lines {
+            assert!(
+                spec.requirements.lines().any(|l| l == line),
+                "missing line: {line}\n{}",
+                spec.requirements
+            );
+        }
+        assert!(!lock.contains("sha256 = \"old\""), "{lock}");
+        Ok(())
+    }
+}
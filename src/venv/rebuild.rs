@@ -0,0 +1,236 @@
+use super::version::minor_version;
+use super::{CreateOptions, EnvConfig, EnvMetadata, SPEC_FILE_NAME, VenvService, bin_dir_in_venv};
+use crate::backends::{Arch, current_shell_arch, run_command};
+use crate::error::ErrorCode;
+use crate::store::history::{self, Operation};
+use crate::store::venv_store::VenvStore;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Where the environment `name` is kept while it is rebuilt, restored if that fails.
+fn rebuilding_path(store: &VenvStore, name: &str) -> PathBuf {
+    store.path().join(format!(".{name}.rebuilding"))
+}
+
+/// Where [`VenvService::rebuild`] took the packages of an environment from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebuildSource {
+    /// The recorded dependency spec, see [`SPEC_FILE_NAME`].
+    Spec,
+    /// The packages installed before the rebuild, pinned by `pip freeze`.
+    Installed,
+    /// Neither was available, the environment is rebuilt empty.
+    Nothing,
+}
+
+/// The interpreter settings an environment was created with, from its metadata and
+/// `pyvenv.cfg`.
+#[derive(Debug, PartialEq)]
+struct RebuildSettings {
+    python: Option<String>,
+    seed: bool,
+    arch: Option<Arch>,
+    system_site_packages: bool,
+    prompt: Option<String>,
+}
+
+impl RebuildSettings {
+    fn load(venv_path: &Path, metadata: &EnvMetadata) -> Self {
+        let config = EnvConfig::parse(venv_path.join("pyvenv.cfg")).ok();
+        let bin_dir = bin_dir_in_venv(venv_path);
+        RebuildSettings {
+            // The exact interpreter of the creation if still there, the same minor version
+            // otherwise, as patch releases replace each other
+            python: metadata
+                .interpreter
+                .as_ref()
+                .map(|interpreter| interpreter.path.clone())
+                .filter(|path| Path::new(path).exists())
+                .or_else(|| {
+                    config
+                        .as_ref()
+                        .and_then(|config| config.version.as_deref())
+                        .map(minor_version)
+                }),
+            // Only uv records seeding, environments of venv have pip unless created without
+            seed: config.as_ref().is_some_and(|config| config.seed)
+                || bin_dir.join("pip").exists()
+                || bin_dir.join("pip.exe").exists(),
+            // Only an architecture other than the shell's needs to be requested, which uv supports
+            arch: metadata
+                .arch
+                .as_deref()
+                .and_then(Arch::from_machine)
+                .filter(|&arch| current_shell_arch().is_some_and(|shell| shell != arch)),
+            system_site_packages: config
+                .as_ref()
+                .is_some_and(|config| config.include_system_site_packages),
+            prompt: config.and_then(|config| config.prompt),
+        }
+    }
+}
+
+impl VenvService {
+    /// Destroys and recreates the environment `name` in place with the same Python, seeding,
+    /// architecture, system site packages, prompt and package indexes, then installs its
+    /// recorded spec or, without one, the packages it had installed. Its tags and frozen state are kept.
+    pub async fn rebuild(&self, store: &VenvStore, name: &str) -> Result<RebuildSource> {
+        let _env_lock = store.lock_env(name).await?;
+        let result = self.rebuild_locked(store, name).await;
+        history::record(store, Operation::Rebuild, name, &result);
+        result
+    }
+
+    async fn rebuild_locked(&self, store: &VenvStore, name: &str) -> Result<RebuildSource> {
+        if !store.exists(name) {
+            return Err(
+                ErrorCode::EnvNotFound.err(format!("Virtual environment '{name}' does not exist"))
+            );
+        }
+        let venv_path = store.env_path(name);
+        if store.is_registered(name) {
+            return Err(ErrorCode::EnvNotManaged.err_with_hint(
+                format!(
+                    "Virtual environment '{name}' is registered from {}, meowda does not own its directory",
+                    venv_path.display()
+                ),
+                format!("adopt it first with `meowda adopt {}`", venv_path.display()),
+            ));
+        }
+        let metadata = EnvMetadata::load(&venv_path)?;
+        let settings = RebuildSettings::load(&venv_path, &metadata);
+        if settings.python.is_none() {
+            warn!("The Python version of '{name}' is unknown, rebuilding it with the default one");
+        }
+        let (source, requirements) = self.rebuild_requirements(name, &venv_path).await?;
+
+        // The old environment is moved back if the new one cannot be built, environments
+        // are not relocatable but work again at their original path
+        let backup = rebuilding_path(store, name);
+        // Left behind by a rebuild which was killed after the new environment was built
+        if backup.exists() {
+            std::fs::remove_dir_all(&backup)
+                .with_context(|| format!("Failed to remove '{}'", backup.display()))?;
+        }
+        std::fs::rename(&venv_path, &backup)
+            .with_context(|| format!("Failed to move '{}' aside", venv_path.display()))?;
+        let options = CreateOptions {
+            python: settings.python.as_deref(),
+            clear: false,
+            arch: settings.arch,
+            seed: settings.seed,
+            index_url: metadata.index_url.as_deref(),
+            extra_index_urls: &metadata.extra_index_urls,
+            system_site_packages: settings.system_site_packages,
+            prompt: settings.prompt.as_deref(),
+        };
+        let mut result = self.create_locked(store, name, options, self.process).await;
+        if result.is_ok() && !requirements.is_empty() {
+            result = self
+                .install_spec_locked(store, name, &requirements, self.process)
+                .await;
+        }
+        if let Err(e) = result {
+            Self::discard_partial_venv(&venv_path);
+            std::fs::rename(&backup, &venv_path).with_context(|| {
+                format!(
+                    "Failed to restore '{name}' after a failed rebuild, it was kept at '{}'",
+                    backup.display()
+                )
+            })?;
+            warn!("Failed to rebuild '{name}', it was left unchanged");
+            return Err(e);
+        }
+        if let Err(e) = std::fs::remove_dir_all(&backup) {
+            warn!("Failed to remove '{}': {e}", backup.display());
+        }
+
+        let mut rebuilt = EnvMetadata::load(&venv_path)?;
+        rebuilt.frozen = metadata.frozen;
+        rebuilt.tags = metadata.tags;
+        rebuilt.sync_fingerprint = metadata.sync_fingerprint;
+        rebuilt.save(&venv_path)?;
+        Ok(source)
+    }
+
+    /// The requirements to rebuild the environment at `venv_path` with.
+    async fn rebuild_requirements(
+        &self,
+        name: &str,
+        venv_path: &Path,
+    ) -> Result<(RebuildSource, String)> {
+        let spec_path = venv_path.join(SPEC_FILE_NAME);
+        if spec_path.exists() {
+            let spec = std::fs::read_to_string(&spec_path)
+                .with_context(|| format!("Failed to read spec file '{}'", spec_path.display()))?;
+            return Ok((RebuildSource::Spec, spec));
+        }
+        // A broken interpreter cannot list its packages, which is often why it is rebuilt
+        let mut command = self.backend.pip_command(venv_path, "freeze")?;
+        command.arg("--exclude-editable");
+        match run_command(command, self.process.capture()).await {
+            Ok(output) if output.status.success() => {
+                Ok((RebuildSource::Installed, output.stdout.clone()))
+            }
+            _ => {
+                warn!(
+                    "'{name}' has no recorded spec and its packages cannot be listed, it is rebuilt without packages"
+                );
+                Ok((RebuildSource::Nothing, String::new()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+    use crate::venv::metadata::Interpreter;
+
+    #[test]
+    fn rebuild_settings_come_from_the_environment() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("venvs"));
+        let paths = create_fixture_store(&store, &["app".to_string()], "3.13.1")?;
+        // The architecture of the shell is used anyway, it is not requested
+        let arch = current_shell_arch().unwrap_or(Arch::X86_64);
+        let metadata = EnvMetadata {
+            arch: Some(arch.name().to_string()),
+            ..EnvMetadata::default()
+        };
+
+        assert_eq!(
+            RebuildSettings::load(&paths[0], &metadata),
+            RebuildSettings {
+                python: Some("3.13".to_string()),
+                seed: false,
+                arch: None,
+                system_site_packages: false,
+                prompt: None,
+            }
+        );
+
+        // A recorded interpreter which disappeared falls back to the version
+        let interpreter = std::env::current_exe()?.display().to_string();
+        let recorded = |path: &str| EnvMetadata {
+            interpreter: Some(Interpreter {
+                implementation: "CPython".to_string(),
+                version: "3.13.1".to_string(),
+                path: path.to_string(),
+                source: None,
+            }),
+            ..EnvMetadata::default()
+        };
+        assert_eq!(
+            RebuildSettings::load(&paths[0], &recorded(&interpreter)).python,
+            Some(interpreter)
+        );
+        assert_eq!(
+            RebuildSettings::load(&paths[0], &recorded("/gone/python3.13")).python,
+            Some("3.13".to_string())
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+/// Environments of single-file scripts declaring their dependencies inline, as by PEP 723.
+use super::version::python_from_requires;
+use super::{CreateOptions, EnvMetadata, VenvService, created_at, last_used, touch_last_used};
+use crate::hash::KeyHasher;
+use crate::store::events::now_timestamp;
+use crate::store::history::{self, Operation};
+use crate::store::venv_store::VenvStore;
+use anyhow::{Context, Result};
+use etcetera::BaseStrategy;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+/// Script environments unused for this long are removed, as the cache grows with every
+/// edit of the metadata of a script.
+const SCRIPT_ENV_MAX_AGE: u64 = 30 * 24 * 60 * 60;
+
+/// The `script` metadata block of a script.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ScriptMetadata {
+    pub requires_python: Option<String>,
+    pub dependencies: Vec<String>,
+    /// Python requested on the command line, e.g., `3.12` or `pypy@3.10`, used as is
+    /// instead of one satisfying `requires-python`.
+    #[serde(skip)]
+    pub python: Option<String>,
+}
+
+impl ScriptMetadata {
+    /// Parses the `# /// script` block of the script `content`, a script without one has
+    /// no dependencies.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut blocks = Vec::new();
+        let mut lines = content.lines().peekable();
+        while let Some(line) = lines.next() {
+            if line.trim_end() != "# /// script" {
+                continue;
+            }
+            // The block runs to the last `# ///` of the following comment lines
+            let mut comments = Vec::new();
+            while let Some(line) = lines.next_if(|line| *line == "#" || line.starts_with("# ")) {
+                comments.push(line);
+            }
+            let end = comments
+                .iter()
+                .rposition(|line| line.trim_end() == "# ///")
+                .context("Unclosed `# /// script` block, it has to end with `# ///`")?;
+            blocks.push(
+                comments[..end]
+                    .iter()
+                    .map(|line| line.strip_prefix("# ").unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        match blocks.as_slice() {
+            [] => Ok(ScriptMetadata::default()),
+            [block] => toml::from_str(block).context("Invalid `# /// script` block"),
+            _ => anyhow::bail!("Multiple `# /// script` blocks, only one is allowed"),
+        }
+    }
+
+    /// Name of the environment provisioned for the metadata, shared by scripts with the
+    /// same requirements.
+    pub fn env_name(&self) -> String {
+        let mut dependencies = self.dependencies.clone();
+        dependencies.sort();
+        let mut hasher = KeyHasher::default();
+        hasher
+            .optional_field(self.requires_python.as_deref())
+            .optional_field(self.python.as_deref());
+        for dependency in &dependencies {
+            hasher.field(dependency);
+        }
+        format!("script-{}", hasher.finish())
+    }
+}
+
+/// The store of the cached script environments, in the user cache directory as they can
+/// be recreated any time.
+pub fn scripts_store() -> Result<VenvStore> {
+    let cache_dir = etcetera::base_strategy::choose_base_strategy()
+        .context("Failed to find the user cache directory")?
+        .cache_dir();
+    Ok(VenvStore::from_path(
+        cache_dir.join("meowda").join("scripts"),
+    ))
+}
+
+/// Removes the environments of the scripts store last used before `cutoff`, seconds since
+/// the Unix epoch, returning their names. Environments in use, i.e., whose lock is held by
+/// a concurrent run, are kept.
+fn remove_script_envs_unused_since(store: &VenvStore, cutoff: u64) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for env in VenvService::list_venvs_in_store(store, None)? {
+        if last_used(&env.path)
+            .or_else(|| created_at(&env.path))
+            .is_none_or(|used| used > cutoff)
+        {
+            continue;
+        }
+        let Some(_env_lock) = store.try_lock_env(&env.name)? else {
+            continue;
+        };
+        std::fs::remove_dir_all(&env.path)
+            .with_context(|| format!("Failed to remove '{}'", env.path.display()))?;
+        // Removed while held, like the environment it guarded
+        if let Err(e) = std::fs::remove_file(store.env_lock_path(&env.name)) {
+            debug!("Failed to remove the lock of '{}': {e}", env.name);
+        }
+        removed.push(env.name);
+    }
+    Ok(removed)
+}
+
+impl VenvService {
+    /// Provisions the environment of a script in `store` unless it already is, returning
+    /// whether it was created. `refresh` recreates it.
+    pub async fn provision_script_env(
+        &self,
+        store: &VenvStore,
+        metadata: &ScriptMetadata,
+        refresh: bool,
+    ) -> Result<bool> {
+        let name = metadata.env_name();
+        let _env_lock = store.lock_env(&name).await?;
+        // Checked under the lock, concurrent runs of the script share one creation
+        let complete = store.exists(&name)
+            && !EnvMetadata::load(&store.env_path(&name)).is_ok_and(|metadata| metadata.installing);
+        if complete && !refresh {
+            touch_last_used(&store.env_path(&name));
+            return Ok(false);
+        }
+        let python = metadata.python.clone().or_else(|| {
+            metadata
+                .requires_python
+                .as_deref()
+                .and_then(python_from_requires)
+        });
+        let options = CreateOptions {
+            python: python.as_deref(),
+            clear: refresh,
+            arch: None,
+            seed: false,
+            index_url: None,
+            extra_index_urls: &[],
+            system_site_packages: false,
+            prompt: None,
+        };
+        let result = if metadata.dependencies.is_empty() {
+            self.create_locked(store, &name, options, self.process)
+                .await
+        } else {
+            let requirements = metadata.dependencies.join("\n") + "\n";
+            self.create_from_requirements_locked(store, &name, options, &requirements, self.process)
+                .await
+        };
+        history::record(store, Operation::Create, &name, &result);
+        result?;
+        // Collected as the cache grows, the new environment was just used
+        match remove_script_envs_unused_since(
+            store,
+            now_timestamp().saturating_sub(SCRIPT_ENV_MAX_AGE),
+        ) {
+            Ok(removed) if !removed.is_empty() => {
+                info!(
+                    "Removed {} script environments unused for {} days",
+                    removed.len(),
+                    SCRIPT_ENV_MAX_AGE / (24 * 60 * 60)
+                );
+            }
+            Ok(_) => {}
+            Err(e) => debug!("Failed to remove unused script environments: {e:#}"),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::fixture::create_fixture_store;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn unused_script_envs_are_removed() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        let store = VenvStore::from_path(temp.path().join("scripts"));
+        let names = ["script-new", "script-old", "script-used"].map(String::from);
+        let paths = create_fixture_store(&store, &names, "3.13.1")?;
+        let long_ago = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        for path in &paths[1..] {
+            std::fs::File::options()
+                .write(true)
+                .open(path.join("pyvenv.cfg"))?
+                .set_modified(long_ago)?;
+        }
+        touch_last_used(&paths[2]);
+
+        let removed = remove_script_envs_unused_since(&store, 2_000_000)?;
+        assert_eq!(removed, ["script-old"]);
+        assert!(!paths[1].exists());
+        assert!(paths[0].exists() && paths[2].exists());
+        Ok(())
+    }
+
+    #[test]
+    fn script_metadata_is_parsed_from_the_script_block() -> Result<()> {
+        let script = r#"#!/usr/bin/env python
+# /// script
+# requires-python = ">=3.12"
+# dependencies = [
+#   "requests<3",
+#   "rich",
+# ]
+# ///
+
+import requests
+"#;
+        let metadata = ScriptMetadata::parse(script)?;
+        assert_eq!(
+            metadata,
+            ScriptMetadata {
+                requires_python: Some(">=3.12".to_string()),
+                dependencies: vec!["requests<3".to_string(), "rich".to_string()],
+                python: None,
+            }
+        );
+        let reordered = ScriptMetadata {
+            dependencies: vec!["rich".to_string(), "requests<3".to_string()],
+            ..ScriptMetadata::parse(script)?
+        };
+        assert_eq!(metadata.env_name(), reordered.env_name());
+
+        assert_eq!(
+            ScriptMetadata::parse("print('hi')\n")?,
+            ScriptMetadata::default()
+        );
+        assert!(ScriptMetadata::parse("# /// script\n# dependencies = []\n").is_err());
+        assert!(ScriptMetadata::parse(&format!("{script}\n{script}")).is_err());
+        Ok(())
+    }
+}
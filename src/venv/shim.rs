@@ -0,0 +1,185 @@
+/// Launchers running an executable of an environment without activating it, e.g., `black`
+/// of a formatting environment, in a user bin directory the shell hook puts on PATH. The
+/// commands of the tools environment and of `meowda tool` are exposed there as well.
+use super::bin_dir_in_venv;
+use crate::error::ErrorCode;
+use crate::store::venv_store::user_state_dir;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Marks launchers written by meowda, followed by the environment and the executable.
+const SHIM_MARKER: &str = "meowda-shim:";
+
+/// A launcher of `meowda shim add`.
+#[derive(Debug, PartialEq)]
+pub struct Shim {
+    pub name: String,
+    pub env_path: PathBuf,
+    pub command: String,
+}
+
+/// The directory of the launchers, e.g., `~/.local/share/meowda/bin`.
+pub fn shim_bin_dir() -> Result<PathBuf> {
+    user_state_dir()
+        .map(|dir| dir.join("bin"))
+        .context("Failed to determine user state directory")
+}
+
+fn launcher_file_name(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{name}.cmd")
+    } else {
+        name.to_string()
+    }
+}
+
+/// The file of the executable `command` in the environment at `env_path`.
+fn executable_path(env_path: &Path, command: &str) -> PathBuf {
+    let bin_dir = bin_dir_in_venv(env_path);
+    if cfg!(windows) {
+        bin_dir.join(format!("{command}.exe"))
+    } else {
+        bin_dir.join(command)
+    }
+}
+
+fn quote_sh(value: &Path) -> String {
+    format!("'{}'", value.display().to_string().replace('\'', r"'\''"))
+}
+
+/// A launcher running `command` of the environment at `env_path` with the scripts of the
+/// environment first on PATH, so the executable finds the right interpreter and tools.
+fn launcher_content(env_path: &Path, command: &str) -> String {
+    let bin_dir = bin_dir_in_venv(env_path);
+    let executable = executable_path(env_path, command);
+    let marker = format!("{SHIM_MARKER} {command} {}", env_path.display());
+    if cfg!(windows) {
+        format!(
+            "@echo off\r\nrem {marker}\r\nsetlocal\r\nset \"PATH={};%PATH%\"\r\n\"{}\" %*\r\n",
+            bin_dir.display(),
+            executable.display()
+        )
+    } else {
+        format!(
+            "#!/bin/sh\n# {marker}\nPATH={}:\"$PATH\" exec {} \"$@\"\n",
+            quote_sh(&bin_dir),
+            quote_sh(&executable)
+        )
+    }
+}
+
+/// The executable and environment of a launcher, `None` for files not written by meowda.
+fn parse_launcher(content: &str) -> Option<(String, PathBuf)> {
+    content.lines().take(3).find_map(|line| {
+        let (_, rest) = line.split_once(SHIM_MARKER)?;
+        let (command, env_path) = rest.trim().split_once(' ')?;
+        Some((command.to_string(), PathBuf::from(env_path)))
+    })
+}
+
+fn read_shim(path: &Path) -> Option<Shim> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let (command, env_path) = parse_launcher(&content)?;
+    let file_name = path.file_name()?.to_str()?;
+    let name = if cfg!(windows) {
+        file_name.strip_suffix(".cmd")?
+    } else {
+        file_name
+    };
+    Some(Shim {
+        name: name.to_string(),
+        env_path,
+        command,
+    })
+}
+
+/// Writes a launcher of the executable `command` of the environment at `env_path`, replacing
+/// a launcher of another environment only with `force`.
+pub fn add_shim(env_path: &Path, command: &str, force: bool) -> Result<PathBuf> {
+    if !executable_path(env_path, command).is_file() {
+        anyhow::bail!(
+            "'{command}' is not an executable of the environment '{}'",
+            env_path.display()
+        );
+    }
+    let bin_dir = shim_bin_dir()?;
+    std::fs::create_dir_all(&bin_dir)
+        .with_context(|| format!("Failed to create '{}'", bin_dir.display()))?;
+    let path = bin_dir.join(launcher_file_name(command));
+    if path.exists() && !force {
+        let existing = read_shim(&path);
+        if existing
+            .as_ref()
+            .is_none_or(|shim| shim.env_path != env_path)
+        {
+            return Err(ErrorCode::EnvAlreadyExists.err_with_hint(
+                format!("'{}' already exists", path.display()),
+                "pass --force to replace it",
+            ));
+        }
+    }
+    std::fs::write(&path, launcher_content(env_path, command))
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make '{}' executable", path.display()))?;
+    }
+    Ok(path)
+}
+
+/// Whether `path` is a launcher written by meowda, e.g., to keep it from being replaced by
+/// the link of a tool command.
+pub(super) fn is_launcher(path: &Path) -> bool {
+    read_shim(path).is_some()
+}
+
+/// The launchers of the shim directory, other files are skipped.
+pub fn list_shims() -> Result<Vec<Shim>> {
+    let Ok(entries) = std::fs::read_dir(shim_bin_dir()?) else {
+        return Ok(Vec::new());
+    };
+    let mut shims = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| read_shim(&entry.path()))
+        .collect::<Vec<_>>();
+    shims.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(shims)
+}
+
+/// Whether the executable of a launcher still exists, it breaks when its environment is
+/// removed.
+pub fn shim_target_exists(shim: &Shim) -> bool {
+    executable_path(&shim.env_path, &shim.command).is_file()
+}
+
+/// Removes the launcher `name`, refusing files not written by meowda.
+pub fn remove_shim(name: &str) -> Result<()> {
+    let path = shim_bin_dir()?.join(launcher_file_name(name));
+    if read_shim(&path).is_none() {
+        anyhow::bail!(
+            "No shim '{name}' in '{}'",
+            path.parent().unwrap_or(&path).display()
+        );
+    }
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launchers_record_their_environment() {
+        let env_path = Path::new("/home/user/envs/it's fmt");
+        let content = launcher_content(env_path, "black");
+        assert_eq!(
+            parse_launcher(&content),
+            Some(("black".to_string(), env_path.to_path_buf()))
+        );
+        #[cfg(unix)]
+        assert!(content.contains(r#"exec '/home/user/envs/it'\''s fmt/bin/black' "$@""#));
+        assert_eq!(parse_launcher("#!/bin/sh\nexec black \"$@\"\n"), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Editing the dependency spec of an environment, see [`super::SPEC_FILE_NAME`].
+
+use super::version::Specifier;
+
+/// A requirement line, e.g., `numpy>=1.20,<2`.
+#[derive(Debug, PartialEq)]
+pub struct Requirement {
+    /// The normalized project name.
+    pub name: String,
+    /// All have to be satisfied, none means any version.
+    pub specifiers: Vec<Specifier>,
+    /// Environment marker, e.g., `python_version < "3.11"`, which meowda cannot evaluate.
+    pub marker: Option<String>,
+}
+
+/// Parses a requirement line, `None` for the lines [`requirement_name`] has no name for,
+/// URLs (`name @ url`) and invalid specifiers.
+pub fn parse_requirement(line: &str) -> Option<Requirement> {
+    let name = requirement_name(line)?;
+    let line = line.split(" #").next().unwrap_or_default().trim();
+    let (line, marker) = match line.split_once(';') {
+        Some((line, marker)) => (line, Some(marker.trim().to_string())),
+        None => (line, None),
+    };
+    let mut rest = line
+        .trim_start_matches(|c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .trim_start();
+    if rest.starts_with('[') {
+        rest = &rest[rest.find(']')? + 1..];
+    }
+    let rest = rest.trim().trim_start_matches('(').trim_end_matches(')');
+    let specifiers = rest
+        .split(',')
+        .filter(|specifier| !specifier.trim().is_empty())
+        .map(Specifier::parse)
+        .collect::<Option<Vec<_>>>()?;
+    Some(Requirement {
+        name,
+        specifiers,
+        marker,
+    })
+}
+
+/// The normalized project name a requirement line refers to, `None` for blank lines,
+/// comments, options like `-r`/`--index-url` and paths or URLs.
+pub fn requirement_name(line: &str) -> Option<String> {
+    let line = line.split(" #").next().unwrap_or_default().trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(line.len());
+    let name = &line[..end];
+    if name.is_empty() || line[end..].starts_with(['/', ':']) {
+        return None;
+    }
+    Some(normalize_name(name))
+}
+
+/// The project name normalized as by PEP 503, e.g., `Foo_Bar` is `foo-bar`.
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// `spec` with `requirements` added, replacing the lines of the same projects in place.
+pub fn add_requirements(spec: &str, requirements: &[String]) -> String {
+    let mut lines: Vec<String> = spec.lines().map(ToString::to_string).collect();
+    for requirement in requirements {
+        let name = requirement_name(requirement);
+        match lines
+            .iter()
+            .position(|line| name.is_some() && requirement_name(line) == name)
+        {
+            Some(index) => lines[index] = requirement.clone(),
+            None => lines.push(requirement.clone()),
+        }
+    }
+    join_lines(&lines)
+}
+
+/// `spec` without the lines of the projects `names`, and the names it had no line for.
+pub fn remove_requirements(spec: &str, names: &[String]) -> (String, Vec<String>) {
+    let names: Vec<(String, &String)> = names
+        .iter()
+        .map(|name| (normalize_name(name), name))
+        .collect();
+    let mut lines = Vec::new();
+    let mut removed = Vec::new();
+    for line in spec.lines() {
+        match requirement_name(line).filter(|name| names.iter().any(|(n, _)| n == name)) {
+            Some(name) => removed.push(name),
+            None => lines.push(line.to_string()),
+        }
+    }
+    let missing = names
+        .into_iter()
+        .filter(|(name, _)| !removed.contains(name))
+        .map(|(_, name)| name.clone())
+        .collect();
+    (join_lines(&lines), missing)
+}
+
+fn join_lines(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut spec = lines.join("\n");
+    spec.push('\n');
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirements_are_matched_by_normalized_name() {
+        assert_eq!(requirement_name("Django>=4.0"), Some("django".to_string()));
+        assert_eq!(
+            requirement_name("typing_extensions[x] ; python_version<'3.11'  # pin"),
+            Some("typing-extensions".to_string())
+        );
+        assert_eq!(requirement_name("-r base.txt"), None);
+        assert_eq!(requirement_name("./vendor/pkg"), None);
+        assert_eq!(requirement_name("https://example.com/pkg.whl"), None);
+
+        let spec = "# deps\nrequests\nDjango>=4.0\n";
+        let spec = add_requirements(spec, &["django<5".to_string(), "pandas".to_string()]);
+        assert_eq!(spec, "# deps\nrequests\ndjango<5\npandas\n");
+
+        let (spec, missing) =
+            remove_requirements(&spec, &["Requests".to_string(), "numpy".to_string()]);
+        assert_eq!(spec, "# deps\ndjango<5\npandas\n");
+        assert_eq!(missing, ["numpy"]);
+    }
+
+    #[test]
+    fn requirements_are_parsed_with_specifiers_and_markers() {
+        let requirement = parse_requirement("Torch[cuda] >=2.0, <3 ; sys_platform == 'linux'")
+            .expect("valid requirement");
+        assert_eq!(requirement.name, "torch");
+        assert_eq!(
+            requirement
+                .specifiers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [">=2.0", "<3"]
+        );
+        assert_eq!(
+            requirement.marker.as_deref(),
+            Some("sys_platform == 'linux'")
+        );
+        assert_eq!(
+            parse_requirement("six").map(|requirement| requirement.specifiers),
+            Some(Vec::new())
+        );
+        assert!(parse_requirement("pkg @ https://example.com/pkg.whl").is_none());
+        assert!(parse_requirement("-r base.txt").is_none());
+    }
+}
@@ -0,0 +1,352 @@
+/// The well-known global tools environment and the isolated environments of single tools of
+/// `meowda tool`, both exposed by shims in the directory the shell hook puts on PATH.
+use super::metadata::dist_infos;
+use super::shim::{is_launcher, shim_bin_dir};
+use super::spec::{normalize_name, requirement_name};
+use super::{CreateOptions, PackageOptions, SPEC_FILE_NAME, VenvService, bin_dir_in_venv};
+use crate::error::ErrorCode;
+use crate::store::history::{self, Operation};
+use crate::store::venv_store::{VenvScope, VenvStore};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Name of the tools environment in the global store.
+pub const TOOLS_ENV_NAME: &str = "tools";
+
+/// Directory of the environments of `meowda tool` in the global store, hidden from its
+/// environments and apart from the shared [`TOOLS_ENV_NAME`] environment.
+const TOOL_ENVS_DIR: &str = ".tools";
+
+/// Scripts every virtual environment has, which are not tools of their own.
+const VENV_SCRIPT_PREFIXES: [&str; 4] = ["python", "pip", "activate", "deactivate"];
+
+pub fn tools_store() -> Result<VenvStore> {
+    VenvStore::from_specified_scope(VenvScope::Global)
+}
+
+/// The directory holding the entry points of the tools environment.
+pub fn tools_bin_dir() -> Result<PathBuf> {
+    Ok(bin_dir_in_venv(&tools_store()?.path().join(TOOLS_ENV_NAME)))
+}
+
+/// Lists the entry points in `bin_dir`, skipping the interpreter and activation scripts.
+pub fn list_tools(bin_dir: &Path) -> Vec<String> {
+    let Ok(entries) = bin_dir.read_dir() else {
+        return Vec::new();
+    };
+    let mut tools = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|name| {
+            let stem = name.split('.').next().unwrap_or(name).to_ascii_lowercase();
+            !VENV_SCRIPT_PREFIXES
+                .iter()
+                .any(|prefix| stem.starts_with(prefix))
+        })
+        .collect::<Vec<_>>();
+    tools.sort();
+    tools
+}
+
+/// The store of the environments of `meowda tool`, one per tool.
+pub fn tool_envs_store() -> Result<VenvStore> {
+    Ok(VenvStore::from_path(
+        tools_store()?.path().join(TOOL_ENVS_DIR),
+    ))
+}
+
+/// Rejects creating an environment named like the tools environment in its store.
+pub fn check_not_reserved(store: &VenvStore, name: &str) -> Result<()> {
+    if name == TOOLS_ENV_NAME && store.path() == tools_store()?.path() {
+        return Err(ErrorCode::InvalidEnvName.err_with_hint(
+            format!("'{name}' is reserved for the tools environment of the global store"),
+            "install packages into it with `meowda install --tools <packages>`, or pick another name",
+        ));
+    }
+    Ok(())
+}
+
+/// A tool installed with `meowda tool install`.
+#[derive(Debug)]
+pub struct ToolInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub commands: Vec<String>,
+}
+
+/// The commands of the `[console_scripts]` and `[gui_scripts]` sections of an
+/// `entry_points.txt`.
+fn parse_entry_points(content: &str) -> Vec<String> {
+    let mut in_scripts = false;
+    let mut commands = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_scripts = matches!(line, "[console_scripts]" | "[gui_scripts]");
+        } else if in_scripts
+            && !line.starts_with(['#', ';'])
+            && let Some((command, _)) = line.split_once('=')
+        {
+            commands.push(command.trim().to_string());
+        }
+    }
+    commands
+}
+
+/// The commands the package `name` of the tool environment at `venv_path` provides, those
+/// of its dependencies are not exposed.
+fn tool_commands(venv_path: &Path, name: &str) -> Vec<String> {
+    dist_infos(venv_path)
+        .into_iter()
+        .find(|(package, _, _)| package == name)
+        .and_then(|(_, _, dist_info)| {
+            std::fs::read_to_string(dist_info.join("entry_points.txt")).ok()
+        })
+        .map(|content| parse_entry_points(&content))
+        .unwrap_or_default()
+}
+
+/// File name of the script of `command` in the scripts directory of an environment.
+fn script_file_name(command: &str) -> String {
+    if cfg!(windows) {
+        format!("{command}.exe")
+    } else {
+        command.to_string()
+    }
+}
+
+#[cfg(unix)]
+fn link_shim(target: &Path, shim: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, shim)
+}
+
+/// Symbolic links need privileges on Windows, the launchers find the interpreter by an
+/// absolute path so copies work as well.
+#[cfg(windows)]
+fn link_shim(target: &Path, shim: &Path) -> std::io::Result<()> {
+    std::fs::copy(target, shim).map(|_| ())
+}
+
+/// The other tool of `store` already providing `command`, whose shim is kept.
+fn command_owner(store: &VenvStore, name: &str, command: &str) -> Option<String> {
+    store.env_names().into_iter().find(|other| {
+        other != name
+            && tool_commands(&store.env_path(other), other)
+                .iter()
+                .any(|provided| provided == command)
+    })
+}
+
+/// Links the commands of the tool `name` into the shims directory, returning them.
+fn link_shims(store: &VenvStore, name: &str) -> Result<Vec<String>> {
+    let shims = shim_bin_dir()?;
+    std::fs::create_dir_all(&shims)
+        .with_context(|| format!("Failed to create '{}'", shims.display()))?;
+    let bin_dir = bin_dir_in_venv(&store.env_path(name));
+    let mut linked = Vec::new();
+    for command in tool_commands(&store.env_path(name), name) {
+        if let Some(owner) = command_owner(store, name, &command) {
+            warn!("Skipping '{command}' of '{name}', it is already provided by the tool '{owner}'");
+            continue;
+        }
+        let file_name = script_file_name(&command);
+        let shim = shims.join(&file_name);
+        if is_launcher(&shim) {
+            warn!(
+                "Skipping '{command}' of '{name}', '{}' is a shim of another environment",
+                shim.display()
+            );
+            continue;
+        }
+        if shim.symlink_metadata().is_ok() {
+            std::fs::remove_file(&shim)
+                .with_context(|| format!("Failed to replace '{}'", shim.display()))?;
+        }
+        link_shim(&bin_dir.join(&file_name), &shim)
+            .with_context(|| format!("Failed to link '{}'", shim.display()))?;
+        linked.push(command);
+    }
+    Ok(linked)
+}
+
+/// Removes the shims of the `commands` of the tool `name`, keeping those of other tools.
+fn unlink_shims(store: &VenvStore, name: &str, commands: &[String]) {
+    let Ok(shims) = shim_bin_dir() else {
+        return;
+    };
+    for command in commands {
+        if command_owner(store, name, command).is_some() {
+            continue;
+        }
+        let shim = shims.join(script_file_name(command));
+        if !is_launcher(&shim)
+            && shim.symlink_metadata().is_ok()
+            && let Err(e) = std::fs::remove_file(&shim)
+        {
+            warn!("Failed to remove '{}': {e}", shim.display());
+        }
+    }
+}
+
+fn tool_not_installed(name: &str) -> anyhow::Error {
+    ErrorCode::EnvNotFound.err_with_hint(
+        format!("Tool '{name}' is not installed"),
+        format!("install it with `meowda tool install {name}`"),
+    )
+}
+
+impl VenvService {
+    /// Installs the tool of `requirement`, e.g., `ruff` or `ruff==0.5.0`, into an environment
+    /// of its own and links its commands into the shims directory, returning them. `force`
+    /// reinstalls an installed tool.
+    pub async fn install_tool(
+        &self,
+        requirement: &str,
+        python: Option<&str>,
+        force: bool,
+    ) -> Result<Vec<String>> {
+        let name = requirement_name(requirement).ok_or_else(|| {
+            ErrorCode::InvalidEnvName.err(format!(
+                "'{requirement}' is not a package requirement, e.g., ruff or ruff==0.5.0"
+            ))
+        })?;
+        let store = tool_envs_store()?;
+        store.init_if_needed()?;
+        let _env_lock = store.lock_env(&name).await?;
+        let venv_path = store.env_path(&name);
+        if store.exists(&name) && !force {
+            return Err(ErrorCode::EnvAlreadyExists.err_with_hint(
+                format!("Tool '{name}' is already installed"),
+                format!(
+                    "upgrade it with `meowda tool upgrade {name}`, or pass --force to reinstall it"
+                ),
+            ));
+        }
+        unlink_shims(&store, &name, &tool_commands(&venv_path, &name));
+
+        let options = CreateOptions {
+            python,
+            clear: force,
+            arch: None,
+            seed: false,
+            index_url: None,
+            extra_index_urls: &[],
+            system_site_packages: false,
+            prompt: None,
+        };
+        let result = async {
+            self.create_locked(&store, &name, options, self.process)
+                .await?;
+            self.install_spec_locked(&store, &name, &format!("{requirement}\n"), self.process)
+                .await?;
+            if tool_commands(&venv_path, &name).is_empty() {
+                return Err(ErrorCode::BackendFailed.err_with_hint(
+                    format!("'{name}' provides no commands to run as a tool"),
+                    "install libraries into a virtual environment with `meowda install` instead",
+                ));
+            }
+            Ok(())
+        }
+        .await;
+        history::record(&store, Operation::Create, &name, &result);
+        if let Err(e) = result {
+            Self::discard_partial_venv(&venv_path);
+            return Err(e);
+        }
+        link_shims(&store, &name)
+    }
+
+    /// Upgrades the tool `name` within the requirement it was installed with, relinking its
+    /// commands as they may have changed.
+    pub async fn upgrade_tool(&self, name: &str) -> Result<Vec<String>> {
+        let name = normalize_name(name);
+        let store = tool_envs_store()?;
+        if !store.exists(&name) {
+            return Err(tool_not_installed(&name));
+        }
+        let venv_path = store.env_path(&name);
+        let commands = tool_commands(&venv_path, &name);
+        let spec_path = venv_path.join(SPEC_FILE_NAME).display().to_string();
+        self.install_into(
+            &store,
+            &venv_path,
+            &["--upgrade", "-r", &spec_path],
+            PackageOptions {
+                freeze: false,
+                thaw: false,
+            },
+        )
+        .await?;
+        unlink_shims(&store, &name, &commands);
+        link_shims(&store, &name)
+    }
+
+    /// Removes the tool `name` and its shims.
+    pub async fn uninstall_tool(&self, name: &str) -> Result<()> {
+        let name = normalize_name(name);
+        let store = tool_envs_store()?;
+        let _env_lock = store.lock_env(&name).await?;
+        if !store.exists(&name) {
+            return Err(tool_not_installed(&name));
+        }
+        unlink_shims(&store, &name, &tool_commands(&store.env_path(&name), &name));
+        let result = Self::remove_venv(&store, &name);
+        history::record(&store, Operation::Remove, &name, &result);
+        result
+    }
+
+    /// Lists the tools installed with `meowda tool install`.
+    pub fn installed_tools(&self) -> Result<Vec<ToolInfo>> {
+        let store = tool_envs_store()?;
+        Ok(store
+            .env_names()
+            .into_iter()
+            .map(|name| {
+                let venv_path = store.env_path(&name);
+                ToolInfo {
+                    version: dist_infos(&venv_path)
+                        .into_iter()
+                        .find(|(package, _, _)| *package == name)
+                        .map(|(_, version, _)| version),
+                    commands: tool_commands(&venv_path, &name),
+                    name,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_tools_skips_venv_scripts() -> Result<()> {
+        let temp = tempfile::tempdir()?;
+        for name in [
+            "python",
+            "python3.12",
+            "pip3",
+            "activate.fish",
+            "Activate.ps1",
+            "deactivate.bat",
+            "ruff",
+            "black.exe",
+        ] {
+            std::fs::write(temp.path().join(name), "")?;
+        }
+        std::fs::create_dir(temp.path().join("subdir"))?;
+        assert_eq!(list_tools(temp.path()), ["black.exe", "ruff"]);
+        Ok(())
+    }
+
+    #[test]
+    fn entry_points_are_read_from_script_sections() {
+        let content = "[console_scripts]\nblack = black:patched_main\nblackd = blackd:patched_main [d]\n\n[gui_scripts]\nblack-gui = black.gui:main\n\n[pytest11]\nblack = black.plugin\n";
+        assert_eq!(
+            parse_entry_points(content),
+            ["black", "blackd", "black-gui"]
+        );
+    }
+}
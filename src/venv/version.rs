@@ -0,0 +1,290 @@
+//! Python package versions and version specifiers, as by PEP 440.
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+/// Epoch, release without trailing zeros, pre-release, post-release and dev release, see
+/// [`Version::key`].
+type VersionKey<'a> = (u64, &'a [u64], (u8, u64), (u8, u64), (u8, u64));
+
+/// A PEP 440 version, e.g., `1.2.3rc1.post2.dev3`; local versions like `+cu121` are
+/// ignored.
+#[derive(Debug, Clone)]
+pub struct Version {
+    epoch: u64,
+    release: Vec<u64>,
+    /// Phase (`a`, `b`, `rc` as 0, 1, 2) and number of a pre-release.
+    pre: Option<(u8, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+}
+
+impl Version {
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim().to_ascii_lowercase();
+        let version = version.strip_prefix('v').unwrap_or(&version);
+        let version = version.split('+').next().unwrap_or_default();
+        let (epoch, rest) = match version.split_once('!') {
+            Some((epoch, rest)) => (epoch.parse().ok()?, rest),
+            None => (0, version),
+        };
+        let release_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let release_part = rest[..release_end].trim_end_matches('.');
+        let release = release_part
+            .split('.')
+            .map(str::parse)
+            .collect::<Result<Vec<u64>, _>>()
+            .ok()?;
+        let mut parsed = Version {
+            epoch,
+            release,
+            pre: None,
+            post: None,
+            dev: None,
+        };
+
+        let mut suffix = &rest[release_part.len()..];
+        while !suffix.is_empty() {
+            let implicit_post = suffix.starts_with('-');
+            suffix = suffix.trim_start_matches(['.', '-', '_']);
+            let word_end = suffix
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(suffix.len());
+            let (word, after_word) = suffix.split_at(word_end);
+            let after_word = after_word.trim_start_matches(['.', '-', '_']);
+            let number_end = after_word
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(after_word.len());
+            let number = after_word[..number_end].parse().unwrap_or(0);
+            match word {
+                "a" | "alpha" => parsed.pre = Some((0, number)),
+                "b" | "beta" => parsed.pre = Some((1, number)),
+                "c" | "rc" | "pre" | "preview" => parsed.pre = Some((2, number)),
+                "post" | "rev" | "r" => parsed.post = Some(number),
+                "dev" => parsed.dev = Some(number),
+                "" if implicit_post && number_end > 0 => parsed.post = Some(number),
+                _ => return None,
+            }
+            suffix = &after_word[number_end..];
+        }
+        Some(parsed)
+    }
+
+    /// The sort key of PEP 440: dev releases sort first, then pre-releases, the release
+    /// and post-releases, trailing zeros of the release do not matter.
+    fn key(&self) -> VersionKey<'_> {
+        let release_len = self
+            .release
+            .iter()
+            .rposition(|&segment| segment != 0)
+            .map_or(0, |index| index + 1);
+        let pre = match (self.pre, self.post, self.dev) {
+            (Some((phase, number)), _, _) => (phase + 1, number),
+            (None, None, Some(_)) => (0, 0),
+            (None, _, _) => (4, 0),
+        };
+        let post = self.post.map_or((0, 0), |number| (1, number));
+        let dev = self.dev.map_or((1, 0), |number| (0, number));
+        (self.epoch, &self.release[..release_len], pre, post, dev)
+    }
+
+    /// Whether this version is a pre-release, e.g., `1.0rc1` or `1.0.dev1`.
+    fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    /// Whether this and `other` share their epoch and release, e.g., `1.0rc1` and
+    /// `1.0.0.post1`.
+    fn same_release(&self, other: &Self) -> bool {
+        let (epoch, release, ..) = self.key();
+        let (other_epoch, other_release, ..) = other.key();
+        epoch == other_epoch && release == other_release
+    }
+
+    /// Whether the release of this version starts with `prefix`, missing segments are 0.
+    fn has_release_prefix(&self, epoch: u64, prefix: &[u64]) -> bool {
+        self.epoch == epoch
+            && prefix
+                .iter()
+                .enumerate()
+                .all(|(index, segment)| self.release.get(index).copied().unwrap_or(0) == *segment)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// A single version specifier of a requirement, e.g., `>=1.20` or `==2.*`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Specifier {
+    operator: String,
+    version: String,
+}
+
+impl Specifier {
+    pub fn parse(specifier: &str) -> Option<Self> {
+        let specifier = specifier.trim();
+        let operator_end = specifier
+            .find(|c: char| !matches!(c, '=' | '!' | '<' | '>' | '~'))
+            .unwrap_or(specifier.len());
+        let (operator, version) = specifier.split_at(operator_end);
+        let version = version.trim();
+        let valid = match operator {
+            "===" => !version.is_empty(),
+            "==" | "!=" => Version::parse(version.strip_suffix(".*").unwrap_or(version)).is_some(),
+            "~=" | ">=" | "<=" | ">" | "<" => Version::parse(version).is_some(),
+            _ => false,
+        };
+        valid.then(|| Specifier {
+            operator: operator.to_string(),
+            version: version.to_string(),
+        })
+    }
+
+    /// Whether the installed version `installed` satisfies this specifier.
+    pub fn contains(&self, installed: &str) -> bool {
+        if self.operator == "===" {
+            return installed.trim() == self.version;
+        }
+        let Some(candidate) = Version::parse(installed) else {
+            return false;
+        };
+        if let Some(prefix) = self.version.strip_suffix(".*") {
+            let Some(prefix) = Version::parse(prefix) else {
+                return false;
+            };
+            let matches = candidate.has_release_prefix(prefix.epoch, &prefix.release);
+            return matches == (self.operator == "==");
+        }
+        let Some(version) = Version::parse(&self.version) else {
+            return false;
+        };
+        match self.operator.as_str() {
+            "==" => candidate == version,
+            "!=" => candidate != version,
+            ">=" => candidate >= version,
+            "<=" => candidate <= version,
+            // `>1.0` excludes the post-releases of `1.0`, `<1.0` its pre-releases, unless
+            // the specified version is one itself
+            ">" => {
+                candidate > version
+                    && !(version.post.is_none()
+                        && candidate.post.is_some()
+                        && candidate.same_release(&version))
+            }
+            "<" => {
+                candidate < version
+                    && !(!version.is_prerelease()
+                        && candidate.is_prerelease()
+                        && candidate.same_release(&version))
+            }
+            // `~=1.4.5` is `>=1.4.5, ==1.4.*`
+            "~=" => {
+                let prefix = &version.release[..version.release.len().saturating_sub(1).max(1)];
+                candidate >= version && candidate.has_release_prefix(version.epoch, prefix)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for Specifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.operator, self.version)
+    }
+}
+
+/// The minor version of a Python version, e.g., `3.12` of `3.12.4`.
+pub fn minor_version(version: &str) -> String {
+    version.splitn(3, '.').take(2).collect::<Vec<_>>().join(".")
+}
+
+/// The Python version a `requires-python` specifier like `>=3.12` or `==3.12.*` asks for,
+/// `None` for ranges of several specifiers.
+pub fn python_from_requires(requires_python: &str) -> Option<String> {
+    if requires_python.contains(',') {
+        return None;
+    }
+    let version = requires_python
+        .trim()
+        .trim_start_matches(['>', '=', '~'])
+        .trim_end_matches(".*")
+        .trim();
+    (!version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        .then(|| version.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_ordered_as_by_pep_440() {
+        let ordered = [
+            "1.0.dev1",
+            "1.0a1",
+            "1.0b2",
+            "1.0rc1",
+            "1.0",
+            "1.0.post1",
+            "1.1",
+            "2!0.1",
+        ];
+        for pair in ordered.windows(2) {
+            let (lower, higher) = (Version::parse(pair[0]), Version::parse(pair[1]));
+            assert!(lower < higher, "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(Version::parse("1.0"), Version::parse("1.0.0+cu121"));
+        assert!(Version::parse("1.0-foo").is_none());
+    }
+
+    #[test]
+    fn specifiers_match_installed_versions() {
+        let cases = [
+            (">=1.20", "1.26.4", true),
+            ("<2", "2.0.0", false),
+            ("==2.*", "2.3.1", true),
+            ("!=2.*", "2.3.1", false),
+            ("~=1.4.5", "1.4.9", true),
+            ("~=1.4.5", "1.5.0", false),
+            ("~=2.2", "2.9", true),
+            ("==1.0", "1.0.0", true),
+            ("===1.0", "1.0.0", false),
+            ("<2", "2.0rc1", false),
+            ("<2", "2.0.dev1", false),
+            ("<2", "1.9", true),
+            ("<2rc2", "2.0rc1", true),
+            (">1.0", "1.0.post1", false),
+            (">1.0", "1.0.1", true),
+            (">1.0.post1", "1.0.post2", true),
+        ];
+        for (specifier, installed, expected) in cases {
+            let parsed = Specifier::parse(specifier).unwrap();
+            assert_eq!(
+                parsed.contains(installed),
+                expected,
+                "{installed} {specifier}"
+            );
+        }
+        assert!(Specifier::parse("=>1.0").is_none());
+    }
+}